@@ -0,0 +1,49 @@
+use crate::error::AppError;
+use crate::settings::{self, AccessSettings};
+use std::path::{Path, PathBuf};
+use tauri::AppHandle;
+
+/// Resolve `..` and symlinks so a path can't step out of an allowed directory.
+/// Files that don't exist yet (export targets) are resolved through their parent.
+fn resolve(path: &Path) -> Result<PathBuf, String> {
+    if let Ok(resolved) = path.canonicalize() {
+        return Ok(resolved);
+    }
+    let parent = match path.parent() {
+        Some(parent) if !parent.as_os_str().is_empty() => parent,
+        _ => Path::new("."),
+    };
+    let name = path
+        .file_name()
+        .ok_or_else(|| format!("Invalid file path: {}", path.display()))?;
+    let parent = parent
+        .canonicalize()
+        .map_err(|e| format!("Failed to resolve {}: {}", path.display(), e))?;
+    Ok(parent.join(name))
+}
+
+fn is_within(path: &Path, dirs: &[String]) -> bool {
+    dirs.iter().any(|dir| {
+        let dir = Path::new(dir);
+        path.starts_with(dir.canonicalize().as_deref().unwrap_or(dir))
+    })
+}
+
+fn check_with(access: &AccessSettings, path: &str) -> Result<(), AppError> {
+    if access.allow.is_empty() && access.deny.is_empty() {
+        return Ok(());
+    }
+    let resolved = resolve(Path::new(path)).map_err(|e| AppError::from(e).with_path(path))?;
+    if is_within(&resolved, &access.deny) || (!access.allow.is_empty() && !is_within(&resolved, &access.allow)) {
+        return Err(AppError::access_denied(
+            format!("Access to {} is not allowed by the file access settings", path),
+            path,
+        ));
+    }
+    Ok(())
+}
+
+/// Fail unless the file access settings let commands read or write `path`
+pub fn check(app: &AppHandle, path: &str) -> Result<(), AppError> {
+    check_with(&settings::load(app).access, path)
+}
@@ -0,0 +1,206 @@
+use crate::dataset::{Dataset, DatasetStore, FileData};
+use crate::error::AppError;
+use crate::logging;
+use crate::refresh::RemoteSource;
+use serde::Deserialize;
+use serde_json::Value;
+use tauri::State;
+use url::Url;
+
+/// Rows fetched when the caller doesn't give a limit
+const DEFAULT_MAX_ROWS: usize = 10_000;
+/// Stop after this many requests even if the API keeps returning pages
+const MAX_PAGES: usize = 1_000;
+
+/// How to ask for the next page
+#[derive(Debug, Clone, Deserialize)]
+pub struct Pagination {
+    /// "page" (page=1,2,...), "offset" (offset=0,n,...), "next_link", or "cursor"
+    pub kind: String,
+    /// Query parameter carrying the page number, offset, or cursor
+    #[serde(default)]
+    pub param: Option<String>,
+    /// First page number or offset (1 for pages, 0 for offsets by default)
+    #[serde(default)]
+    pub start: Option<u64>,
+    /// Dot path in the response to the next page's URL or cursor, e.g.
+    /// "links.next" or "meta.next_cursor". Next links also fall back to the
+    /// `Link: <...>; rel="next"` header.
+    #[serde(default)]
+    pub next_path: Option<String>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct ApiRequest {
+    pub url: String,
+    /// Sent as the Authorization header
+    #[serde(default)]
+    pub auth_header: Option<String>,
+    /// Dot path to the array of records; found automatically when omitted
+    #[serde(default)]
+    pub records_path: Option<String>,
+    #[serde(default)]
+    pub pagination: Option<Pagination>,
+    #[serde(default)]
+    pub max_rows: Option<usize>,
+}
+
+/// Follow a dot-separated path into a JSON value
+fn lookup<'a>(value: &'a Value, path: &str) -> Option<&'a Value> {
+    path.split('.')
+        .filter(|key| !key.is_empty())
+        .try_fold(value, |v, key| match v {
+            Value::Array(items) => key.parse::<usize>().ok().and_then(|i| items.get(i)),
+            _ => v.get(key),
+        })
+}
+
+/// The `rel="next"` target of a Link header
+fn link_header_next(link: &str) -> Option<String> {
+    link.split(',').find_map(|part| {
+        let (target, params) = part.split_once(';')?;
+        params
+            .split(';')
+            .any(|p| matches!(p.trim(), "rel=\"next\"" | "rel=next"))
+            .then(|| target.trim().trim_start_matches('<').trim_end_matches('>').to_string())
+    })
+}
+
+/// `url` with one query parameter set, replacing any existing value
+fn with_param(url: &str, param: &str, value: &str) -> Result<String, String> {
+    let mut parsed = Url::parse(url).map_err(|e| format!("Invalid URL {}: {}", url, e))?;
+    let others: Vec<(String, String)> = parsed
+        .query_pairs()
+        .filter(|(k, _)| k != param)
+        .map(|(k, v)| (k.into_owned(), v.into_owned()))
+        .collect();
+    parsed.query_pairs_mut().clear().extend_pairs(others).append_pair(param, value);
+    Ok(parsed.to_string())
+}
+
+/// GET a URL, returning the JSON body and the next-page Link header if any
+fn fetch(url: &str, auth_header: Option<&str>) -> Result<(Value, Option<String>), String> {
+    let mut request = ureq::get(url).set("Accept", "application/json");
+    if let Some(auth) = auth_header.filter(|a| !a.trim().is_empty()) {
+        request = request.set("Authorization", auth);
+    }
+    let response = request.call().map_err(|e| format!("Failed to fetch {}: {}", url, e))?;
+    let next = response.header("Link").and_then(link_header_next);
+    let text = response
+        .into_string()
+        .map_err(|e| format!("Failed to read response from {}: {}", url, e))?;
+    let body = serde_json::from_str(&text).map_err(|e| format!("{} did not return JSON: {}", url, e))?;
+    Ok((body, next))
+}
+
+/// The records on one page of a response
+fn page_records(body: &Value, records_path: Option<&str>) -> Result<Vec<Value>, String> {
+    match records_path.filter(|p| !p.is_empty()) {
+        Some(path) => match lookup(body, path) {
+            Some(Value::Array(items)) => Ok(items.clone()),
+            Some(_) => Err(format!("{} is not an array in the response", path)),
+            None => Ok(Vec::new()),
+        },
+        None if body.as_array().is_some_and(|a| a.is_empty()) => Ok(Vec::new()),
+        None => crate::extract_data_array(body.clone()).map(|(records, _)| records),
+    }
+}
+
+/// Fetch JSON records from an API, following its pagination until a page
+/// comes back empty, there is no next page, or `max_rows` is reached. The
+/// merged records go through the same flattening as JSON files.
+pub fn fetch_records(request: &ApiRequest) -> Result<Dataset, AppError> {
+    let max_rows = request.max_rows.unwrap_or(DEFAULT_MAX_ROWS);
+    let auth = request.auth_header.as_deref();
+    let pagination = request.pagination.as_ref();
+    let kind = pagination.map(|p| p.kind.as_str());
+    if let Some(other) = kind.filter(|k| !matches!(*k, "page" | "offset" | "next_link" | "cursor")) {
+        return Err(format!("Unknown pagination kind: {}", other).into());
+    }
+    let param = |default: &str| {
+        pagination
+            .and_then(|p| p.param.clone())
+            .unwrap_or_else(|| default.to_string())
+    };
+    let next_path = pagination.and_then(|p| p.next_path.as_deref());
+    if kind == Some("cursor") && next_path.is_none() {
+        return Err("Cursor pagination needs next_path to find the cursor".into());
+    }
+
+    let mut counter = pagination
+        .and_then(|p| p.start)
+        .unwrap_or(if kind == Some("page") { 1 } else { 0 });
+    let mut url = match kind {
+        Some("page") => with_param(&request.url, &param("page"), &counter.to_string())?,
+        Some("offset") => with_param(&request.url, &param("offset"), &counter.to_string())?,
+        _ => request.url.clone(),
+    };
+
+    let mut records: Vec<Value> = Vec::new();
+    let mut seen_cursors = std::collections::HashSet::new();
+    for _ in 0..MAX_PAGES {
+        let (body, link_next) = fetch(&url, auth)?;
+        let page = page_records(&body, request.records_path.as_deref())?;
+        if page.is_empty() {
+            break;
+        }
+        let page_len = page.len() as u64;
+        records.extend(page);
+        if records.len() >= max_rows {
+            records.truncate(max_rows);
+            break;
+        }
+
+        let next_value = next_path
+            .and_then(|path| lookup(&body, path))
+            .and_then(|v| match v {
+                Value::String(s) if !s.is_empty() => Some(s.clone()),
+                Value::Number(n) => Some(n.to_string()),
+                _ => None,
+            });
+        url = match kind {
+            Some("page") => {
+                counter += 1;
+                with_param(&url, &param("page"), &counter.to_string())?
+            }
+            Some("offset") => {
+                counter += page_len;
+                with_param(&url, &param("offset"), &counter.to_string())?
+            }
+            Some("next_link") => match next_value.or(link_next) {
+                // Relative links are resolved against the current page
+                Some(next) => Url::parse(&url)
+                    .and_then(|base| base.join(&next))
+                    .map_err(|e| format!("Invalid next link {}: {}", next, e))?
+                    .to_string(),
+                None => break,
+            },
+            Some("cursor") => match next_value {
+                Some(cursor) if seen_cursors.insert(cursor.clone()) => {
+                    with_param(&url, &param("cursor"), &cursor)?
+                }
+                _ => break,
+            },
+            _ => break,
+        };
+    }
+
+    if records.is_empty() {
+        return Err("The API returned no records".into());
+    }
+    let mut dataset = crate::json_to_dataset(Value::Array(records))?;
+    dataset.file_name = Url::parse(&request.url)
+        .map(|u| format!("{}{}", u.host_str().unwrap_or_default(), u.path()))
+        .unwrap_or_else(|_| request.url.clone());
+    dataset.origin = Some(request.url.clone());
+    Ok(dataset)
+}
+
+/// Fetch JSON records from an API and open them; see `fetch_records`
+#[tauri::command(async)]
+pub fn fetch_api(store: State<'_, DatasetStore>, request: ApiRequest) -> Result<FileData, AppError> {
+    let _timed = logging::timed("fetch_api");
+    let mut dataset = fetch_records(&request)?;
+    dataset.remote = Some(RemoteSource::Api { request });
+    Ok(store.register(dataset))
+}
@@ -0,0 +1,229 @@
+use crate::access;
+use crate::dataset::{Dataset, DatasetStore, FileData};
+use crate::logging;
+use crate::recipe::{self, RecipeStep};
+use crate::storage;
+use crate::error::AppError;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::Duration;
+use tauri::{AppHandle, Manager, State};
+
+const AUTOSAVE_DIR: &str = "autosave";
+/// How often unsaved work is written out
+const AUTOSAVE_INTERVAL: Duration = Duration::from_secs(30);
+
+/// Autosave bookkeeping for this run. Files are named `<session>-<handle>.json`,
+/// so files from any other session were left behind by a crash.
+pub struct Autosave {
+    session: String,
+    /// Journal version last written, per dataset handle
+    written: Mutex<HashMap<u64, u64>>,
+}
+
+impl Default for Autosave {
+    fn default() -> Self {
+        Autosave {
+            session: chrono::Local::now().timestamp_millis().to_string(),
+            written: Mutex::new(HashMap::new()),
+        }
+    }
+}
+
+/// Unsaved work for one dataset. Edited datasets keep their whole table;
+/// unedited transform results only need the steps to rebuild them.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct AutosaveFile {
+    file_name: String,
+    file_type: String,
+    source_path: Option<String>,
+    origin: Option<String>,
+    #[serde(default)]
+    steps: Vec<RecipeStep>,
+    saved_at: String,
+    headers: Vec<String>,
+    rows: Option<Vec<Vec<String>>>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct RecoverableDataset {
+    pub id: String,
+    pub file_name: String,
+    /// The file edits would be saved to, or the file transforms started from
+    pub path: Option<String>,
+    pub saved_at: String,
+    /// Whether there are cell edits; otherwise only transforms are recovered
+    pub edited: bool,
+}
+
+fn autosave_path(dir: &Path, id: &str) -> PathBuf {
+    dir.join(format!("{}.json", id))
+}
+
+/// What to write for a dataset, if it holds anything that would be lost
+fn snapshot(ds: &Dataset) -> Option<AutosaveFile> {
+    let derived = ds.source_path.is_none() && !ds.steps.is_empty();
+    if !ds.dirty && !derived {
+        return None;
+    }
+    Some(AutosaveFile {
+        file_name: ds.file_name.clone(),
+        file_type: ds.file_type.clone(),
+        source_path: ds.source_path.clone(),
+        origin: ds.origin.clone(),
+        steps: ds.steps.clone(),
+        saved_at: chrono::Local::now().to_rfc3339(),
+        headers: ds.headers.clone(),
+        rows: ds.dirty.then(|| ds.rows.clone()),
+    })
+}
+
+/// Write out datasets that changed since the last pass and drop files for
+/// datasets that were saved or closed
+fn autosave_once(app: &AppHandle) -> Result<(), String> {
+    let store = app.state::<DatasetStore>();
+    let state = app.state::<Autosave>();
+    let dir = storage::dir(app, AUTOSAVE_DIR)?;
+    let mut written = state.written.lock().unwrap();
+
+    let handles = store.handles();
+    for &handle in &handles {
+        let id = format!("{}-{}", state.session, handle);
+        let (version, file) = store.with(handle, |ds| (ds.journal.version(), snapshot(ds)))?;
+        match file {
+            Some(file) if written.get(&handle) != Some(&version) => {
+                let json = serde_json::to_string(&file).map_err(|e| format!("Failed to serialize autosave: {}", e))?;
+                fs::write(autosave_path(&dir, &id), json).map_err(|e| format!("Failed to write autosave: {}", e))?;
+                written.insert(handle, version);
+            }
+            Some(_) => {}
+            None => {
+                if written.remove(&handle).is_some() {
+                    let _ = fs::remove_file(autosave_path(&dir, &id));
+                }
+            }
+        }
+    }
+    written.retain(|handle, _| {
+        let open = handles.contains(handle);
+        if !open {
+            let _ = fs::remove_file(autosave_path(&dir, &format!("{}-{}", state.session, handle)));
+        }
+        open
+    });
+    Ok(())
+}
+
+/// Autosave in the background for as long as the app runs
+pub fn start(app: &AppHandle) {
+    let app = app.clone();
+    std::thread::spawn(move || loop {
+        std::thread::sleep(AUTOSAVE_INTERVAL);
+        if let Err(e) = autosave_once(&app) {
+            tracing::warn!("Autosave failed: {}", e);
+        }
+    });
+}
+
+/// Remove this session's autosaves after a clean exit
+pub fn clear(app: &AppHandle) {
+    let state = app.state::<Autosave>();
+    if let Ok(dir) = storage::dir(app, AUTOSAVE_DIR) {
+        for handle in state.written.lock().unwrap().drain().map(|(handle, _)| handle) {
+            let _ = fs::remove_file(autosave_path(&dir, &format!("{}-{}", state.session, handle)));
+        }
+    }
+}
+
+/// Autosave ids come back from the frontend; only accept ones we could have made
+fn check_id(id: &str) -> Result<(), String> {
+    if id.is_empty() || !id.chars().all(|c| c.is_ascii_digit() || c == '-') {
+        return Err(format!("Invalid autosave id: {}", id));
+    }
+    Ok(())
+}
+
+fn read_autosave(dir: &Path, id: &str) -> Result<AutosaveFile, String> {
+    check_id(id)?;
+    let content = fs::read_to_string(autosave_path(dir, id)).map_err(|e| format!("Failed to read autosave: {}", e))?;
+    serde_json::from_str(&content).map_err(|e| format!("Invalid autosave file: {}", e))
+}
+
+/// Unsaved work left behind by a session that didn't exit cleanly
+#[tauri::command]
+pub fn list_autosaves(app: AppHandle, autosave: State<'_, Autosave>) -> Result<Vec<RecoverableDataset>, AppError> {
+    let dir = storage::dir(&app, AUTOSAVE_DIR)?;
+    let entries = fs::read_dir(&dir).map_err(|e| format!("Failed to read autosave directory: {}", e))?;
+    let current = format!("{}-", autosave.session);
+    let mut recoverable: Vec<RecoverableDataset> = entries
+        .filter_map(|entry| entry.ok())
+        .filter_map(|entry| {
+            let id = entry.path().file_stem()?.to_string_lossy().into_owned();
+            if id.starts_with(&current) {
+                return None;
+            }
+            let file = read_autosave(&dir, &id).ok()?;
+            Some(RecoverableDataset {
+                id,
+                file_name: file.file_name,
+                path: file.source_path.or(file.origin),
+                saved_at: file.saved_at,
+                edited: file.rows.is_some(),
+            })
+        })
+        .collect();
+    recoverable.sort_by(|a, b| a.saved_at.cmp(&b.saved_at));
+    Ok(recoverable)
+}
+
+/// Reopen autosaved work as a dataset with unsaved changes, then remove the autosave
+#[tauri::command(async)]
+pub fn recover_autosave(app: AppHandle, store: State<'_, DatasetStore>, id: String) -> Result<FileData, AppError> {
+    let _timed = logging::timed("recover_autosave");
+    let dir = storage::dir(&app, AUTOSAVE_DIR)?;
+    let file = read_autosave(&dir, &id)?;
+
+    let data = match file.rows {
+        Some(rows) => {
+            // Reload the source so the edits can be saved back in its format
+            let mut dataset = file
+                .source_path
+                .as_deref()
+                .filter(|path| access::check(&app, path).is_ok())
+                .and_then(|path| crate::load_file(path).ok())
+                .unwrap_or_default();
+            dataset.headers = file.headers;
+            dataset.rows = rows;
+            // What was read from the file no longer lines up with the rows
+            dataset.record_spans.clear();
+            dataset.parse_failures.clear();
+            dataset.forget_missing_columns();
+            dataset.file_name = file.file_name;
+            dataset.file_type = file.file_type;
+            dataset.origin = file.origin;
+            dataset.steps = file.steps;
+            dataset.dirty = true;
+            store.register(dataset)
+        }
+        None => {
+            let origin = file.origin.ok_or("The autosave has no source file to rebuild from")?;
+            access::check(&app, &origin)?;
+            let handle = store.insert(crate::load_file(&origin)?);
+            let result = recipe::apply_steps(&app, &store, handle, &file.steps);
+            store.remove(handle);
+            result?
+        }
+    };
+    let _ = fs::remove_file(autosave_path(&dir, &id));
+    Ok(data)
+}
+
+#[tauri::command]
+pub fn discard_autosave(app: AppHandle, id: String) -> Result<(), AppError> {
+    check_id(&id)?;
+    let dir = storage::dir(&app, AUTOSAVE_DIR)?;
+    Ok(fs::remove_file(autosave_path(&dir, &id)).map_err(|e| format!("Failed to remove autosave: {}", e))?)
+}
@@ -0,0 +1,119 @@
+use crate::dataset::DatasetStore;
+use crate::recipe::{self, RecipeStep};
+use std::fs;
+use std::path::{Path, PathBuf};
+use tauri::{AppHandle, Manager};
+
+/// Options for a headless run:
+/// `--batch recipe.json --input dir/ --output dir/ [--format csv|json] [--verify]`
+#[derive(Debug)]
+pub struct BatchArgs {
+    pub recipe: String,
+    pub input: PathBuf,
+    pub output: PathBuf,
+    pub format: String,
+    /// Read every output back and fail the file if it doesn't match
+    pub verify: bool,
+}
+
+impl BatchArgs {
+    /// Parse the command line; `Ok(None)` when `--batch` wasn't given
+    pub fn parse(args: &[String]) -> Result<Option<BatchArgs>, String> {
+        let value = |flag: &str| {
+            args.iter()
+                .position(|a| a == flag)
+                .map(|i| args.get(i + 1).cloned().ok_or_else(|| format!("{} needs a value", flag)))
+                .transpose()
+        };
+        let Some(recipe) = value("--batch")? else {
+            return Ok(None);
+        };
+        let input = value("--input")?.ok_or("--batch needs --input <dir>")?;
+        let output = value("--output")?.ok_or("--batch needs --output <dir>")?;
+        let format = value("--format")?.unwrap_or_else(|| "csv".to_string());
+        if format != "csv" && format != "json" {
+            return Err(format!("Unsupported output format: {}", format));
+        }
+        Ok(Some(BatchArgs {
+            recipe,
+            input: PathBuf::from(input),
+            output: PathBuf::from(output),
+            format,
+            verify: args.iter().any(|a| a == "--verify"),
+        }))
+    }
+}
+
+/// Parse one file, run the recipe over it, and export the result
+fn process(app: &AppHandle, path: &Path, steps: &[RecipeStep], args: &BatchArgs) -> Result<PathBuf, String> {
+    let store = app.state::<DatasetStore>();
+    let handle = store.insert(crate::load_file(&path.to_string_lossy())?);
+    let result = if steps.is_empty() {
+        // No steps: just convert the file
+        store.with(handle, |ds| ds.to_file_data(handle))
+    } else {
+        recipe::apply_steps(app, &store, handle, steps)
+    };
+    store.remove(handle);
+    let data = result?;
+    // The result's own rows, not `data.rows`, whose long cells are cut short
+    let rows = store.with(data.handle, |ds| ds.rows.clone());
+    store.remove(data.handle);
+    let rows = rows?;
+
+    let stem = path.file_stem().map(|s| s.to_string_lossy().into_owned()).unwrap_or_default();
+    let target = args.output.join(format!("{}.{}", stem, args.format));
+    let target_str = target.to_string_lossy().into_owned();
+    if args.format == "json" {
+        crate::export_json(app.clone(), app.state(), target_str, data.headers, rows, Some(args.verify), None, None)?;
+    } else {
+        crate::export_csv(app.clone(), app.state(), target_str, data.headers, rows, Some(args.verify), None, None)?;
+    }
+    Ok(target)
+}
+
+/// Apply the recipe to every CSV/JSON/JSONL file in the input folder and
+/// return the process exit code: 0 on success, 1 if any file failed
+pub fn run(app: &AppHandle, args: &BatchArgs) -> i32 {
+    let recipe = match recipe::read_recipe_file(&args.recipe) {
+        Ok(recipe) => recipe,
+        Err(e) => {
+            eprintln!("{}", e);
+            return 2;
+        }
+    };
+    let mut files: Vec<PathBuf> = match fs::read_dir(&args.input) {
+        Ok(entries) => entries
+            .filter_map(|e| e.ok().map(|e| e.path()))
+            .filter(|p| {
+                p.is_file()
+                    && matches!(
+                        p.extension().and_then(|e| e.to_str()).map(|e| e.to_ascii_lowercase()).as_deref(),
+                        Some("csv" | "json" | "jsonl")
+                    )
+            })
+            .collect(),
+        Err(e) => {
+            eprintln!("Failed to read input folder: {}", e);
+            return 2;
+        }
+    };
+    files.sort();
+    if let Err(e) = fs::create_dir_all(&args.output) {
+        eprintln!("Failed to create output folder: {}", e);
+        return 2;
+    }
+
+    let mut failed = 0;
+    for path in &files {
+        match process(app, path, &recipe.steps, args) {
+            Ok(target) => println!("{} -> {}", path.display(), target.display()),
+            Err(e) => {
+                failed += 1;
+                eprintln!("{}: {}", path.display(), e);
+            }
+        }
+    }
+    println!("Processed {} of {} files", files.len() - failed, files.len());
+    if failed > 0 { 1 } else { 0 }
+}
@@ -0,0 +1,378 @@
+use crate::access;
+use crate::dataset::{Dataset, DatasetStore, FileData};
+use crate::history;
+use crate::error::AppError;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::{BTreeMap, HashMap};
+use std::fs;
+use tauri::{AppHandle, State};
+
+/// Appended to the data file's path to name its sidecar, e.g. "data.csv.qdv.json"
+const SIDECAR_SUFFIX: &str = ".qdv.json";
+
+/// The bookmark, note, and tags on one row, as stored in the sidecar file
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Annotation {
+    /// Hash of the row's values; see `row_ids`
+    pub row_id: String,
+    /// Which of several identical rows this is, counting from 0
+    #[serde(default)]
+    pub occurrence: usize,
+    #[serde(default)]
+    pub bookmarked: bool,
+    #[serde(default)]
+    pub note: String,
+    /// Review labels such as "needs-review" or "approved"
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<String>,
+    /// When the annotation was last changed, RFC 3339
+    #[serde(default)]
+    pub updated: String,
+}
+
+impl Annotation {
+    fn new(row_id: String, occurrence: usize) -> Annotation {
+        Annotation {
+            row_id,
+            occurrence,
+            bookmarked: false,
+            note: String::new(),
+            tags: Vec::new(),
+            updated: String::new(),
+        }
+    }
+
+    fn is_empty(&self) -> bool {
+        !self.bookmarked && self.note.trim().is_empty() && self.tags.is_empty()
+    }
+}
+
+/// Review data kept next to a data file
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct Sidecar {
+    #[serde(default)]
+    pub annotations: Vec<Annotation>,
+}
+
+/// An annotation resolved to the row it currently belongs to
+#[derive(Debug, Serialize)]
+pub struct RowAnnotation {
+    pub row: usize,
+    pub bookmarked: bool,
+    pub note: String,
+    pub tags: Vec<String>,
+    pub updated: String,
+}
+
+impl RowAnnotation {
+    fn new(row: usize, annotation: Annotation) -> RowAnnotation {
+        RowAnnotation {
+            row,
+            bookmarked: annotation.bookmarked,
+            note: annotation.note,
+            tags: annotation.tags,
+            updated: annotation.updated,
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct Annotations {
+    pub rows: Vec<RowAnnotation>,
+    /// Annotations whose row no longer exists, e.g. because it was edited in
+    /// another program; they stay in the sidecar file
+    pub unmatched: usize,
+}
+
+fn sidecar_path(ds: &Dataset) -> Result<String, String> {
+    ds.source_path
+        .as_ref()
+        .filter(|path| !path.contains("://"))
+        .map(|path| format!("{}{}", path, SIDECAR_SUFFIX))
+        .ok_or_else(|| "Bookmarks are only available for datasets loaded from a local file".to_string())
+}
+
+pub fn load_sidecar(path: &str) -> Result<Sidecar, String> {
+    match fs::read_to_string(path) {
+        Ok(content) => serde_json::from_str(&content).map_err(|e| format!("Failed to parse {}: {}", path, e)),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(Sidecar::default()),
+        Err(e) => Err(format!("Failed to read {}: {}", path, e)),
+    }
+}
+
+pub fn save_sidecar(app: &AppHandle, path: &str, sidecar: &Sidecar) -> Result<(), String> {
+    access::check(app, path)?;
+    if sidecar.annotations.is_empty() {
+        return match fs::remove_file(path) {
+            Err(e) if e.kind() != std::io::ErrorKind::NotFound => Err(format!("Failed to remove {}: {}", path, e)),
+            _ => Ok(()),
+        };
+    }
+    let json = serde_json::to_string_pretty(sidecar).map_err(|e| format!("Failed to serialize annotations: {}", e))?;
+    fs::write(path, json).map_err(|e| format!("Failed to write {}: {}", path, e))
+}
+
+fn row_hash(row: &[String]) -> String {
+    let mut hasher = Sha256::new();
+    for cell in row {
+        hasher.update(cell.as_bytes());
+        hasher.update([0x1f]);
+    }
+    hex::encode(&hasher.finalize()[..16])
+}
+
+/// A stable identity for every row: a hash of its values plus, for duplicate
+/// rows, its position among them. Unlike row numbers, this survives rows
+/// being sorted, inserted, or deleted elsewhere in the file.
+pub fn row_ids(ds: &Dataset) -> Vec<(String, usize)> {
+    let mut seen: HashMap<String, usize> = HashMap::new();
+    ds.rows
+        .iter()
+        .map(|row| {
+            let id = row_hash(row);
+            let count = seen.entry(id.clone()).or_default();
+            *count += 1;
+            (id, *count - 1)
+        })
+        .collect()
+}
+
+fn row_id(ds: &Dataset, row: usize) -> Result<(String, usize), String> {
+    if row >= ds.rows.len() {
+        return Err(format!("Row {} is out of range", row));
+    }
+    let id = row_hash(&ds.rows[row]);
+    let occurrence = ds.rows[..row].iter().filter(|r| row_hash(r) == id).count();
+    Ok((id, occurrence))
+}
+
+/// Bookmark a row and/or set its note, saved to the sidecar file next to the
+/// data file. Leaving out `bookmarked` or `note` keeps the current value;
+/// a row left with no bookmark, note, or tags is dropped from the file.
+#[tauri::command]
+pub fn annotate_row(
+    app: AppHandle,
+    store: State<'_, DatasetStore>,
+    handle: u64,
+    row: usize,
+    bookmarked: Option<bool>,
+    note: Option<String>,
+) -> Result<Option<RowAnnotation>, AppError> {
+    let (path, (row_id, occurrence)) = store.with(handle, |ds| Ok::<_, String>((sidecar_path(ds)?, row_id(ds, row)?)))??;
+    let mut sidecar = load_sidecar(&path)?;
+    let position = sidecar
+        .annotations
+        .iter()
+        .position(|a| a.row_id == row_id && a.occurrence == occurrence);
+    let mut annotation = match position {
+        Some(i) => sidecar.annotations.remove(i),
+        None => Annotation::new(row_id, occurrence),
+    };
+    if let Some(bookmarked) = bookmarked {
+        annotation.bookmarked = bookmarked;
+    }
+    if let Some(note) = note {
+        annotation.note = note;
+    }
+    annotation.updated = chrono::Local::now().to_rfc3339();
+
+    let result = (!annotation.is_empty()).then(|| RowAnnotation::new(row, annotation.clone()));
+    if !annotation.is_empty() {
+        sidecar.annotations.push(annotation);
+    }
+    save_sidecar(&app, &path, &sidecar)?;
+    Ok(result)
+}
+
+/// Each saved annotation matched to the row it belongs to, plus the number
+/// that matched no row
+fn resolve(ds: &Dataset, sidecar: Sidecar) -> (BTreeMap<usize, Annotation>, usize) {
+    if sidecar.annotations.is_empty() {
+        return (BTreeMap::new(), 0);
+    }
+    let rows: HashMap<(String, usize), usize> = row_ids(ds).into_iter().enumerate().map(|(i, id)| (id, i)).collect();
+    let mut matched = BTreeMap::new();
+    let mut unmatched = 0;
+    for annotation in sidecar.annotations {
+        match rows.get(&(annotation.row_id.clone(), annotation.occurrence)) {
+            Some(&row) => {
+                matched.insert(row, annotation);
+            }
+            None => unmatched += 1,
+        }
+    }
+    (matched, unmatched)
+}
+
+/// The bookmarks, notes, and tags saved for the dataset's file, matched to
+/// its current rows
+#[tauri::command]
+pub fn get_annotations(store: State<'_, DatasetStore>, handle: u64) -> Result<Annotations, AppError> {
+    store.with(handle, |ds| {
+        let (matched, unmatched) = resolve(ds, load_sidecar(&sidecar_path(ds)?)?);
+        Ok(Annotations {
+            rows: matched.into_iter().map(|(row, a)| RowAnnotation::new(row, a)).collect(),
+            unmatched,
+        })
+    })?
+}
+
+/// Add `tag` to the given rows, or take it off them when `remove` is set.
+/// Returns how many rows changed.
+#[tauri::command]
+pub fn tag_rows(
+    app: AppHandle,
+    store: State<'_, DatasetStore>,
+    handle: u64,
+    rows: Vec<usize>,
+    tag: String,
+    remove: Option<bool>,
+) -> Result<usize, AppError> {
+    let tag = tag.trim().to_string();
+    if tag.is_empty() {
+        return Err("Tag cannot be empty".into());
+    }
+    let remove = remove.unwrap_or(false);
+    let (path, ids) = store.with(handle, |ds| {
+        if let Some(&row) = rows.iter().find(|&&r| r >= ds.rows.len()) {
+            return Err(format!("Row {} is out of range", row));
+        }
+        let ids = row_ids(ds);
+        Ok((sidecar_path(ds)?, rows.iter().map(|&r| ids[r].clone()).collect::<Vec<_>>()))
+    })??;
+
+    let mut sidecar = load_sidecar(&path)?;
+    let mut positions: HashMap<(String, usize), usize> = sidecar
+        .annotations
+        .iter()
+        .enumerate()
+        .map(|(i, a)| ((a.row_id.clone(), a.occurrence), i))
+        .collect();
+    let updated = chrono::Local::now().to_rfc3339();
+    let mut changed = 0;
+    for (row_id, occurrence) in ids {
+        let i = match positions.get(&(row_id.clone(), occurrence)) {
+            Some(&i) => i,
+            None if remove => continue,
+            None => {
+                sidecar.annotations.push(Annotation::new(row_id.clone(), occurrence));
+                positions.insert((row_id, occurrence), sidecar.annotations.len() - 1);
+                sidecar.annotations.len() - 1
+            }
+        };
+        let annotation = &mut sidecar.annotations[i];
+        let has_tag = annotation.tags.contains(&tag);
+        if remove && has_tag {
+            annotation.tags.retain(|t| *t != tag);
+        } else if !remove && !has_tag {
+            annotation.tags.push(tag.clone());
+        } else {
+            continue;
+        }
+        annotation.updated = updated.clone();
+        changed += 1;
+    }
+    sidecar.annotations.retain(|a| !a.is_empty());
+    save_sidecar(&app, &path, &sidecar)?;
+    Ok(changed)
+}
+
+#[derive(Debug, Serialize)]
+pub struct TagCount {
+    pub tag: String,
+    pub rows: usize,
+}
+
+/// The tags in use on the dataset's rows, most used first
+#[tauri::command]
+pub fn list_tags(store: State<'_, DatasetStore>, handle: u64) -> Result<Vec<TagCount>, AppError> {
+    store.with(handle, |ds| {
+        let (matched, _) = resolve(ds, load_sidecar(&sidecar_path(ds)?)?);
+        let mut counts: HashMap<String, usize> = HashMap::new();
+        for tag in matched.into_values().flat_map(|a| a.tags) {
+            *counts.entry(tag).or_default() += 1;
+        }
+        let mut tags: Vec<TagCount> = counts.into_iter().map(|(tag, rows)| TagCount { tag, rows }).collect();
+        tags.sort_by(|a, b| b.rows.cmp(&a.rows).then_with(|| a.tag.cmp(&b.tag)));
+        Ok(tags)
+    })?
+}
+
+/// A new dataset with only the rows carrying any of `tags`; with `untagged`,
+/// the rows carrying none instead
+#[tauri::command]
+pub fn filter_by_tag(
+    app: AppHandle,
+    store: State<'_, DatasetStore>,
+    handle: u64,
+    tags: Vec<String>,
+    untagged: Option<bool>,
+) -> Result<FileData, AppError> {
+    let untagged = untagged.unwrap_or(false);
+    let mut result = store.with(handle, |ds| {
+        let (matched, _) = resolve(ds, load_sidecar(&sidecar_path(ds)?)?);
+        let has_tag = |row: usize| matched.get(&row).is_some_and(|a| a.tags.iter().any(|t| tags.contains(t)));
+        Ok::<_, String>(Dataset {
+            headers: ds.headers.clone(),
+            rows: (0..ds.rows.len())
+                .filter(|&row| has_tag(row) != untagged)
+                .map(|row| ds.rows[row].clone())
+                .collect(),
+            file_name: format!("{} (tagged)", ds.file_name),
+            file_type: ds.file_type.clone(),
+            units: ds.units.clone(),
+            ..Default::default()
+        })
+    })??;
+    history::record_transform(
+        &app,
+        &store,
+        handle,
+        &mut result,
+        "filter_by_tag",
+        serde_json::json!({ "tags": tags, "untagged": untagged }),
+    );
+    Ok(store.register(result))
+}
+
+/// A new dataset with each row's tags in an extra column (default "tags"),
+/// joined with "; ", ready to export
+#[tauri::command]
+pub fn add_tag_column(
+    app: AppHandle,
+    store: State<'_, DatasetStore>,
+    handle: u64,
+    name: Option<String>,
+) -> Result<FileData, AppError> {
+    let name = name.unwrap_or_else(|| "tags".to_string());
+    let mut result = store.with(handle, |ds| {
+        if ds.headers.contains(&name) {
+            return Err(format!("Column already exists: {}", name));
+        }
+        let (matched, _) = resolve(ds, load_sidecar(&sidecar_path(ds)?)?);
+        let mut headers = ds.headers.clone();
+        headers.push(name.clone());
+        let rows = ds
+            .rows
+            .iter()
+            .enumerate()
+            .map(|(i, row)| {
+                let mut row = row.clone();
+                row.resize(ds.headers.len(), String::new());
+                row.push(matched.get(&i).map(|a| a.tags.join("; ")).unwrap_or_default());
+                row
+            })
+            .collect();
+        Ok(Dataset {
+            headers,
+            rows,
+            file_name: format!("{} (with tags)", ds.file_name),
+            file_type: ds.file_type.clone(),
+            units: ds.units.clone(),
+            ..Default::default()
+        })
+    })??;
+    history::record_transform(&app, &store, handle, &mut result, "add_tag_column", serde_json::json!({ "name": name }));
+    Ok(store.register(result))
+}
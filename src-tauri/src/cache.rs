@@ -0,0 +1,105 @@
+use crate::dataset::DatasetStore;
+use crate::error::AppError;
+use serde::Serialize;
+use std::any::Any;
+use std::collections::HashMap;
+use tauri::State;
+
+/// Results kept across all datasets; the least recently used go first
+const MAX_ENTRIES: usize = 64;
+
+struct Entry {
+    value: Box<dyn Any + Send>,
+    last_used: u64,
+}
+
+/// Results of expensive reads (statistics, aggregations, sorts, queries),
+/// keyed by dataset handle and operation. The store drops a dataset's
+/// entries whenever it is modified or closed, so a hit always matches the
+/// data. Query results belong to no dataset and are kept under `UNOWNED`.
+#[derive(Default)]
+pub struct ResultCache {
+    entries: HashMap<(u64, String), Entry>,
+    clock: u64,
+    /// Bumped each time a dataset's results are dropped, so a result worked
+    /// out from an older copy of it isn't kept
+    generations: HashMap<u64, u64>,
+}
+
+/// Handle for results that belong to no dataset; no dataset is given 0
+pub const UNOWNED: u64 = 0;
+
+/// Cache key for an operation and the parameters it was called with
+pub fn key(operation: &str, params: &impl Serialize) -> String {
+    format!("{}:{}", operation, serde_json::to_string(params).unwrap_or_default())
+}
+
+impl ResultCache {
+    /// The cached result for `key`, or `compute`'s result, which is kept if
+    /// it succeeds
+    pub fn get_or_insert<T: Clone + Send + 'static, E>(
+        &mut self,
+        handle: u64,
+        key: String,
+        compute: impl FnOnce() -> Result<T, E>,
+    ) -> Result<T, E> {
+        if let Some(value) = self.get(handle, &key) {
+            return Ok(value);
+        }
+        let value = compute()?;
+        self.insert(handle, key, value.clone());
+        Ok(value)
+    }
+
+    /// The cached result for `key`, if there is one
+    pub fn get<T: Clone + 'static>(&mut self, handle: u64, key: &str) -> Option<T> {
+        self.clock += 1;
+        let entry = self.entries.get_mut(&(handle, key.to_string()))?;
+        let value = entry.value.downcast_ref::<T>()?;
+        entry.last_used = self.clock;
+        tracing::debug!(handle, key, "cache hit");
+        Some(value.clone())
+    }
+
+    /// Keep a result, pushing out the least recently used one if full
+    pub fn insert<T: Send + 'static>(&mut self, handle: u64, key: String, value: T) {
+        self.clock += 1;
+        let key = (handle, key);
+        if self.entries.len() >= MAX_ENTRIES && !self.entries.contains_key(&key) {
+            if let Some(oldest) = self.entries.iter().min_by_key(|(_, e)| e.last_used).map(|(k, _)| k.clone()) {
+                self.entries.remove(&oldest);
+            }
+        }
+        self.entries.insert(
+            key,
+            Entry {
+                value: Box::new(value),
+                last_used: self.clock,
+            },
+        );
+    }
+
+    /// Forget every result for a dataset
+    pub fn invalidate(&mut self, handle: u64) {
+        self.entries.retain(|(h, _), _| *h != handle);
+        *self.generations.entry(handle).or_default() += 1;
+    }
+
+    /// How many times a dataset's results have been dropped
+    pub fn generation(&self, handle: u64) -> u64 {
+        self.generations.get(&handle).copied().unwrap_or_default()
+    }
+
+    pub fn clear(&mut self) -> usize {
+        let count = self.entries.len();
+        self.entries.clear();
+        count
+    }
+}
+
+/// Drop every cached result, e.g. to time an operation from scratch.
+/// Returns how many were dropped.
+#[tauri::command]
+pub fn clear_result_cache(store: State<'_, DatasetStore>) -> Result<usize, AppError> {
+    Ok(store.clear_cache())
+}
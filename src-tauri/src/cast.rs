@@ -0,0 +1,152 @@
+use crate::dataset::{DatasetStore, JsonKind};
+use crate::dates;
+use crate::journal::Edit;
+use crate::stats::{is_null, parse_number};
+use crate::types::{parse_bool, ColumnType};
+use crate::error::AppError;
+use chrono::{NaiveDate, NaiveDateTime, Timelike};
+use serde::{Deserialize, Serialize};
+use tauri::State;
+
+/// Failures beyond this many are counted but not listed
+const MAX_REPORTED_FAILURES: usize = 1000;
+
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct CastOptions {
+    /// chrono format of the incoming dates; ISO-style dates are detected when omitted
+    #[serde(default)]
+    pub input_format: Option<String>,
+    /// chrono format for converted dates (default `%Y-%m-%d`, plus time when present)
+    #[serde(default)]
+    pub output_format: Option<String>,
+    /// Leave the column untouched if any cell fails to convert
+    #[serde(default)]
+    pub strict: bool,
+}
+
+#[derive(Debug, Serialize)]
+pub struct CastFailure {
+    pub row: usize,
+    pub value: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct CastReport {
+    pub converted: usize,
+    pub failed: usize,
+    /// The first failing cells, which are left as they were
+    pub failures: Vec<CastFailure>,
+    /// False when `strict` was set and the cast was abandoned
+    pub applied: bool,
+}
+
+/// A date in `input_format`, or in any format `dates` recognizes when none is given
+pub fn parse_date(value: &str, input_format: Option<&str>) -> Option<NaiveDateTime> {
+    match input_format {
+        Some(format) => NaiveDateTime::parse_from_str(value, format).ok().or_else(|| {
+            NaiveDate::parse_from_str(value, format)
+                .ok()
+                .and_then(|d| d.and_hms_opt(0, 0, 0))
+        }),
+        None => dates::parse_datetime(value),
+    }
+}
+
+/// Convert one non-null cell, or `None` if it has no representation in `target`
+fn convert(value: &str, target: ColumnType, options: &CastOptions) -> Option<String> {
+    let value = value.trim();
+    match target {
+        ColumnType::Integer => {
+            let n = parse_number(value)?;
+            (n.fract() == 0.0 && n.abs() < i64::MAX as f64).then(|| (n as i64).to_string())
+        }
+        ColumnType::Float => parse_number(value).map(|n| n.to_string()),
+        ColumnType::Boolean => match value {
+            "1" => Some(true),
+            "0" => Some(false),
+            _ => parse_bool(value),
+        }
+        .map(|b| b.to_string()),
+        ColumnType::Date => {
+            let dt = parse_date(value, options.input_format.as_deref())?;
+            let format = options.output_format.as_deref().unwrap_or(
+                if dt.time().num_seconds_from_midnight() == 0 { "%Y-%m-%d" } else { "%Y-%m-%d %H:%M:%S" },
+            );
+            dates::format_datetime(dt, format)
+        }
+        ColumnType::String => Some(value.to_string()),
+        ColumnType::Empty => None,
+    }
+}
+
+/// Convert every value in a column to `target_type`. Cells that can't be
+/// converted are left as they were and listed in the report.
+#[tauri::command]
+pub fn cast_column(
+    store: State<'_, DatasetStore>,
+    handle: u64,
+    column: String,
+    target_type: ColumnType,
+    options: Option<CastOptions>,
+) -> Result<CastReport, AppError> {
+    let options = options.unwrap_or_default();
+    if target_type == ColumnType::Empty {
+        return Err("Cannot cast to the empty type".into());
+    }
+    if let Some(format) = &options.output_format {
+        dates::check_pattern(format)?;
+    }
+
+    store.with_mut(handle, |ds| {
+        let col = ds.column_index(&column)?;
+        let mut converted: Vec<(usize, String)> = Vec::new();
+        let mut failures = Vec::new();
+        let mut failed = 0;
+        for (i, row) in ds.rows.iter().enumerate() {
+            let value = row.get(col).map(|v| v.as_str()).unwrap_or("");
+            if is_null(value) {
+                continue;
+            }
+            match convert(value, target_type, &options) {
+                Some(new) => {
+                    if new != value {
+                        converted.push((i, new));
+                    }
+                }
+                None => {
+                    failed += 1;
+                    if failures.len() < MAX_REPORTED_FAILURES {
+                        failures.push(CastFailure { row: i, value: value.to_string() });
+                    }
+                }
+            }
+        }
+
+        let applied = !(options.strict && failed > 0);
+        let count = converted.len();
+        if applied {
+            let kind = match target_type {
+                ColumnType::Integer | ColumnType::Float => JsonKind::Number,
+                ColumnType::Boolean => JsonKind::Bool,
+                _ => JsonKind::String,
+            };
+            let inverse = Edit::CastColumn {
+                col,
+                cells: converted,
+                kind: Some(kind),
+                // A type the schema declared follows the cast
+                declared: ds.column_types.contains_key(&column).then_some(target_type),
+            }
+            .apply(ds);
+            ds.record_edit(inverse);
+            ds.dirty = true;
+        }
+
+        Ok(CastReport {
+            converted: if applied { count } else { 0 },
+            failed,
+            failures,
+            applied,
+        })
+    })?
+}
@@ -0,0 +1,301 @@
+use crate::dataset::{Dataset, DatasetStore};
+use crate::error::AppError;
+use crate::journal::Edit;
+use crate::logging;
+use crate::progress::Progress;
+use serde::Serialize;
+use similar::{capture_diff_slices_deadline, Algorithm, DiffTag};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, State};
+
+/// Cell changes listed in full; past this only the count is kept
+const MAX_CELL_CHANGES: usize = 10_000;
+
+/// How long aligning rows may take before settling for a rougher match
+const DIFF_DEADLINE: Duration = Duration::from_secs(5);
+
+/// The table as it was loaded, kept from the first in-place edit on
+#[derive(Debug)]
+pub struct Baseline {
+    pub headers: Vec<String>,
+    pub rows: Vec<Vec<String>>,
+}
+
+impl Baseline {
+    /// The table before the edit `inverse` reverses
+    pub fn before(ds: &Dataset, inverse: &Edit) -> Baseline {
+        let mut scratch = Dataset {
+            headers: ds.headers.clone(),
+            rows: ds.rows.clone(),
+            source_format: ds.source_format.clone(),
+            ..Default::default()
+        };
+        inverse.clone().apply(&mut scratch);
+        Baseline {
+            headers: scratch.headers,
+            rows: scratch.rows,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct CellChange {
+    /// Row in the current table
+    pub row: usize,
+    /// Row in the table as loaded
+    pub original_row: usize,
+    pub column: String,
+    pub old_value: String,
+    pub new_value: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct RemovedRow {
+    /// Row in the table as loaded
+    pub original_row: usize,
+    /// Its values, in the loaded column order
+    pub values: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct RenamedColumn {
+    pub from: String,
+    pub to: String,
+}
+
+/// What differs between the table as loaded and as it is now
+#[derive(Debug, Default, Serialize)]
+pub struct Changes {
+    pub cells: Vec<CellChange>,
+    /// Changed cells, including any past `MAX_CELL_CHANGES`
+    pub cell_count: usize,
+    /// Current rows with at least one changed cell
+    pub modified_rows: Vec<usize>,
+    /// Current columns with at least one changed cell
+    pub modified_columns: Vec<String>,
+    /// Current rows that weren't in the table as loaded
+    pub added_rows: Vec<usize>,
+    pub removed_rows: Vec<RemovedRow>,
+    pub added_columns: Vec<String>,
+    pub removed_columns: Vec<String>,
+    pub renamed_columns: Vec<RenamedColumn>,
+}
+
+/// A row's value in a column, empty past the end of a short row
+fn cell(row: &[String], col: usize) -> &str {
+    row.get(col).map_or("", String::as_str)
+}
+
+/// For each current column, the loaded column it came from. Columns match
+/// by name, then by position when both names are gone (a rename).
+fn match_columns(old: &[String], new: &[String]) -> Vec<Option<usize>> {
+    let mut used = vec![false; old.len()];
+    let mut matched: Vec<Option<usize>> = new
+        .iter()
+        .map(|name| {
+            let i = old.iter().zip(&used).position(|(o, &u)| !u && o == name)?;
+            used[i] = true;
+            Some(i)
+        })
+        .collect();
+    for (i, m) in matched.iter_mut().enumerate() {
+        if m.is_none() && i < old.len() && !used[i] && !new.contains(&old[i]) {
+            used[i] = true;
+            *m = Some(i);
+        }
+    }
+    matched
+}
+
+/// Compare `baseline` with the current table. Rows are lined up on the
+/// columns both share, so inserted and removed rows don't show up as every
+/// row below them changing.
+pub fn diff(baseline: &Baseline, headers: &[String], rows: &[Vec<String>]) -> Changes {
+    let mut changes = Changes::default();
+    let matched = match_columns(&baseline.headers, headers);
+    let shared: Vec<(usize, usize)> = matched.iter().enumerate().filter_map(|(new, old)| Some(((*old)?, new))).collect();
+
+    for (new, old) in matched.iter().enumerate() {
+        match old {
+            Some(old) if baseline.headers[*old] != headers[new] => changes.renamed_columns.push(RenamedColumn {
+                from: baseline.headers[*old].clone(),
+                to: headers[new].clone(),
+            }),
+            Some(_) => {}
+            None => changes.added_columns.push(headers[new].clone()),
+        }
+    }
+    changes.removed_columns = (0..baseline.headers.len())
+        .filter(|i| !matched.contains(&Some(*i)))
+        .map(|i| baseline.headers[i].clone())
+        .collect();
+
+    let old_keys: Vec<Vec<&str>> = baseline
+        .rows
+        .iter()
+        .map(|row| shared.iter().map(|&(o, _)| cell(row, o)).collect())
+        .collect();
+    let new_keys: Vec<Vec<&str>> = rows.iter().map(|row| shared.iter().map(|&(_, n)| cell(row, n)).collect()).collect();
+    let deadline = Instant::now() + DIFF_DEADLINE;
+    let ops = capture_diff_slices_deadline(Algorithm::Myers, &old_keys, &new_keys, Some(deadline));
+
+    let mut modified_columns = vec![false; headers.len()];
+    for op in ops {
+        let (tag, old_range, new_range) = op.as_tag_tuple();
+        if tag == DiffTag::Equal {
+            continue;
+        }
+        // Rows replaced one for one are the same rows with cells changed
+        let paired = old_range.len().min(new_range.len());
+        for (original_row, row) in old_range.clone().zip(new_range.clone()).take(paired) {
+            let mut modified = false;
+            for &(o, n) in &shared {
+                let (old_value, new_value) = (cell(&baseline.rows[original_row], o), cell(&rows[row], n));
+                if old_value == new_value {
+                    continue;
+                }
+                modified = true;
+                modified_columns[n] = true;
+                changes.cell_count += 1;
+                if changes.cells.len() < MAX_CELL_CHANGES {
+                    changes.cells.push(CellChange {
+                        row,
+                        original_row,
+                        column: headers[n].clone(),
+                        old_value: old_value.to_string(),
+                        new_value: new_value.to_string(),
+                    });
+                }
+            }
+            if modified {
+                changes.modified_rows.push(row);
+            }
+        }
+        changes.removed_rows.extend(old_range.skip(paired).map(|original_row| RemovedRow {
+            original_row,
+            values: baseline.rows[original_row].clone(),
+        }));
+        changes.added_rows.extend(new_range.skip(paired));
+    }
+    changes.modified_columns = headers
+        .iter()
+        .zip(modified_columns)
+        .filter(|(_, modified)| *modified)
+        .map(|(h, _)| h.clone())
+        .collect();
+    changes
+}
+
+/// The table as loaded, and the current headers and rows
+type Tables = (Option<Arc<Baseline>>, Vec<String>, Vec<Vec<String>>);
+
+/// The dataset's baseline and a copy of its current table, so the diff can
+/// run without holding the store's lock. Rows are only copied once there is
+/// a baseline to compare them with.
+fn tables(store: &DatasetStore, handle: u64) -> Result<Tables, String> {
+    store.with(handle, |ds| {
+        let rows = if ds.baseline.is_some() { ds.rows.clone() } else { Vec::new() };
+        (ds.baseline.clone(), ds.headers.clone(), rows)
+    })
+}
+
+/// The dataset's changes since it was loaded; empty before any edit
+fn changes_for((baseline, headers, rows): &Tables) -> Changes {
+    match baseline {
+        Some(baseline) => diff(baseline, headers, rows),
+        None => Changes::default(),
+    }
+}
+
+/// Cells, rows, and columns changed since the dataset was loaded, including
+/// edits that were saved since
+#[tauri::command(async)]
+pub fn get_changes(store: State<'_, DatasetStore>, handle: u64) -> Result<Changes, AppError> {
+    let _timed = logging::timed("get_changes");
+    Ok(changes_for(&tables(&store, handle)?))
+}
+
+/// Export only what changed: one line per changed cell, added or removed
+/// row cell, and added, removed, or renamed column, with the old and new
+/// value. Rows are keyed by `key_column`'s value, or else their 1-based row
+/// number (in the loaded table for removed rows). `format` is "csv" or
+/// "json"; `verify` works as for `export_csv`.
+#[tauri::command(async)]
+pub fn export_changes(
+    app: AppHandle,
+    store: State<'_, DatasetStore>,
+    handle: u64,
+    file_path: String,
+    format: String,
+    key_column: Option<String>,
+    verify: Option<bool>,
+) -> Result<String, AppError> {
+    let _timed = logging::timed("export_changes");
+    let tables = tables(&store, handle)?;
+    let changes = changes_for(&tables);
+    let (baseline, headers, rows) = tables;
+    if changes.cell_count > changes.cells.len() {
+        return Err(format!(
+            "Too many changed cells to export ({}); at most {} are listed",
+            changes.cell_count, MAX_CELL_CHANGES
+        )
+        .into());
+    }
+    let new_key_col = key_column
+        .as_deref()
+        .map(|name| headers.iter().position(|h| h == name).ok_or_else(|| format!("Column not found: {}", name)))
+        .transpose()?;
+    let new_key = |row: usize| match new_key_col {
+        Some(col) => rows[row].get(col).cloned().unwrap_or_default(),
+        None => (row + 1).to_string(),
+    };
+    let old_key_col = key_column.as_ref().and_then(|name| {
+        let baseline = baseline.as_ref()?;
+        baseline.headers.iter().position(|h| h == name)
+    });
+
+    let total = changes.cells.len() + changes.added_rows.len() + changes.removed_rows.len();
+    let mut progress = Progress::preparing(&app, &file_path, total);
+    let mut lines = Vec::new();
+    let mut line = |key: String, change: &str, column: &str, old: &str, new: &str| {
+        lines.push(vec![key, change.to_string(), column.to_string(), old.to_string(), new.to_string()]);
+    };
+    for column in &changes.added_columns {
+        line(String::new(), "added_column", column, "", "");
+    }
+    for column in &changes.removed_columns {
+        line(String::new(), "removed_column", column, "", "");
+    }
+    for renamed in &changes.renamed_columns {
+        line(String::new(), "renamed_column", &renamed.to, &renamed.from, &renamed.to);
+    }
+    for c in &changes.cells {
+        line(new_key(c.row), "modified", &c.column, &c.old_value, &c.new_value);
+        progress.row_written();
+    }
+    for &row in &changes.added_rows {
+        progress.row_written();
+        for (header, value) in headers.iter().zip(&rows[row]) {
+            line(new_key(row), "added_row", header, "", value);
+        }
+    }
+    if let Some(baseline) = &baseline {
+        for removed in &changes.removed_rows {
+            progress.row_written();
+            let key = match old_key_col {
+                Some(col) => removed.values.get(col).cloned().unwrap_or_default(),
+                None => (removed.original_row + 1).to_string(),
+            };
+            for (header, value) in baseline.headers.iter().zip(&removed.values) {
+                line(key.clone(), "removed_row", header, value, "");
+            }
+        }
+    }
+    let columns = ["row_key", "change", "column", "old_value", "new_value"].map(String::from).to_vec();
+    match format.as_str() {
+        "csv" => crate::export_csv(app, store, file_path, columns, lines, verify, None, None),
+        "json" => crate::export_json(app, store, file_path, columns, lines, verify, None, None),
+        other => Err(format!("Unsupported export format: {}", other).into()),
+    }
+}
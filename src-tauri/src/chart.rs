@@ -0,0 +1,410 @@
+use crate::cache;
+use crate::dataset::{Dataset, DatasetStore};
+use crate::dates::{self, TimeUnit};
+use crate::logging;
+use crate::stats::{is_null, median, parse_number};
+use crate::types::ColumnType;
+use crate::error::AppError;
+use crate::operations::Operations;
+use chrono::NaiveDateTime;
+use serde::Serialize;
+use std::collections::{BTreeMap, HashMap};
+use tauri::{AppHandle, State};
+
+/// Default and largest number of x labels returned
+const DEFAULT_LABELS: usize = 50;
+const MAX_LABELS: usize = 1_000;
+/// Groups beyond this many (the smallest ones) are left out of grouped charts
+const MAX_SERIES: usize = 10;
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ChartSeries {
+    /// The group value, or the y column when not grouped
+    pub name: String,
+    /// One value per label; `None` where the group has no rows for that label
+    pub values: Vec<Option<f64>>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ChartData {
+    pub labels: Vec<String>,
+    pub series: Vec<ChartSeries>,
+    /// Labels and groups beyond the limits, not included
+    pub dropped_labels: usize,
+    pub dropped_series: usize,
+    /// Rows whose y value isn't a number, ignored by numeric aggregates
+    pub skipped_rows: usize,
+}
+
+/// How to reduce the y values that share a label
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Aggregate {
+    Count,
+    Sum,
+    Mean,
+    Min,
+    Max,
+    Median,
+}
+
+impl Aggregate {
+    pub fn parse(name: &str) -> Result<Aggregate, String> {
+        Ok(match name {
+            "count" => Aggregate::Count,
+            "sum" => Aggregate::Sum,
+            "mean" | "avg" => Aggregate::Mean,
+            "min" => Aggregate::Min,
+            "max" => Aggregate::Max,
+            "median" => Aggregate::Median,
+            other => return Err(format!("Unknown aggregate: {}", other)),
+        })
+    }
+
+    pub fn apply(self, values: &mut [f64], rows: usize) -> Option<f64> {
+        if self == Aggregate::Count {
+            return Some(rows as f64);
+        }
+        if values.is_empty() {
+            return None;
+        }
+        Some(match self {
+            Aggregate::Count => rows as f64,
+            Aggregate::Sum => values.iter().sum(),
+            Aggregate::Mean => values.iter().sum::<f64>() / values.len() as f64,
+            Aggregate::Min => values.iter().copied().fold(f64::INFINITY, f64::min),
+            Aggregate::Max => values.iter().copied().fold(f64::NEG_INFINITY, f64::max),
+            Aggregate::Median => {
+                values.sort_by(|a, b| a.total_cmp(b));
+                median(values)
+            }
+        })
+    }
+}
+
+/// Rows and numeric y values collected for one label and group
+#[derive(Default)]
+struct Bucket {
+    rows: usize,
+    values: Vec<f64>,
+}
+
+/// Numeric y value of a row, or whether it had to be skipped
+fn y_value(row: &[String], y_col: Option<usize>, agg: Aggregate) -> Result<Option<f64>, ()> {
+    let Some(col) = y_col.filter(|_| agg != Aggregate::Count) else {
+        return Ok(None);
+    };
+    let value = row.get(col).map(String::as_str).unwrap_or("");
+    if is_null(value) {
+        return Ok(None);
+    }
+    parse_number(value).map(Some).ok_or(())
+}
+
+fn optional_column(ds: &Dataset, name: Option<&str>) -> Result<Option<usize>, String> {
+    name.map(|n| ds.column_index(n)).transpose()
+}
+
+/// Sort labels along the axis when the x column is numeric or a date;
+/// otherwise biggest first, as for bar and pie charts
+fn order_labels(ds: &Dataset, x_col: usize, totals: &HashMap<&str, f64>) -> Vec<String> {
+    let mut labels: Vec<&str> = totals.keys().copied().collect();
+    match ds.column_type(x_col) {
+        ColumnType::Integer | ColumnType::Float => labels.sort_by(|a, b| {
+            let n = |s: &str| parse_number(s).unwrap_or(f64::NAN);
+            n(a).total_cmp(&n(b))
+        }),
+        ColumnType::Date => labels.sort_by_key(|l| dates::parse_datetime(l)),
+        _ => labels.sort_by(|a, b| totals[b].total_cmp(&totals[a]).then(a.cmp(b))),
+    }
+    labels.into_iter().map(str::to_string).collect()
+}
+
+/// Aggregate `y_column` (or count rows) per distinct `x_column` value, with
+/// one series per `group_by` value, for bar, line, and pie charts. `agg` is
+/// count (default), sum, mean, min, max, or median. Runs as a cancellable
+/// operation, as `filter_rows` does, but fails rather than chart part of the
+/// rows.
+#[tauri::command(async)]
+#[allow(clippy::too_many_arguments)]
+pub fn chart_data(
+    app: AppHandle,
+    store: State<'_, DatasetStore>,
+    ops: State<'_, Operations>,
+    handle: u64,
+    x_column: String,
+    y_column: Option<String>,
+    agg: Option<String>,
+    group_by: Option<String>,
+    limit: Option<usize>,
+    operation_id: Option<String>,
+    timeout_ms: Option<u64>,
+) -> Result<ChartData, AppError> {
+    let _timed = logging::timed("chart_data");
+    let agg = Aggregate::parse(agg.as_deref().unwrap_or("count"))?;
+    if agg != Aggregate::Count && y_column.is_none() {
+        return Err("Choose a y column to aggregate".into());
+    }
+    let limit = limit.unwrap_or(DEFAULT_LABELS).clamp(1, MAX_LABELS);
+
+    let op = ops.start(&app, operation_id, timeout_ms)?;
+    store.with_cached(handle, cache::key("chart_data", &(&x_column, &y_column, format!("{:?}", agg), &group_by, limit)), |ds| {
+        let x_col = ds.column_index(&x_column)?;
+        let y_col = optional_column(ds, y_column.as_deref())?;
+        let group_col = optional_column(ds, group_by.as_deref())?;
+
+        let mut buckets: HashMap<(&str, &str), Bucket> = HashMap::new();
+        let mut group_rows: HashMap<&str, usize> = HashMap::new();
+        let mut skipped_rows = 0;
+        for (i, row) in ds.rows.iter().enumerate() {
+            op.check(i)?;
+            let x = row.get(x_col).map(String::as_str).unwrap_or("");
+            let group = group_col.and_then(|c| row.get(c)).map(String::as_str).unwrap_or("");
+            let Ok(y) = y_value(row, y_col, agg) else {
+                skipped_rows += 1;
+                continue;
+            };
+            let bucket = buckets.entry((x, group)).or_default();
+            bucket.rows += 1;
+            bucket.values.extend(y);
+            *group_rows.entry(group).or_insert(0) += 1;
+        }
+
+        let mut groups: Vec<&str> = group_rows.keys().copied().collect();
+        groups.sort_by(|a, b| group_rows[b].cmp(&group_rows[a]).then(a.cmp(b)));
+        let dropped_series = groups.len().saturating_sub(MAX_SERIES);
+        groups.truncate(MAX_SERIES);
+
+        let mut values: HashMap<(&str, &str), f64> = HashMap::new();
+        let mut totals: HashMap<&str, f64> = HashMap::new();
+        for ((x, group), bucket) in &mut buckets {
+            if !groups.contains(group) {
+                continue;
+            }
+            if let Some(value) = agg.apply(&mut bucket.values, bucket.rows) {
+                values.insert((x, group), value);
+                *totals.entry(x).or_insert(0.0) += value;
+            }
+        }
+
+        let mut labels = order_labels(ds, x_col, &totals);
+        let dropped_labels = labels.len().saturating_sub(limit);
+        labels.truncate(limit);
+
+        let series = groups
+            .iter()
+            .map(|group| ChartSeries {
+                name: match group_col {
+                    Some(_) => group.to_string(),
+                    None => y_column.clone().unwrap_or_else(|| "count".to_string()),
+                },
+                values: labels.iter().map(|x| values.get(&(x.as_str(), *group)).copied()).collect(),
+            })
+            .collect();
+
+        Ok(ChartData {
+            labels,
+            series,
+            dropped_labels,
+            dropped_series,
+            skipped_rows,
+        })
+    })?
+}
+
+/// Default number of buckets a time series is fitted into
+const DEFAULT_TIME_BUCKETS: usize = 100;
+
+#[derive(Debug, Clone, Serialize)]
+pub struct TimeBucket {
+    pub label: String,
+    /// Bucket bounds as Unix milliseconds
+    pub start: i64,
+    pub end: i64,
+    /// `None` for numeric aggregates over a bucket with no values
+    pub value: Option<f64>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct TimeSeries {
+    pub unit: TimeUnit,
+    pub buckets: Vec<TimeBucket>,
+    /// Rows with an empty or unparseable datetime
+    pub skipped_rows: usize,
+}
+
+/// Count rows (or aggregate `y_column`) per time bucket of a datetime column.
+/// Without a `unit`, the finest of minute, hour, day, month, or year that
+/// covers the data in at most `max_buckets` (default 100) buckets is used.
+/// Empty buckets are included so gaps show on a timeline. Cancellable like
+/// `chart_data`.
+#[tauri::command(async)]
+#[allow(clippy::too_many_arguments)]
+pub fn time_series(
+    app: AppHandle,
+    store: State<'_, DatasetStore>,
+    ops: State<'_, Operations>,
+    handle: u64,
+    column: String,
+    y_column: Option<String>,
+    agg: Option<String>,
+    unit: Option<TimeUnit>,
+    max_buckets: Option<usize>,
+    operation_id: Option<String>,
+    timeout_ms: Option<u64>,
+) -> Result<TimeSeries, AppError> {
+    let _timed = logging::timed("time_series");
+    let agg = Aggregate::parse(agg.as_deref().unwrap_or("count"))?;
+    if agg != Aggregate::Count && y_column.is_none() {
+        return Err("Choose a y column to aggregate".into());
+    }
+    let max_buckets = max_buckets.unwrap_or(DEFAULT_TIME_BUCKETS).clamp(1, MAX_LABELS);
+
+    let op = ops.start(&app, operation_id, timeout_ms)?;
+    Ok(store.with_cached(handle, cache::key("time_series", &(&column, &y_column, format!("{:?}", agg), unit, max_buckets)), |ds| {
+        let col = ds.column_index(&column)?;
+        let y_col = optional_column(ds, y_column.as_deref())?;
+
+        let mut points = Vec::new();
+        let mut skipped_rows = 0;
+        for (i, row) in ds.rows.iter().enumerate() {
+            op.check(i)?;
+            let time = row.get(col).and_then(|v| dates::parse_datetime(v));
+            match (time, y_value(row, y_col, agg)) {
+                (Some(time), Ok(y)) => points.push((time, y)),
+                _ => skipped_rows += 1,
+            }
+        }
+        let (Some(min), Some(max)) = (points.iter().map(|p| p.0).min(), points.iter().map(|p| p.0).max()) else {
+            return Err(format!("Column has no datetime values: {}", column));
+        };
+        let unit = unit.unwrap_or_else(|| TimeUnit::choose(min, max, max_buckets));
+        if unit.bucket_count(min, max) > MAX_LABELS as i64 {
+            return Err("The data spans too many buckets of this unit; choose a coarser one".to_string());
+        }
+
+        let mut buckets: BTreeMap<NaiveDateTime, Bucket> = BTreeMap::new();
+        for (time, y) in points {
+            let bucket = buckets.entry(unit.truncate(time)).or_default();
+            bucket.rows += 1;
+            bucket.values.extend(y);
+        }
+
+        let mut series = Vec::new();
+        let mut start = unit.truncate(min);
+        while start <= max {
+            let end = unit.next(start);
+            let value = match buckets.get_mut(&start) {
+                Some(bucket) => agg.apply(&mut bucket.values, bucket.rows),
+                None if agg == Aggregate::Count || agg == Aggregate::Sum => Some(0.0),
+                None => None,
+            };
+            series.push(TimeBucket {
+                label: unit.label(start),
+                start: start.and_utc().timestamp_millis(),
+                end: end.and_utc().timestamp_millis(),
+                value,
+            });
+            start = end;
+        }
+
+        Ok(TimeSeries {
+            unit,
+            buckets: series,
+            skipped_rows,
+        })
+    })??)
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ParetoCategory {
+    pub category: String,
+    pub value: f64,
+    /// Share of the total, as a percentage
+    pub percent: f64,
+    pub cumulative_percent: f64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ParetoResult {
+    pub categories: Vec<ParetoCategory>,
+    pub total: f64,
+    /// How many of the largest categories it takes to reach `threshold` percent
+    pub threshold_count: usize,
+    pub threshold: f64,
+    /// Rows whose value isn't a number
+    pub skipped_rows: usize,
+}
+
+/// Sum `value_column` (or count rows) per category, largest first, with each
+/// category's cumulative share of the total, e.g. which customers make up
+/// 80% of revenue. `threshold` defaults to 80. Cancellable like `chart_data`.
+#[tauri::command(async)]
+#[allow(clippy::too_many_arguments)]
+pub fn pareto(
+    app: AppHandle,
+    store: State<'_, DatasetStore>,
+    ops: State<'_, Operations>,
+    handle: u64,
+    category_column: String,
+    value_column: Option<String>,
+    threshold: Option<f64>,
+    operation_id: Option<String>,
+    timeout_ms: Option<u64>,
+) -> Result<ParetoResult, AppError> {
+    let _timed = logging::timed("pareto");
+    let threshold = threshold.unwrap_or(80.0);
+    if !(0.0..=100.0).contains(&threshold) {
+        return Err("Threshold must be between 0 and 100".into());
+    }
+    let agg = if value_column.is_some() { Aggregate::Sum } else { Aggregate::Count };
+
+    let op = ops.start(&app, operation_id, timeout_ms)?;
+    store.with_cached(handle, cache::key("pareto", &(&category_column, &value_column, threshold)), |ds| {
+        let category_col = ds.column_index(&category_column)?;
+        let value_col = optional_column(ds, value_column.as_deref())?;
+
+        let mut totals: HashMap<&str, f64> = HashMap::new();
+        let mut skipped_rows = 0;
+        for (i, row) in ds.rows.iter().enumerate() {
+            op.check(i)?;
+            let category = row.get(category_col).map(String::as_str).unwrap_or("");
+            match y_value(row, value_col, agg) {
+                Ok(Some(value)) => *totals.entry(category).or_insert(0.0) += value,
+                Ok(None) if agg == Aggregate::Count => *totals.entry(category).or_insert(0.0) += 1.0,
+                Ok(None) => {}
+                Err(()) => skipped_rows += 1,
+            }
+        }
+
+        let mut totals: Vec<(&str, f64)> = totals.into_iter().collect();
+        totals.sort_by(|a, b| b.1.total_cmp(&a.1).then(a.0.cmp(b.0)));
+        let total: f64 = totals.iter().map(|(_, v)| v).sum();
+
+        let mut running = 0.0;
+        let categories: Vec<ParetoCategory> = totals
+            .into_iter()
+            .map(|(category, value)| {
+                running += value;
+                let percent = |v: f64| if total == 0.0 { 0.0 } else { v / total * 100.0 };
+                ParetoCategory {
+                    category: category.to_string(),
+                    value,
+                    percent: percent(value),
+                    cumulative_percent: percent(running),
+                }
+            })
+            .collect();
+        let threshold_count = categories
+            .iter()
+            .position(|c| c.cumulative_percent >= threshold)
+            .map_or(categories.len(), |i| i + 1);
+
+        Ok(ParetoResult {
+            categories,
+            total,
+            threshold_count,
+            threshold,
+            skipped_rows,
+        })
+    })?
+}
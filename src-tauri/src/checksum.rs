@@ -0,0 +1,71 @@
+use crate::access;
+use crate::dataset::{DatasetStore, FileStamp};
+use crate::error::AppError;
+use crate::logging;
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+use std::fs::File;
+use std::io::Read;
+use tauri::{AppHandle, Emitter, State};
+
+/// Event emitted while a checksum is computed
+pub const CHECKSUM_PROGRESS_EVENT: &str = "checksum-progress";
+/// Emit progress once per this many bytes hashed
+const PROGRESS_STEP: u64 = 16 * 1024 * 1024;
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ChecksumProgress {
+    pub path: String,
+    pub processed: u64,
+    pub total: u64,
+}
+
+#[derive(Debug, Serialize)]
+pub struct SourceStatus {
+    pub path: Option<String>,
+    /// The file's size or modification time differs from when it was loaded or saved
+    pub changed: bool,
+    /// The file is gone
+    pub missing: bool,
+}
+
+/// Hex SHA-256 of a file, emitting progress as large files are read
+#[tauri::command(async)]
+pub fn file_checksum(app: AppHandle, path: String) -> Result<String, AppError> {
+    let _timed = logging::timed("file_checksum");
+    access::check(&app, &path)?;
+    let mut file = File::open(&path).map_err(|e| format!("Failed to open file: {}", e))?;
+    let total = file.metadata().map(|m| m.len()).unwrap_or(0);
+    let mut hasher = Sha256::new();
+    let mut buffer = vec![0u8; 1024 * 1024];
+    let mut processed = 0u64;
+    loop {
+        let n = file.read(&mut buffer).map_err(|e| format!("Failed to read file: {}", e))?;
+        if n == 0 {
+            break;
+        }
+        hasher.update(&buffer[..n]);
+        processed += n as u64;
+        if processed % PROGRESS_STEP < n as u64 {
+            let _ = app.emit(CHECKSUM_PROGRESS_EVENT, ChecksumProgress { path: path.clone(), processed, total });
+        }
+    }
+    Ok(hex::encode(hasher.finalize()))
+}
+
+/// Whether a dataset's source file was changed by something else since it
+/// was loaded (or last saved from here)
+#[tauri::command]
+pub fn check_source_changed(store: State<'_, DatasetStore>, handle: u64) -> Result<SourceStatus, AppError> {
+    Ok(store.with(handle, |ds| {
+        let Some(path) = ds.source_path.clone() else {
+            return SourceStatus { path: None, changed: false, missing: false };
+        };
+        let current = FileStamp::read(&path);
+        SourceStatus {
+            changed: current.is_some() && current != ds.source_stamp,
+            missing: current.is_none(),
+            path: Some(path),
+        }
+    })?)
+}
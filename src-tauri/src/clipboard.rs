@@ -0,0 +1,41 @@
+use crate::dataset::{Dataset, DatasetStore, FileData};
+use crate::error::AppError;
+use tauri::{AppHandle, State};
+use tauri_plugin_clipboard_manager::ClipboardExt;
+
+/// Build a dataset from pasted text: JSON when it parses as JSON, TSV when
+/// the first line has tabs (what spreadsheets copy), CSV otherwise
+pub fn parse_text(text: &str) -> Result<Dataset, String> {
+    let trimmed = text.trim();
+    if trimmed.is_empty() {
+        return Err("The clipboard is empty".to_string());
+    }
+    if trimmed.starts_with('[') || trimmed.starts_with('{') {
+        if let Ok(parsed) = serde_json::from_str(trimmed) {
+            return crate::json_to_dataset(parsed);
+        }
+    }
+    let first_line = trimmed.lines().next().unwrap_or_default();
+    if first_line.contains('\t') {
+        Ok(Dataset {
+            file_type: "TSV".to_string(),
+            ..crate::csv_to_dataset(trimmed, b'\t', true, false)?
+        })
+    } else {
+        crate::csv_to_dataset(trimmed, b',', true, false)
+    }
+}
+
+/// Create a dataset from the text on the system clipboard
+#[tauri::command]
+pub fn parse_clipboard(app: AppHandle, store: State<'_, DatasetStore>) -> Result<FileData, AppError> {
+    let text = app
+        .clipboard()
+        .read_text()
+        .map_err(|e| format!("Failed to read the clipboard: {}", e))?;
+    let dataset = parse_text(&text)?;
+    Ok(store.register(Dataset {
+        file_name: format!("Pasted {}", dataset.file_type),
+        ..dataset
+    }))
+}
@@ -0,0 +1,186 @@
+use crate::dataset::{Dataset, DatasetStore, FileData};
+use crate::logging;
+use crate::refresh::RemoteSource;
+use crate::remote;
+use crate::error::AppError;
+use futures::StreamExt;
+use object_store::path::Path as ObjectPath;
+use object_store::{ObjectStore, ObjectStoreScheme};
+use serde::Serialize;
+use std::collections::HashMap;
+use std::io::Write;
+use std::path::PathBuf;
+use tauri::{AppHandle, State};
+use url::Url;
+
+#[derive(Debug, Serialize)]
+pub struct ObjectEntry {
+    /// Full URL of the object or folder, usable with `open_object`/`list_objects`
+    pub url: String,
+    pub name: String,
+    pub is_folder: bool,
+    pub size: Option<u64>,
+    pub last_modified: Option<String>,
+}
+
+/// `key = value` pairs under `[section]` in an INI file, keys lowercased
+fn ini_section(text: &str, section: &str) -> Vec<(String, String)> {
+    let mut in_section = false;
+    let mut pairs = Vec::new();
+    for line in text.lines().map(str::trim).filter(|l| !l.starts_with(['#', ';'])) {
+        if let Some(name) = line.strip_prefix('[').and_then(|l| l.strip_suffix(']')) {
+            in_section = name.trim() == section;
+        } else if let Some((key, value)) = line.split_once('=').filter(|_| in_section) {
+            pairs.push((key.trim().to_ascii_lowercase(), value.trim().to_string()));
+        }
+    }
+    pairs
+}
+
+/// Keys and region of the AWS_PROFILE (or default) profile in the shared
+/// credentials and config files, as the AWS CLI reads them. Only static keys
+/// are read; SSO and credential_process profiles aren't.
+fn aws_profile_options() -> Vec<(String, String)> {
+    let home = std::env::var_os("HOME").or_else(|| std::env::var_os("USERPROFILE")).map(PathBuf::from);
+    let file = |var: &str, name: &str| {
+        std::env::var_os(var)
+            .map(PathBuf::from)
+            .or_else(|| home.as_ref().map(|h| h.join(".aws").join(name)))
+    };
+    let profile = std::env::var("AWS_PROFILE").unwrap_or_else(|_| "default".to_string());
+    // The config file names its sections "profile <name>", except the default one
+    let config_section = match profile.as_str() {
+        "default" => profile.clone(),
+        name => format!("profile {}", name),
+    };
+    let sources = [
+        (file("AWS_SHARED_CREDENTIALS_FILE", "credentials"), profile.as_str()),
+        (file("AWS_CONFIG_FILE", "config"), config_section.as_str()),
+    ];
+    let mut options = Vec::new();
+    for (path, section) in sources {
+        let Some(text) = path.and_then(|p| std::fs::read_to_string(p).ok()) else { continue };
+        for (key, value) in ini_section(&text, section) {
+            let option = match key.as_str() {
+                "aws_access_key_id" | "aws_secret_access_key" | "aws_session_token" => key,
+                "region" => "aws_region".to_string(),
+                _ => continue,
+            };
+            options.push((option, value));
+        }
+    }
+    options
+}
+
+/// Whether the Azure CLI has been logged in on this machine
+fn azure_cli_logged_in() -> bool {
+    let dir = std::env::var_os("AZURE_CONFIG_DIR").map(PathBuf::from).or_else(|| {
+        std::env::var_os("HOME")
+            .or_else(|| std::env::var_os("USERPROFILE"))
+            .map(|home| PathBuf::from(home).join(".azure"))
+    });
+    dir.is_some_and(|dir| dir.join("azureProfile.json").is_file())
+}
+
+/// Connect to the bucket or container named in an `s3://`, `gs://`, or
+/// `az://` URL. Credentials come from the usual environment variables
+/// (AWS_*, GOOGLE_*, AZURE_*), then the AWS profile files, gcloud's
+/// application default credentials, or the Azure CLI login, and finally the
+/// cloud providers' instance metadata.
+fn connect(url: &str) -> Result<(Box<dyn ObjectStore>, ObjectPath, Url), String> {
+    let parsed = Url::parse(url).map_err(|e| format!("Invalid URL {}: {}", url, e))?;
+    let supported = matches!(
+        ObjectStoreScheme::parse(&parsed),
+        Ok((
+            ObjectStoreScheme::AmazonS3 | ObjectStoreScheme::GoogleCloudStorage | ObjectStoreScheme::MicrosoftAzure,
+            _
+        ))
+    );
+    if !supported {
+        return Err(format!("Not an S3, GCS, or Azure Blob URL: {}", url));
+    }
+    let env: HashMap<String, String> = std::env::vars().map(|(k, v)| (k.to_ascii_lowercase(), v)).collect();
+    let mut options = HashMap::new();
+    for (key, value) in aws_profile_options() {
+        // Keys in the environment replace the profile's as a set
+        if key == "aws_region" || !env.contains_key("aws_access_key_id") {
+            options.insert(key, value);
+        }
+    }
+    if azure_cli_logged_in() {
+        options.insert("azure_use_azure_cli".to_string(), "true".to_string());
+    }
+    options.extend(env);
+    let (store, path) = object_store::parse_url_opts(&parsed, options)
+        .map_err(|e| format!("Failed to connect to {}: {}", url, e))?;
+    Ok((store, path, parsed))
+}
+
+/// URL of a location in the same bucket as `base`
+fn object_url(base: &Url, location: &ObjectPath) -> String {
+    format!("{}://{}/{}", base.scheme(), base.host_str().unwrap_or_default(), location)
+}
+
+/// List the folders and objects directly under a bucket/prefix URL
+#[tauri::command]
+pub async fn list_objects(url: String) -> Result<Vec<ObjectEntry>, AppError> {
+    let _timed = logging::timed("list_objects");
+    let (store, prefix, base) = connect(&url)?;
+    let prefix = (!prefix.as_ref().is_empty()).then_some(prefix);
+    let listing = store
+        .list_with_delimiter(prefix.as_ref())
+        .await
+        .map_err(|e| format!("Failed to list {}: {}", url, e))?;
+
+    let folders = listing.common_prefixes.into_iter().map(|p| ObjectEntry {
+        url: format!("{}/", object_url(&base, &p)),
+        name: p.filename().unwrap_or_default().to_string(),
+        is_folder: true,
+        size: None,
+        last_modified: None,
+    });
+    let objects = listing.objects.into_iter().map(|meta| ObjectEntry {
+        url: object_url(&base, &meta.location),
+        name: meta.location.filename().unwrap_or_default().to_string(),
+        is_folder: false,
+        size: Some(meta.size),
+        last_modified: Some(meta.last_modified.to_rfc3339()),
+    });
+    Ok(folders.chain(objects).collect())
+}
+
+/// Download an object to a temp file and parse it
+pub async fn fetch_object(app: &AppHandle, url: &str) -> Result<Dataset, AppError> {
+    let (object_store, path, _) = connect(url)?;
+    let name = path.filename().map(|n| n.to_string());
+    let ext = remote::file_extension(name.as_deref(), "")?;
+    let mut chunks = object_store
+        .get(&path)
+        .await
+        .map_err(|e| format!("Failed to fetch {}: {}", url, e))?
+        .into_stream();
+
+    // Streamed to disk a chunk at a time rather than held whole in memory
+    let temp = remote::temp_file(app, ext)?;
+    let mut file = std::fs::File::create(&temp).map_err(|e| format!("Failed to create temp file: {}", e))?;
+    while let Some(chunk) = chunks.next().await {
+        let written = chunk
+            .map_err(|e| format!("Failed to download {}: {}", url, e))
+            .and_then(|chunk| file.write_all(&chunk).map_err(|e| format!("Failed to write temp file: {}", e)));
+        if let Err(e) = written {
+            let _ = std::fs::remove_file(&temp);
+            return Err(e.into());
+        }
+    }
+    drop(file);
+    remote::load_download(&temp, url, name)
+}
+
+/// Download an object to a temp file and open it
+#[tauri::command]
+pub async fn open_object(app: AppHandle, store: State<'_, DatasetStore>, url: String) -> Result<FileData, AppError> {
+    let _timed = logging::timed("open_object");
+    let mut dataset = fetch_object(&app, &url).await?;
+    dataset.remote = Some(RemoteSource::Cloud { url });
+    Ok(store.register(dataset))
+}
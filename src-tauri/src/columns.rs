@@ -0,0 +1,99 @@
+use crate::dataset::{ColumnMeta, Dataset, DatasetStore, FileData};
+use crate::journal::Edit;
+use crate::error::AppError;
+use tauri::State;
+
+fn check_new_name(ds: &Dataset, name: &str) -> Result<(), String> {
+    if name.trim().is_empty() {
+        return Err("Column name cannot be empty".to_string());
+    }
+    if ds.headers.iter().any(|h| h == name) {
+        return Err(format!("Column already exists: {}", name));
+    }
+    Ok(())
+}
+
+/// Add a column before `index` (or at the end), filled with `default_value`
+#[tauri::command]
+pub fn add_column(
+    store: State<'_, DatasetStore>,
+    handle: u64,
+    name: String,
+    index: Option<usize>,
+    default_value: Option<String>,
+) -> Result<FileData, AppError> {
+    Ok(store.with_mut(handle, |ds| {
+        check_new_name(ds, &name)?;
+        let at = index.unwrap_or(ds.headers.len());
+        if at > ds.headers.len() {
+            return Err(format!("Column position {} is out of range", at));
+        }
+        let values = vec![default_value.unwrap_or_default(); ds.rows.len()];
+        let inverse = Edit::InsertColumns(vec![(at, name, values, ColumnMeta::default())]).apply(ds);
+        ds.record_edit(inverse);
+        ds.dirty = true;
+        Ok(ds.to_file_data(handle))
+    })??)
+}
+
+/// Remove the named columns
+#[tauri::command]
+pub fn delete_columns(store: State<'_, DatasetStore>, handle: u64, columns: Vec<String>) -> Result<FileData, AppError> {
+    store.with_mut(handle, |ds| {
+        let mut remove = columns
+            .iter()
+            .map(|c| ds.column_index(c))
+            .collect::<Result<Vec<_>, _>>()?;
+        remove.sort_unstable();
+        remove.dedup();
+        let inverse = Edit::RemoveColumns(remove).apply(ds);
+        ds.record_edit(inverse);
+        ds.dirty = true;
+        Ok(ds.to_file_data(handle))
+    })?
+}
+
+/// Give a column a new name
+#[tauri::command]
+pub fn rename_column(
+    store: State<'_, DatasetStore>,
+    handle: u64,
+    column: String,
+    new_name: String,
+) -> Result<FileData, AppError> {
+    store.with_mut(handle, |ds| {
+        let col = ds.column_index(&column)?;
+        if new_name != column {
+            check_new_name(ds, &new_name)?;
+            ds.record_edit(Edit::RenameColumn(col, column.clone()));
+            ds.rename_column_at(col, new_name);
+            ds.dirty = true;
+        }
+        Ok(ds.to_file_data(handle))
+    })?
+}
+
+/// Rearrange columns into `order`, which must name every column exactly once
+#[tauri::command]
+pub fn reorder_columns(store: State<'_, DatasetStore>, handle: u64, order: Vec<String>) -> Result<FileData, AppError> {
+    Ok(store.with_mut(handle, |ds| {
+        let indices = order
+            .iter()
+            .map(|c| ds.column_index(c))
+            .collect::<Result<Vec<_>, _>>()?;
+        let mut seen = vec![false; ds.headers.len()];
+        for &i in &indices {
+            if std::mem::replace(&mut seen[i], true) {
+                return Err(format!("Column listed more than once: {}", ds.headers[i]));
+            }
+        }
+        if let Some(missing) = seen.iter().position(|s| !s) {
+            return Err(format!("Column order is missing: {}", ds.headers[missing]));
+        }
+
+        let inverse = Edit::ReorderColumns(indices).apply(ds);
+        ds.record_edit(inverse);
+        ds.dirty = true;
+        Ok(ds.to_file_data(handle))
+    })??)
+}
@@ -0,0 +1,336 @@
+use crate::dataset::{Dataset, DatasetStore, FileData};
+use crate::error::AppError;
+use serde::Serialize;
+use std::collections::{HashMap, VecDeque};
+use tauri::State;
+
+fn key_of(row: &[String], cols: &[usize]) -> Vec<String> {
+    cols.iter()
+        .map(|&c| row.get(c).cloned().unwrap_or_default())
+        .collect()
+}
+
+/// Join two datasets on one or more key columns present in both.
+/// `how` is "inner" (default), "left", or "full". Key columns appear once;
+/// other right-hand columns whose names clash get a "_right" suffix.
+#[tauri::command]
+pub fn join_datasets(
+    store: State<'_, DatasetStore>,
+    left_handle: u64,
+    right_handle: u64,
+    on: Vec<String>,
+    how: Option<String>,
+) -> Result<FileData, AppError> {
+    let how = how.unwrap_or_else(|| "inner".to_string());
+    let (keep_left, keep_right) = match how.as_str() {
+        "inner" => (false, false),
+        "left" => (true, false),
+        "full" => (true, true),
+        other => return Err(format!("Unknown join type: {}", other).into()),
+    };
+    if on.is_empty() {
+        return Err("Select at least one key column to join on".into());
+    }
+
+    let joined = store.with_pair(left_handle, right_handle, |left, right| {
+        let left_keys = on.iter().map(|c| left.column_index(c)).collect::<Result<Vec<_>, _>>()?;
+        let right_keys = on.iter().map(|c| right.column_index(c)).collect::<Result<Vec<_>, _>>()?;
+        let right_other: Vec<usize> = (0..right.headers.len())
+            .filter(|c| !right_keys.contains(c))
+            .collect();
+
+        let mut headers = left.headers.clone();
+        for &c in &right_other {
+            let name = &right.headers[c];
+            headers.push(if left.headers.contains(name) {
+                format!("{}_right", name)
+            } else {
+                name.clone()
+            });
+        }
+
+        let mut right_index: HashMap<Vec<String>, Vec<usize>> = HashMap::new();
+        for (i, row) in right.rows.iter().enumerate() {
+            right_index.entry(key_of(row, &right_keys)).or_default().push(i);
+        }
+
+        let left_width = left.headers.len();
+        let mut matched_right = vec![false; right.rows.len()];
+        let mut rows = Vec::new();
+        for row in &left.rows {
+            let mut base = row.clone();
+            base.resize(left_width, String::new());
+            match right_index.get(&key_of(row, &left_keys)) {
+                Some(matches) => {
+                    for &r in matches {
+                        matched_right[r] = true;
+                        let mut out = base.clone();
+                        out.extend(key_of(&right.rows[r], &right_other));
+                        rows.push(out);
+                    }
+                }
+                None if keep_left => {
+                    base.resize(headers.len(), String::new());
+                    rows.push(base);
+                }
+                None => {}
+            }
+        }
+
+        // Full joins also keep right rows that never matched, with their key
+        // values carried into the left key columns
+        if keep_right {
+            for (row, _) in right.rows.iter().zip(&matched_right).filter(|(_, m)| !**m) {
+                let mut out = vec![String::new(); left_width];
+                for (&lc, &rc) in left_keys.iter().zip(&right_keys) {
+                    out[lc] = row.get(rc).cloned().unwrap_or_default();
+                }
+                out.extend(key_of(row, &right_other));
+                rows.push(out);
+            }
+        }
+
+        Ok::<_, String>(Dataset {
+            headers,
+            rows,
+            file_name: format!("{} (joined with {})", left.file_name, right.file_name),
+            file_type: left.file_type.clone(),
+            ..Default::default()
+        })
+    })??;
+    Ok(store.register(joined))
+}
+
+#[derive(Debug, Serialize)]
+pub struct CellChange {
+    /// Row index within the diff result
+    pub row: usize,
+    pub key: Vec<String>,
+    pub column: String,
+    pub old_value: String,
+    pub new_value: String,
+}
+
+#[derive(Debug, Default, Serialize)]
+pub struct DiffSummary {
+    pub added: usize,
+    pub removed: usize,
+    pub changed: usize,
+    pub unchanged: usize,
+}
+
+#[derive(Debug, Serialize)]
+pub struct DatasetDiff {
+    pub data: FileData,
+    pub summary: DiffSummary,
+    pub changes: Vec<CellChange>,
+}
+
+/// Union of two header lists by name: `a`'s order first, then anything new in `b`
+fn merged_headers(a: &[String], b: &[String]) -> Vec<String> {
+    let mut headers = a.to_vec();
+    headers.extend(b.iter().filter(|h| !a.contains(h)).cloned());
+    headers
+}
+
+/// Compare dataset `b` (new) against `a` (old), matching rows on `key_columns`.
+/// The result is a new dataset with a leading `_status` column of
+/// added/removed/changed (and unchanged, when requested) holding the new
+/// values for changed rows, plus a list of every differing cell.
+#[tauri::command]
+pub fn diff_datasets(
+    store: State<'_, DatasetStore>,
+    a: u64,
+    b: u64,
+    key_columns: Vec<String>,
+    include_unchanged: Option<bool>,
+) -> Result<DatasetDiff, AppError> {
+    if key_columns.is_empty() {
+        return Err("Select at least one key column to match rows on".into());
+    }
+    let include_unchanged = include_unchanged.unwrap_or(false);
+
+    let (dataset, summary, changes) = store.with_pair(a, b, |old, new| {
+        let old_keys = key_columns.iter().map(|c| old.column_index(c)).collect::<Result<Vec<_>, _>>()?;
+        let new_keys = key_columns.iter().map(|c| new.column_index(c)).collect::<Result<Vec<_>, _>>()?;
+
+        let columns = merged_headers(&old.headers, &new.headers);
+        let positions = |ds: &Dataset| -> Vec<Option<usize>> {
+            columns.iter().map(|c| ds.headers.iter().position(|h| h == c)).collect()
+        };
+        let (old_pos, new_pos) = (positions(old), positions(new));
+        let aligned = |row: &[String], pos: &[Option<usize>]| -> Vec<String> {
+            pos.iter()
+                .map(|p| p.and_then(|i| row.get(i).cloned()).unwrap_or_default())
+                .collect()
+        };
+
+        // Rows sharing a key are paired up in file order
+        let mut old_index: HashMap<Vec<String>, VecDeque<usize>> = HashMap::new();
+        for (i, row) in old.rows.iter().enumerate() {
+            old_index.entry(key_of(row, &old_keys)).or_default().push_back(i);
+        }
+
+        let mut summary = DiffSummary::default();
+        let mut changes = Vec::new();
+        let mut rows = Vec::new();
+        let mut matched_old = vec![false; old.rows.len()];
+        for new_row in &new.rows {
+            let key = key_of(new_row, &new_keys);
+            let values = aligned(new_row, &new_pos);
+            let old_row = old_index.get_mut(&key).and_then(|q| q.pop_front());
+            let status = match old_row {
+                None => {
+                    summary.added += 1;
+                    "added"
+                }
+                Some(o) => {
+                    matched_old[o] = true;
+                    let before = changes.len();
+                    let old_values = aligned(&old.rows[o], &old_pos);
+                    for ((column, old_value), new_value) in columns.iter().zip(old_values).zip(&values) {
+                        if &old_value != new_value {
+                            changes.push(CellChange {
+                                row: rows.len(),
+                                key: key.clone(),
+                                column: column.clone(),
+                                old_value,
+                                new_value: new_value.clone(),
+                            });
+                        }
+                    }
+                    if changes.len() > before {
+                        summary.changed += 1;
+                        "changed"
+                    } else {
+                        summary.unchanged += 1;
+                        if !include_unchanged {
+                            continue;
+                        }
+                        "unchanged"
+                    }
+                }
+            };
+            let mut out = vec![status.to_string()];
+            out.extend(values);
+            rows.push(out);
+        }
+
+        for (old_row, _) in old.rows.iter().zip(&matched_old).filter(|(_, m)| !**m) {
+            summary.removed += 1;
+            let mut out = vec!["removed".to_string()];
+            out.extend(aligned(old_row, &old_pos));
+            rows.push(out);
+        }
+
+        let mut headers = vec!["_status".to_string()];
+        headers.extend(columns);
+        let dataset = Dataset {
+            headers,
+            rows,
+            file_name: format!("{} vs {}", old.file_name, new.file_name),
+            file_type: new.file_type.clone(),
+            ..Default::default()
+        };
+        Ok::<_, String>((dataset, summary, changes))
+    })??;
+
+    Ok(DatasetDiff {
+        data: store.register(dataset),
+        summary,
+        changes,
+    })
+}
+
+#[derive(Debug, Serialize)]
+pub struct SchemaMismatch {
+    pub handle: u64,
+    pub file_name: String,
+    /// Columns of the combined result this dataset doesn't have (filled with nulls)
+    pub missing_columns: Vec<String>,
+    /// Columns this dataset adds that the first dataset doesn't have
+    pub extra_columns: Vec<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct UnionResult {
+    pub data: FileData,
+    pub mismatches: Vec<SchemaMismatch>,
+}
+
+/// Stack datasets vertically, aligning columns by name. Columns missing from
+/// a dataset are left blank; `source_column`, when given, records which file
+/// each row came from.
+#[tauri::command]
+pub fn union_datasets(
+    store: State<'_, DatasetStore>,
+    handles: Vec<u64>,
+    source_column: Option<String>,
+) -> Result<UnionResult, AppError> {
+    if handles.len() < 2 {
+        return Err("Select at least two datasets to combine".into());
+    }
+
+    let (dataset, mismatches) = store.with_many(&handles, |datasets| {
+        let mut columns = datasets[0].headers.clone();
+        for ds in &datasets[1..] {
+            columns = merged_headers(&columns, &ds.headers);
+        }
+
+        let mut rows = Vec::with_capacity(datasets.iter().map(|ds| ds.rows.len()).sum());
+        let mut mismatches = Vec::new();
+        for (&handle, ds) in handles.iter().zip(datasets) {
+            let positions: Vec<Option<usize>> = columns
+                .iter()
+                .map(|c| ds.headers.iter().position(|h| h == c))
+                .collect();
+            for row in &ds.rows {
+                let mut out: Vec<String> = positions
+                    .iter()
+                    .map(|p| p.and_then(|i| row.get(i).cloned()).unwrap_or_default())
+                    .collect();
+                if source_column.is_some() {
+                    out.push(ds.file_name.clone());
+                }
+                rows.push(out);
+            }
+
+            let missing_columns: Vec<String> = columns
+                .iter()
+                .zip(&positions)
+                .filter(|(_, p)| p.is_none())
+                .map(|(c, _)| c.clone())
+                .collect();
+            let extra_columns: Vec<String> = ds
+                .headers
+                .iter()
+                .filter(|h| !datasets[0].headers.contains(h))
+                .cloned()
+                .collect();
+            if !missing_columns.is_empty() || !extra_columns.is_empty() {
+                mismatches.push(SchemaMismatch {
+                    handle,
+                    file_name: ds.file_name.clone(),
+                    missing_columns,
+                    extra_columns,
+                });
+            }
+        }
+
+        let mut headers = columns;
+        headers.extend(source_column.clone());
+        let dataset = Dataset {
+            headers,
+            rows,
+            file_name: format!("{} (+{} more)", datasets[0].file_name, datasets.len() - 1),
+            file_type: datasets[0].file_type.clone(),
+            ..Default::default()
+        };
+        (dataset, mismatches)
+    })?;
+
+    Ok(UnionResult {
+        data: store.register(dataset),
+        mismatches,
+    })
+}
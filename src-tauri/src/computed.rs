@@ -0,0 +1,56 @@
+use crate::dataset::{Dataset, DatasetStore, FileData};
+use crate::{expr, history};
+use crate::error::AppError;
+use tauri::{AppHandle, State};
+
+/// Append a column whose value is an expression evaluated against each row,
+/// e.g. `unit_price * quantity` or `if(amount > 1000, "large", "small")`
+#[tauri::command]
+pub fn add_computed_column(
+    app: AppHandle,
+    store: State<'_, DatasetStore>,
+    handle: u64,
+    name: String,
+    expression: String,
+) -> Result<FileData, AppError> {
+    let mut result = store.with(handle, |ds| {
+        if name.trim().is_empty() {
+            return Err("Column name cannot be empty".to_string());
+        }
+        if ds.headers.contains(&name) {
+            return Err(format!("Column already exists: {}", name));
+        }
+        let compiled = expr::compile(&expression, &ds.headers)?;
+
+        let width = ds.headers.len();
+        let mut headers = ds.headers.clone();
+        headers.push(name.clone());
+        let rows = ds
+            .rows
+            .iter()
+            .map(|row| {
+                let mut out = row.clone();
+                out.resize(width, String::new());
+                out.push(compiled.eval(row).to_cell());
+                out
+            })
+            .collect();
+
+        Ok(Dataset {
+            headers,
+            rows,
+            file_name: format!("{} (computed)", ds.file_name),
+            file_type: ds.file_type.clone(),
+            ..Default::default()
+        })
+    })??;
+    history::record_transform(
+        &app,
+        &store,
+        handle,
+        &mut result,
+        "add_computed_column",
+        serde_json::json!({ "name": name, "expression": expression }),
+    );
+    Ok(store.register(result))
+}
@@ -0,0 +1,496 @@
+use crate::{access, cache, logging};
+use crate::dataset::{Dataset, DatasetStore, FileData, FileStamp};
+use crate::operations::{Operation, Operations};
+use crate::refresh::RemoteSource;
+use crate::{history, storage};
+use crate::error::AppError;
+use mysql::prelude::Queryable;
+use postgres::error::SqlState;
+use postgres::SimpleQueryMessage;
+use serde::{Deserialize, Serialize};
+use std::io::Read;
+use std::sync::Arc;
+use tauri::{AppHandle, State};
+
+const CONNECTIONS_FILE: &str = "connections.json";
+/// Keychain service name the database passwords are stored under
+const KEYRING_SERVICE: &str = "quick-data-viewer";
+/// Rows loaded when the caller doesn't give a limit
+const DEFAULT_ROW_LIMIT: usize = 100_000;
+
+/// A named database connection. The password is kept in the OS keychain,
+/// never in the connections file.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ConnectionConfig {
+    pub name: String,
+    /// "postgres" or "mysql"
+    pub kind: String,
+    pub host: String,
+    #[serde(default)]
+    pub port: Option<u16>,
+    pub database: String,
+    pub user: String,
+}
+
+fn keyring_entry(name: &str) -> Result<keyring::Entry, String> {
+    keyring::Entry::new(KEYRING_SERVICE, &format!("connection:{}", name))
+        .map_err(|e| format!("Failed to access the keychain: {}", e))
+}
+
+fn password(name: &str) -> Result<Option<String>, String> {
+    match keyring_entry(name)?.get_password() {
+        Ok(password) => Ok(Some(password)),
+        Err(keyring::Error::NoEntry) => Ok(None),
+        Err(e) => Err(format!("Failed to read password from the keychain: {}", e)),
+    }
+}
+
+/// Headers and rows of the last result set, all values as text
+type ResultSet = (Vec<String>, Vec<Vec<String>>);
+
+/// TLS for Postgres connections, trusting the system's root certificates.
+/// Servers without TLS are still reached in plain text, as with libpq's
+/// default `sslmode=prefer`.
+fn postgres_tls() -> Result<tokio_postgres_rustls::MakeRustlsConnect, String> {
+    let mut roots = rustls::RootCertStore::empty();
+    roots.add_parsable_certificates(rustls_native_certs::load_native_certs().certs);
+    let config = rustls::ClientConfig::builder_with_provider(Arc::new(rustls::crypto::ring::default_provider()))
+        .with_safe_default_protocol_versions()
+        .map_err(|e| format!("Failed to set up TLS: {}", e))?
+        .with_root_certificates(roots)
+        .with_no_client_auth();
+    Ok(tokio_postgres_rustls::MakeRustlsConnect::new(config))
+}
+
+/// Up to `limit` rows of a single query read through a cursor, so the server
+/// sends no more than that. None when the SQL can't be declared as a cursor:
+/// several statements, or one that isn't a SELECT or VALUES.
+fn fetch_postgres_cursor(
+    client: &mut postgres::Client,
+    sql: &str,
+    limit: usize,
+) -> Result<Option<Vec<SimpleQueryMessage>>, postgres::Error> {
+    let statement = sql.trim().trim_end_matches(';').trim_end();
+    if statement.contains(';') {
+        return Ok(None);
+    }
+    let mut transaction = client.transaction()?;
+    if let Err(e) = transaction.batch_execute(&format!("DECLARE qdv_rows NO SCROLL CURSOR FOR {}", statement)) {
+        // Dropping the transaction rolls it back
+        return match e.code() {
+            Some(&SqlState::SYNTAX_ERROR) | Some(&SqlState::FEATURE_NOT_SUPPORTED) => Ok(None),
+            _ => Err(e),
+        };
+    }
+    let messages = transaction.simple_query(&format!("FETCH FORWARD {} FROM qdv_rows", limit))?;
+    transaction.commit()?;
+    Ok(Some(messages))
+}
+
+/// Fails when interrupted: the result only arrives once the query is done
+fn query_postgres(
+    config: &ConnectionConfig,
+    password: Option<&str>,
+    sql: &str,
+    limit: usize,
+    op: &Operation,
+) -> Result<ResultSet, String> {
+    let mut pg = postgres::Config::new();
+    pg.host(&config.host)
+        .port(config.port.unwrap_or(5432))
+        .dbname(&config.database)
+        .user(&config.user);
+    if let Some(password) = password {
+        pg.password(password);
+    }
+    let tls = postgres_tls()?;
+    let mut client = pg
+        .connect(tls.clone())
+        .map_err(|e| format!("Failed to connect to {}: {}", config.name, e))?;
+
+    let token = client.cancel_token();
+    op.on_interrupt(move || {
+        let _ = token.cancel_query(tls);
+    });
+    // The simple query protocol returns every value as text, whatever its type
+    let messages = match fetch_postgres_cursor(&mut client, sql, limit) {
+        Ok(Some(messages)) => Ok(messages),
+        Ok(None) => client.simple_query(sql),
+        Err(e) => Err(e),
+    };
+    let messages = messages.map_err(|e| match op.interrupted() {
+        Some(reason) => reason.message(),
+        None => format!("Query failed: {}", e),
+    })?;
+    let mut headers = Vec::new();
+    let mut rows = Vec::new();
+    for message in messages {
+        match message {
+            SimpleQueryMessage::RowDescription(columns) => {
+                headers = columns.iter().map(|c| c.name().to_string()).collect();
+                rows.clear();
+            }
+            SimpleQueryMessage::Row(row) if rows.len() < limit => {
+                rows.push((0..row.len()).map(|i| row.get(i).unwrap_or("").to_string()).collect());
+            }
+            _ => {}
+        }
+    }
+    Ok((headers, rows))
+}
+
+fn mysql_value(value: mysql::Value) -> String {
+    use mysql::Value;
+    match value {
+        Value::NULL => String::new(),
+        Value::Bytes(bytes) => String::from_utf8_lossy(&bytes).into_owned(),
+        Value::Int(n) => n.to_string(),
+        Value::UInt(n) => n.to_string(),
+        Value::Float(f) => f.to_string(),
+        Value::Double(f) => f.to_string(),
+        Value::Date(y, mo, d, 0, 0, 0, 0) => format!("{:04}-{:02}-{:02}", y, mo, d),
+        Value::Date(y, mo, d, h, mi, s, 0) => format!("{:04}-{:02}-{:02} {:02}:{:02}:{:02}", y, mo, d, h, mi, s),
+        Value::Date(y, mo, d, h, mi, s, us) => {
+            format!("{:04}-{:02}-{:02} {:02}:{:02}:{:02}.{:06}", y, mo, d, h, mi, s, us)
+        }
+        Value::Time(negative, days, h, mi, s, us) => {
+            let sign = if negative { "-" } else { "" };
+            let hours = days * 24 + h as u32;
+            if us == 0 {
+                format!("{}{:02}:{:02}:{:02}", sign, hours, mi, s)
+            } else {
+                format!("{}{:02}:{:02}:{:02}.{:06}", sign, hours, mi, s, us)
+            }
+        }
+    }
+}
+
+/// The last result set's headers and its first `limit` rows, stopping early
+/// (with the rows read so far) when interrupted
+fn read_mysql_rows(
+    mut result: mysql::QueryResult<'_, '_, '_, mysql::Text>,
+    limit: usize,
+    op: &Operation,
+) -> Result<ResultSet, String> {
+    let mut headers = Vec::new();
+    let mut rows = Vec::new();
+    while let Some(set) = result.iter() {
+        headers = set.columns().as_ref().iter().map(|c| c.name_str().into_owned()).collect();
+        rows.clear();
+        for (i, row) in set.enumerate() {
+            if op.check(i).is_err() {
+                return Ok((headers, rows));
+            }
+            let mut row = row.map_err(|e| format!("Query failed: {}", e))?;
+            if rows.len() < limit {
+                rows.push((0..row.len()).map(|i| row.take(i).map_or_else(String::new, mysql_value)).collect());
+            }
+        }
+    }
+    Ok((headers, rows))
+}
+
+/// Rows read before an interruption are kept
+fn query_mysql(
+    config: &ConnectionConfig,
+    password: Option<&str>,
+    sql: &str,
+    limit: usize,
+    op: &Operation,
+) -> Result<ResultSet, String> {
+    let opts = mysql::OptsBuilder::new()
+        .ip_or_hostname(Some(config.host.clone()))
+        .tcp_port(config.port.unwrap_or(3306))
+        .db_name(Some(config.database.clone()))
+        .user(Some(config.user.clone()))
+        .pass(password.map(str::to_string));
+    let mut conn =
+        mysql::Conn::new(opts).map_err(|e| format!("Failed to connect to {}: {}", config.name, e))?;
+
+    // Wrap a single query in a LIMIT so the server sends no more rows than
+    // are kept. Several statements, or one that can't be a derived table
+    // (SHOW, or two columns of the same name), run as given.
+    let statement = sql.trim().trim_end_matches(';').trim_end();
+    if !statement.contains(';') {
+        match conn.query_iter(format!("SELECT * FROM ({}) AS qdv_rows LIMIT {}", statement, limit)) {
+            Ok(result) => return read_mysql_rows(result, limit, op),
+            Err(mysql::Error::MySqlError(e)) if e.code == 1064 || e.code == 1060 => {}
+            Err(e) => return Err(format!("Query failed: {}", e)),
+        }
+    }
+    let result = conn.query_iter(sql).map_err(|e| format!("Query failed: {}", e))?;
+    read_mysql_rows(result, limit, op)
+}
+
+/// Save (or overwrite by name) a connection. A given password replaces the
+/// stored one; leave it out to keep the current password.
+#[tauri::command]
+pub fn save_connection(app: AppHandle, connection: ConnectionConfig, password: Option<String>) -> Result<(), AppError> {
+    if connection.name.trim().is_empty() {
+        return Err("Connection name cannot be empty".into());
+    }
+    if connection.kind != "postgres" && connection.kind != "mysql" {
+        return Err(format!("Unsupported database: {}", connection.kind).into());
+    }
+    if let Some(password) = password {
+        keyring_entry(&connection.name)?
+            .set_password(&password)
+            .map_err(|e| format!("Failed to store password in the keychain: {}", e))?;
+    }
+    let mut connections: Vec<ConnectionConfig> = storage::load(&app, CONNECTIONS_FILE);
+    connections.retain(|c| c.name != connection.name);
+    connections.push(connection);
+    Ok(storage::save(&app, CONNECTIONS_FILE, &connections)?)
+}
+
+#[tauri::command]
+pub fn list_connections(app: AppHandle) -> Vec<ConnectionConfig> {
+    storage::load(&app, CONNECTIONS_FILE)
+}
+
+/// Forget a connection and its stored password
+#[tauri::command]
+pub fn delete_connection(app: AppHandle, name: String) -> Result<(), AppError> {
+    match keyring_entry(&name)?.delete_credential() {
+        Ok(()) | Err(keyring::Error::NoEntry) => {}
+        Err(e) => return Err(format!("Failed to remove password from the keychain: {}", e).into()),
+    }
+    let mut connections: Vec<ConnectionConfig> = storage::load(&app, CONNECTIONS_FILE);
+    connections.retain(|c| c.name != name);
+    Ok(storage::save(&app, CONNECTIONS_FILE, &connections)?)
+}
+
+/// The last result set a query on a saved connection returns, up to `limit`
+/// rows. A MySQL query stopped early keeps the rows read so far, marked as a
+/// preview.
+pub fn run_query(app: &AppHandle, connection: &str, sql: &str, limit: usize, op: &Operation) -> Result<Dataset, AppError> {
+    let connections: Vec<ConnectionConfig> = storage::load(app, CONNECTIONS_FILE);
+    let config = connections
+        .into_iter()
+        .find(|c| c.name == connection)
+        .ok_or_else(|| format!("No saved connection named {}", connection))?;
+    let password = password(&config.name)?;
+
+    let (headers, rows) = match config.kind.as_str() {
+        "postgres" => query_postgres(&config, password.as_deref(), sql, limit, op)?,
+        "mysql" => query_mysql(&config, password.as_deref(), sql, limit, op)?,
+        other => return Err(format!("Unsupported database: {}", other).into()),
+    };
+    if headers.is_empty() {
+        return Err("The query returned no result set".into());
+    }
+    Ok(Dataset {
+        headers,
+        rows,
+        file_name: format!("{} query", config.name),
+        file_type: "SQL".to_string(),
+        preview: op.interrupted().is_some(),
+        ..Default::default()
+    })
+}
+
+/// Whether a query only reads: a single SELECT or WITH that doesn't insert,
+/// update, delete, merge, or select into a table anywhere, data-modifying
+/// CTEs included. Errs on the side of no, as it decides what can be cached.
+fn is_read_only(sql: &str) -> bool {
+    let statement = sql.trim().trim_end_matches(';').trim_end();
+    if statement.contains(';') {
+        return false;
+    }
+    let mut words = statement
+        .split(|c: char| !c.is_alphanumeric() && c != '_')
+        .filter(|w| !w.is_empty())
+        .map(str::to_ascii_uppercase);
+    matches!(words.next().as_deref(), Some("SELECT" | "WITH"))
+        && !words.any(|w| matches!(w.as_str(), "INSERT" | "UPDATE" | "DELETE" | "MERGE" | "INTO"))
+}
+
+/// Run a query on a saved connection and load the last result set it returns,
+/// up to `row_limit` rows (100,000 by default). The query runs as a
+/// cancellable operation; see `run_query` for what happens when it stops.
+#[tauri::command(async)]
+#[allow(clippy::too_many_arguments)]
+pub fn query_database(
+    app: AppHandle,
+    store: State<'_, DatasetStore>,
+    ops: State<'_, Operations>,
+    connection: String,
+    sql: String,
+    row_limit: Option<usize>,
+    operation_id: Option<String>,
+    timeout_ms: Option<u64>,
+) -> Result<FileData, AppError> {
+    let _timed = logging::timed("query_database");
+    let row_limit = row_limit.unwrap_or(DEFAULT_ROW_LIMIT);
+    // Saved connections can change under us; `refresh_dataset` or
+    // `clear_result_cache` fetch anew. Queries that write are always run.
+    let key = cache::key("query_database", &(&connection, &sql, row_limit));
+    let cacheable = is_read_only(&sql);
+    let mut dataset = match store.cached_result::<Dataset>(&key).filter(|_| cacheable) {
+        Some(dataset) => dataset,
+        None => {
+            let op = ops.start(&app, operation_id, timeout_ms)?;
+            let dataset = run_query(&app, &connection, &sql, row_limit, &op)?;
+            if cacheable && !dataset.preview {
+                store.cache_result(key, dataset.clone());
+            }
+            dataset
+        }
+    };
+    let args = serde_json::json!({ "connection": connection, "sql": sql, "row_limit": row_limit });
+    dataset.remote = Some(RemoteSource::Database { connection, sql, row_limit });
+    let data = store.register(dataset);
+    history::record(&app, &store, data.handle, "query", "query_database", args);
+    Ok(data)
+}
+
+fn sqlite_value(value: rusqlite::types::ValueRef) -> String {
+    use rusqlite::types::ValueRef;
+    match value {
+        ValueRef::Null => String::new(),
+        ValueRef::Integer(n) => n.to_string(),
+        ValueRef::Real(f) => f.to_string(),
+        ValueRef::Text(text) => String::from_utf8_lossy(text).into_owned(),
+        ValueRef::Blob(bytes) => hex::encode(bytes),
+    }
+}
+
+/// Run a read-only query against a DuckDB file, opened read-only so nothing
+/// can be written. Every column comes back as text, and the limit is applied
+/// by DuckDB, which otherwise reads the whole result at once.
+fn query_duckdb(path: &str, sql: &str, limit: usize, op: &Operation) -> Result<ResultSet, AppError> {
+    let config = duckdb::Config::default()
+        .access_mode(duckdb::AccessMode::ReadOnly)
+        .map_err(|e| format!("Failed to open database: {}", e))?;
+    let conn = duckdb::Connection::open_with_flags(path, config).map_err(|e| format!("Failed to open database: {}", e))?;
+    let wrapped = format!(
+        "SELECT COLUMNS(*)::VARCHAR FROM ({}) LIMIT {}",
+        sql.trim().trim_end_matches(';'),
+        limit
+    );
+    let mut statement = conn.prepare(&wrapped).map_err(|e| format!("Query failed: {}", e))?;
+    let interrupt = conn.interrupt_handle();
+    op.on_interrupt(move || interrupt.interrupt());
+    let mut result = statement.query([]).map_err(|e| match op.interrupted() {
+        Some(reason) => reason.message(),
+        None => format!("Query failed: {}", e),
+    })?;
+    let headers = result.as_ref().map(|s| s.column_names()).unwrap_or_default();
+    let width = headers.len();
+    let mut rows = Vec::new();
+    loop {
+        let row = match result.next() {
+            Ok(Some(row)) => row,
+            Ok(None) => break,
+            Err(_) if op.interrupted().is_some() => break,
+            Err(e) => return Err(format!("Query failed: {}", e).into()),
+        };
+        rows.push(
+            (0..width)
+                .map(|i| row.get::<_, Option<String>>(i).ok().flatten().unwrap_or_default())
+                .collect(),
+        );
+    }
+    Ok((headers, rows))
+}
+
+/// Run a read-only statement against a SQLite or DuckDB file. Rows read
+/// before an interruption are kept.
+fn query_sqlite(path: &str, sql: &str, limit: usize, op: &Operation) -> Result<ResultSet, AppError> {
+    let mut header = [0u8; 16];
+    std::fs::File::open(path)
+        .and_then(|mut f| f.read_exact(&mut header))
+        .map_err(|e| format!("Failed to read database file: {}", e))?;
+    if &header[8..12] == b"DUCK" {
+        return query_duckdb(path, sql, limit, op);
+    }
+    if &header != b"SQLite format 3\0" {
+        return Err("Not a SQLite or DuckDB database file".into());
+    }
+
+    let conn = rusqlite::Connection::open_with_flags(path, rusqlite::OpenFlags::SQLITE_OPEN_READ_ONLY)
+        .map_err(|e| format!("Failed to open database: {}", e))?;
+    let mut statement = conn.prepare(sql).map_err(|e| format!("Query failed: {}", e))?;
+    if !statement.readonly() {
+        return Err("Only read-only queries can be run".into());
+    }
+    let headers: Vec<String> = statement.column_names().into_iter().map(str::to_string).collect();
+    let width = headers.len();
+    let interrupt = conn.get_interrupt_handle();
+    op.on_interrupt(move || interrupt.interrupt());
+    let mut result = statement.query([]).map_err(|e| format!("Query failed: {}", e))?;
+    let mut rows = Vec::new();
+    while rows.len() < limit {
+        let row = match result.next() {
+            Ok(Some(row)) => row,
+            Ok(None) => break,
+            Err(_) if op.interrupted().is_some() => break,
+            Err(e) => return Err(format!("Query failed: {}", e).into()),
+        };
+        rows.push(
+            (0..width)
+                .map(|i| row.get_ref(i).map(sqlite_value).unwrap_or_default())
+                .collect(),
+        );
+    }
+    Ok((headers, rows))
+}
+
+/// Run read-only SQL (e.g. a join across tables) against a SQLite or DuckDB
+/// file and load the result, up to `row_limit` rows (100,000 by default). As
+/// with `query_database`, a query stopped early loads the rows read so far.
+#[tauri::command(async)]
+#[allow(clippy::too_many_arguments)]
+pub fn run_database_sql(
+    app: AppHandle,
+    store: State<'_, DatasetStore>,
+    ops: State<'_, Operations>,
+    connection: String,
+    query: String,
+    row_limit: Option<usize>,
+    operation_id: Option<String>,
+    timeout_ms: Option<u64>,
+) -> Result<FileData, AppError> {
+    let _timed = logging::timed("run_database_sql");
+    access::check(&app, &connection)?;
+    let row_limit = row_limit.unwrap_or(DEFAULT_ROW_LIMIT);
+    // Keyed on the file's size and modification time too, so edits to it miss
+    let stamp = FileStamp::read(&connection).map(|s| (s.size, s.modified));
+    let key = cache::key("run_database_sql", &(&connection, &query, row_limit, stamp));
+    let mut dataset = match store.cached_result::<Dataset>(&key) {
+        Some(dataset) => dataset,
+        None => {
+            let op = ops.start(&app, operation_id, timeout_ms)?;
+            let dataset = run_sqlite(&connection, &query, row_limit, &op)?;
+            if !dataset.preview {
+                store.cache_result(key, dataset.clone());
+            }
+            dataset
+        }
+    };
+    let args = serde_json::json!({ "connection": connection, "query": query, "row_limit": row_limit });
+    dataset.remote = Some(RemoteSource::Sqlite { path: connection, query, row_limit });
+    let data = store.register(dataset);
+    history::record(&app, &store, data.handle, "query", "run_database_sql", args);
+    Ok(data)
+}
+
+/// The result of read-only SQL against a SQLite or DuckDB file, up to `limit` rows
+pub fn run_sqlite(path: &str, query: &str, limit: usize, op: &Operation) -> Result<Dataset, AppError> {
+    let (headers, rows) = query_sqlite(path, query, limit, op)?;
+    if headers.is_empty() {
+        return Err("The query returned no result set".into());
+    }
+    let file_name = std::path::Path::new(path)
+        .file_name()
+        .map(|n| n.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    Ok(Dataset {
+        headers,
+        rows,
+        file_name: format!("{} query", file_name),
+        file_type: "SQL".to_string(),
+        preview: op.interrupted().is_some(),
+        ..Default::default()
+    })
+}
@@ -0,0 +1,650 @@
+use crate::cache::{self, ResultCache};
+use crate::changes::Baseline;
+use crate::display::DisplayFormat;
+use crate::formatting::FormatRule;
+use crate::journal::{Edit, Journal};
+use crate::operations::Operation;
+use crate::paging::{truncate, TruncatedCell};
+use crate::recipe::RecipeStep;
+use crate::refresh::RemoteSource;
+use crate::scan::Scan;
+use crate::schema_override::SchemaOverride;
+use crate::settings::DEFAULT_MAX_CELL_LENGTH;
+use crate::types::{infer_type, ColumnType};
+use crate::views::{self, ViewConfig};
+use crate::error::AppError;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant, SystemTime};
+
+/// A column's settings that are kept by name rather than in the table, so
+/// they can go along with the column when it's removed and put back
+#[derive(Debug, Clone, Default)]
+pub struct ColumnMeta {
+    pub unit: Option<String>,
+    pub declared: Option<ColumnType>,
+    pub display: Option<DisplayFormat>,
+    pub kind: Option<JsonKind>,
+}
+
+/// A parsed dataset held in memory by the backend
+#[derive(Debug, Clone, Default)]
+pub struct Dataset {
+    pub headers: Vec<String>,
+    pub rows: Vec<Vec<String>>,
+    pub file_name: String,
+    pub file_type: String,
+    /// File the dataset was loaded from; `None` for derived datasets
+    pub source_path: Option<String>,
+    /// Size and modification time of the source file when it was loaded or saved
+    pub source_stamp: Option<FileStamp>,
+    /// Layout details of the source file, used when saving back to it
+    pub source_format: Option<SourceFormat>,
+    /// Modified since it was loaded or last saved
+    pub dirty: bool,
+    /// Undo/redo history of in-place edits
+    pub journal: Journal,
+    /// The table as loaded, kept once it's first edited in place
+    pub baseline: Option<Arc<Baseline>>,
+    /// Unit or currency of numeric columns, e.g. "$" or "MB"
+    pub units: HashMap<String, String>,
+    /// Column types declared by a schema override, which win over inference
+    pub column_types: HashMap<String, ColumnType>,
+    /// Schema override that rewrote cells, headers, or JSON types when loading
+    pub schema: Option<SchemaOverride>,
+    /// For derived datasets, the file the original dataset was loaded from
+    pub origin: Option<String>,
+    /// Transforms that produced this dataset from that file, in order
+    pub steps: Vec<RecipeStep>,
+    /// Filter and sort currently shown for this dataset, as last reported by the frontend
+    pub view: Option<ViewConfig>,
+    /// Only the first rows of the source were loaded, to stay under the memory limit
+    pub preview: bool,
+    /// Conditional formatting applied as rows are paged
+    pub format_rules: Vec<FormatRule>,
+    /// How columns are shown when paged and exported, by column name
+    pub display_formats: HashMap<String, DisplayFormat>,
+    /// Where each row's record sits in the source file; cleared once rows
+    /// are added or removed, or the file is rewritten
+    pub record_spans: Vec<RecordSpan>,
+    /// Records a lenient load skipped because they didn't parse
+    pub parse_failures: Vec<ParseFailure>,
+    /// Filter and columns applied while loading; the dataset holds only
+    /// part of the file
+    pub scan: Option<Scan>,
+    /// Rows are still being appended, e.g. while stdin streams in
+    pub loading: bool,
+    /// The URL, API, or query the rows were fetched from, for refreshing
+    pub remote: Option<RemoteSource>,
+    /// Refresh from `remote` this often in the background
+    pub refresh_interval: Option<Duration>,
+    /// When the last refresh was attempted, or the interval was set
+    pub last_refresh: Option<Instant>,
+}
+
+/// The bytes of the source file a row was parsed from
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RecordSpan {
+    pub start: u64,
+    pub end: u64,
+    /// 1-based line the record starts on
+    pub line: u64,
+    /// Line it ends on, for records with line breaks in quoted values
+    pub end_line: u64,
+}
+
+/// A record that failed to parse, kept so it can be sent back upstream
+#[derive(Debug, Clone, Serialize)]
+pub struct ParseFailure {
+    /// 1-based line the record starts on
+    pub line: u64,
+    /// The record exactly as it appears in the file
+    pub text: String,
+    pub error: String,
+}
+
+/// Enough about a file to notice when it changes on disk
+#[derive(Debug, Clone, PartialEq)]
+pub struct FileStamp {
+    pub size: u64,
+    pub modified: Option<SystemTime>,
+}
+
+impl FileStamp {
+    pub fn read(path: &str) -> Option<FileStamp> {
+        let metadata = std::fs::metadata(path).ok()?;
+        Some(FileStamp {
+            size: metadata.len(),
+            modified: metadata.modified().ok(),
+        })
+    }
+}
+
+/// The JSON type a column's values had in the source file
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum JsonKind {
+    String,
+    Number,
+    Bool,
+    Array,
+    Object,
+    Mixed,
+}
+
+/// Where the rows lived inside a JSON document (see `extract_data_array`)
+#[derive(Debug, Clone, PartialEq)]
+pub enum JsonLayout {
+    /// A top-level array of objects
+    Array,
+    /// An array under this key of a top-level object
+    Wrapped(String),
+    /// A dict of objects, with each key surfaced as the "Name" column
+    Keyed,
+    /// A single top-level object
+    Single,
+}
+
+#[derive(Debug, Clone)]
+pub enum SourceFormat {
+    Csv {
+        crlf: bool,
+        header: bool,
+        delimiter: u8,
+        /// Every field was quoted, not only those that need it
+        quote_all: bool,
+        /// The last record ended with a line break
+        final_newline: bool,
+    },
+    Json { layout: JsonLayout, kinds: HashMap<String, JsonKind> },
+    Jsonl { kinds: HashMap<String, JsonKind> },
+}
+
+impl SourceFormat {
+    /// The CSV layout of `content`, judging quoting by its first line
+    pub fn csv(content: &[u8], delimiter: u8, header: bool) -> SourceFormat {
+        let first_line = content.split(|&b| b == b'\n').next().unwrap_or_default();
+        let first_line = first_line.strip_suffix(b"\r").unwrap_or(first_line);
+        let delimiters = first_line.iter().filter(|&&b| b == delimiter).count();
+        let quoted_delimiters = first_line.windows(3).filter(|w| *w == [b'"', delimiter, b'"']).count();
+        SourceFormat::Csv {
+            crlf: content.windows(2).any(|w| w == b"\r\n"),
+            header,
+            delimiter,
+            quote_all: first_line.len() >= 2
+                && first_line.starts_with(b"\"")
+                && first_line.ends_with(b"\"")
+                && quoted_delimiters == delimiters,
+            final_newline: content.is_empty() || content.ends_with(b"\n"),
+        }
+    }
+
+    /// Record the JSON type a column should be written back as
+    pub fn set_kind(&mut self, column: &str, kind: JsonKind) {
+        self.replace_kind(column, Some(kind));
+    }
+
+    /// Set or clear the JSON type a column is written back as, returning the
+    /// one it had
+    pub fn replace_kind(&mut self, column: &str, kind: Option<JsonKind>) -> Option<JsonKind> {
+        let (SourceFormat::Json { kinds, .. } | SourceFormat::Jsonl { kinds }) = self else {
+            return None;
+        };
+        match kind {
+            Some(kind) => kinds.insert(column.to_string(), kind),
+            None => kinds.remove(column),
+        }
+    }
+
+    /// Carry a column's source type over to its new name
+    pub fn rename_column(&mut self, from: &str, to: &str) {
+        if let SourceFormat::Json { kinds, .. } | SourceFormat::Jsonl { kinds } = self {
+            if let Some(kind) = kinds.remove(from) {
+                kinds.insert(to.to_string(), kind);
+            }
+        }
+    }
+}
+
+/// What the frontend receives for a loaded dataset
+#[derive(Debug, Serialize, Deserialize)]
+pub struct FileData {
+    pub handle: u64,
+    pub headers: Vec<String>,
+    pub rows: Vec<Vec<String>>,
+    pub row_count: usize,
+    pub file_name: String,
+    pub file_type: String,
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub units: HashMap<String, String>,
+    /// See `Dataset::column_types`
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub column_types: HashMap<String, ColumnType>,
+    /// Only the first rows were loaded; see `Dataset::preview`
+    #[serde(default)]
+    pub preview: bool,
+    /// Records a lenient load skipped; see `Dataset::parse_failures`
+    #[serde(default)]
+    pub failed_lines: usize,
+    /// Cells in `rows` cut short so huge values don't clog the IPC
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub truncated: Vec<TruncatedCell>,
+}
+
+impl Dataset {
+    /// Look up a column index by header name
+    pub fn column_index(&self, name: &str) -> Result<usize, String> {
+        self.headers
+            .iter()
+            .position(|h| h == name)
+            .ok_or_else(|| format!("Column not found: {}", name))
+    }
+
+    /// The column's declared type, or else the type inferred from its values
+    pub fn column_type(&self, col: usize) -> ColumnType {
+        match self.headers.get(col).and_then(|name| self.column_types.get(name)) {
+            Some(&kind) => kind,
+            None => infer_type(self.rows.iter().filter_map(|row| row.get(col)).map(String::as_str)),
+        }
+    }
+
+    /// The file this dataset came from, directly or through transforms
+    pub fn origin_path(&self) -> Option<String> {
+        self.source_path.clone().or_else(|| self.origin.clone())
+    }
+
+    /// Drop per-column settings of columns the dataset no longer has
+    pub fn forget_missing_columns(&mut self) {
+        let headers = &self.headers;
+        self.units.retain(|name, _| headers.contains(name));
+        self.column_types.retain(|name, _| headers.contains(name));
+        self.display_formats.retain(|name, _| headers.contains(name));
+        if let Some(SourceFormat::Json { kinds, .. } | SourceFormat::Jsonl { kinds }) = &mut self.source_format {
+            kinds.retain(|name, _| headers.contains(name));
+        }
+    }
+
+    /// Remove a column's settings, returning them
+    pub fn take_column_meta(&mut self, name: &str) -> ColumnMeta {
+        ColumnMeta {
+            unit: self.units.remove(name),
+            declared: self.column_types.remove(name),
+            display: self.display_formats.remove(name),
+            kind: self.source_format.as_mut().and_then(|format| format.replace_kind(name, None)),
+        }
+    }
+
+    /// Give a column these settings, clearing any it has that `meta` leaves out
+    pub fn set_column_meta(&mut self, name: &str, meta: ColumnMeta) {
+        match meta.unit {
+            Some(unit) => self.units.insert(name.to_string(), unit),
+            None => self.units.remove(name),
+        };
+        match meta.declared {
+            Some(declared) => self.column_types.insert(name.to_string(), declared),
+            None => self.column_types.remove(name),
+        };
+        match meta.display {
+            Some(display) => self.display_formats.insert(name.to_string(), display),
+            None => self.display_formats.remove(name),
+        };
+        if let Some(format) = &mut self.source_format {
+            format.replace_kind(name, meta.kind);
+        }
+    }
+
+    /// Rename the column at `col`, carrying its metadata along; returns the old name
+    pub fn rename_column_at(&mut self, col: usize, name: String) -> String {
+        let old = std::mem::replace(&mut self.headers[col], name.clone());
+        if let Some(format) = &mut self.source_format {
+            format.rename_column(&old, &name);
+        }
+        if let Some(unit) = self.units.remove(&old) {
+            self.units.insert(name.clone(), unit);
+        }
+        if let Some(kind) = self.column_types.remove(&old) {
+            self.column_types.insert(name.clone(), kind);
+        }
+        if let Some(format) = self.display_formats.remove(&old) {
+            self.display_formats.insert(name, format);
+        }
+        old
+    }
+
+    /// Record an in-place edit for undo, keeping the table as it was
+    /// before the first one to report changes against
+    pub fn record_edit(&mut self, inverse: Edit) {
+        if self.baseline.is_none() {
+            self.baseline = Some(Arc::new(Baseline::before(self, &inverse)));
+        }
+        self.journal.record(inverse);
+    }
+
+    /// What the frontend receives for this dataset, with cells longer than
+    /// the default `max_cell_length` cut short
+    pub fn to_file_data(&self, handle: u64) -> FileData {
+        let mut truncated = Vec::new();
+        let rows = self
+            .rows
+            .iter()
+            .enumerate()
+            .map(|(row, cells)| {
+                cells
+                    .iter()
+                    .enumerate()
+                    .map(|(column, cell)| truncate(cell, row, column, DEFAULT_MAX_CELL_LENGTH, &mut truncated))
+                    .collect()
+            })
+            .collect();
+        FileData {
+            handle,
+            headers: self.headers.clone(),
+            rows,
+            row_count: self.rows.len(),
+            file_name: self.file_name.clone(),
+            file_type: self.file_type.clone(),
+            units: self.units.clone(),
+            column_types: self.column_types.clone(),
+            preview: self.preview,
+            failed_lines: self.parse_failures.len(),
+            truncated,
+        }
+    }
+}
+
+/// A filter/sort/column view over a loaded dataset with a handle of its own,
+/// so several views of one dataset can be paged side by side
+#[derive(Debug, Clone)]
+pub struct DatasetView {
+    pub dataset: u64,
+    pub config: ViewConfig,
+    /// Rows in view order, with the journal version and row count they were
+    /// worked out for
+    cached: Option<((u64, usize), Vec<usize>)>,
+}
+
+impl DatasetView {
+    pub fn set_config(&mut self, config: ViewConfig) {
+        self.config = config;
+        self.cached = None;
+    }
+
+    /// Indices of the dataset rows the view shows, in display order
+    pub fn rows(&mut self, ds: &Dataset) -> Result<&[usize], String> {
+        self.rows_within(ds, &Operation::unbounded())
+    }
+
+    /// Like `rows`, but working them out stops when `op` is interrupted
+    pub fn rows_within(&mut self, ds: &Dataset, op: &Operation) -> Result<&[usize], String> {
+        let key = (ds.journal.version(), ds.rows.len());
+        if self.cached.as_ref().is_none_or(|(k, _)| *k != key) {
+            self.cached = Some((key, views::view_rows(ds, &self.config, op)?));
+        }
+        Ok(self.cached.as_ref().map_or(&[], |(_, rows)| rows))
+    }
+}
+
+#[derive(Default)]
+struct StoreInner {
+    next_handle: u64,
+    datasets: HashMap<u64, Dataset>,
+    views: HashMap<u64, DatasetView>,
+    cache: ResultCache,
+    /// The dataset each window shows, by window label
+    shown: HashMap<String, u64>,
+}
+
+/// Registry of loaded datasets, keyed by the handle returned to the frontend
+#[derive(Default)]
+pub struct DatasetStore {
+    inner: Mutex<StoreInner>,
+}
+
+impl DatasetStore {
+    /// Store a dataset and return its new handle
+    pub fn insert(&self, dataset: Dataset) -> u64 {
+        let mut inner = self.inner.lock().unwrap();
+        inner.next_handle += 1;
+        let handle = inner.next_handle;
+        inner.datasets.insert(handle, dataset);
+        handle
+    }
+
+    /// Take a handle for something kept outside the store, such as an open
+    /// Parquet file, so it never matches a dataset or view handle
+    pub fn reserve_handle(&self) -> u64 {
+        let mut inner = self.inner.lock().unwrap();
+        inner.next_handle += 1;
+        inner.next_handle
+    }
+
+    /// Store a dataset and build the response the frontend expects
+    pub fn register(&self, dataset: Dataset) -> FileData {
+        let data = dataset.to_file_data(0);
+        let handle = self.insert(dataset);
+        FileData { handle, ..data }
+    }
+
+    /// Run a closure against a loaded dataset
+    pub fn with<T>(&self, handle: u64, f: impl FnOnce(&Dataset) -> T) -> Result<T, String> {
+        let inner = self.inner.lock().unwrap();
+        let dataset = inner
+            .datasets
+            .get(&handle)
+            .ok_or_else(|| format!("Unknown dataset handle: {}", handle))?;
+        Ok(f(dataset))
+    }
+
+    /// Run a closure against a loaded dataset and the result cache
+    pub fn with_cache<T>(&self, handle: u64, f: impl FnOnce(&Dataset, &mut ResultCache) -> T) -> Result<T, String> {
+        let mut inner = self.inner.lock().unwrap();
+        let StoreInner { datasets, cache, .. } = &mut *inner;
+        let dataset = datasets
+            .get(&handle)
+            .ok_or_else(|| format!("Unknown dataset handle: {}", handle))?;
+        Ok(f(dataset, cache))
+    }
+
+    /// Like `with`, but a successful result is cached under `key` until the
+    /// dataset changes, so asking again returns it without recomputing
+    pub fn with_cached<T: Clone + Send + 'static, E>(
+        &self,
+        handle: u64,
+        key: String,
+        f: impl FnOnce(&Dataset) -> Result<T, E>,
+    ) -> Result<Result<T, E>, String> {
+        self.with_cache(handle, |ds, cache| cache.get_or_insert(handle, key, || f(ds)))
+    }
+
+    /// Like `with_cached`, but only `snapshot` runs with the store locked, to
+    /// copy what `f` needs out of the dataset; `f` then works it out without
+    /// holding up other commands. The result is kept only if the dataset
+    /// wasn't changed in the meantime.
+    pub fn with_cached_unlocked<S, T: Clone + Send + 'static, E>(
+        &self,
+        handle: u64,
+        key: String,
+        snapshot: impl FnOnce(&Dataset) -> S,
+        f: impl FnOnce(S) -> Result<T, E>,
+    ) -> Result<Result<T, E>, String> {
+        let (copied, generation) = {
+            let mut inner = self.inner.lock().unwrap();
+            let StoreInner { datasets, cache, .. } = &mut *inner;
+            let dataset = datasets
+                .get(&handle)
+                .ok_or_else(|| format!("Unknown dataset handle: {}", handle))?;
+            if let Some(value) = cache.get(handle, &key) {
+                return Ok(Ok(value));
+            }
+            (snapshot(dataset), cache.generation(handle))
+        };
+        let value = match f(copied) {
+            Ok(value) => value,
+            Err(e) => return Ok(Err(e)),
+        };
+        let mut inner = self.inner.lock().unwrap();
+        if inner.datasets.contains_key(&handle) && inner.cache.generation(handle) == generation {
+            inner.cache.insert(handle, key, value.clone());
+        }
+        Ok(Ok(value))
+    }
+
+    /// A cached result that belongs to no dataset, such as a query's
+    pub fn cached_result<T: Clone + 'static>(&self, key: &str) -> Option<T> {
+        self.inner.lock().unwrap().cache.get(cache::UNOWNED, key)
+    }
+
+    /// Keep a result that belongs to no dataset until the cache is cleared
+    /// or pushes it out. Unlike `with_cached`, nothing is locked while the
+    /// result is worked out, so slow queries don't hold up other commands.
+    pub fn cache_result<T: Send + 'static>(&self, key: String, value: T) {
+        self.inner.lock().unwrap().cache.insert(cache::UNOWNED, key, value);
+    }
+
+    /// Run a closure that may modify a loaded dataset. Cached results for it
+    /// are dropped.
+    pub fn with_mut<T>(&self, handle: u64, f: impl FnOnce(&mut Dataset) -> T) -> Result<T, String> {
+        let mut inner = self.inner.lock().unwrap();
+        let StoreInner { datasets, cache, .. } = &mut *inner;
+        let dataset = datasets
+            .get_mut(&handle)
+            .ok_or_else(|| format!("Unknown dataset handle: {}", handle))?;
+        cache.invalidate(handle);
+        Ok(f(dataset))
+    }
+
+    /// Run a closure against two loaded datasets (which may be the same one)
+    pub fn with_pair<T>(
+        &self,
+        a: u64,
+        b: u64,
+        f: impl FnOnce(&Dataset, &Dataset) -> T,
+    ) -> Result<T, String> {
+        let inner = self.inner.lock().unwrap();
+        let get = |handle: u64| {
+            inner
+                .datasets
+                .get(&handle)
+                .ok_or_else(|| format!("Unknown dataset handle: {}", handle))
+        };
+        Ok(f(get(a)?, get(b)?))
+    }
+
+    /// Run a closure against several loaded datasets, in the order given
+    pub fn with_many<T>(&self, handles: &[u64], f: impl FnOnce(&[&Dataset]) -> T) -> Result<T, String> {
+        let inner = self.inner.lock().unwrap();
+        let datasets = handles
+            .iter()
+            .map(|h| {
+                inner
+                    .datasets
+                    .get(h)
+                    .ok_or_else(|| format!("Unknown dataset handle: {}", h))
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(f(&datasets))
+    }
+
+    /// Handles of every loaded dataset, in the order they were opened
+    pub fn handles(&self) -> Vec<u64> {
+        let mut handles: Vec<u64> = self.inner.lock().unwrap().datasets.keys().copied().collect();
+        handles.sort_unstable();
+        handles
+    }
+
+    pub fn remove(&self, handle: u64) -> Option<Dataset> {
+        let mut inner = self.inner.lock().unwrap();
+        inner.views.retain(|_, view| view.dataset != handle);
+        inner.shown.retain(|_, h| *h != handle);
+        inner.cache.invalidate(handle);
+        inner.datasets.remove(&handle)
+    }
+
+    /// Note that the window labelled `label` now shows `handle`. The dataset
+    /// it showed before is dropped, unless another window still shows it.
+    pub fn show_in(&self, label: &str, handle: u64) {
+        let released = {
+            let mut inner = self.inner.lock().unwrap();
+            inner
+                .shown
+                .insert(label.to_string(), handle)
+                .filter(|&previous| previous != handle && !inner.shown.values().any(|&h| h == previous))
+        };
+        if let Some(previous) = released {
+            self.remove(previous);
+        }
+    }
+
+    /// Drop every cached result and return how many there were
+    pub fn clear_cache(&self) -> usize {
+        self.inner.lock().unwrap().cache.clear()
+    }
+
+    /// Open a view over a loaded dataset and return the view's handle
+    pub fn insert_view(&self, dataset: u64, config: ViewConfig) -> Result<u64, String> {
+        let mut inner = self.inner.lock().unwrap();
+        if !inner.datasets.contains_key(&dataset) {
+            return Err(format!("Unknown dataset handle: {}", dataset));
+        }
+        inner.next_handle += 1;
+        let handle = inner.next_handle;
+        inner.views.insert(handle, DatasetView { dataset, config, cached: None });
+        Ok(handle)
+    }
+
+    /// Run a closure against an open view and the dataset it shows
+    pub fn with_view<T>(&self, handle: u64, f: impl FnOnce(&Dataset, &mut DatasetView) -> T) -> Result<T, String> {
+        let mut inner = self.inner.lock().unwrap();
+        let StoreInner { datasets, views, .. } = &mut *inner;
+        let view = views
+            .get_mut(&handle)
+            .ok_or_else(|| format!("Unknown view handle: {}", handle))?;
+        let dataset = datasets
+            .get(&view.dataset)
+            .ok_or_else(|| format!("Unknown dataset handle: {}", view.dataset))?;
+        Ok(f(dataset, view))
+    }
+
+    pub fn is_view(&self, handle: u64) -> bool {
+        self.inner.lock().unwrap().views.contains_key(&handle)
+    }
+
+    /// Handles of the views open over a dataset, oldest first
+    pub fn view_handles(&self, dataset: u64) -> Vec<u64> {
+        let inner = self.inner.lock().unwrap();
+        let mut handles: Vec<u64> = inner
+            .views
+            .iter()
+            .filter(|(_, view)| view.dataset == dataset)
+            .map(|(handle, _)| *handle)
+            .collect();
+        handles.sort_unstable();
+        handles
+    }
+
+    pub fn remove_view(&self, handle: u64) -> Option<DatasetView> {
+        self.inner.lock().unwrap().views.remove(&handle)
+    }
+}
+
+/// Drop a dataset the frontend no longer needs
+#[tauri::command]
+pub fn close_dataset(store: tauri::State<'_, DatasetStore>, handle: u64) -> Result<(), AppError> {
+    Ok(store
+        .remove(handle)
+        .map(|_| ())
+        .ok_or_else(|| format!("Unknown dataset handle: {}", handle))?)
+}
+
+/// Switch a window to another dataset, dropping the one it showed unless
+/// another window still shows it
+#[tauri::command]
+pub fn show_dataset(store: tauri::State<'_, DatasetStore>, label: String, handle: u64) {
+    store.show_in(&label, handle);
+}
+
+/// The current contents of a loaded dataset
+#[tauri::command]
+pub fn get_dataset(store: tauri::State<'_, DatasetStore>, handle: u64) -> Result<FileData, AppError> {
+    Ok(store.with(handle, |ds| ds.to_file_data(handle))?)
+}
@@ -0,0 +1,199 @@
+use crate::dataset::{Dataset, DatasetStore, FileData};
+use crate::dates;
+use crate::history;
+use crate::stats::is_null;
+use crate::error::AppError;
+use chrono::{NaiveDate, NaiveDateTime, Timelike};
+use serde::Serialize;
+use tauri::{AppHandle, State};
+
+/// Numeric day/month formats, which only differ in which part is the day.
+/// Two-digit years come first, since `%Y` would happily read "24" as year 24.
+const DMY_FORMATS: &[&str] = &["%d/%m/%y", "%d/%m/%Y", "%d-%m-%Y", "%d.%m.%Y"];
+const MDY_FORMATS: &[&str] = &["%m/%d/%y", "%m/%d/%Y", "%m-%d-%Y", "%m.%d.%Y"];
+
+/// Formats that spell out the month, so they're never ambiguous
+const NAMED_MONTH_FORMATS: &[&str] = &[
+    "%b %d %Y",
+    "%b %d, %Y",
+    "%B %d %Y",
+    "%B %d, %Y",
+    "%d %b %Y",
+    "%d %B %Y",
+    "%d-%b-%Y",
+    "%d-%b-%y",
+    "%a, %d %b %Y",
+];
+
+fn parse_with(value: &str, formats: &[&str]) -> Option<NaiveDate> {
+    formats
+        .iter()
+        .find_map(|f| NaiveDate::parse_from_str(value, f).ok())
+}
+
+/// How a single value can be read
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Reading {
+    Iso,
+    NamedMonth,
+    DayFirst,
+    MonthFirst,
+    /// Valid as both day-first and month-first, e.g. 03/04/2024
+    Ambiguous,
+    /// The same date either way, e.g. 05/05/2024, so no hint at the order
+    SameEitherWay,
+    Unparsed,
+}
+
+/// ISO parse, limited to values starting with a four-digit year so that
+/// "03/04/24" isn't read as the year 3
+fn parse_iso(value: &str) -> Option<NaiveDateTime> {
+    let year_first = value.len() >= 4 && value.as_bytes()[..4].iter().all(u8::is_ascii_digit);
+    year_first.then(|| dates::parse_datetime(value)).flatten()
+}
+
+fn classify(value: &str) -> Reading {
+    if parse_iso(value).is_some() {
+        return Reading::Iso;
+    }
+    if parse_with(value, NAMED_MONTH_FORMATS).is_some() {
+        return Reading::NamedMonth;
+    }
+    match (parse_with(value, DMY_FORMATS), parse_with(value, MDY_FORMATS)) {
+        (Some(a), Some(b)) if a == b => Reading::SameEitherWay,
+        (Some(_), Some(_)) => Reading::Ambiguous,
+        (Some(_), None) => Reading::DayFirst,
+        (None, Some(_)) => Reading::MonthFirst,
+        (None, None) => Reading::Unparsed,
+    }
+}
+
+/// Parse a value in any supported format, resolving day/month order with `day_first`
+fn parse_any(value: &str, day_first: bool) -> Option<NaiveDateTime> {
+    let value = value.trim();
+    if let Some(dt) = parse_iso(value) {
+        return Some(dt);
+    }
+    let (preferred, fallback) = if day_first { (DMY_FORMATS, MDY_FORMATS) } else { (MDY_FORMATS, DMY_FORMATS) };
+    parse_with(value, NAMED_MONTH_FORMATS)
+        .or_else(|| parse_with(value, preferred))
+        .or_else(|| parse_with(value, fallback))
+        .and_then(|d| d.and_hms_opt(0, 0, 0))
+}
+
+#[derive(Debug, Serialize)]
+pub struct FormatCount {
+    /// "iso", "named_month", "day_first", "month_first", "ambiguous",
+    /// "same_either_way", or "unparsed"
+    pub kind: String,
+    pub count: usize,
+    pub example: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct DateFormatReport {
+    pub column: String,
+    pub formats: Vec<FormatCount>,
+    /// "dmy" or "mdy", based on the values that can only be read one way
+    pub suggested_order: String,
+}
+
+/// Survey the date formats used in a column so the user can confirm how
+/// ambiguous values like 03/04/2024 should be read
+#[tauri::command]
+pub fn detect_date_formats(
+    store: State<'_, DatasetStore>,
+    handle: u64,
+    column: String,
+) -> Result<DateFormatReport, AppError> {
+    store.with(handle, |ds| {
+        let col = ds.column_index(&column)?;
+        let kinds = [
+            (Reading::Iso, "iso"),
+            (Reading::NamedMonth, "named_month"),
+            (Reading::DayFirst, "day_first"),
+            (Reading::MonthFirst, "month_first"),
+            (Reading::Ambiguous, "ambiguous"),
+            (Reading::SameEitherWay, "same_either_way"),
+            (Reading::Unparsed, "unparsed"),
+        ];
+        let mut counts: Vec<(usize, String)> = vec![(0, String::new()); kinds.len()];
+        for row in &ds.rows {
+            let value = row.get(col).map(|v| v.trim()).unwrap_or("");
+            if is_null(value) {
+                continue;
+            }
+            let reading = classify(value);
+            let slot = &mut counts[kinds.iter().position(|(r, _)| *r == reading).unwrap_or(0)];
+            if slot.0 == 0 {
+                slot.1 = value.to_string();
+            }
+            slot.0 += 1;
+        }
+
+        let day_first = counts[2].0 > counts[3].0;
+        Ok(DateFormatReport {
+            column: column.clone(),
+            formats: kinds
+                .iter()
+                .zip(counts)
+                .filter(|(_, (count, _))| *count > 0)
+                .map(|((_, kind), (count, example))| FormatCount {
+                    kind: kind.to_string(),
+                    count,
+                    example,
+                })
+                .collect(),
+            suggested_order: if day_first { "dmy" } else { "mdy" }.to_string(),
+        })
+    })?
+}
+
+/// Rewrite every recognisable date in a column as ISO 8601, reading
+/// ambiguous numeric dates in `order` ("dmy" or "mdy"). Values that can't be
+/// parsed are left untouched.
+#[tauri::command]
+pub fn normalize_dates(
+    app: AppHandle,
+    store: State<'_, DatasetStore>,
+    handle: u64,
+    column: String,
+    order: String,
+) -> Result<FileData, AppError> {
+    let day_first = match order.as_str() {
+        "dmy" => true,
+        "mdy" => false,
+        other => return Err(format!("Unknown date order: {}", other).into()),
+    };
+
+    let mut result = store.with(handle, |ds| {
+        let col = ds.column_index(&column)?;
+        let mut rows = ds.rows.clone();
+        for row in &mut rows {
+            let Some(cell) = row.get_mut(col) else { continue };
+            if let Some(dt) = parse_any(cell, day_first) {
+                *cell = if dt.time().num_seconds_from_midnight() == 0 && dt.nanosecond() == 0 {
+                    dt.format("%Y-%m-%d").to_string()
+                } else {
+                    dt.format("%Y-%m-%dT%H:%M:%S").to_string()
+                };
+            }
+        }
+        Ok::<_, String>(Dataset {
+            headers: ds.headers.clone(),
+            rows,
+            file_name: format!("{} (dates normalized)", ds.file_name),
+            file_type: ds.file_type.clone(),
+            ..Default::default()
+        })
+    })??;
+    history::record_transform(
+        &app,
+        &store,
+        handle,
+        &mut result,
+        "normalize_dates",
+        serde_json::json!({ "column": column, "order": order }),
+    );
+    Ok(store.register(result))
+}
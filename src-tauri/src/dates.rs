@@ -0,0 +1,155 @@
+use chrono::format::{Fixed, Item, StrftimeItems};
+use chrono::{Datelike, Duration, NaiveDate, NaiveDateTime, Timelike};
+use serde::{Deserialize, Serialize};
+use std::fmt::Write;
+
+const DATETIME_FORMATS: &[&str] = &[
+    "%Y-%m-%dT%H:%M:%S%.f",
+    "%Y-%m-%d %H:%M:%S%.f",
+    "%Y-%m-%dT%H:%M",
+    "%Y-%m-%d %H:%M",
+];
+
+const DATE_FORMATS: &[&str] = &["%Y-%m-%d", "%Y/%m/%d"];
+
+/// Parse ISO-style dates and datetimes. Values carrying a timezone offset
+/// are converted to UTC; everything else is taken as-is.
+pub fn parse_datetime(value: &str) -> Option<NaiveDateTime> {
+    let value = value.trim();
+    if let Ok(dt) = chrono::DateTime::parse_from_rfc3339(value) {
+        return Some(dt.naive_utc());
+    }
+    for format in DATETIME_FORMATS {
+        if let Ok(dt) = NaiveDateTime::parse_from_str(value, format) {
+            return Some(dt);
+        }
+    }
+    for format in DATE_FORMATS {
+        if let Ok(date) = NaiveDate::parse_from_str(value, format) {
+            return date.and_hms_opt(0, 0, 0);
+        }
+    }
+    None
+}
+
+/// Check a chrono pattern can format dates that have no timezone. Formatting
+/// with an invalid pattern, or one asking for an offset, panics in `to_string`.
+pub fn check_pattern(pattern: &str) -> Result<(), String> {
+    let needs_offset = |item: &Item| {
+        matches!(
+            item,
+            Item::Fixed(
+                Fixed::TimezoneName
+                    | Fixed::TimezoneOffset
+                    | Fixed::TimezoneOffsetZ
+                    | Fixed::TimezoneOffsetColon
+                    | Fixed::TimezoneOffsetColonZ
+                    | Fixed::TimezoneOffsetDoubleColon
+                    | Fixed::TimezoneOffsetTripleColon
+                    | Fixed::RFC2822
+                    | Fixed::RFC3339
+            )
+        )
+    };
+    if pattern.is_empty() || StrftimeItems::new(pattern).any(|item| item == Item::Error) {
+        return Err(format!("Invalid date pattern: {}", pattern));
+    }
+    if StrftimeItems::new(pattern).any(|item| needs_offset(&item)) {
+        return Err(format!("Date pattern {} needs a timezone, which these dates don't have", pattern));
+    }
+    Ok(())
+}
+
+/// `dt` written with `pattern`, or `None` if the pattern can't format it
+pub fn format_datetime(dt: NaiveDateTime, pattern: &str) -> Option<String> {
+    let mut text = String::new();
+    write!(text, "{}", dt.format(pattern)).ok()?;
+    Some(text)
+}
+
+/// Calendar granularity used when bucketing dates
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TimeUnit {
+    Minute,
+    Hour,
+    Day,
+    Month,
+    Year,
+}
+
+impl TimeUnit {
+    pub const ALL: [TimeUnit; 5] = [
+        TimeUnit::Minute,
+        TimeUnit::Hour,
+        TimeUnit::Day,
+        TimeUnit::Month,
+        TimeUnit::Year,
+    ];
+
+    /// Round a timestamp down to the start of its bucket
+    pub fn truncate(self, dt: NaiveDateTime) -> NaiveDateTime {
+        let date = dt.date();
+        match self {
+            TimeUnit::Minute => dt.with_second(0).and_then(|d| d.with_nanosecond(0)).unwrap_or(dt),
+            TimeUnit::Hour => date.and_hms_opt(dt.hour(), 0, 0).unwrap_or(dt),
+            TimeUnit::Day => date.and_hms_opt(0, 0, 0).unwrap_or(dt),
+            TimeUnit::Month => NaiveDate::from_ymd_opt(date.year(), date.month(), 1)
+                .and_then(|d| d.and_hms_opt(0, 0, 0))
+                .unwrap_or(dt),
+            TimeUnit::Year => NaiveDate::from_ymd_opt(date.year(), 1, 1)
+                .and_then(|d| d.and_hms_opt(0, 0, 0))
+                .unwrap_or(dt),
+        }
+    }
+
+    /// Start of the bucket following the one `start` begins
+    pub fn next(self, start: NaiveDateTime) -> NaiveDateTime {
+        match self {
+            TimeUnit::Minute => start + Duration::minutes(1),
+            TimeUnit::Hour => start + Duration::hours(1),
+            TimeUnit::Day => start + Duration::days(1),
+            TimeUnit::Month => start
+                .checked_add_months(chrono::Months::new(1))
+                .unwrap_or(start),
+            TimeUnit::Year => start
+                .checked_add_months(chrono::Months::new(12))
+                .unwrap_or(start),
+        }
+    }
+
+    /// Number of buckets needed to cover `min..=max`
+    pub fn bucket_count(self, min: NaiveDateTime, max: NaiveDateTime) -> i64 {
+        let (min, max) = (self.truncate(min), self.truncate(max));
+        match self {
+            TimeUnit::Minute => (max - min).num_minutes() + 1,
+            TimeUnit::Hour => (max - min).num_hours() + 1,
+            TimeUnit::Day => (max - min).num_days() + 1,
+            TimeUnit::Month => {
+                (max.year() as i64 * 12 + max.month() as i64)
+                    - (min.year() as i64 * 12 + min.month() as i64)
+                    + 1
+            }
+            TimeUnit::Year => (max.year() - min.year()) as i64 + 1,
+        }
+    }
+
+    /// Finest unit that covers the range in at most `max_buckets` buckets
+    pub fn choose(min: NaiveDateTime, max: NaiveDateTime, max_buckets: usize) -> TimeUnit {
+        TimeUnit::ALL
+            .into_iter()
+            .find(|unit| unit.bucket_count(min, max) <= max_buckets as i64)
+            .unwrap_or(TimeUnit::Year)
+    }
+
+    pub fn label(self, start: NaiveDateTime) -> String {
+        let format = match self {
+            TimeUnit::Minute => "%Y-%m-%d %H:%M",
+            TimeUnit::Hour => "%Y-%m-%d %H:00",
+            TimeUnit::Day => "%Y-%m-%d",
+            TimeUnit::Month => "%Y-%m",
+            TimeUnit::Year => "%Y",
+        };
+        start.format(format).to_string()
+    }
+}
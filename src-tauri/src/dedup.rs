@@ -0,0 +1,309 @@
+use crate::dataset::{Dataset, DatasetStore, FileData};
+use crate::error::AppError;
+use crate::operations::Operations;
+use crate::{history, logging};
+use serde::Serialize;
+use std::collections::HashMap;
+use tauri::{AppHandle, State};
+
+#[derive(Debug, Serialize)]
+pub struct DuplicateGroup {
+    /// The shared values of the key columns
+    pub key: Vec<String>,
+    /// Zero-based row indices, in file order
+    pub rows: Vec<usize>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct DuplicateReport {
+    pub key_columns: Vec<String>,
+    pub groups: Vec<DuplicateGroup>,
+    /// Rows that would be removed by deduplication (every member but one per group)
+    pub duplicate_rows: usize,
+}
+
+/// Resolve the key columns, defaulting to every column (exact row match)
+fn key_indices(ds: &Dataset, key_columns: Option<&[String]>) -> Result<Vec<usize>, String> {
+    match key_columns {
+        Some(cols) if !cols.is_empty() => cols.iter().map(|c| ds.column_index(c)).collect(),
+        _ => Ok((0..ds.headers.len()).collect()),
+    }
+}
+
+fn row_key(row: &[String], cols: &[usize]) -> Vec<String> {
+    cols.iter()
+        .map(|&c| row.get(c).cloned().unwrap_or_default())
+        .collect()
+}
+
+/// Group row indices by key, keeping groups in order of first appearance
+fn group_rows(ds: &Dataset, cols: &[usize]) -> Vec<(Vec<String>, Vec<usize>)> {
+    let mut index: HashMap<Vec<String>, usize> = HashMap::new();
+    let mut groups: Vec<(Vec<String>, Vec<usize>)> = Vec::new();
+    for (i, row) in ds.rows.iter().enumerate() {
+        let key = row_key(row, cols);
+        match index.get(&key) {
+            Some(&g) => groups[g].1.push(i),
+            None => {
+                index.insert(key.clone(), groups.len());
+                groups.push((key, vec![i]));
+            }
+        }
+    }
+    groups
+}
+
+/// Find rows that are exact duplicates, optionally comparing only `key_columns`
+#[tauri::command]
+pub fn find_duplicates(
+    store: State<'_, DatasetStore>,
+    handle: u64,
+    key_columns: Option<Vec<String>>,
+) -> Result<DuplicateReport, AppError> {
+    store.with(handle, |ds| {
+        let cols = key_indices(ds, key_columns.as_deref())?;
+        let groups: Vec<DuplicateGroup> = group_rows(ds, &cols)
+            .into_iter()
+            .filter(|(_, rows)| rows.len() > 1)
+            .map(|(key, rows)| DuplicateGroup { key, rows })
+            .collect();
+
+        Ok(DuplicateReport {
+            key_columns: cols.iter().map(|&c| ds.headers[c].clone()).collect(),
+            duplicate_rows: groups.iter().map(|g| g.rows.len() - 1).sum(),
+            groups,
+        })
+    })?
+}
+
+/// Build a new dataset with one row per key. `keep` chooses whether the
+/// "first" (default) or "last" occurrence survives; row order is preserved.
+#[tauri::command]
+pub fn deduplicate(
+    app: AppHandle,
+    store: State<'_, DatasetStore>,
+    handle: u64,
+    key_columns: Option<Vec<String>>,
+    keep: Option<String>,
+) -> Result<FileData, AppError> {
+    let keep_last = match keep.as_deref().unwrap_or("first") {
+        "first" => false,
+        "last" => true,
+        other => return Err(format!("Unknown keep option: {}", other).into()),
+    };
+
+    let mut deduped = store.with(handle, |ds| {
+        let cols = key_indices(ds, key_columns.as_deref())?;
+        let mut kept: Vec<usize> = group_rows(ds, &cols)
+            .into_iter()
+            .map(|(_, rows)| if keep_last { rows[rows.len() - 1] } else { rows[0] })
+            .collect();
+        kept.sort_unstable();
+
+        Ok::<_, String>(Dataset {
+            headers: ds.headers.clone(),
+            rows: kept.into_iter().map(|i| ds.rows[i].clone()).collect(),
+            file_name: format!("{} (deduplicated)", ds.file_name),
+            file_type: ds.file_type.clone(),
+            ..Default::default()
+        })
+    })??;
+    history::record_transform(
+        &app,
+        &store,
+        handle,
+        &mut deduped,
+        "deduplicate",
+        serde_json::json!({ "key_columns": key_columns, "keep": keep }),
+    );
+    Ok(store.register(deduped))
+}
+
+#[derive(Debug, Serialize)]
+pub struct FuzzyGroup {
+    pub rows: Vec<usize>,
+    /// The compared text of each row, aligned with `rows`
+    pub values: Vec<String>,
+    /// Lowest pairwise similarity that linked this group together
+    pub min_similarity: f64,
+}
+
+/// Lowercase, trim, and collapse runs of whitespace/punctuation so that
+/// "Doe,  John" and "doe john" compare as equal
+fn normalize_for_match(value: &str) -> String {
+    value
+        .split(|c: char| c.is_whitespace() || c.is_ascii_punctuation())
+        .filter(|s| !s.is_empty())
+        .map(|s| s.to_lowercase())
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Character trigrams of a value with how often each occurs. The value is
+/// padded so a value of n characters has n + 2 of them.
+fn trigrams(value: &str) -> Vec<([char; 3], usize)> {
+    let pad = std::iter::repeat_n('\0', 2);
+    let chars: Vec<char> = pad.clone().chain(value.chars()).chain(pad).collect();
+    let mut grams: Vec<[char; 3]> = chars.windows(3).map(|w| [w[0], w[1], w[2]]).collect();
+    grams.sort_unstable();
+    let mut counted: Vec<([char; 3], usize)> = Vec::new();
+    for gram in grams {
+        match counted.last_mut() {
+            Some((last, n)) if *last == gram => *n += 1,
+            _ => counted.push((gram, 1)),
+        }
+    }
+    counted
+}
+
+fn find_root(parent: &mut [usize], mut i: usize) -> usize {
+    while parent[i] != i {
+        parent[i] = parent[parent[i]];
+        i = parent[i];
+    }
+    i
+}
+
+/// Find near-duplicate rows by comparing the normalized text of `columns`.
+/// `algorithm` is "levenshtein" (default) or "jaro_winkler"; pairs scoring at
+/// least `threshold` (0-1, default 0.85) are linked into candidate groups.
+/// For Levenshtein, values sharing too few character trigrams to be within
+/// reach of the threshold are skipped; above a threshold of 2/3 that rules
+/// out every value with no trigram in common, so only those are compared.
+#[tauri::command(async)]
+#[allow(clippy::too_many_arguments)]
+pub fn find_fuzzy_duplicates(
+    app: AppHandle,
+    store: State<'_, DatasetStore>,
+    ops: State<'_, Operations>,
+    handle: u64,
+    columns: Vec<String>,
+    threshold: Option<f64>,
+    algorithm: Option<String>,
+    operation_id: Option<String>,
+    timeout_ms: Option<u64>,
+) -> Result<Vec<FuzzyGroup>, AppError> {
+    let _timed = logging::timed("find_fuzzy_duplicates");
+    let threshold = threshold.unwrap_or(0.85);
+    let algorithm = algorithm.unwrap_or_else(|| "levenshtein".to_string());
+    let is_levenshtein = algorithm == "levenshtein";
+    let similarity: fn(&str, &str) -> f64 = match algorithm.as_str() {
+        "levenshtein" => strsim::normalized_levenshtein,
+        "jaro_winkler" => strsim::jaro_winkler,
+        other => return Err(format!("Unknown similarity algorithm: {}", other).into()),
+    };
+    if columns.is_empty() {
+        return Err("Select at least one column to compare".into());
+    }
+
+    let op = ops.start(&app, operation_id, timeout_ms)?;
+    let texts: Vec<String> = store.with(handle, |ds| {
+        let cols = columns
+            .iter()
+            .map(|c| ds.column_index(c))
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok::<_, String>(ds.rows.iter().map(|row| row_key(row, &cols).join(" ")).collect())
+    })??;
+
+    // Compare each distinct normalized value once rather than every row pair
+    let mut distinct: Vec<String> = Vec::new();
+    let mut members: Vec<Vec<usize>> = Vec::new();
+    let mut index: HashMap<String, usize> = HashMap::new();
+    for (row, text) in texts.iter().enumerate() {
+        op.check(row)?;
+        let norm = normalize_for_match(text);
+        if norm.is_empty() {
+            continue;
+        }
+        let i = *index.entry(norm.clone()).or_insert_with(|| {
+            distinct.push(norm);
+            members.push(Vec::new());
+            distinct.len() - 1
+        });
+        members[i].push(row);
+    }
+
+    // Count the trigrams each pair of values shares; each edit changes at
+    // most three, so a pair within k edits shares at least max(len) + 2 - 3k
+    let grams: Vec<Vec<([char; 3], usize)>> = distinct.iter().map(|s| trigrams(s)).collect();
+    let mut postings: HashMap<[char; 3], Vec<(usize, usize)>> = HashMap::new();
+    for (i, value_grams) in grams.iter().enumerate() {
+        for &(gram, n) in value_grams {
+            postings.entry(gram).or_default().push((i, n));
+        }
+    }
+    let blocked = is_levenshtein && threshold > 2.0 / 3.0;
+
+    let mut parent: Vec<usize> = (0..distinct.len()).collect();
+    let mut min_score = vec![1.0f64; distinct.len()];
+    let lengths: Vec<usize> = distinct.iter().map(|s| s.chars().count()).collect();
+    let mut shared = vec![0usize; distinct.len()];
+    let mut candidates = Vec::new();
+    let mut steps = 0;
+    for i in 0..distinct.len() {
+        for &(gram, n) in &grams[i] {
+            let posting = &postings[&gram];
+            for &(j, m) in &posting[posting.partition_point(|&(j, _)| j <= i)..] {
+                op.check(steps)?;
+                steps += 1;
+                if shared[j] == 0 {
+                    candidates.push(j);
+                }
+                shared[j] += n.min(m);
+            }
+        }
+        if !blocked {
+            candidates = (i + 1..distinct.len()).collect();
+        }
+        for j in candidates.drain(..) {
+            op.check(steps)?;
+            steps += 1;
+            let common = std::mem::take(&mut shared[j]);
+            if is_levenshtein {
+                // Edit distance can't beat the length difference or the
+                // trigrams the pair doesn't share, so skip hopeless pairs
+                let longest = lengths[i].max(lengths[j]);
+                let diff = lengths[i].abs_diff(lengths[j]) as f64;
+                if 1.0 - diff / (longest as f64) < threshold {
+                    continue;
+                }
+                let max_edits = ((1.0 - threshold) * longest as f64 + 1e-9).floor() as usize;
+                if common + 3 * max_edits < longest + 2 {
+                    continue;
+                }
+            }
+            let score = similarity(&distinct[i], &distinct[j]);
+            if score >= threshold {
+                let (a, b) = (find_root(&mut parent, i), find_root(&mut parent, j));
+                if a != b {
+                    parent[b] = a;
+                }
+                min_score[a] = min_score[a].min(min_score[b]).min(score);
+            }
+        }
+    }
+
+    let mut groups: Vec<(usize, Vec<usize>)> = Vec::new();
+    let mut group_of: HashMap<usize, usize> = HashMap::new();
+    for (i, rows) in members.iter().enumerate() {
+        let root = find_root(&mut parent, i);
+        let g = *group_of.entry(root).or_insert_with(|| {
+            groups.push((root, Vec::new()));
+            groups.len() - 1
+        });
+        groups[g].1.extend(rows);
+    }
+
+    Ok(groups
+        .into_iter()
+        .filter(|(_, rows)| rows.len() > 1)
+        .map(|(root, mut rows)| {
+            rows.sort_unstable();
+            FuzzyGroup {
+                values: rows.iter().map(|&r| texts[r].clone()).collect(),
+                rows,
+                min_similarity: min_score[root],
+            }
+        })
+        .collect())
+}
@@ -0,0 +1,142 @@
+use crate::dataset::DatasetStore;
+use crate::dates;
+use crate::error::AppError;
+use crate::stats::{is_null, parse_number};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use tauri::State;
+
+/// How a column's values are shown and exported. The stored values stay as
+/// they are; values the format doesn't apply to are shown unchanged.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct DisplayFormat {
+    /// Round numbers to this many digits after the decimal point
+    pub decimals: Option<usize>,
+    /// Group the digits of numbers in threes with this, e.g. "," or " "
+    pub thousands_separator: Option<String>,
+    /// Show numbers as percentages: 0.25 becomes 25%
+    pub percent: bool,
+    /// chrono pattern for dates, e.g. "%d %b %Y"
+    pub date_pattern: Option<String>,
+}
+
+/// Put `separator` between each group of three digits in the integer part
+fn group_thousands(number: &str, separator: &str) -> String {
+    let (sign, rest) = number.strip_prefix('-').map_or(("", number), |rest| ("-", rest));
+    let (int, fraction) = rest.split_at(rest.find('.').unwrap_or(rest.len()));
+    if !int.bytes().all(|b| b.is_ascii_digit()) {
+        return number.to_string();
+    }
+    let mut grouped = String::with_capacity(number.len() + int.len() / 3 * separator.len());
+    for (i, digit) in int.chars().enumerate() {
+        if i > 0 && (int.len() - i).is_multiple_of(3) {
+            grouped.push_str(separator);
+        }
+        grouped.push(digit);
+    }
+    format!("{}{}{}", sign, grouped, fraction)
+}
+
+impl DisplayFormat {
+    fn formats_numbers(&self) -> bool {
+        self.decimals.is_some() || self.thousands_separator.is_some() || self.percent
+    }
+
+    fn validate(&self) -> Result<(), String> {
+        if let Some(pattern) = &self.date_pattern {
+            dates::check_pattern(pattern)?;
+        }
+        if self.decimals.is_some_and(|d| d > 15) {
+            return Err("Numbers can show at most 15 decimal places".to_string());
+        }
+        Ok(())
+    }
+
+    /// The text to show for a stored value
+    pub fn apply(&self, value: &str) -> String {
+        let trimmed = value.trim();
+        if is_null(trimmed) {
+            return value.to_string();
+        }
+        if let Some(pattern) = &self.date_pattern {
+            if let Some(text) = dates::parse_datetime(trimmed).and_then(|dt| dates::format_datetime(dt, pattern)) {
+                return text;
+            }
+        }
+        if !self.formats_numbers() {
+            return value.to_string();
+        }
+        let Some(n) = parse_number(trimmed) else {
+            return value.to_string();
+        };
+        let n = if self.percent { n * 100.0 } else { n };
+        let mut text = match self.decimals {
+            Some(decimals) => format!("{:.*}", decimals, n),
+            // Keep the digits as written, bar float noise from scaling
+            None if self.percent => ((n * 1e9).round() / 1e9).to_string(),
+            None => trimmed.to_string(),
+        };
+        if let Some(separator) = &self.thousands_separator {
+            text = group_thousands(&text, separator);
+        }
+        if self.percent {
+            text.push('%');
+        }
+        text
+    }
+}
+
+/// Rows about to be exported with `handle`'s display formats applied,
+/// matching columns by name; unchanged when no handle is given
+pub fn format_export(
+    store: &DatasetStore,
+    handle: Option<u64>,
+    headers: &[String],
+    mut rows: Vec<Vec<String>>,
+) -> Result<Vec<Vec<String>>, String> {
+    let Some(handle) = handle else {
+        return Ok(rows);
+    };
+    let formats: Vec<Option<DisplayFormat>> =
+        store.with(handle, |ds| headers.iter().map(|h| ds.display_formats.get(h).cloned()).collect())?;
+    if formats.iter().all(Option::is_none) {
+        return Ok(rows);
+    }
+    for row in &mut rows {
+        for (cell, format) in row.iter_mut().zip(&formats) {
+            if let Some(format) = format {
+                *cell = format.apply(cell);
+            }
+        }
+    }
+    Ok(rows)
+}
+
+/// Set how a column is shown when paged and exported, or go back to its
+/// stored values with `None`
+#[tauri::command]
+pub fn set_display_format(
+    store: State<'_, DatasetStore>,
+    handle: u64,
+    column: String,
+    format: Option<DisplayFormat>,
+) -> Result<(), AppError> {
+    if let Some(format) = &format {
+        format.validate()?;
+    }
+    store.with_mut(handle, |ds| {
+        ds.column_index(&column)?;
+        match format.filter(|f| *f != DisplayFormat::default()) {
+            Some(format) => ds.display_formats.insert(column, format),
+            None => ds.display_formats.remove(&column),
+        };
+        Ok(())
+    })?
+}
+
+/// Display formats by column name
+#[tauri::command]
+pub fn get_display_formats(store: State<'_, DatasetStore>, handle: u64) -> Result<HashMap<String, DisplayFormat>, AppError> {
+    Ok(store.with(handle, |ds| ds.display_formats.clone())?)
+}
@@ -0,0 +1,126 @@
+use crate::dataset::{Dataset, DatasetStore, FileData};
+use crate::journal::Edit;
+use crate::types::infer_type;
+use crate::error::AppError;
+use tauri::State;
+
+/// Change a single cell. The new value must fit the column's inferred type
+/// (e.g. no text in an integer column) unless `force` is set.
+#[tauri::command]
+pub fn update_cell(
+    store: State<'_, DatasetStore>,
+    handle: u64,
+    row: usize,
+    column: String,
+    value: String,
+    force: Option<bool>,
+) -> Result<(), AppError> {
+    Ok(store.with_mut(handle, |ds| {
+        let col = ds.column_index(&column)?;
+        if row >= ds.rows.len() {
+            return Err(format!("Row {} is out of range", row));
+        }
+
+        if !force.unwrap_or(false) {
+            let column_type = ds.column_types.get(&column).copied().unwrap_or_else(|| {
+                infer_type(
+                    ds.rows
+                        .iter()
+                        .enumerate()
+                        .filter(|(i, _)| *i != row)
+                        .filter_map(|(_, r)| r.get(col).map(|v| v.as_str())),
+                )
+            });
+            if !column_type.accepts(&value) {
+                return Err(format!(
+                    "'{}' is not a valid {} value for column {}",
+                    value,
+                    column_type.label(),
+                    column
+                ));
+            }
+        }
+
+        let cells = &mut ds.rows[row];
+        if cells.len() <= col {
+            cells.resize(col + 1, String::new());
+        }
+        let old = std::mem::replace(&mut cells[col], value);
+        ds.record_edit(Edit::Cells(vec![(row, col, old)]));
+        ds.dirty = true;
+        Ok(())
+    })??)
+}
+
+/// Validate row indices and return them sorted and deduplicated
+fn checked_rows(ds: &Dataset, rows: &[usize]) -> Result<Vec<usize>, AppError> {
+    let mut sorted = rows.to_vec();
+    sorted.sort_unstable();
+    sorted.dedup();
+    match sorted.last() {
+        Some(&last) if last >= ds.rows.len() => Err(format!("Row {} is out of range", last).into()),
+        _ => Ok(sorted),
+    }
+}
+
+/// Insert `count` (default 1) blank rows before `index`, or at the end
+#[tauri::command]
+pub fn insert_rows(
+    store: State<'_, DatasetStore>,
+    handle: u64,
+    index: Option<usize>,
+    count: Option<usize>,
+) -> Result<FileData, AppError> {
+    Ok(store.with_mut(handle, |ds| {
+        let at = index.unwrap_or(ds.rows.len());
+        if at > ds.rows.len() {
+            return Err(format!("Row {} is out of range", at));
+        }
+        let blank = vec![String::new(); ds.headers.len()];
+        let count = count.unwrap_or(1);
+        ds.rows.splice(at..at, std::iter::repeat_n(blank, count));
+        ds.record_edit(Edit::RemoveRows((at..at + count).collect()));
+        ds.record_spans.clear();
+        ds.dirty = true;
+        Ok(ds.to_file_data(handle))
+    })??)
+}
+
+/// Remove the given rows
+#[tauri::command]
+pub fn delete_rows(store: State<'_, DatasetStore>, handle: u64, rows: Vec<usize>) -> Result<FileData, AppError> {
+    store.with_mut(handle, |ds| {
+        let rows = checked_rows(ds, &rows)?;
+        let removed = rows.iter().map(|&r| (r, ds.rows[r].clone())).collect();
+        let mut next = rows.iter().peekable();
+        let mut i = 0;
+        ds.rows.retain(|_| {
+            let keep = next.next_if_eq(&&i).is_none();
+            i += 1;
+            keep
+        });
+        ds.record_edit(Edit::InsertRows(removed));
+        ds.record_spans.clear();
+        ds.dirty = true;
+        Ok(ds.to_file_data(handle))
+    })?
+}
+
+/// Copy the given rows, placing each copy directly below its original
+#[tauri::command]
+pub fn duplicate_rows(store: State<'_, DatasetStore>, handle: u64, rows: Vec<usize>) -> Result<FileData, AppError> {
+    store.with_mut(handle, |ds| {
+        let rows = checked_rows(ds, &rows)?;
+        // Work from the bottom up so earlier indices stay valid
+        for &r in rows.iter().rev() {
+            let copy = ds.rows[r].clone();
+            ds.rows.insert(r + 1, copy);
+        }
+        // Each copy sits below its original, shifted by the copies above it
+        let copies = rows.iter().enumerate().map(|(k, &r)| r + k + 1).collect();
+        ds.record_edit(Edit::RemoveRows(copies));
+        ds.record_spans.clear();
+        ds.dirty = true;
+        Ok(ds.to_file_data(handle))
+    })?
+}
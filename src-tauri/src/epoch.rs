@@ -0,0 +1,144 @@
+use crate::dataset::{Dataset, DatasetStore, FileData};
+use crate::history;
+use crate::stats::{column_values, parse_number};
+use crate::error::AppError;
+use chrono::format::{Item, StrftimeItems};
+use chrono::{DateTime, FixedOffset, Local, TimeZone, Utc};
+use serde::Serialize;
+use tauri::{AppHandle, State};
+
+/// Epoch units with how many of them make a second, and the range of
+/// magnitudes that land between 1990 and 2100
+const UNITS: &[(&str, f64)] = &[("s", 1.0), ("ms", 1e3), ("us", 1e6), ("ns", 1e9)];
+const PLAUSIBLE_SECONDS: (f64, f64) = (631_152_000.0, 4_102_444_800.0);
+
+#[derive(Debug, Serialize)]
+pub struct EpochHint {
+    pub column: String,
+    /// "s", "ms", "us", or "ns"
+    pub unit: String,
+}
+
+/// Guess the unit of a column of epoch timestamps, if every value fits one
+fn detect_unit(values: &[&str]) -> Option<&'static str> {
+    let numbers: Vec<f64> = values.iter().map(|v| parse_number(v)).collect::<Option<_>>()?;
+    if numbers.is_empty() {
+        return None;
+    }
+    UNITS.iter().find_map(|&(unit, per_second)| {
+        numbers
+            .iter()
+            .all(|n| (PLAUSIBLE_SECONDS.0..PLAUSIBLE_SECONDS.1).contains(&(n / per_second)))
+            .then_some(unit)
+    })
+}
+
+/// Columns that look like they hold epoch timestamps
+#[tauri::command]
+pub fn detect_epoch_columns(store: State<'_, DatasetStore>, handle: u64) -> Result<Vec<EpochHint>, AppError> {
+    Ok(store.with(handle, |ds| {
+        (0..ds.headers.len())
+            .filter_map(|col| {
+                detect_unit(&column_values(ds, col)).map(|unit| EpochHint {
+                    column: ds.headers[col].clone(),
+                    unit: unit.to_string(),
+                })
+            })
+            .collect()
+    })?)
+}
+
+/// Split an epoch value into whole seconds and nanoseconds, staying in
+/// integers where possible so nanosecond values keep their precision
+fn to_utc(value: &str, per_second: f64) -> Option<DateTime<Utc>> {
+    let (seconds, nanos) = match value.trim().parse::<i64>() {
+        Ok(n) => {
+            let per = per_second as i64;
+            (n.div_euclid(per), (n.rem_euclid(per) * (1_000_000_000 / per)) as u32)
+        }
+        Err(_) => {
+            let seconds = parse_number(value)? / per_second;
+            (seconds.floor() as i64, ((seconds - seconds.floor()) * 1e9).round().min(999_999_999.0) as u32)
+        }
+    };
+    Utc.timestamp_opt(seconds, nanos).single()
+}
+
+fn format_in<Tz: TimeZone>(utc: DateTime<Utc>, tz: &Tz, format: &str) -> String
+where
+    Tz::Offset: std::fmt::Display,
+{
+    utc.with_timezone(tz).format(format).to_string()
+}
+
+/// Convert epoch timestamps in `column` to readable datetimes. `unit` is
+/// detected when omitted; `timezone` is "UTC" (default), "local", an IANA
+/// name like "Europe/Berlin", or a fixed offset like "+05:30".
+#[tauri::command]
+pub fn convert_epoch(
+    app: AppHandle,
+    store: State<'_, DatasetStore>,
+    handle: u64,
+    column: String,
+    unit: Option<String>,
+    timezone: Option<String>,
+    format: Option<String>,
+) -> Result<FileData, AppError> {
+    let tz_name = timezone.clone().unwrap_or_else(|| "UTC".to_string());
+    let named_tz = tz_name.parse::<chrono_tz::Tz>().ok();
+    let fixed_tz = tz_name.parse::<FixedOffset>().ok();
+    if !tz_name.eq_ignore_ascii_case("local") && named_tz.is_none() && fixed_tz.is_none() {
+        return Err(format!("Unknown timezone: {}", tz_name).into());
+    }
+
+    let mut result = store.with(handle, |ds| {
+        let col = ds.column_index(&column)?;
+        let unit = match unit.as_deref() {
+            Some(u) => u.to_string(),
+            None => detect_unit(&column_values(ds, col))
+                .ok_or_else(|| format!("Couldn't detect an epoch unit for column {}", column))?
+                .to_string(),
+        };
+        let per_second = UNITS
+            .iter()
+            .find(|(u, _)| *u == unit)
+            .map(|(_, p)| *p)
+            .ok_or_else(|| format!("Unknown epoch unit: {}", unit))?;
+        let format = format.clone().unwrap_or_else(|| {
+            if unit == "s" { "%Y-%m-%d %H:%M:%S" } else { "%Y-%m-%d %H:%M:%S%.3f" }.to_string()
+        });
+        if StrftimeItems::new(&format).any(|item| item == Item::Error) {
+            return Err(format!("Invalid date format: {}", format));
+        }
+
+        let mut rows = ds.rows.clone();
+        for row in &mut rows {
+            let Some(cell) = row.get_mut(col) else { continue };
+            let Some(utc) = to_utc(cell, per_second) else { continue };
+            *cell = if let Some(tz) = &named_tz {
+                format_in(utc, tz, &format)
+            } else if let Some(tz) = &fixed_tz {
+                format_in(utc, tz, &format)
+            } else {
+                format_in(utc, &Local, &format)
+            };
+        }
+
+        Ok::<_, String>(Dataset {
+            headers: ds.headers.clone(),
+            rows,
+            file_name: format!("{} (converted)", ds.file_name),
+            file_type: ds.file_type.clone(),
+            ..Default::default()
+        })
+    })??;
+    history::record_transform(
+        &app,
+        &store,
+        handle,
+        &mut result,
+        "convert_epoch",
+        serde_json::json!({ "column": column, "unit": unit, "timezone": timezone, "format": format }),
+    );
+    Ok(store.register(result))
+}
@@ -0,0 +1,209 @@
+use regex::Regex;
+use serde::Serialize;
+use std::fmt;
+use std::sync::OnceLock;
+use tauri::ipc::InvokeError;
+
+/// What went wrong, beyond the kind of error
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct ErrorInfo {
+    pub message: String,
+    /// The underlying cause, e.g. the OS or parser error
+    pub detail: Option<String>,
+    /// The file involved
+    pub path: Option<String>,
+    /// 1-based line of that file
+    pub line: Option<u64>,
+}
+
+/// The error every command returns. It reaches the frontend as
+/// `{code, message, detail, path, line}`, so the UI can pick a response by
+/// `code`: import options for an unsupported format, lenient parsing for a
+/// parse error, or just the message.
+#[derive(Debug, Clone, PartialEq)]
+pub enum AppError {
+    /// A file couldn't be read or written
+    Io(ErrorInfo),
+    /// A file was read but its contents don't parse
+    Parse(ErrorInfo),
+    /// A file type or format the app can't open
+    UnsupportedFormat(ErrorInfo),
+    /// Blocked by the file access settings
+    AccessDenied(ErrorInfo),
+    /// An unknown dataset handle, column, row, or saved item
+    NotFound(ErrorInfo),
+    /// Arguments the command can't work with, e.g. an invalid expression
+    InvalidInput(ErrorInfo),
+    Other(ErrorInfo),
+}
+
+impl AppError {
+    pub fn code(&self) -> &'static str {
+        match self {
+            AppError::Io(_) => "io",
+            AppError::Parse(_) => "parse",
+            AppError::UnsupportedFormat(_) => "unsupported_format",
+            AppError::AccessDenied(_) => "access_denied",
+            AppError::NotFound(_) => "not_found",
+            AppError::InvalidInput(_) => "invalid_input",
+            AppError::Other(_) => "other",
+        }
+    }
+
+    pub fn info(&self) -> &ErrorInfo {
+        match self {
+            AppError::Io(info)
+            | AppError::Parse(info)
+            | AppError::UnsupportedFormat(info)
+            | AppError::AccessDenied(info)
+            | AppError::NotFound(info)
+            | AppError::InvalidInput(info)
+            | AppError::Other(info) => info,
+        }
+    }
+
+    fn info_mut(&mut self) -> &mut ErrorInfo {
+        match self {
+            AppError::Io(info)
+            | AppError::Parse(info)
+            | AppError::UnsupportedFormat(info)
+            | AppError::AccessDenied(info)
+            | AppError::NotFound(info)
+            | AppError::InvalidInput(info)
+            | AppError::Other(info) => info,
+        }
+    }
+
+    pub fn io(message: String) -> AppError {
+        AppError::Io(ErrorInfo::from_message(message))
+    }
+
+    pub fn parse(message: String) -> AppError {
+        AppError::Parse(ErrorInfo::from_message(message))
+    }
+
+    pub fn unsupported_format(message: String) -> AppError {
+        AppError::UnsupportedFormat(ErrorInfo::from_message(message))
+    }
+
+    pub fn access_denied(message: String, path: &str) -> AppError {
+        AppError::AccessDenied(ErrorInfo {
+            message,
+            path: Some(path.to_string()),
+            ..Default::default()
+        })
+    }
+
+    /// Note the file the error is about, unless one is already known
+    pub fn with_path(mut self, path: &str) -> AppError {
+        let info = self.info_mut();
+        if info.path.is_none() {
+            info.path = Some(path.to_string());
+        }
+        self
+    }
+}
+
+/// "...: found record with 3 fields (line: 12, ...)" or "... at line 12 column 5"
+fn line_number(message: &str) -> Option<u64> {
+    static LINE: OnceLock<Regex> = OnceLock::new();
+    LINE.get_or_init(|| Regex::new(r"\bline:? (\d+)").unwrap())
+        .captures(message)
+        .and_then(|c| c[1].parse().ok())
+}
+
+impl ErrorInfo {
+    /// The cause after the first ": " and any line number the message mentions
+    fn from_message(message: String) -> ErrorInfo {
+        ErrorInfo {
+            line: line_number(&message),
+            detail: message.split_once(": ").map(|(_, cause)| cause.to_string()),
+            message,
+            path: None,
+        }
+    }
+}
+
+/// Many errors are still built as strings where they happen; sort them by
+/// their wording, which follows a few fixed patterns ("Failed to read ...:
+/// cause", "Unknown dataset handle", "Column not found", ...). Errors the UI
+/// responds to by code (parse, unsupported format) are built typed where
+/// they happen instead.
+impl From<String> for AppError {
+    fn from(message: String) -> AppError {
+        let lower = message.to_ascii_lowercase();
+        let info = ErrorInfo::from_message(message);
+        if lower.starts_with("failed to parse") || lower.starts_with("failed to read record") || lower.starts_with("failed to read headers") {
+            AppError::Parse(info)
+        } else if lower.starts_with("failed to") {
+            AppError::Io(info)
+        } else if lower.starts_with("unsupported") || lower.contains("n't supported") {
+            AppError::UnsupportedFormat(info)
+        } else if (lower.starts_with("unknown") && lower.contains(" handle"))
+            || lower.starts_with("column not found")
+            || lower.starts_with("no saved")
+            || lower.contains("out of range")
+        {
+            AppError::NotFound(info)
+        } else if lower.starts_with("invalid") || lower.starts_with("unknown") || lower.contains("cannot be empty") {
+            AppError::InvalidInput(info)
+        } else {
+            AppError::Other(info)
+        }
+    }
+}
+
+impl From<&str> for AppError {
+    fn from(message: &str) -> AppError {
+        AppError::from(message.to_string())
+    }
+}
+
+/// For the helpers that still report errors as strings
+impl From<AppError> for String {
+    fn from(error: AppError) -> String {
+        error.to_string()
+    }
+}
+
+impl fmt::Display for AppError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.info().message)
+    }
+}
+
+impl std::error::Error for AppError {}
+
+/// An error as the frontend sees it: `{code, message, detail, path, line}`
+#[derive(Debug, Clone, Serialize)]
+pub struct ErrorPayload {
+    pub code: &'static str,
+    #[serde(flatten)]
+    pub info: ErrorInfo,
+}
+
+impl From<AppError> for ErrorPayload {
+    fn from(error: AppError) -> ErrorPayload {
+        let code = error.code();
+        let info = match error {
+            AppError::Io(info)
+            | AppError::Parse(info)
+            | AppError::UnsupportedFormat(info)
+            | AppError::AccessDenied(info)
+            | AppError::NotFound(info)
+            | AppError::InvalidInput(info)
+            | AppError::Other(info) => info,
+        };
+        ErrorPayload { code, info }
+    }
+}
+
+/// How a command's error is sent back to the frontend, once per failed call,
+/// which makes it where command errors get logged
+impl From<AppError> for InvokeError {
+    fn from(error: AppError) -> InvokeError {
+        let info = error.info();
+        tracing::warn!(code = error.code(), path = info.path.as_deref(), "{}", info.message);
+        InvokeError(serde_json::to_value(ErrorPayload::from(error)).unwrap_or_default())
+    }
+}
@@ -0,0 +1,566 @@
+use crate::dates;
+use crate::stats::parse_number;
+use chrono::{Datelike, NaiveDateTime, TimeDelta, Timelike};
+use regex::Regex;
+use std::cmp::Ordering;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Value {
+    Null,
+    Bool(bool),
+    Number(f64),
+    Str(String),
+}
+
+impl Value {
+    pub fn is_truthy(&self) -> bool {
+        match self {
+            Value::Null => false,
+            Value::Bool(b) => *b,
+            Value::Number(n) => *n != 0.0,
+            Value::Str(s) => !s.is_empty(),
+        }
+    }
+
+    /// Render the value the way it would be stored in a cell
+    pub fn to_cell(&self) -> String {
+        match self {
+            Value::Null => String::new(),
+            Value::Bool(b) => b.to_string(),
+            Value::Number(n) => n.to_string(),
+            Value::Str(s) => s.clone(),
+        }
+    }
+
+    fn as_number(&self) -> Option<f64> {
+        match self {
+            Value::Number(n) => Some(*n),
+            Value::Str(s) => parse_number(s),
+            Value::Bool(_) | Value::Null => None,
+        }
+    }
+
+    fn as_text(&self) -> String {
+        self.to_cell()
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Number(f64),
+    Str(String),
+    Ident(String),
+    /// Identifier written in brackets/backticks, never treated as a keyword
+    QuotedIdent(String),
+    Op(&'static str),
+}
+
+const OPERATORS: &[&str] = &[
+    "&&", "||", "==", "!=", "<>", "<=", ">=", "<", ">", "=", "!", "+", "-", "*", "/", "%", "(", ")", ",",
+];
+
+fn tokenize(source: &str) -> Result<Vec<Token>, String> {
+    let chars: Vec<char> = source.chars().collect();
+    let mut tokens = Vec::new();
+    let mut i = 0;
+    while i < chars.len() {
+        let c = chars[i];
+        if c.is_whitespace() {
+            i += 1;
+        } else if c.is_ascii_digit() || (c == '.' && chars.get(i + 1).is_some_and(|d| d.is_ascii_digit())) {
+            let start = i;
+            while i < chars.len() && (chars[i].is_ascii_digit() || chars[i] == '.') {
+                i += 1;
+            }
+            let text: String = chars[start..i].iter().collect();
+            let n = text
+                .parse::<f64>()
+                .map_err(|_| format!("Invalid number: {}", text))?;
+            tokens.push(Token::Number(n));
+        } else if c == '"' || c == '\'' {
+            let mut text = String::new();
+            i += 1;
+            loop {
+                match chars.get(i) {
+                    None => return Err("Unterminated string literal".to_string()),
+                    Some(&ch) if ch == c => break,
+                    Some('\\') => {
+                        i += 1;
+                        match chars.get(i) {
+                            Some('n') => text.push('\n'),
+                            Some('t') => text.push('\t'),
+                            Some(&other) => text.push(other),
+                            None => return Err("Unterminated string literal".to_string()),
+                        }
+                    }
+                    Some(&ch) => text.push(ch),
+                }
+                i += 1;
+            }
+            i += 1;
+            tokens.push(Token::Str(text));
+        } else if c == '[' || c == '`' {
+            let close = if c == '[' { ']' } else { '`' };
+            let start = i + 1;
+            let end = chars[start..]
+                .iter()
+                .position(|&ch| ch == close)
+                .ok_or_else(|| format!("Missing closing {} in column reference", close))?;
+            tokens.push(Token::QuotedIdent(chars[start..start + end].iter().collect()));
+            i = start + end + 1;
+        } else if c.is_alphabetic() || c == '_' {
+            let start = i;
+            while i < chars.len() && (chars[i].is_alphanumeric() || chars[i] == '_' || chars[i] == '.') {
+                i += 1;
+            }
+            tokens.push(Token::Ident(chars[start..i].iter().collect()));
+        } else {
+            let rest: String = chars[i..chars.len().min(i + 2)].iter().collect();
+            let op = OPERATORS
+                .iter()
+                .find(|op| rest.starts_with(**op))
+                .ok_or_else(|| format!("Unexpected character '{}'", c))?;
+            tokens.push(Token::Op(op));
+            i += op.len();
+        }
+    }
+    Ok(tokens)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum BinaryOp {
+    Or,
+    And,
+    Eq,
+    NotEq,
+    Lt,
+    LtEq,
+    Gt,
+    GtEq,
+    Add,
+    Sub,
+    Mul,
+    Div,
+    Rem,
+}
+
+#[derive(Debug)]
+enum Expr {
+    Literal(Value),
+    Column(usize),
+    Not(Box<Expr>),
+    Negate(Box<Expr>),
+    Binary(BinaryOp, Box<Expr>, Box<Expr>),
+    Call(String, Vec<Expr>),
+    /// `matches(x, "literal pattern")`, with the regex compiled once up front
+    Matches(Box<Expr>, Regex),
+}
+
+/// Function names and their accepted argument counts
+const FUNCTIONS: &[(&str, usize, usize)] = &[
+    ("contains", 2, 2),
+    ("starts_with", 2, 2),
+    ("ends_with", 2, 2),
+    ("matches", 2, 2),
+    ("lower", 1, 1),
+    ("upper", 1, 1),
+    ("trim", 1, 1),
+    ("len", 1, 1),
+    ("is_null", 1, 1),
+    ("if", 3, 3),
+    ("coalesce", 1, usize::MAX),
+    ("concat", 1, usize::MAX),
+    ("substr", 2, 3),
+    ("replace", 3, 3),
+    ("number", 1, 1),
+    ("round", 1, 2),
+    ("abs", 1, 1),
+    ("floor", 1, 1),
+    ("ceil", 1, 1),
+    ("year", 1, 1),
+    ("month", 1, 1),
+    ("day", 1, 1),
+    ("date", 1, 1),
+    ("format_date", 2, 2),
+    ("add_days", 2, 2),
+    ("days_between", 2, 2),
+];
+
+struct Parser<'a> {
+    tokens: Vec<Token>,
+    pos: usize,
+    headers: &'a [String],
+}
+
+impl<'a> Parser<'a> {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.pos)
+    }
+
+    fn next(&mut self) -> Option<Token> {
+        let token = self.tokens.get(self.pos).cloned();
+        self.pos += 1;
+        token
+    }
+
+    fn eat_op(&mut self, ops: &[&str]) -> Option<&'static str> {
+        match self.peek() {
+            Some(Token::Op(op)) if ops.contains(op) => {
+                let op = *op;
+                self.pos += 1;
+                Some(op)
+            }
+            _ => None,
+        }
+    }
+
+    fn eat_keyword(&mut self, keyword: &str) -> bool {
+        match self.peek() {
+            Some(Token::Ident(word)) if word.eq_ignore_ascii_case(keyword) => {
+                self.pos += 1;
+                true
+            }
+            _ => false,
+        }
+    }
+
+    fn expect_op(&mut self, op: &str) -> Result<(), String> {
+        self.eat_op(&[op])
+            .map(|_| ())
+            .ok_or_else(|| format!("Expected '{}'", op))
+    }
+
+    fn parse_or(&mut self) -> Result<Expr, String> {
+        let mut left = self.parse_and()?;
+        while self.eat_op(&["||"]).is_some() || self.eat_keyword("or") {
+            let right = self.parse_and()?;
+            left = Expr::Binary(BinaryOp::Or, Box::new(left), Box::new(right));
+        }
+        Ok(left)
+    }
+
+    fn parse_and(&mut self) -> Result<Expr, String> {
+        let mut left = self.parse_not()?;
+        while self.eat_op(&["&&"]).is_some() || self.eat_keyword("and") {
+            let right = self.parse_not()?;
+            left = Expr::Binary(BinaryOp::And, Box::new(left), Box::new(right));
+        }
+        Ok(left)
+    }
+
+    fn parse_not(&mut self) -> Result<Expr, String> {
+        if self.eat_op(&["!"]).is_some() || self.eat_keyword("not") {
+            return Ok(Expr::Not(Box::new(self.parse_not()?)));
+        }
+        self.parse_comparison()
+    }
+
+    fn parse_comparison(&mut self) -> Result<Expr, String> {
+        let left = self.parse_additive()?;
+        let op = match self.eat_op(&["==", "=", "!=", "<>", "<=", ">=", "<", ">"]) {
+            Some("==") | Some("=") => BinaryOp::Eq,
+            Some("!=") | Some("<>") => BinaryOp::NotEq,
+            Some("<=") => BinaryOp::LtEq,
+            Some(">=") => BinaryOp::GtEq,
+            Some("<") => BinaryOp::Lt,
+            Some(">") => BinaryOp::Gt,
+            _ => return Ok(left),
+        };
+        let right = self.parse_additive()?;
+        Ok(Expr::Binary(op, Box::new(left), Box::new(right)))
+    }
+
+    fn parse_additive(&mut self) -> Result<Expr, String> {
+        let mut left = self.parse_multiplicative()?;
+        while let Some(op) = self.eat_op(&["+", "-"]) {
+            let op = if op == "+" { BinaryOp::Add } else { BinaryOp::Sub };
+            let right = self.parse_multiplicative()?;
+            left = Expr::Binary(op, Box::new(left), Box::new(right));
+        }
+        Ok(left)
+    }
+
+    fn parse_multiplicative(&mut self) -> Result<Expr, String> {
+        let mut left = self.parse_unary()?;
+        while let Some(op) = self.eat_op(&["*", "/", "%"]) {
+            let op = match op {
+                "*" => BinaryOp::Mul,
+                "/" => BinaryOp::Div,
+                _ => BinaryOp::Rem,
+            };
+            let right = self.parse_unary()?;
+            left = Expr::Binary(op, Box::new(left), Box::new(right));
+        }
+        Ok(left)
+    }
+
+    fn parse_unary(&mut self) -> Result<Expr, String> {
+        if self.eat_op(&["-"]).is_some() {
+            return Ok(Expr::Negate(Box::new(self.parse_unary()?)));
+        }
+        self.parse_primary()
+    }
+
+    fn parse_primary(&mut self) -> Result<Expr, String> {
+        match self.next() {
+            Some(Token::Number(n)) => Ok(Expr::Literal(Value::Number(n))),
+            Some(Token::Str(s)) => Ok(Expr::Literal(Value::Str(s))),
+            Some(Token::Op("(")) => {
+                let inner = self.parse_or()?;
+                self.expect_op(")")?;
+                Ok(inner)
+            }
+            Some(Token::QuotedIdent(name)) => self.column(&name),
+            Some(Token::Ident(name)) => {
+                if self.eat_op(&["("]).is_some() {
+                    return self.call(name);
+                }
+                match name.to_ascii_lowercase().as_str() {
+                    "true" => Ok(Expr::Literal(Value::Bool(true))),
+                    "false" => Ok(Expr::Literal(Value::Bool(false))),
+                    "null" => Ok(Expr::Literal(Value::Null)),
+                    _ => self.column(&name),
+                }
+            }
+            Some(token) => Err(format!("Unexpected token {:?}", token)),
+            None => Err("Unexpected end of expression".to_string()),
+        }
+    }
+
+    fn column(&self, name: &str) -> Result<Expr, String> {
+        self.headers
+            .iter()
+            .position(|h| h == name)
+            .map(Expr::Column)
+            .ok_or_else(|| format!("Column not found: {}", name))
+    }
+
+    fn call(&mut self, name: String) -> Result<Expr, String> {
+        let name = name.to_ascii_lowercase();
+        let mut args = Vec::new();
+        if self.eat_op(&[")"]).is_none() {
+            loop {
+                args.push(self.parse_or()?);
+                if self.eat_op(&[")"]).is_some() {
+                    break;
+                }
+                self.expect_op(",")?;
+            }
+        }
+
+        let (_, min, max) = FUNCTIONS
+            .iter()
+            .find(|(f, _, _)| *f == name)
+            .ok_or_else(|| format!("Unknown function: {}", name))?;
+        if args.len() < *min || args.len() > *max {
+            return Err(format!("Wrong number of arguments to {}()", name));
+        }
+
+        if name == "matches" {
+            if let Expr::Literal(Value::Str(pattern)) = &args[1] {
+                let regex = Regex::new(pattern).map_err(|e| format!("Invalid regex: {}", e))?;
+                return Ok(Expr::Matches(Box::new(args.remove(0)), regex));
+            }
+        }
+        Ok(Expr::Call(name, args))
+    }
+}
+
+/// A parsed expression bound to a dataset's columns
+#[derive(Debug)]
+pub struct CompiledExpr {
+    expr: Expr,
+}
+
+/// Parse `source`, resolving column references against `headers`.
+/// e.g. `amount > 1000 && status != "shipped" && date >= "2024-01-01"`
+///
+/// Columns are referenced by bare name (`amount`, `user.name`) or, when the
+/// name has spaces or clashes with a keyword, in brackets or backticks
+/// (`[order id]`, `` `and` ``). Empty cells evaluate to null.
+pub fn compile(source: &str, headers: &[String]) -> Result<CompiledExpr, String> {
+    let mut parser = Parser {
+        tokens: tokenize(source)?,
+        pos: 0,
+        headers,
+    };
+    if parser.tokens.is_empty() {
+        return Err("Expression is empty".to_string());
+    }
+    let expr = parser.parse_or()?;
+    if let Some(token) = parser.peek() {
+        return Err(format!("Unexpected token {:?}", token));
+    }
+    Ok(CompiledExpr { expr })
+}
+
+impl CompiledExpr {
+    pub fn eval(&self, row: &[String]) -> Value {
+        eval(&self.expr, row)
+    }
+
+    pub fn matches(&self, row: &[String]) -> bool {
+        self.eval(row).is_truthy()
+    }
+}
+
+/// Compare two values: numerically when both sides are numbers, otherwise as text.
+/// Returns `None` when either side is null.
+fn compare(a: &Value, b: &Value) -> Option<Ordering> {
+    if *a == Value::Null || *b == Value::Null {
+        return None;
+    }
+    match (a.as_number(), b.as_number()) {
+        (Some(x), Some(y)) => x.partial_cmp(&y),
+        _ => Some(a.as_text().cmp(&b.as_text())),
+    }
+}
+
+fn values_equal(a: &Value, b: &Value) -> bool {
+    match (a, b) {
+        (Value::Null, Value::Null) => true,
+        (Value::Null, other) | (other, Value::Null) => other.as_text().is_empty(),
+        _ => compare(a, b) == Some(Ordering::Equal),
+    }
+}
+
+fn arithmetic(op: BinaryOp, a: &Value, b: &Value) -> Value {
+    // `+` falls back to concatenation when either side isn't numeric
+    let (x, y) = match (a.as_number(), b.as_number()) {
+        (Some(x), Some(y)) => (x, y),
+        _ if op == BinaryOp::Add && *a != Value::Null && *b != Value::Null => {
+            return Value::Str(a.as_text() + &b.as_text());
+        }
+        _ => return Value::Null,
+    };
+    let result = match op {
+        BinaryOp::Add => x + y,
+        BinaryOp::Sub => x - y,
+        BinaryOp::Mul => x * y,
+        BinaryOp::Div if y != 0.0 => x / y,
+        BinaryOp::Rem if y != 0.0 => x % y,
+        _ => return Value::Null,
+    };
+    Value::Number(result)
+}
+
+fn eval(expr: &Expr, row: &[String]) -> Value {
+    match expr {
+        Expr::Literal(v) => v.clone(),
+        Expr::Column(i) => match row.get(*i) {
+            Some(cell) if !cell.is_empty() => Value::Str(cell.clone()),
+            _ => Value::Null,
+        },
+        Expr::Not(inner) => Value::Bool(!eval(inner, row).is_truthy()),
+        Expr::Negate(inner) => match eval(inner, row).as_number() {
+            Some(n) => Value::Number(-n),
+            None => Value::Null,
+        },
+        Expr::Binary(op, left, right) => {
+            let a = eval(left, row);
+            // Short-circuit the logical operators
+            match op {
+                BinaryOp::And if !a.is_truthy() => return Value::Bool(false),
+                BinaryOp::Or if a.is_truthy() => return Value::Bool(true),
+                _ => {}
+            }
+            let b = eval(right, row);
+            let ord = compare(&a, &b);
+            match op {
+                BinaryOp::And | BinaryOp::Or => Value::Bool(b.is_truthy()),
+                BinaryOp::Eq => Value::Bool(values_equal(&a, &b)),
+                BinaryOp::NotEq => Value::Bool(!values_equal(&a, &b)),
+                BinaryOp::Lt => Value::Bool(ord == Some(Ordering::Less)),
+                BinaryOp::LtEq => Value::Bool(matches!(ord, Some(Ordering::Less | Ordering::Equal))),
+                BinaryOp::Gt => Value::Bool(ord == Some(Ordering::Greater)),
+                BinaryOp::GtEq => Value::Bool(matches!(ord, Some(Ordering::Greater | Ordering::Equal))),
+                _ => arithmetic(*op, &a, &b),
+            }
+        }
+        Expr::Matches(inner, regex) => Value::Bool(regex.is_match(&eval(inner, row).as_text())),
+        Expr::Call(name, args) => {
+            let args: Vec<Value> = args.iter().map(|a| eval(a, row)).collect();
+            call_function(name, &args)
+        }
+    }
+}
+
+/// Render a date, dropping the time when it's midnight
+fn date_value(dt: NaiveDateTime) -> Value {
+    let format = if dt.time().num_seconds_from_midnight() == 0 { "%Y-%m-%d" } else { "%Y-%m-%d %H:%M:%S" };
+    Value::Str(dt.format(format).to_string())
+}
+
+fn call_function(name: &str, args: &[Value]) -> Value {
+    let text = |i: usize| args[i].as_text();
+    let number = |i: usize| args[i].as_number();
+    let datetime = |i: usize| dates::parse_datetime(&text(i));
+    let numeric = |f: fn(f64) -> f64| number(0).map_or(Value::Null, |n| Value::Number(f(n)));
+    match name {
+        "contains" => Value::Bool(text(0).to_lowercase().contains(&text(1).to_lowercase())),
+        "starts_with" => Value::Bool(text(0).starts_with(&text(1))),
+        "ends_with" => Value::Bool(text(0).ends_with(&text(1))),
+        "matches" => match Regex::new(&text(1)) {
+            Ok(re) => Value::Bool(re.is_match(&text(0))),
+            Err(_) => Value::Null,
+        },
+        "lower" => Value::Str(text(0).to_lowercase()),
+        "upper" => Value::Str(text(0).to_uppercase()),
+        "trim" => Value::Str(text(0).trim().to_string()),
+        "len" => Value::Number(text(0).chars().count() as f64),
+        "is_null" => Value::Bool(args[0] == Value::Null),
+        "if" => {
+            if args[0].is_truthy() {
+                args[1].clone()
+            } else {
+                args[2].clone()
+            }
+        }
+        "coalesce" => args
+            .iter()
+            .find(|v| **v != Value::Null)
+            .cloned()
+            .unwrap_or(Value::Null),
+        "concat" => Value::Str(args.iter().map(Value::as_text).collect()),
+        "substr" => {
+            // 1-based start, like SQL
+            let start = number(1).map_or(0, |n| (n.max(1.0) as usize) - 1);
+            let len = if args.len() > 2 { number(2).map_or(0, |n| n.max(0.0) as usize) } else { usize::MAX };
+            Value::Str(text(0).chars().skip(start).take(len).collect())
+        }
+        "replace" => Value::Str(text(0).replace(&text(1), &text(2))),
+        "number" => number(0).map_or(Value::Null, Value::Number),
+        "round" => {
+            let digits = if args.len() > 1 { number(1).unwrap_or(0.0) } else { 0.0 };
+            let scale = 10f64.powi(digits as i32);
+            number(0).map_or(Value::Null, |n| Value::Number((n * scale).round() / scale))
+        }
+        "abs" => numeric(f64::abs),
+        "floor" => numeric(f64::floor),
+        "ceil" => numeric(f64::ceil),
+        "year" => datetime(0).map_or(Value::Null, |d| Value::Number(d.year() as f64)),
+        "month" => datetime(0).map_or(Value::Null, |d| Value::Number(d.month() as f64)),
+        "day" => datetime(0).map_or(Value::Null, |d| Value::Number(d.day() as f64)),
+        "date" => datetime(0).map_or(Value::Null, |d| Value::Str(d.format("%Y-%m-%d").to_string())),
+        "format_date" => datetime(0).map_or(Value::Null, |d| {
+            let mut out = String::new();
+            // chrono panics on a bad format string when displaying, so check it first
+            match std::fmt::write(&mut out, format_args!("{}", d.format(&text(1)))) {
+                Ok(()) => Value::Str(out),
+                Err(_) => Value::Null,
+            }
+        }),
+        "add_days" => match (datetime(0), number(1)) {
+            // Out-of-range shifts give null rather than overflowing
+            (Some(d), Some(n)) => TimeDelta::try_seconds((n * 86_400.0).round() as i64)
+                .and_then(|delta| d.checked_add_signed(delta))
+                .map_or(Value::Null, date_value),
+            _ => Value::Null,
+        },
+        "days_between" => match (datetime(0), datetime(1)) {
+            (Some(a), Some(b)) => Value::Number((b - a).num_seconds() as f64 / 86_400.0),
+            _ => Value::Null,
+        },
+        _ => Value::Null,
+    }
+}
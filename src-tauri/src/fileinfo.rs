@@ -0,0 +1,156 @@
+use crate::access;
+use crate::error::AppError;
+use serde::Serialize;
+use std::fs::{self, File};
+use std::io::Read;
+use std::path::Path;
+use tauri::AppHandle;
+
+/// Bytes read from the start of the file to detect its properties
+const SAMPLE_SIZE: usize = 64 * 1024;
+
+#[derive(Debug, Serialize)]
+pub struct FileInfo {
+    pub path: String,
+    pub size: u64,
+    pub modified: Option<String>,
+    /// "utf-8", "utf-8-bom", "utf-16le", "utf-16be", "ascii", or "windows-1252"
+    pub encoding: String,
+    /// "csv", "tsv", "json", "jsonl", or "unknown"
+    pub format: String,
+    /// `None` for compressed files
+    pub line_count: Option<u64>,
+    /// Whether `line_count` was extrapolated from the start of the file
+    pub line_count_estimated: bool,
+    /// "gzip", "zip", "zstd", "bzip2", or "xz" when the file is compressed
+    pub compression: Option<String>,
+}
+
+fn detect_compression(sample: &[u8]) -> Option<&'static str> {
+    const MAGIC: [(&[u8], &str); 5] = [
+        (&[0x1f, 0x8b], "gzip"),
+        (b"PK\x03\x04", "zip"),
+        (&[0x28, 0xb5, 0x2f, 0xfd], "zstd"),
+        (b"BZh", "bzip2"),
+        (&[0xfd, b'7', b'z', b'X', b'Z', 0x00], "xz"),
+    ];
+    MAGIC
+        .iter()
+        .find(|(magic, _)| sample.starts_with(magic))
+        .map(|(_, name)| *name)
+}
+
+fn detect_encoding(sample: &[u8]) -> &'static str {
+    if sample.starts_with(&[0xef, 0xbb, 0xbf]) {
+        return "utf-8-bom";
+    }
+    if sample.starts_with(&[0xff, 0xfe]) {
+        return "utf-16le";
+    }
+    if sample.starts_with(&[0xfe, 0xff]) {
+        return "utf-16be";
+    }
+    // UTF-16 without a BOM: ASCII text leaves every other byte zero
+    let zeros = sample.iter().filter(|&&b| b == 0).count();
+    if zeros > sample.len() / 4 {
+        let even_zeros = sample.iter().step_by(2).filter(|&&b| b == 0).count();
+        return if even_zeros > zeros / 2 { "utf-16be" } else { "utf-16le" };
+    }
+    if sample.is_ascii() {
+        return "ascii";
+    }
+    match std::str::from_utf8(sample) {
+        Ok(_) => "utf-8",
+        // The sample may end partway through a multi-byte character
+        Err(e) if e.error_len().is_none() => "utf-8",
+        Err(_) => "windows-1252",
+    }
+}
+
+/// Guess the format from the text itself, falling back to the extension
+fn detect_format(text: &str, extension: &str) -> &'static str {
+    let lines: Vec<&str> = text.lines().filter(|l| !l.trim().is_empty()).take(20).collect();
+    let Some(first) = lines.first().map(|l| l.trim_start_matches('\u{feff}').trim_start()) else {
+        return "unknown";
+    };
+    if first.starts_with('{') && lines.len() > 1 && lines.iter().all(|l| l.trim_start().starts_with('{')) {
+        return "jsonl";
+    }
+    if first.starts_with('[') || first.starts_with('{') {
+        return "json";
+    }
+
+    // A delimiter that appears the same number of times on every sampled line
+    // (the last one may be cut short)
+    let complete = &lines[..lines.len().saturating_sub(1).max(1)];
+    for (delimiter, format) in [('\t', "tsv"), (',', "csv"), (';', "csv"), ('|', "csv")] {
+        let count = first.matches(delimiter).count();
+        if count > 0 && complete.iter().all(|l| l.matches(delimiter).count() == count) {
+            return format;
+        }
+    }
+    match extension {
+        "csv" => "csv",
+        "tsv" | "tab" => "tsv",
+        "json" => "json",
+        "jsonl" | "ndjson" => "jsonl",
+        _ => "unknown",
+    }
+}
+
+/// Size, timestamps, encoding, format, and an approximate line count, read
+/// from the first 64 KB of the file rather than parsing all of it
+#[tauri::command]
+pub fn get_file_info(app: AppHandle, path: String) -> Result<FileInfo, AppError> {
+    access::check(&app, &path)?;
+    let metadata = fs::metadata(&path).map_err(|e| format!("Failed to read file info: {}", e))?;
+    if !metadata.is_file() {
+        return Err(format!("Not a file: {}", path).into());
+    }
+    let mut sample = Vec::with_capacity(SAMPLE_SIZE);
+    File::open(&path)
+        .and_then(|f| f.take(SAMPLE_SIZE as u64).read_to_end(&mut sample))
+        .map_err(|e| format!("Failed to read file: {}", e))?;
+
+    let size = metadata.len();
+    let modified = metadata
+        .modified()
+        .ok()
+        .map(|t| chrono::DateTime::<chrono::Local>::from(t).to_rfc3339());
+    let compression = detect_compression(&sample);
+    let extension = Path::new(&path)
+        .extension()
+        .and_then(|e| e.to_str())
+        .map(|e| e.to_ascii_lowercase())
+        .unwrap_or_default();
+
+    let (encoding, format) = match compression {
+        // The contents can't be inspected without decompressing
+        Some(_) => ("unknown", "unknown"),
+        None => {
+            let encoding = detect_encoding(&sample);
+            let text = String::from_utf8_lossy(&sample);
+            (encoding, detect_format(&text, &extension))
+        }
+    };
+
+    let newlines = sample.iter().filter(|&&b| b == b'\n').count() as u64;
+    let complete = sample.len() as u64 >= size;
+    let line_count = compression.is_none().then_some(match (complete, newlines) {
+        // Count a final line without a trailing newline
+        (true, n) => n + u64::from(sample.last().is_some_and(|&b| b != b'\n')),
+        (false, 0) => 1,
+        (false, n) => size * n / sample.len() as u64,
+    });
+
+    Ok(FileInfo {
+        path,
+        size,
+        modified,
+        encoding: encoding.to_string(),
+        format: format.to_string(),
+        line_count,
+        line_count_estimated: !complete,
+        compression: compression.map(str::to_string),
+    })
+}
@@ -0,0 +1,108 @@
+use crate::dataset::{Dataset, DatasetStore, FileData};
+use crate::history;
+use crate::stats::{column_values, count_values, is_null, median, parse_number};
+use crate::error::AppError;
+use tauri::{AppHandle, State};
+
+/// The value to put in every blank cell of a column, for the non-positional methods
+fn fill_value(ds: &Dataset, col: usize, method: &str, value: Option<&str>) -> Result<String, String> {
+    let values = column_values(ds, col);
+    let numbers = || {
+        let numbers: Vec<f64> = values.iter().filter_map(|v| parse_number(v)).collect();
+        if numbers.is_empty() {
+            Err(format!("Column {} has no numeric values", ds.headers[col]))
+        } else {
+            Ok(numbers)
+        }
+    };
+    match method {
+        "constant" => value
+            .map(str::to_string)
+            .ok_or_else(|| "A fill value is required".to_string()),
+        "mean" => {
+            let numbers = numbers()?;
+            Ok((numbers.iter().sum::<f64>() / numbers.len() as f64).to_string())
+        }
+        "median" => {
+            let mut numbers = numbers()?;
+            numbers.sort_by(|a, b| a.total_cmp(b));
+            Ok(median(&numbers).to_string())
+        }
+        "mode" => count_values(&values)
+            .into_iter()
+            .next()
+            .map(|c| c.value)
+            .ok_or_else(|| format!("Column {} has no values", ds.headers[col])),
+        other => Err(format!("Unknown fill method: {}", other)),
+    }
+}
+
+/// Fill blank cells in the chosen columns. `method` is "previous" (fill down),
+/// "next" (fill up), "constant" (with `value`), "mean", "median", or "mode".
+#[tauri::command]
+pub fn fill_missing(
+    app: AppHandle,
+    store: State<'_, DatasetStore>,
+    handle: u64,
+    columns: Vec<String>,
+    method: String,
+    value: Option<String>,
+) -> Result<FileData, AppError> {
+    if columns.is_empty() {
+        return Err("Select at least one column to fill".into());
+    }
+
+    let mut result = store.with(handle, |ds| {
+        let mut rows = ds.rows.clone();
+        let width = ds.headers.len();
+        for row in &mut rows {
+            row.resize(width.max(row.len()), String::new());
+        }
+
+        for column in &columns {
+            let col = ds.column_index(column)?;
+            match method.as_str() {
+                "previous" | "next" => {
+                    let mut last: Option<String> = None;
+                    let mut fill = |row: &mut Vec<String>| {
+                        if is_null(&row[col]) {
+                            if let Some(v) = &last {
+                                row[col] = v.clone();
+                            }
+                        } else {
+                            last = Some(row[col].clone());
+                        }
+                    };
+                    if method == "previous" {
+                        rows.iter_mut().for_each(&mut fill);
+                    } else {
+                        rows.iter_mut().rev().for_each(&mut fill);
+                    }
+                }
+                _ => {
+                    let fill = fill_value(ds, col, &method, value.as_deref())?;
+                    for row in rows.iter_mut().filter(|r| is_null(&r[col])) {
+                        row[col] = fill.clone();
+                    }
+                }
+            }
+        }
+
+        Ok::<_, String>(Dataset {
+            headers: ds.headers.clone(),
+            rows,
+            file_name: format!("{} (filled)", ds.file_name),
+            file_type: ds.file_type.clone(),
+            ..Default::default()
+        })
+    })??;
+    history::record_transform(
+        &app,
+        &store,
+        handle,
+        &mut result,
+        "fill_missing",
+        serde_json::json!({ "columns": columns, "method": method, "value": value }),
+    );
+    Ok(store.register(result))
+}
@@ -0,0 +1,66 @@
+use crate::dataset::DatasetStore;
+use crate::operations::{Interrupted, Operations};
+use crate::{expr, history, logging};
+use crate::error::AppError;
+use serde::Serialize;
+use tauri::{AppHandle, State};
+
+#[derive(Debug, Serialize)]
+pub struct FilteredRows {
+    /// Indices of the matching rows in the full dataset
+    pub indices: Vec<usize>,
+    pub rows: Vec<Vec<String>>,
+    pub row_count: usize,
+    pub total_rows: usize,
+    /// Pass to `cancel_operation` to stop the filter early
+    pub operation_id: String,
+    /// Set when the filter stopped early; the rows are the matches among
+    /// the first `scanned_rows` rows
+    pub interrupted: Option<Interrupted>,
+    pub scanned_rows: usize,
+}
+
+/// Return the rows for which `expression` evaluates to true. The filter runs
+/// as operation `operation_id` (one is made up when not given) and stops
+/// after `timeout_ms`, or the timeout from settings.
+#[tauri::command(async)]
+pub fn filter_rows(
+    app: AppHandle,
+    store: State<'_, DatasetStore>,
+    ops: State<'_, Operations>,
+    handle: u64,
+    expression: String,
+    operation_id: Option<String>,
+    timeout_ms: Option<u64>,
+) -> Result<FilteredRows, AppError> {
+    let _timed = logging::timed("filter_rows");
+    let op = ops.start(&app, operation_id, timeout_ms)?;
+    let result = store.with(handle, |ds| {
+        let compiled = expr::compile(&expression, &ds.headers)?;
+        let mut indices = Vec::new();
+        let mut scanned_rows = 0;
+        let mut interrupted = None;
+        for (i, row) in ds.rows.iter().enumerate() {
+            if op.check(i).is_err() {
+                interrupted = op.interrupted();
+                break;
+            }
+            if compiled.matches(row) {
+                indices.push(i);
+            }
+            scanned_rows += 1;
+        }
+
+        Ok::<_, String>(FilteredRows {
+            rows: indices.iter().map(|&i| ds.rows[i].clone()).collect(),
+            row_count: indices.len(),
+            total_rows: ds.rows.len(),
+            indices,
+            operation_id: op.id.clone(),
+            interrupted,
+            scanned_rows,
+        })
+    })??;
+    history::record(&app, &store, handle, "filter", "filter_rows", serde_json::json!({ "expression": expression }));
+    Ok(result)
+}
@@ -0,0 +1,97 @@
+use crate::dataset::{Dataset, DatasetStore};
+use crate::expr::{self, CompiledExpr};
+use crate::error::AppError;
+use serde::{Deserialize, Serialize};
+use tauri::State;
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+    Info,
+    Warning,
+    Error,
+}
+
+/// Highlight cells whose row matches `condition`, an expression like
+/// `amount > 100`, `status == "FAILED"` or `matches(email, "@example\.com$")`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FormatRule {
+    pub condition: String,
+    /// Column to highlight; the whole row when not given
+    #[serde(default)]
+    pub column: Option<String>,
+    /// Any CSS color
+    #[serde(default)]
+    pub color: Option<String>,
+    #[serde(default)]
+    pub severity: Option<Severity>,
+}
+
+/// A style hint for one cell, or a whole row when `column` is `None`
+#[derive(Debug, Clone, Serialize)]
+pub struct CellStyle {
+    pub row: usize,
+    pub column: Option<usize>,
+    pub color: Option<String>,
+    pub severity: Option<Severity>,
+}
+
+struct CompiledRule<'a> {
+    rule: &'a FormatRule,
+    condition: CompiledExpr,
+    column: Option<usize>,
+}
+
+fn compile_rule<'a>(ds: &Dataset, rule: &'a FormatRule) -> Result<CompiledRule<'a>, String> {
+    if rule.color.is_none() && rule.severity.is_none() {
+        return Err(format!("Rule '{}' needs a color or a severity", rule.condition));
+    }
+    Ok(CompiledRule {
+        rule,
+        condition: expr::compile(&rule.condition, &ds.headers)
+            .map_err(|e| format!("Invalid rule '{}': {}", rule.condition, e))?,
+        column: rule.column.as_deref().map(|c| ds.column_index(c)).transpose()?,
+    })
+}
+
+/// Style hints for the given rows from the dataset's formatting rules.
+/// When several rules target the same cell (or row), the first one wins.
+pub fn evaluate(ds: &Dataset, rows: impl IntoIterator<Item = usize>) -> Vec<CellStyle> {
+    // Rules referring to columns that have since been renamed or removed are skipped
+    let rules: Vec<CompiledRule> = ds.format_rules.iter().filter_map(|rule| compile_rule(ds, rule).ok()).collect();
+    let mut styles = Vec::new();
+    for row in rows.into_iter().filter(|&row| row < ds.rows.len()) {
+        let first = styles.len();
+        for rule in &rules {
+            let taken = styles[first..].iter().any(|s: &CellStyle| s.column == rule.column);
+            if !taken && rule.condition.matches(&ds.rows[row]) {
+                styles.push(CellStyle {
+                    row,
+                    column: rule.column,
+                    color: rule.rule.color.clone(),
+                    severity: rule.rule.severity,
+                });
+            }
+        }
+    }
+    styles
+}
+
+/// Replace the dataset's conditional formatting rules, in priority order.
+/// They're evaluated as rows are paged, so highlighting costs the same for
+/// any dataset size.
+#[tauri::command]
+pub fn set_format_rules(store: State<'_, DatasetStore>, handle: u64, rules: Vec<FormatRule>) -> Result<(), AppError> {
+    store.with_mut(handle, |ds| {
+        for rule in &rules {
+            compile_rule(ds, rule)?;
+        }
+        ds.format_rules = rules;
+        Ok(())
+    })?
+}
+
+#[tauri::command]
+pub fn get_format_rules(store: State<'_, DatasetStore>, handle: u64) -> Result<Vec<FormatRule>, AppError> {
+    Ok(store.with(handle, |ds| ds.format_rules.clone())?)
+}
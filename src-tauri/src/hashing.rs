@@ -0,0 +1,80 @@
+use crate::dataset::{Dataset, DatasetStore, FileData};
+use crate::history;
+use crate::stats::is_null;
+use crate::error::AppError;
+use hmac::{Hmac, Mac};
+use sha2::{Digest, Sha256};
+use tauri::{AppHandle, State};
+
+/// Replace the values of `columns` with hex digests so they can still be
+/// joined and counted but not read. `method` is "sha256" (default), which
+/// hashes `secret` + value, or "hmac" for HMAC-SHA256 keyed with `secret`.
+/// The same value always maps to the same hash; blank cells stay blank.
+#[tauri::command]
+pub fn hash_columns(
+    app: AppHandle,
+    store: State<'_, DatasetStore>,
+    handle: u64,
+    columns: Vec<String>,
+    secret: String,
+    method: Option<String>,
+) -> Result<FileData, AppError> {
+    let method = method.unwrap_or_else(|| "sha256".to_string());
+    let hash: Box<dyn Fn(&str) -> String> = match method.as_str() {
+        "sha256" => {
+            let secret = secret.clone();
+            Box::new(move |value: &str| {
+                let mut hasher = Sha256::new();
+                hasher.update(secret.as_bytes());
+                hasher.update(value.as_bytes());
+                hex::encode(hasher.finalize())
+            })
+        }
+        "hmac" => {
+            let mac = Hmac::<Sha256>::new_from_slice(secret.as_bytes()).map_err(|e| format!("Invalid key: {}", e))?;
+            Box::new(move |value: &str| {
+                let mut mac = mac.clone();
+                mac.update(value.as_bytes());
+                hex::encode(mac.finalize().into_bytes())
+            })
+        }
+        other => return Err(format!("Unknown hash method: {}", other).into()),
+    };
+    if columns.is_empty() {
+        return Err("Select at least one column to hash".into());
+    }
+
+    let result = store.with(handle, |ds| {
+        let cols = columns
+            .iter()
+            .map(|c| ds.column_index(c))
+            .collect::<Result<Vec<_>, _>>()?;
+        let mut rows = ds.rows.clone();
+        for row in &mut rows {
+            for &c in &cols {
+                if let Some(cell) = row.get_mut(c).filter(|v| !is_null(v)) {
+                    *cell = hash(cell);
+                }
+            }
+        }
+        Ok::<_, String>(Dataset {
+            headers: ds.headers.clone(),
+            rows,
+            file_name: format!("{} (hashed)", ds.file_name),
+            file_type: ds.file_type.clone(),
+            ..Default::default()
+        })
+    })??;
+    // The secret is deliberately left out of the history. Without it the
+    // step can't be replayed, so the hashed dataset starts no recipe and has
+    // no origin to be rebuilt from (which would bring back the raw values).
+    history::record(
+        &app,
+        &store,
+        handle,
+        "transform",
+        "hash_columns",
+        serde_json::json!({ "columns": columns, "method": method }),
+    );
+    Ok(store.register(result))
+}
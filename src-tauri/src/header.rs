@@ -0,0 +1,136 @@
+use crate::access;
+use crate::stats::is_null;
+use crate::types::{infer_type, value_type, ColumnType};
+use crate::error::AppError;
+use serde::Serialize;
+use std::collections::HashSet;
+use std::fs::File;
+use std::io::Read;
+use tauri::AppHandle;
+
+/// Bytes read from the start of a file to judge its first row
+const SAMPLE_SIZE: u64 = 64 * 1024;
+/// Rows after the first one compared against it
+const SAMPLE_ROWS: usize = 200;
+/// Below this confidence the guess is little better than a coin toss and the
+/// user should be asked
+const CERTAIN: f64 = 0.75;
+
+#[derive(Debug, Serialize)]
+pub struct HeaderGuess {
+    pub has_header: bool,
+    /// From 0.5 (no idea) to 1.0
+    pub confidence: f64,
+    /// Confident enough not to ask
+    pub certain: bool,
+    /// What the guess is based on, for showing alongside the question
+    pub reasons: Vec<String>,
+}
+
+fn is_typed(kind: ColumnType) -> bool {
+    matches!(kind, ColumnType::Integer | ColumnType::Float | ColumnType::Date | ColumnType::Boolean)
+}
+
+/// Guess whether the first record of delimited text is a header. Evidence for
+/// a header: text over columns whose other values are numbers or dates.
+/// Evidence against: a first row that is itself numeric, repeats a value,
+/// leaves cells empty, or holds values seen further down the same column.
+pub fn guess(content: &str, delimiter: u8) -> HeaderGuess {
+    let mut reader = csv::ReaderBuilder::new()
+        .has_headers(false)
+        .flexible(true)
+        .delimiter(delimiter)
+        .from_reader(content.as_bytes());
+    // The sample may end mid-record; drop whatever fails to parse
+    let records: Vec<Vec<String>> = reader
+        .records()
+        .map_while(Result::ok)
+        .take(SAMPLE_ROWS + 1)
+        .map(|r| r.iter().map(str::to_string).collect())
+        .collect();
+    let Some((first, rest)) = records.split_first() else {
+        return HeaderGuess {
+            has_header: true,
+            confidence: 0.5,
+            certain: false,
+            reasons: vec!["The file is empty".to_string()],
+        };
+    };
+
+    let mut reasons = Vec::new();
+    let mut score = 0.0;
+
+    let mut column_evidence = Vec::new();
+    let (mut mismatched, mut matched, mut repeated) = (0, 0, 0);
+    for (col, value) in first.iter().enumerate() {
+        let first_kind = value_type(value);
+        let values: Vec<&str> = rest.iter().filter_map(|row| row.get(col)).map(String::as_str).collect();
+        if values.iter().all(|v| is_null(v)) {
+            continue;
+        }
+        let kind = infer_type(values.iter().copied());
+        if is_typed(kind) && first_kind == ColumnType::String {
+            mismatched += 1;
+            column_evidence.push(1.0);
+        } else if is_typed(kind) && is_typed(first_kind) {
+            matched += 1;
+            column_evidence.push(-1.0);
+        } else if values.contains(&value.as_str()) {
+            repeated += 1;
+            column_evidence.push(-0.5);
+        } else {
+            column_evidence.push(0.0);
+        }
+    }
+    if !column_evidence.is_empty() {
+        score += column_evidence.iter().sum::<f64>() / column_evidence.len() as f64;
+    }
+    if mismatched > 0 {
+        reasons.push(format!("{} column(s) hold numbers or dates below a text first row", mismatched));
+    }
+    if matched > 0 {
+        reasons.push(format!("{} column(s) have a number or date in the first row too", matched));
+    }
+    if repeated > 0 {
+        reasons.push(format!("{} first-row value(s) also appear further down their column", repeated));
+    }
+
+    if !first.is_empty() && first.iter().all(|v| is_typed(value_type(v))) {
+        score -= 1.0;
+        reasons.push("Every value in the first row is a number, date, or boolean".to_string());
+    }
+    let mut seen = HashSet::new();
+    if first.iter().filter(|v| !is_null(v)).any(|v| !seen.insert(v.trim())) {
+        score -= 0.5;
+        reasons.push("The first row repeats a value, which column names rarely do".to_string());
+    }
+    if first.iter().any(|v| is_null(v)) {
+        score -= 0.25;
+        reasons.push("The first row has empty cells".to_string());
+    }
+    if reasons.is_empty() {
+        reasons.push("The first row looks like the rest of the data".to_string());
+    }
+
+    let score: f64 = score.clamp(-1.0, 1.0);
+    let confidence = ((0.5 + score.abs() / 2.0) * 100.0).round() / 100.0;
+    HeaderGuess {
+        // Ties keep the usual assumption of a header row
+        has_header: score >= 0.0,
+        confidence,
+        certain: confidence >= CERTAIN,
+        reasons,
+    }
+}
+
+/// Guess whether a CSV file starts with a header row, so the UI can ask
+/// before loading when the guess is uncertain
+#[tauri::command]
+pub fn detect_header(app: AppHandle, file_path: String) -> Result<HeaderGuess, AppError> {
+    access::check(&app, &file_path)?;
+    let mut sample = Vec::new();
+    File::open(&file_path)
+        .and_then(|file| file.take(SAMPLE_SIZE).read_to_end(&mut sample))
+        .map_err(|e| format!("Failed to read file: {}", e))?;
+    Ok(guess(&String::from_utf8_lossy(&sample), b','))
+}
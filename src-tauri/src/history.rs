@@ -0,0 +1,264 @@
+use crate::dataset::{Dataset, DatasetStore, FileData};
+use crate::error::AppError;
+use crate::filter::{self, FilteredRows};
+use crate::recipe::RecipeStep;
+use crate::views::{self, ViewResult};
+use crate::{
+    bookmarks, computed, database, date_formats, decode, dedup, epoch, fill, merge, normalize, pii, recode, reshape,
+    logging, split, storage, units, window,
+};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use tauri::{AppHandle, Manager, State};
+
+const HISTORY_FILE: &str = "history.json";
+const MAX_HISTORY_ENTRIES: usize = 500;
+
+/// One executed filter/query/transform, with the arguments needed to re-run it
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HistoryEntry {
+    pub id: u64,
+    pub timestamp: String,
+    /// "filter", "view", "query", or "transform"
+    pub kind: String,
+    pub command: String,
+    pub args: Value,
+    pub file_path: Option<String>,
+}
+
+/// Append an operation to the persistent history. Failures to write are
+/// ignored: history is a convenience and must never break the operation itself.
+pub fn record(app: &AppHandle, store: &DatasetStore, handle: u64, kind: &str, command: &str, args: Value) {
+    let file_path = store.with(handle, |ds| ds.origin_path()).ok().flatten();
+    let mut history: Vec<HistoryEntry> = storage::load(app, HISTORY_FILE);
+    let id = history.iter().map(|e| e.id).max().unwrap_or(0) + 1;
+    history.push(HistoryEntry {
+        id,
+        timestamp: chrono::Local::now().to_rfc3339(),
+        kind: kind.to_string(),
+        command: command.to_string(),
+        args,
+        file_path,
+    });
+    if history.len() > MAX_HISTORY_ENTRIES {
+        history.drain(..history.len() - MAX_HISTORY_ENTRIES);
+    }
+    let _ = storage::save(app, HISTORY_FILE, &history);
+}
+
+/// Record a transform of `handle` into the not yet registered `derived`
+/// dataset, extending the parent's recipe with this step
+pub fn record_transform(
+    app: &AppHandle,
+    store: &DatasetStore,
+    handle: u64,
+    derived: &mut Dataset,
+    command: &str,
+    args: Value,
+) {
+    record(app, store, handle, "transform", command, args.clone());
+    if let Ok((origin, steps)) = store.with(handle, |ds| (ds.origin_path(), ds.steps.clone())) {
+        derived.origin = origin;
+        derived.steps = steps;
+        derived.steps.push(RecipeStep {
+            command: command.to_string(),
+            args,
+        });
+    }
+}
+
+/// Most recent entries first, optionally limited to one file
+#[tauri::command]
+pub fn get_history(app: AppHandle, file_path: Option<String>, limit: Option<usize>) -> Vec<HistoryEntry> {
+    let history: Vec<HistoryEntry> = storage::load(&app, HISTORY_FILE);
+    history
+        .into_iter()
+        .rev()
+        .filter(|e| file_path.is_none() || e.file_path == file_path)
+        .take(limit.unwrap_or(usize::MAX))
+        .collect()
+}
+
+#[tauri::command]
+pub fn clear_history(app: AppHandle) -> Result<(), AppError> {
+    Ok(storage::save(&app, HISTORY_FILE, &Vec::<HistoryEntry>::new())?)
+}
+
+#[derive(Debug, Serialize)]
+#[serde(untagged)]
+pub enum RerunResult {
+    Rows(FilteredRows),
+    View(ViewResult),
+    Dataset(FileData),
+}
+
+fn arg<T: DeserializeOwned>(args: &Value, key: &str) -> Result<T, String> {
+    serde_json::from_value(args.get(key).cloned().unwrap_or(Value::Null))
+        .map_err(|e| format!("Invalid history argument {}: {}", key, e))
+}
+
+/// Re-run a history entry against a (possibly different) dataset. Filters,
+/// views, and queries run as cancellable operations, as they do directly.
+#[tauri::command(async)]
+pub fn rerun(
+    app: AppHandle,
+    store: State<'_, DatasetStore>,
+    id: u64,
+    handle: u64,
+    operation_id: Option<String>,
+    timeout_ms: Option<u64>,
+) -> Result<RerunResult, AppError> {
+    let _timed = logging::timed("rerun");
+    let history: Vec<HistoryEntry> = storage::load(&app, HISTORY_FILE);
+    let entry = history
+        .into_iter()
+        .find(|e| e.id == id)
+        .ok_or_else(|| format!("No history entry with id {}", id))?;
+    run_command(app, store, handle, &entry.command, &entry.args, operation_id, timeout_ms)
+}
+
+/// Run a recorded command with its saved arguments. `operation_id` and
+/// `timeout_ms` apply to the commands that run as operations.
+pub fn run_command(
+    app: AppHandle,
+    store: State<'_, DatasetStore>,
+    handle: u64,
+    command: &str,
+    args: &Value,
+    operation_id: Option<String>,
+    timeout_ms: Option<u64>,
+) -> Result<RerunResult, AppError> {
+    match command {
+        "filter_rows" => filter::filter_rows(
+            app.clone(),
+            store,
+            app.state(),
+            handle,
+            arg(args, "expression")?,
+            operation_id,
+            timeout_ms,
+        )
+        .map(RerunResult::Rows),
+        "apply_view" => views::apply_view(
+            app.clone(),
+            store,
+            app.state(),
+            handle,
+            arg(args, "name")?,
+            operation_id,
+            timeout_ms,
+        )
+        .map(RerunResult::View),
+        "query_database" => database::query_database(
+            app.clone(),
+            store,
+            app.state(),
+            arg(args, "connection")?,
+            arg(args, "sql")?,
+            arg(args, "row_limit")?,
+            operation_id,
+            timeout_ms,
+        )
+        .map(RerunResult::Dataset),
+        "run_database_sql" => database::run_database_sql(
+            app.clone(),
+            store,
+            app.state(),
+            arg(args, "connection")?,
+            arg(args, "query")?,
+            arg(args, "row_limit")?,
+            operation_id,
+            timeout_ms,
+        )
+        .map(RerunResult::Dataset),
+        "melt" => reshape::melt(
+            app,
+            store,
+            handle,
+            arg(args, "id_columns")?,
+            arg(args, "value_columns")?,
+            arg(args, "var_name")?,
+            arg(args, "value_name")?,
+        )
+        .map(RerunResult::Dataset),
+        "transpose" => {
+            reshape::transpose(app, store, handle, arg(args, "use_first_column")?).map(RerunResult::Dataset)
+        }
+        "deduplicate" => dedup::deduplicate(app, store, handle, arg(args, "key_columns")?, arg(args, "keep")?)
+            .map(RerunResult::Dataset),
+        "window_columns" => {
+            window::window_columns(app, store, handle, arg(args, "windows")?).map(RerunResult::Dataset)
+        }
+        "split_column" => {
+            split::split_column(app, store, handle, arg(args, "column")?, arg(args, "spec")?).map(RerunResult::Dataset)
+        }
+        "merge_columns" => merge::merge_columns(
+            app,
+            store,
+            handle,
+            arg(args, "template")?,
+            arg(args, "name")?,
+            arg(args, "drop_sources")?,
+        )
+        .map(RerunResult::Dataset),
+        "normalize_text" => {
+            normalize::normalize_text(app, store, handle, arg(args, "columns")?, arg(args, "operations")?)
+                .map(RerunResult::Dataset)
+        }
+        "normalize_dates" => {
+            date_formats::normalize_dates(app, store, handle, arg(args, "column")?, arg(args, "order")?)
+                .map(RerunResult::Dataset)
+        }
+        "fill_missing" => fill::fill_missing(
+            app,
+            store,
+            handle,
+            arg(args, "columns")?,
+            arg(args, "method")?,
+            arg(args, "value")?,
+        )
+        .map(RerunResult::Dataset),
+        "add_computed_column" => {
+            computed::add_computed_column(app, store, handle, arg(args, "name")?, arg(args, "expression")?)
+                .map(RerunResult::Dataset)
+        }
+        "convert_epoch" => epoch::convert_epoch(
+            app,
+            store,
+            handle,
+            arg(args, "column")?,
+            arg(args, "unit")?,
+            arg(args, "timezone")?,
+            arg(args, "format")?,
+        )
+        .map(RerunResult::Dataset),
+        "decode_column" => {
+            decode::decode_column(app, store, handle, arg(args, "column")?, arg(args, "encoding")?)
+                .map(RerunResult::Dataset)
+        }
+        "normalize_units" => {
+            units::normalize_units(app, store, handle, arg(args, "columns")?, arg(args, "decimal_comma")?)
+                .map(RerunResult::Dataset)
+        }
+        "mask_pii" => {
+            pii::mask_pii(app, store, handle, arg(args, "columns")?, arg(args, "kinds")?, arg(args, "mode")?)
+                .map(RerunResult::Dataset)
+        }
+        "recode_column" => recode::recode_column(
+            app,
+            store,
+            handle,
+            arg(args, "column")?,
+            arg(args, "pairs")?,
+            arg(args, "lookup")?,
+            arg(args, "target_column")?,
+            arg(args, "blank_unmapped")?,
+        )
+        .map(|result| RerunResult::Dataset(result.data)),
+        "filter_by_tag" => bookmarks::filter_by_tag(app, store, handle, arg(args, "tags")?, arg(args, "untagged")?)
+            .map(RerunResult::Dataset),
+        "add_tag_column" => bookmarks::add_tag_column(app, store, handle, arg(args, "name")?).map(RerunResult::Dataset),
+        other => Err(format!("{} can't be re-run on a single dataset", other).into()),
+    }
+}
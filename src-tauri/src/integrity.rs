@@ -0,0 +1,181 @@
+use crate::dataset::{Dataset, DatasetStore};
+use crate::stats::is_null;
+use crate::error::AppError;
+use serde::Serialize;
+use std::collections::{HashMap, HashSet};
+use tauri::State;
+
+/// Orphans listed per direction; the counts cover all of them
+const MAX_ORPHANS: usize = 1_000;
+
+#[derive(Debug, Serialize)]
+pub struct OrphanValue {
+    pub value: String,
+    /// Rows holding this value
+    pub count: usize,
+    /// Index of the first of those rows
+    pub first_row: usize,
+}
+
+/// Values of one dataset's column that the other dataset's column lacks
+#[derive(Debug, Serialize)]
+pub struct Orphans {
+    pub values: Vec<OrphanValue>,
+    pub distinct_count: usize,
+    pub row_count: usize,
+    /// Empty cells, which aren't treated as references
+    pub null_count: usize,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ReferenceCheck {
+    /// Values in A's column missing from B's, e.g. facts without a dimension row
+    pub missing_from_b: Orphans,
+    /// Values in B's column never used in A's
+    pub missing_from_a: Orphans,
+}
+
+fn orphans(from: &Dataset, from_col: usize, to: &Dataset, to_col: usize) -> Orphans {
+    let known: HashSet<&str> = to.rows.iter().filter_map(|row| row.get(to_col)).map(String::as_str).collect();
+    let mut found: HashMap<&str, OrphanValue> = HashMap::new();
+    let mut null_count = 0;
+    for (i, row) in from.rows.iter().enumerate() {
+        let value = row.get(from_col).map(String::as_str).unwrap_or("");
+        if is_null(value) {
+            null_count += 1;
+        } else if !known.contains(value) {
+            found
+                .entry(value)
+                .or_insert_with(|| OrphanValue { value: value.to_string(), count: 0, first_row: i })
+                .count += 1;
+        }
+    }
+
+    let mut values: Vec<OrphanValue> = found.into_values().collect();
+    values.sort_by_key(|v| v.first_row);
+    let distinct_count = values.len();
+    let row_count = values.iter().map(|v| v.count).sum();
+    values.truncate(MAX_ORPHANS);
+    Orphans {
+        values,
+        distinct_count,
+        row_count,
+        null_count,
+    }
+}
+
+/// Check that every value of `column_a` in dataset A exists in `column_b`
+/// of dataset B and the other way round, listing the orphans each way
+#[tauri::command]
+pub fn check_references(
+    store: State<'_, DatasetStore>,
+    handle_a: u64,
+    column_a: String,
+    handle_b: u64,
+    column_b: String,
+) -> Result<ReferenceCheck, AppError> {
+    store.with_pair(handle_a, handle_b, |a, b| {
+        let col_a = a.column_index(&column_a)?;
+        let col_b = b.column_index(&column_b)?;
+        Ok(ReferenceCheck {
+            missing_from_b: orphans(a, col_a, b, col_b),
+            missing_from_a: orphans(b, col_b, a, col_a),
+        })
+    })?
+}
+
+/// Largest column combination tried as a key
+const MAX_KEY_COLUMNS: usize = 3;
+/// Columns considered for multi-column keys, to keep the combinations manageable
+const MAX_COMBINED_COLUMNS: usize = 30;
+
+#[derive(Debug, Serialize)]
+pub struct KeyCandidate {
+    pub columns: Vec<String>,
+}
+
+/// Each column's values as small ids, so combinations compare cheaply
+fn encode(ds: &Dataset, col: usize) -> (Vec<u32>, usize) {
+    let mut ids: HashMap<&str, u32> = HashMap::new();
+    let codes = ds
+        .rows
+        .iter()
+        .map(|row| {
+            let value = row.get(col).map(String::as_str).unwrap_or("");
+            let next = ids.len() as u32;
+            *ids.entry(value).or_insert(next)
+        })
+        .collect();
+    (codes, ids.len())
+}
+
+fn combinations(n: usize, k: usize) -> Vec<Vec<usize>> {
+    if k == 0 {
+        return vec![Vec::new()];
+    }
+    (0..n)
+        .flat_map(|last| {
+            combinations(last, k - 1).into_iter().map(move |mut combo| {
+                combo.push(last);
+                combo
+            })
+        })
+        .collect()
+}
+
+/// Columns, or combinations of up to `max_columns` columns (default 2, at
+/// most 3), whose values are non-null and unique in every row. Only minimal
+/// combinations are listed: none contains a smaller key.
+#[tauri::command]
+pub fn find_key_candidates(
+    store: State<'_, DatasetStore>,
+    handle: u64,
+    max_columns: Option<usize>,
+) -> Result<Vec<KeyCandidate>, AppError> {
+    let max_columns = max_columns.unwrap_or(2).clamp(1, MAX_KEY_COLUMNS);
+    Ok(store.with(handle, |ds| {
+        let rows = ds.rows.len();
+        if rows == 0 {
+            return Vec::new();
+        }
+        let non_null: Vec<usize> = (0..ds.headers.len())
+            .filter(|&col| ds.rows.iter().all(|row| !is_null(row.get(col).map(String::as_str).unwrap_or(""))))
+            .collect();
+        let encoded: Vec<(Vec<u32>, usize)> = non_null.iter().map(|&col| encode(ds, col)).collect();
+
+        let mut keys: Vec<Vec<usize>> = Vec::new();
+        // Columns with few distinct values are the least likely to help
+        let all: Vec<usize> = (0..non_null.len()).collect();
+        let mut order = all.clone();
+        order.sort_by_key(|&i| std::cmp::Reverse(encoded[i].1));
+        order.truncate(MAX_COMBINED_COLUMNS);
+
+        for size in 1..=max_columns {
+            let pool = if size == 1 { &all } else { &order };
+            for combo in combinations(pool.len(), size) {
+                let combo: Vec<usize> = combo.iter().map(|&i| pool[i]).collect();
+                if keys.iter().any(|key| key.iter().all(|k| combo.contains(k))) {
+                    continue;
+                }
+                // Too few distinct values between them to tell every row apart
+                if combo.iter().map(|&i| encoded[i].1 as f64).product::<f64>() < rows as f64 {
+                    continue;
+                }
+                let mut seen = HashSet::with_capacity(rows);
+                let unique = (0..rows).all(|r| seen.insert(combo.iter().map(|&i| encoded[i].0[r]).collect::<Vec<u32>>()));
+                if unique {
+                    keys.push(combo);
+                }
+            }
+        }
+
+        keys.into_iter()
+            .map(|mut key| {
+                key.sort_unstable();
+                KeyCandidate {
+                    columns: key.into_iter().map(|i| ds.headers[non_null[i]].clone()).collect(),
+                }
+            })
+            .collect()
+    })?)
+}
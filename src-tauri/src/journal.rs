@@ -0,0 +1,377 @@
+use crate::dataset::{ColumnMeta, Dataset, DatasetStore, FileData, JsonKind};
+use crate::error::AppError;
+use crate::types::ColumnType;
+use crate::views::ViewConfig;
+use serde::Serialize;
+use tauri::State;
+
+/// How many edits are kept for undo per dataset
+const MAX_UNDO: usize = 100;
+
+/// How many edits a snapshot can hold on to past `MAX_UNDO`; snapshots
+/// older than that are dropped
+const MAX_SNAPSHOT_UNDO: usize = 1_000;
+
+/// A reversible change to a dataset. Applying an edit returns the edit that
+/// reverses it, so the same type serves both the undo and redo stacks.
+/// Transforms such as filters, joins, and deduplication aren't edits: they
+/// leave their input as it was and produce a new dataset, which closing undoes.
+#[derive(Debug, Clone)]
+pub enum Edit {
+    /// Set cells to these values: (row, column, value)
+    Cells(Vec<(usize, usize, String)>),
+    /// Insert rows so they end up at these (ascending) positions
+    InsertRows(Vec<(usize, Vec<String>)>),
+    /// Remove the rows at these (ascending) positions
+    RemoveRows(Vec<usize>),
+    /// Give the column at this position a new name
+    RenameColumn(usize, String),
+    /// Insert columns so they end up at these (ascending) positions:
+    /// (position, name, a value per row, its unit, types, and display format)
+    InsertColumns(Vec<(usize, String, Vec<String>, ColumnMeta)>),
+    /// Remove the columns at these (ascending) positions
+    RemoveColumns(Vec<usize>),
+    /// Rearrange columns so the one at `order[i]` ends up at position `i`
+    ReorderColumns(Vec<usize>),
+    /// Set cells of one column, along with the JSON type it's saved as and
+    /// its declared type (`None` clears them)
+    CastColumn {
+        col: usize,
+        cells: Vec<(usize, String)>,
+        kind: Option<JsonKind>,
+        declared: Option<ColumnType>,
+    },
+}
+
+impl Edit {
+    pub fn apply(self, ds: &mut Dataset) -> Edit {
+        match self {
+            Edit::Cells(cells) => Edit::Cells(
+                cells
+                    .into_iter()
+                    .map(|(row, col, value)| {
+                        let cells = &mut ds.rows[row];
+                        if cells.len() <= col {
+                            cells.resize(col + 1, String::new());
+                        }
+                        (row, col, std::mem::replace(&mut cells[col], value))
+                    })
+                    .collect(),
+            ),
+            Edit::InsertRows(rows) => {
+                let positions = rows.iter().map(|(i, _)| *i).collect();
+                ds.record_spans.clear();
+                for (i, row) in rows {
+                    ds.rows.insert(i, row);
+                }
+                Edit::RemoveRows(positions)
+            }
+            Edit::RemoveRows(positions) => {
+                ds.record_spans.clear();
+                let mut removed: Vec<(usize, Vec<String>)> = positions
+                    .into_iter()
+                    .rev()
+                    .map(|i| (i, ds.rows.remove(i)))
+                    .collect();
+                removed.reverse();
+                Edit::InsertRows(removed)
+            }
+            Edit::RenameColumn(col, name) => {
+                let old = ds.rename_column_at(col, name);
+                Edit::RenameColumn(col, old)
+            }
+            Edit::InsertColumns(columns) => {
+                let positions = columns.iter().map(|(c, _, _, _)| *c).collect();
+                for (c, name, values, meta) in columns {
+                    ds.set_column_meta(&name, meta);
+                    ds.headers.insert(c, name);
+                    for (row, value) in ds.rows.iter_mut().zip(values) {
+                        if row.len() < c {
+                            row.resize(c, String::new());
+                        }
+                        row.insert(c, value);
+                    }
+                }
+                Edit::RemoveColumns(positions)
+            }
+            Edit::RemoveColumns(positions) => {
+                let mut removed: Vec<(usize, String, Vec<String>, ColumnMeta)> = positions
+                    .into_iter()
+                    .rev()
+                    .map(|c| {
+                        let values = ds
+                            .rows
+                            .iter_mut()
+                            .map(|row| if c < row.len() { row.remove(c) } else { String::new() })
+                            .collect();
+                        let name = ds.headers.remove(c);
+                        let meta = ds.take_column_meta(&name);
+                        (c, name, values, meta)
+                    })
+                    .collect();
+                removed.reverse();
+                Edit::InsertColumns(removed)
+            }
+            Edit::ReorderColumns(order) => {
+                ds.headers = order.iter().map(|&i| ds.headers[i].clone()).collect();
+                for row in &mut ds.rows {
+                    *row = order.iter().map(|&i| row.get(i).cloned().unwrap_or_default()).collect();
+                }
+                let mut inverse = vec![0; order.len()];
+                for (to, from) in order.into_iter().enumerate() {
+                    inverse[from] = to;
+                }
+                Edit::ReorderColumns(inverse)
+            }
+            Edit::CastColumn { col, cells, kind, declared } => {
+                let name = ds.headers[col].clone();
+                let cells = cells
+                    .into_iter()
+                    .map(|(row, value)| {
+                        let cells = &mut ds.rows[row];
+                        if cells.len() <= col {
+                            cells.resize(col + 1, String::new());
+                        }
+                        (row, std::mem::replace(&mut cells[col], value))
+                    })
+                    .collect();
+                let kind = ds.source_format.as_mut().and_then(|format| format.replace_kind(&name, kind));
+                let declared = match declared {
+                    Some(declared) => ds.column_types.insert(name, declared),
+                    None => ds.column_types.remove(&name),
+                };
+                Edit::CastColumn { col, cells, kind, declared }
+            }
+        }
+    }
+}
+
+/// An edit on the undo or redo stack, tagged with the journal state it leads to
+#[derive(Debug, Clone)]
+struct Entry {
+    id: u64,
+    edit: Edit,
+}
+
+/// A named restore point. Rather than a copy of the table it holds the
+/// journal state the table was in, plus any edits needed to get back there
+/// after that state was undone and edited over.
+#[derive(Debug, Clone)]
+struct Snapshot {
+    name: String,
+    created: String,
+    base: u64,
+    /// Edits to re-apply after reaching `base`
+    replay: Vec<Edit>,
+    view: Option<ViewConfig>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct SnapshotInfo {
+    pub name: String,
+    pub created: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct RestoredSnapshot {
+    pub data: FileData,
+    /// Filter, sort, and columns shown when the snapshot was taken
+    pub view: Option<ViewConfig>,
+}
+
+/// Undo and redo stacks for one dataset
+#[derive(Debug, Clone, Default)]
+pub struct Journal {
+    undo: Vec<Entry>,
+    redo: Vec<Entry>,
+    /// Bumped on every change, including undo and redo
+    version: u64,
+    /// The oldest state undo can return to; older edits fell off the undo limit
+    floor: u64,
+    snapshots: Vec<Snapshot>,
+}
+
+impl Journal {
+    /// Remember how to reverse a change that was just made
+    pub fn record(&mut self, inverse: Edit) {
+        self.branch_off();
+        self.version += 1;
+        self.undo.push(Entry {
+            id: self.version,
+            edit: inverse,
+        });
+        // Edits back to the oldest snapshot are kept past the limit, up to a point
+        while self.undo.len() > MAX_UNDO
+            && (self.undo.len() > MAX_SNAPSHOT_UNDO || !self.snapshots.iter().any(|s| s.base == self.floor))
+        {
+            self.floor = self.undo.remove(0).id;
+        }
+        let floor = self.floor;
+        self.snapshots.retain(|s| s.base >= floor);
+    }
+
+    /// Forget every edit and snapshot once the rows were replaced wholesale,
+    /// e.g. by a refresh. The version still moves on, so anything worked out
+    /// for the old rows is redone.
+    pub fn reset(&mut self) {
+        let version = self.version + 1;
+        *self = Journal {
+            version,
+            floor: version,
+            ..Default::default()
+        };
+    }
+
+    /// Changes seen so far; differs whenever the table may have changed
+    pub fn version(&self) -> u64 {
+        self.version
+    }
+
+    /// The journal state the table is in
+    fn current(&self) -> u64 {
+        self.undo.last().map_or(self.floor, |e| e.id)
+    }
+
+    /// Drop the redo stack for a new edit, first handing each snapshot taken
+    /// in an undone state the edits that lead back to it
+    fn branch_off(&mut self) {
+        let current = self.current();
+        for snapshot in &mut self.snapshots {
+            if let Some(pos) = self.redo.iter().position(|e| e.id == snapshot.base) {
+                let edits: Vec<Edit> = self.redo[pos..].iter().rev().map(|e| e.edit.clone()).collect();
+                snapshot.base = current;
+                snapshot.replay.splice(0..0, edits);
+            }
+        }
+        self.redo.clear();
+    }
+
+    fn snapshot_infos(&self) -> Vec<SnapshotInfo> {
+        self.snapshots
+            .iter()
+            .map(|s| SnapshotInfo {
+                name: s.name.clone(),
+                created: s.created.clone(),
+            })
+            .collect()
+    }
+}
+
+fn step_back(ds: &mut Dataset) -> Option<()> {
+    let Entry { id, edit } = ds.journal.undo.pop()?;
+    let forward = edit.apply(ds);
+    ds.journal.redo.push(Entry { id, edit: forward });
+    ds.journal.version += 1;
+    Some(())
+}
+
+fn step_forward(ds: &mut Dataset) -> Option<()> {
+    let Entry { id, edit } = ds.journal.redo.pop()?;
+    let inverse = edit.apply(ds);
+    ds.journal.undo.push(Entry { id, edit: inverse });
+    ds.journal.version += 1;
+    Some(())
+}
+
+/// Reverse the most recent edit to a dataset
+#[tauri::command]
+pub fn undo(store: State<'_, DatasetStore>, handle: u64) -> Result<FileData, AppError> {
+    store.with_mut(handle, |ds| {
+        step_back(ds).ok_or("Nothing to undo")?;
+        ds.dirty = true;
+        Ok(ds.to_file_data(handle))
+    })?
+}
+
+/// Re-apply the most recently undone edit
+#[tauri::command]
+pub fn redo(store: State<'_, DatasetStore>, handle: u64) -> Result<FileData, AppError> {
+    store.with_mut(handle, |ds| {
+        step_forward(ds).ok_or("Nothing to redo")?;
+        ds.dirty = true;
+        Ok(ds.to_file_data(handle))
+    })?
+}
+
+/// Save the dataset's current edits and view under a name, replacing any
+/// snapshot of the same name
+#[tauri::command]
+pub fn take_snapshot(store: State<'_, DatasetStore>, handle: u64, name: String) -> Result<Vec<SnapshotInfo>, AppError> {
+    let name = name.trim().to_string();
+    if name.is_empty() {
+        return Err("Snapshot name cannot be empty".into());
+    }
+    Ok(store.with_mut(handle, |ds| {
+        let snapshot = Snapshot {
+            name,
+            created: chrono::Local::now().to_rfc3339(),
+            base: ds.journal.current(),
+            replay: Vec::new(),
+            view: ds.view.clone(),
+        };
+        let snapshots = &mut ds.journal.snapshots;
+        snapshots.retain(|s| s.name != snapshot.name);
+        snapshots.push(snapshot);
+        ds.journal.snapshot_infos()
+    })?)
+}
+
+#[tauri::command]
+pub fn list_snapshots(store: State<'_, DatasetStore>, handle: u64) -> Result<Vec<SnapshotInfo>, AppError> {
+    Ok(store.with(handle, |ds| ds.journal.snapshot_infos())?)
+}
+
+#[tauri::command]
+pub fn delete_snapshot(store: State<'_, DatasetStore>, handle: u64, name: String) -> Result<Vec<SnapshotInfo>, AppError> {
+    Ok(store.with_mut(handle, |ds| {
+        ds.journal.snapshots.retain(|s| s.name != name);
+        ds.journal.snapshot_infos()
+    })?)
+}
+
+/// Put the table back the way it was when a snapshot was taken, by undoing
+/// or redoing edits. Edits made since stay on the redo stack, so reverting
+/// can itself be undone with redo, unless the snapshot's state was undone and
+/// edited over: its edits are then made again as new ones, which clears the
+/// redo stack, and undo steps back through them instead.
+#[tauri::command]
+pub fn revert_to_snapshot(store: State<'_, DatasetStore>, handle: u64, name: String) -> Result<RestoredSnapshot, AppError> {
+    store.with_mut(handle, |ds| {
+        let snapshot = ds
+            .journal
+            .snapshots
+            .iter()
+            .find(|s| s.name == name)
+            .cloned()
+            .ok_or_else(|| format!("Snapshot not found: {}", name))?;
+        let journal = &ds.journal;
+        let step = if snapshot.base == journal.floor || journal.undo.iter().any(|e| e.id == snapshot.base) {
+            step_back
+        } else if journal.redo.iter().any(|e| e.id == snapshot.base) {
+            step_forward
+        } else {
+            return Err(format!("Snapshot '{}' can no longer be reached", name).into());
+        };
+        while ds.journal.current() != snapshot.base {
+            step(ds).ok_or_else(|| format!("Snapshot '{}' can no longer be reached", name))?;
+        }
+        if !snapshot.replay.is_empty() {
+            for edit in snapshot.replay {
+                let inverse = edit.apply(ds);
+                ds.record_edit(inverse);
+            }
+            // The snapshot's state is on the undo stack again
+            let base = ds.journal.current();
+            if let Some(s) = ds.journal.snapshots.iter_mut().find(|s| s.name == name) {
+                s.base = base;
+                s.replay.clear();
+            }
+        }
+        ds.view = snapshot.view.clone();
+        ds.dirty = true;
+        Ok(RestoredSnapshot {
+            data: ds.to_file_data(handle),
+            view: snapshot.view,
+        })
+    })?
+}
@@ -0,0 +1,143 @@
+use crate::dataset::{Dataset, DatasetStore};
+use crate::dates;
+use crate::sketch::HyperLogLog;
+use crate::stats::{is_null, parse_number, APPROX_DISTINCT_THRESHOLD};
+use crate::types::{infer_type, ColumnType};
+use crate::error::AppError;
+use chrono::NaiveDateTime;
+use serde::Serialize;
+use std::collections::HashSet;
+use tauri::State;
+
+/// Rows looked at when picking a date column automatically
+const DATE_SAMPLE: usize = 100;
+
+#[derive(Debug, Serialize)]
+pub struct DateRange {
+    pub column: String,
+    pub min: String,
+    pub max: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct MeasureSummary {
+    pub column: String,
+    pub sum: f64,
+    pub mean: Option<f64>,
+    /// Rows with a numeric value
+    pub count: usize,
+}
+
+#[derive(Debug, Serialize)]
+pub struct DimensionSummary {
+    pub column: String,
+    pub distinct: usize,
+    /// Estimated for very large datasets
+    pub exact: bool,
+}
+
+#[derive(Debug, Serialize)]
+pub struct KpiSummary {
+    pub row_count: usize,
+    pub column_count: usize,
+    pub date_range: Option<DateRange>,
+    pub measure: Option<MeasureSummary>,
+    pub dimension: Option<DimensionSummary>,
+}
+
+/// The first column whose leading values are all dates
+fn find_date_column(ds: &Dataset) -> Option<usize> {
+    (0..ds.headers.len()).find(|&col| {
+        let sample = ds.rows.iter().take(DATE_SAMPLE).filter_map(|row| row.get(col)).map(String::as_str);
+        let declared = ds.column_types.get(&ds.headers[col]).copied();
+        declared.unwrap_or_else(|| infer_type(sample)) == ColumnType::Date
+    })
+}
+
+enum Distinct<'a> {
+    Exact(HashSet<&'a str>),
+    Approx(HyperLogLog),
+}
+
+/// Headline figures for a dashboard header, computed in one pass over the
+/// rows: size, the range of `date_column` (the first date column when not
+/// given), the sum and mean of `measure`, and distinct values of `dimension`
+#[tauri::command]
+pub fn kpi_summary(
+    store: State<'_, DatasetStore>,
+    handle: u64,
+    date_column: Option<String>,
+    measure: Option<String>,
+    dimension: Option<String>,
+) -> Result<KpiSummary, AppError> {
+    store.with(handle, |ds| {
+        let date_col = match &date_column {
+            Some(name) => Some(ds.column_index(name)?),
+            None => find_date_column(ds),
+        };
+        let measure_col = measure.as_deref().map(|m| ds.column_index(m)).transpose()?;
+        let dimension_col = dimension.as_deref().map(|d| ds.column_index(d)).transpose()?;
+
+        let mut earliest: Option<(NaiveDateTime, &str)> = None;
+        let mut latest: Option<(NaiveDateTime, &str)> = None;
+        let (mut sum, mut count) = (0.0, 0);
+        let mut distinct = if ds.rows.len() > APPROX_DISTINCT_THRESHOLD {
+            Distinct::Approx(HyperLogLog::default())
+        } else {
+            Distinct::Exact(HashSet::new())
+        };
+
+        for row in &ds.rows {
+            let cell = |col: Option<usize>| col.and_then(|c| row.get(c)).map(String::as_str).filter(|v| !is_null(v));
+            if let Some(value) = cell(date_col) {
+                if let Some(dt) = dates::parse_datetime(value) {
+                    if earliest.is_none_or(|(min, _)| dt < min) {
+                        earliest = Some((dt, value));
+                    }
+                    if latest.is_none_or(|(max, _)| dt > max) {
+                        latest = Some((dt, value));
+                    }
+                }
+            }
+            if let Some(n) = cell(measure_col).and_then(parse_number) {
+                sum += n;
+                count += 1;
+            }
+            if let Some(value) = cell(dimension_col) {
+                match &mut distinct {
+                    Distinct::Exact(set) => {
+                        set.insert(value);
+                    }
+                    Distinct::Approx(hll) => hll.add(value),
+                }
+            }
+        }
+
+        Ok(KpiSummary {
+            row_count: ds.rows.len(),
+            column_count: ds.headers.len(),
+            date_range: date_col.zip(earliest.zip(latest)).map(|(col, ((_, min), (_, max)))| DateRange {
+                column: ds.headers[col].clone(),
+                min: min.to_string(),
+                max: max.to_string(),
+            }),
+            measure: measure_col.map(|col| MeasureSummary {
+                column: ds.headers[col].clone(),
+                sum,
+                mean: (count > 0).then(|| sum / count as f64),
+                count,
+            }),
+            dimension: dimension_col.map(|col| {
+                let (distinct, exact) = match &distinct {
+                    Distinct::Exact(set) => (set.len(), true),
+                    Distinct::Approx(hll) => (hll.estimate(), false),
+                };
+                DimensionSummary {
+                    column: ds.headers[col].clone(),
+                    distinct,
+                    exact,
+                }
+            }),
+        })
+    })?
+}
@@ -0,0 +1,136 @@
+use percent_encoding::percent_decode_str;
+use serde::Serialize;
+use std::path::Path;
+use std::sync::Mutex;
+use tauri::{AppHandle, Emitter, Manager};
+
+/// Event telling the frontend that files are waiting in the queue
+pub const OPEN_FILES_EVENT: &str = "open-files";
+
+/// URL scheme for links like `quickdataviewer://open?path=...&row=12&filter=...`
+pub const DEEP_LINK_SCHEME: &str = "quickdataviewer";
+
+/// A file the OS, command line, or a deep link asked us to open
+#[derive(Debug, Clone, Serialize)]
+pub struct OpenRequest {
+    pub path: String,
+    /// 1-based row to scroll to
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub row: Option<usize>,
+    /// Filter expression to apply once the file is loaded
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub filter: Option<String>,
+}
+
+impl OpenRequest {
+    pub fn file(path: &Path) -> Self {
+        OpenRequest {
+            path: path.to_string_lossy().into_owned(),
+            row: None,
+            filter: None,
+        }
+    }
+}
+
+/// Parse a `quickdataviewer://open?...` link. Query values are percent-encoded,
+/// with `+` standing for a space as in HTML forms.
+pub fn parse_deep_link(link: &str) -> Result<OpenRequest, String> {
+    let rest = link
+        .strip_prefix(DEEP_LINK_SCHEME)
+        .and_then(|r| r.strip_prefix("://"))
+        .ok_or_else(|| format!("Not a {} link: {}", DEEP_LINK_SCHEME, link))?;
+    let (action, query) = rest.split_once('?').unwrap_or((rest, ""));
+    if action.trim_end_matches('/') != "open" {
+        return Err(format!("Unknown deep link action: {}", action));
+    }
+
+    let mut request = OpenRequest {
+        path: String::new(),
+        row: None,
+        filter: None,
+    };
+    for pair in query.split('&').filter(|p| !p.is_empty()) {
+        let (key, value) = pair.split_once('=').unwrap_or((pair, ""));
+        let value = percent_decode_str(&value.replace('+', " "))
+            .decode_utf8()
+            .map_err(|e| format!("Invalid deep link value for {}: {}", key, e))?
+            .into_owned();
+        match key {
+            "path" => request.path = value,
+            "row" => {
+                let row = value
+                    .parse::<usize>()
+                    .ok()
+                    .filter(|&r| r > 0)
+                    .ok_or_else(|| format!("Invalid row in deep link: {}", value))?;
+                request.row = Some(row);
+            }
+            "filter" => request.filter = Some(value).filter(|f| !f.trim().is_empty()),
+            _ => {}
+        }
+    }
+    if request.path.is_empty() {
+        return Err("Deep link has no path".to_string());
+    }
+    Ok(request)
+}
+
+/// Files to open that the frontend hasn't picked up yet. Requests are queued
+/// rather than only emitted, since the first ones arrive before the page loads.
+#[derive(Default)]
+pub struct PendingFiles(Mutex<Vec<OpenRequest>>);
+
+/// Files and deep links given on the command line (without the program name),
+/// e.g. by a file-manager double-click. Relative paths are resolved against `cwd`.
+pub fn file_args(args: &[String], cwd: &Path) -> Vec<OpenRequest> {
+    args.iter()
+        .filter(|a| !a.starts_with('-'))
+        .filter_map(|a| {
+            // Not just the scheme name, which a file such as "quickdataviewer-export.csv" starts with too
+            if a.strip_prefix(DEEP_LINK_SCHEME).is_some_and(|rest| rest.starts_with("://")) {
+                return parse_deep_link(a).ok();
+            }
+            let path = cwd.join(a);
+            path.is_file().then(|| OpenRequest::file(&path))
+        })
+        .collect()
+}
+
+/// Files and deep links the OS handed over as URLs (macOS open events)
+#[cfg(any(target_os = "macos", target_os = "ios"))]
+pub fn url_args(urls: &[String]) -> Vec<OpenRequest> {
+    urls.iter()
+        .filter_map(|url| match url.strip_prefix("file://") {
+            Some(path) => percent_decode_str(path)
+                .decode_utf8()
+                .ok()
+                .map(|p| OpenRequest::file(Path::new(p.as_ref()))),
+            None => parse_deep_link(url).ok(),
+        })
+        .collect()
+}
+
+/// Another launch was redirected to this instance: open its files here and
+/// bring the window to the front
+pub fn second_instance(app: &AppHandle, args: Vec<String>, cwd: String) {
+    queue(app, file_args(args.get(1..).unwrap_or_default(), Path::new(&cwd)));
+    if let Some(window) = app.get_webview_window("main") {
+        let _ = window.unminimize();
+        let _ = window.set_focus();
+    }
+}
+
+/// Queue files to open and let the frontend know
+pub fn queue(app: &AppHandle, requests: Vec<OpenRequest>) {
+    if requests.is_empty() {
+        return;
+    }
+    app.state::<PendingFiles>().0.lock().unwrap().extend(requests);
+    let _ = app.emit(OPEN_FILES_EVENT, ());
+}
+
+/// Hand the queued files to the frontend, oldest first
+#[tauri::command]
+pub fn take_pending_files(pending: tauri::State<'_, PendingFiles>) -> Vec<OpenRequest> {
+    std::mem::take(&mut *pending.0.lock().unwrap())
+}
@@ -0,0 +1,114 @@
+use crate::error::AppError;
+use std::collections::VecDeque;
+use std::fs;
+use std::path::PathBuf;
+use std::time::Instant;
+use tauri::ipc::Invoke;
+use tauri::{AppHandle, Manager};
+use tracing_appender::rolling::{RollingFileAppender, Rotation};
+
+const LOG_DIR: &str = "logs";
+const LOG_PREFIX: &str = "qdv";
+const LOG_SUFFIX: &str = "log";
+/// One file per day; older ones are deleted as new ones are started
+const KEEP_FILES: usize = 7;
+const DEFAULT_LINES: usize = 200;
+
+fn log_dir(app: &AppHandle) -> Result<PathBuf, String> {
+    app.path()
+        .app_data_dir()
+        .map(|dir| dir.join(LOG_DIR))
+        .map_err(|e| format!("Failed to resolve app data dir: {}", e))
+}
+
+/// Send log events to a daily file in the app data dir. Logging is
+/// best-effort: when the file can't be opened the app runs without it.
+pub fn init(app: &AppHandle) {
+    let appender = log_dir(app).and_then(|dir| {
+        RollingFileAppender::builder()
+            .rotation(Rotation::DAILY)
+            .filename_prefix(LOG_PREFIX)
+            .filename_suffix(LOG_SUFFIX)
+            .max_log_files(KEEP_FILES)
+            .build(dir)
+            .map_err(|e| format!("Failed to open log file: {}", e))
+    });
+    match appender {
+        Ok(appender) => {
+            let _ = tracing_subscriber::fmt()
+                .with_writer(appender)
+                .with_ansi(false)
+                .with_target(false)
+                .try_init();
+        }
+        Err(e) => eprintln!("{}", e),
+    }
+}
+
+/// Wrap the command handler so every call is logged with how long the
+/// handler took. For synchronous commands that's the whole call; async
+/// commands return as soon as they're spawned, so they time their own work
+/// with `timed`. Errors are logged as they're sent back to the frontend (see
+/// `AppError`).
+pub fn commands(
+    handler: impl Fn(Invoke) -> bool + Send + Sync + 'static,
+) -> impl Fn(Invoke) -> bool + Send + Sync + 'static {
+    move |invoke| {
+        let span = tracing::info_span!("command", name = invoke.message.command());
+        let _entered = span.enter();
+        let started = Instant::now();
+        let handled = handler(invoke);
+        tracing::info!(duration_ms = started.elapsed().as_millis() as u64, "handled");
+        handled
+    }
+}
+
+/// Logs how long an async command's work took when dropped
+pub struct Timed {
+    command: &'static str,
+    started: Instant,
+}
+
+/// Start timing an async command; hold the guard for the rest of its body
+pub fn timed(command: &'static str) -> Timed {
+    Timed { command, started: Instant::now() }
+}
+
+impl Drop for Timed {
+    fn drop(&mut self) {
+        tracing::info!(command = self.command, duration_ms = self.started.elapsed().as_millis() as u64, "finished");
+    }
+}
+
+/// The last `lines` lines of the log (200 by default), oldest first, so a
+/// user can copy them into a bug report
+#[tauri::command]
+pub fn get_recent_logs(app: AppHandle, lines: Option<usize>) -> Result<Vec<String>, AppError> {
+    let lines = lines.unwrap_or(DEFAULT_LINES);
+    let dir = log_dir(&app)?;
+    let mut files: Vec<PathBuf> = match fs::read_dir(&dir) {
+        Ok(entries) => entries
+            .filter_map(|entry| entry.ok().map(|e| e.path()))
+            .filter(|path| {
+                let name = path.file_name().and_then(|n| n.to_str()).unwrap_or("");
+                name.starts_with(LOG_PREFIX) && name.ends_with(LOG_SUFFIX)
+            })
+            .collect(),
+        // Nothing has been logged yet
+        Err(_) => return Ok(Vec::new()),
+    };
+    // Names end in the date, so they sort oldest first
+    files.sort();
+
+    let mut recent = VecDeque::new();
+    for path in files.iter().rev() {
+        let content = fs::read_to_string(path).map_err(|e| format!("Failed to read log file: {}", e))?;
+        for line in content.lines().rev() {
+            if recent.len() == lines {
+                return Ok(recent.into());
+            }
+            recent.push_front(line.to_string());
+        }
+    }
+    Ok(recent.into())
+}
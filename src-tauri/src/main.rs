@@ -1,59 +1,236 @@
 // Prevents additional console window on Windows in release
 #![cfg_attr(not(debug_assertions), windows_subsystem = "windows")]
 
-use serde::{Deserialize, Serialize};
+mod access;
+mod api;
+mod autosave;
+mod batch;
+mod bookmarks;
+mod cache;
+mod cast;
+mod changes;
+mod chart;
+mod checksum;
+mod clipboard;
+mod cloud;
+mod columns;
+mod combine;
+mod computed;
+mod database;
+mod dataset;
+mod date_formats;
+mod dates;
+mod decode;
+mod dedup;
+mod display;
+mod edit;
+mod epoch;
+mod error;
+mod expr;
+mod fill;
+mod fileinfo;
+mod filter;
+mod formatting;
+mod hashing;
+mod header;
+mod history;
+mod integrity;
+mod journal;
+mod kpi;
+mod launch;
+mod logging;
+mod memory;
+mod merge;
+mod normalize;
+mod open;
+mod operations;
+mod paging;
+mod parquet_file;
+mod pii;
+mod profile;
+mod progress;
+mod quantile;
+mod quarantine;
+mod raw;
+mod recent;
+mod recipe;
+mod recode;
+mod refresh;
+mod replace;
+mod remote;
+mod reshape;
+mod save;
+mod scan;
+mod schema;
+mod schema_override;
+mod search;
+mod session;
+mod settings;
+mod sketch;
+mod split;
+mod statements;
+mod stats;
+mod stdin;
+mod storage;
+mod types;
+mod units;
+mod validate;
+mod verify;
+mod viewer;
+mod views;
+mod watch;
+mod window;
+
+use dataset::{Dataset, DatasetStore, FileData, FileStamp, JsonKind, JsonLayout, ParseFailure, RecordSpan, SourceFormat};
+use error::AppError;
+use scan::Scan;
+use schema_override::SchemaOverride;
+use std::collections::HashMap;
 use std::fs;
+use std::io::Write;
+use tauri::{AppHandle, Manager, State};
 
-#[derive(Debug, Serialize, Deserialize)]
-struct FileData {
-    headers: Vec<String>,
-    rows: Vec<Vec<String>>,
-    row_count: usize,
-    file_name: String,
-    file_type: String,
+/// Parse CSV file and return structured data. `has_header: false` reads the
+/// first line as data and names the columns column_1, column_2, ...
+/// `lenient: true` skips records that don't parse instead of failing; see
+/// `export_failed_lines`. `scan` filters rows and drops columns as the file
+/// is read, so only what's wanted of a large file is ever loaded. `schema`
+/// overrides inferred types and names; without one, the file's sidecar
+/// `.schema.json` is used if it has one.
+#[tauri::command]
+#[allow(clippy::too_many_arguments)]
+fn parse_csv(
+    app: AppHandle,
+    store: State<'_, DatasetStore>,
+    file_path: String,
+    has_header: Option<bool>,
+    lenient: Option<bool>,
+    scan: Option<Scan>,
+    schema: Option<SchemaOverride>,
+) -> Result<FileData, AppError> {
+    access::check(&app, &file_path)?;
+    let budget = memory::available(&app, &store);
+    let (has_header, lenient) = (has_header.unwrap_or(true), lenient.unwrap_or(false));
+    let loaded = match scan.filter(|scan| !scan.is_empty()) {
+        Some(scan) => scan::scan_csv(&file_path, has_header, lenient, &scan, budget).map_err(AppError::from),
+        None => load_csv(&file_path, budget, has_header, lenient),
+    };
+    let data = store.register(with_schema(&file_path, schema, loaded).map_err(|e| e.with_path(&file_path))?);
+    recent::record(&app, &file_path, &data);
+    Ok(data)
 }
 
-#[derive(Debug, Serialize)]
-struct ErrorResponse {
-    error: String,
+/// Load a CSV file, or only its first rows when all of them wouldn't fit in `budget` bytes
+fn load_csv(file_path: &str, budget: Option<u64>, has_header: bool, lenient: bool) -> Result<Dataset, AppError> {
+    let (content, truncated) = memory::read_within(file_path, budget)?;
+    
+    let file_name = std::path::Path::new(file_path)
+        .file_name()
+        .and_then(|n| n.to_str())
+        .unwrap_or("unknown")
+        .to_string();
+    
+    let mut dataset = Dataset {
+        file_name,
+        source_path: Some(file_path.to_string()),
+        source_stamp: FileStamp::read(file_path),
+        ..csv_to_dataset(&content, b',', has_header, lenient)?
+    };
+    dataset.preview = memory::trim_to(&mut dataset, budget) || truncated;
+    Ok(dataset)
 }
 
-/// Parse CSV file and return structured data
-#[tauri::command]
-fn parse_csv(file_path: String) -> Result<FileData, String> {
-    let content = fs::read_to_string(&file_path)
-        .map_err(|e| format!("Failed to read file: {}", e))?;
-    
+/// Parse delimited text, with or without a header row. With `lenient`,
+/// records that fail to parse are set aside in `parse_failures` instead of
+/// failing the whole load.
+fn csv_to_dataset(content: &str, delimiter: u8, has_header: bool, lenient: bool) -> Result<Dataset, String> {
     let mut reader = csv::ReaderBuilder::new()
-        .has_headers(true)
+        .has_headers(has_header)
+        .delimiter(delimiter)
         .from_reader(content.as_bytes());
     
-    let headers = reader.headers()
-        .map_err(|e| format!("Failed to read headers: {}", e))?
-        .iter()
-        .map(|s| s.to_string())
-        .collect();
+    let mut headers: Vec<String> = if has_header {
+        reader.headers()
+            .map_err(|e| format!("Failed to read headers: {}", e))?
+            .iter()
+            .map(|s| s.to_string())
+            .collect()
+    } else {
+        Vec::new()
+    };
     
     let mut rows = Vec::new();
+    // Spans of every record, including failed ones, so each ends where the next begins
+    let mut spans: Vec<RecordSpan> = Vec::new();
+    let mut failed: Vec<(usize, String)> = Vec::new();
+    let push_span = |spans: &mut Vec<RecordSpan>, position: &csv::Position| {
+        if let Some(last) = spans.last_mut() {
+            last.end = position.byte();
+        }
+        spans.push(RecordSpan {
+            start: position.byte(),
+            end: content.len() as u64,
+            line: position.line(),
+            end_line: position.line(),
+        });
+    };
     for result in reader.records() {
-        let record = result.map_err(|e| format!("Failed to read record: {}", e))?;
+        let record = match result {
+            Ok(record) => record,
+            Err(e) => match e.position() {
+                Some(position) if lenient => {
+                    failed.push((spans.len(), e.to_string()));
+                    push_span(&mut spans, position);
+                    continue;
+                }
+                _ => return Err(format!("Failed to read record: {}", e)),
+            },
+        };
         let row: Vec<String> = record.iter().map(|s| s.to_string()).collect();
         rows.push(row);
+        if let Some(position) = record.position() {
+            push_span(&mut spans, position);
+        }
+    }
+    // A record ends where the next one starts. The reader may report a
+    // position just before the line break, and blank lines are skipped, so
+    // trim line breaks off both ends.
+    for span in &mut spans {
+        let text = &content.as_bytes()[span.start as usize..span.end as usize];
+        let leading = text.iter().position(|&b| b != b'\n' && b != b'\r').unwrap_or(text.len());
+        let trailing = text.iter().rposition(|&b| b != b'\n' && b != b'\r').map_or(leading, |i| i + 1);
+        span.line += text[..leading].iter().filter(|&&b| b == b'\n').count() as u64;
+        span.end_line = span.line + text[leading..trailing].iter().filter(|&&b| b == b'\n').count() as u64;
+        span.end = span.start + trailing as u64;
+        span.start += leading as u64;
+    }
+    let mut failed = failed.into_iter().peekable();
+    let mut parse_failures = Vec::new();
+    let mut record_spans = Vec::with_capacity(rows.len());
+    for (i, span) in spans.into_iter().enumerate() {
+        match failed.next_if(|(f, _)| *f == i) {
+            Some((_, error)) => parse_failures.push(ParseFailure {
+                line: span.line,
+                text: content[span.start as usize..span.end as usize].to_string(),
+                error,
+            }),
+            None => record_spans.push(span),
+        }
     }
     
-    let row_count = rows.len();
-    let file_name = std::path::Path::new(&file_path)
-        .file_name()
-        .and_then(|n| n.to_str())
-        .unwrap_or("unknown")
-        .to_string();
+    if !has_header {
+        let width = rows.first().map_or(0, Vec::len);
+        headers = (1..=width).map(|i| format!("column_{}", i)).collect();
+    }
     
-    Ok(FileData {
+    Ok(Dataset {
         headers,
         rows,
-        row_count,
-        file_name,
         file_type: "CSV".to_string(),
+        source_format: Some(SourceFormat::csv(content.as_bytes(), delimiter, has_header)),
+        record_spans,
+        parse_failures,
+        ..Default::default()
     })
 }
 
@@ -91,18 +268,51 @@ fn flatten_object(prefix: &str, value: &serde_json::Value, out: &mut Vec<(String
     }
 }
 
+/// Note the JSON type seen for a column, downgrading to Mixed on conflicts
+fn record_kind(kinds: &mut HashMap<String, JsonKind>, key: &str, value: &serde_json::Value) {
+    let kind = match value {
+        serde_json::Value::Null => return,
+        serde_json::Value::String(_) => JsonKind::String,
+        serde_json::Value::Number(_) => JsonKind::Number,
+        serde_json::Value::Bool(_) => JsonKind::Bool,
+        serde_json::Value::Array(_) => JsonKind::Array,
+        serde_json::Value::Object(_) => JsonKind::Object,
+    };
+    kinds
+        .entry(key.to_string())
+        .and_modify(|k| if *k != kind { *k = JsonKind::Mixed })
+        .or_insert(kind);
+}
+
+/// Record the JSON type behind each flattened column, mirroring `flatten_object`
+fn collect_kinds(prefix: &str, value: &serde_json::Value, kinds: &mut HashMap<String, JsonKind>) {
+    match value {
+        serde_json::Value::Object(map) => {
+            for (k, v) in map {
+                let key = if prefix.is_empty() {
+                    k.clone()
+                } else {
+                    format!("{}.{}", prefix, k)
+                };
+                collect_kinds(&key, v, kinds);
+            }
+        }
+        other => record_kind(kinds, prefix, other),
+    }
+}
+
 /// Extract the data array from a JSON value:
 /// - Already an array of objects -> use directly
 /// - A dict of objects (each key maps to an object) -> each key becomes a row with a "Name" column
 /// - An object with a key whose value is the largest array of objects -> use that array
 /// - A single object -> wrap in a one-element array
-fn extract_data_array(parsed: serde_json::Value) -> Result<Vec<serde_json::Value>, String> {
+fn extract_data_array(parsed: serde_json::Value) -> Result<(Vec<serde_json::Value>, JsonLayout), String> {
     match parsed {
         serde_json::Value::Array(arr) => {
             if arr.is_empty() {
                 return Err("JSON array is empty".to_string());
             }
-            Ok(arr)
+            Ok((arr, JsonLayout::Array))
         }
         serde_json::Value::Object(map) => {
             // Check for dictionary-of-objects pattern: {"key1": {...}, "key2": {...}}
@@ -119,7 +329,7 @@ fn extract_data_array(parsed: serde_json::Value) -> Result<Vec<serde_json::Value
                         rows.push(serde_json::Value::Object(row));
                     }
                 }
-                return Ok(rows);
+                return Ok((rows, JsonLayout::Keyed));
             }
 
             // Find the key with the largest array-of-objects value
@@ -137,36 +347,71 @@ fn extract_data_array(parsed: serde_json::Value) -> Result<Vec<serde_json::Value
 
             if let Some(key) = best_key {
                 if let Some(serde_json::Value::Array(arr)) = map.get(&key) {
-                    return Ok(arr.clone());
+                    return Ok((arr.clone(), JsonLayout::Wrapped(key)));
                 }
             }
 
             // No nested array found — treat the object itself as a single row
-            Ok(vec![serde_json::Value::Object(map)])
+            Ok((vec![serde_json::Value::Object(map)], JsonLayout::Single))
         }
         _ => Err("JSON must be an object or an array of objects".to_string()),
     }
 }
 
-/// Parse JSON file and return structured data
+/// Parse JSON file and return structured data. `schema` works as for `parse_csv`.
 #[tauri::command]
-fn parse_json(file_path: String) -> Result<FileData, String> {
-    let content = fs::read_to_string(&file_path)
+fn parse_json(
+    app: AppHandle,
+    store: State<'_, DatasetStore>,
+    file_path: String,
+    schema: Option<SchemaOverride>,
+) -> Result<FileData, AppError> {
+    access::check(&app, &file_path)?;
+    memory::check_json(&file_path, memory::available(&app, &store))?;
+    let loaded = with_schema(&file_path, schema, load_json(&file_path).map_err(AppError::from));
+    let data = store.register(loaded.map_err(|e| e.with_path(&file_path))?);
+    recent::record(&app, &file_path, &data);
+    Ok(data)
+}
+
+fn load_json(file_path: &str) -> Result<Dataset, String> {
+    let content = fs::read_to_string(file_path)
         .map_err(|e| format!("Failed to read file: {}", e))?;
 
     let parsed: serde_json::Value = serde_json::from_str(&content)
         .map_err(|e| format!("Failed to parse JSON: {}", e))?;
 
-    let data = extract_data_array(parsed)?;
+    let file_name = std::path::Path::new(file_path)
+        .file_name()
+        .and_then(|n| n.to_str())
+        .unwrap_or("unknown")
+        .to_string();
+
+    let mut dataset = Dataset {
+        file_name,
+        source_path: Some(file_path.to_string()),
+        source_stamp: FileStamp::read(file_path),
+        ..json_to_dataset(parsed)?
+    };
+    dataset.record_spans = raw::json_spans(&content, &dataset);
+    Ok(dataset)
+}
+
+/// Turn a parsed JSON document into a table: find the rows with
+/// `extract_data_array` and flatten each one into columns
+fn json_to_dataset(parsed: serde_json::Value) -> Result<Dataset, String> {
+    let (data, layout) = extract_data_array(parsed)?;
 
     // Flatten all rows and collect every header we see (preserving order of first appearance)
     let mut all_flat: Vec<Vec<(String, String)>> = Vec::new();
     let mut headers: Vec<String> = Vec::new();
     let mut header_set = std::collections::HashSet::new();
 
+    let mut kinds = HashMap::new();
     for item in &data {
         let mut pairs = Vec::new();
         flatten_object("", item, &mut pairs);
+        collect_kinds("", item, &mut kinds);
         for (key, _) in &pairs {
             if header_set.insert(key.clone()) {
                 headers.push(key.clone());
@@ -194,138 +439,518 @@ fn parse_json(file_path: String) -> Result<FileData, String> {
         rows.push(row);
     }
 
-    let row_count = rows.len();
-    let file_name = std::path::Path::new(&file_path)
-        .file_name()
-        .and_then(|n| n.to_str())
-        .unwrap_or("unknown")
-        .to_string();
-
-    Ok(FileData {
+    Ok(Dataset {
         headers,
         rows,
-        row_count,
-        file_name,
         file_type: "JSON".to_string(),
+        source_format: Some(SourceFormat::Json { layout, kinds }),
+        ..Default::default()
     })
 }
 
-/// Parse JSONL file (newline-delimited JSON) and return structured data
+/// Parse JSONL file (newline-delimited JSON) and return structured data.
+/// `lenient: true` skips lines that aren't valid JSON objects instead of
+/// failing. `scan` filters rows and drops columns as lines are read.
+/// `schema` works as for `parse_csv`.
 #[tauri::command]
-fn parse_jsonl(file_path: String) -> Result<FileData, String> {
-    let content = fs::read_to_string(&file_path)
-        .map_err(|e| format!("Failed to read file: {}", e))?;
+fn parse_jsonl(
+    app: AppHandle,
+    store: State<'_, DatasetStore>,
+    file_path: String,
+    lenient: Option<bool>,
+    scan: Option<Scan>,
+    schema: Option<SchemaOverride>,
+) -> Result<FileData, AppError> {
+    access::check(&app, &file_path)?;
+    let budget = memory::available(&app, &store);
+    let lenient = lenient.unwrap_or(false);
+    let loaded = match scan.filter(|scan| !scan.is_empty()) {
+        Some(scan) => scan::scan_jsonl(&file_path, lenient, &scan, budget).map_err(AppError::from),
+        None => load_jsonl(&file_path, budget, lenient),
+    };
+    let data = store.register(with_schema(&file_path, schema, loaded).map_err(|e| e.with_path(&file_path))?);
+    recent::record(&app, &file_path, &data);
+    Ok(data)
+}
+
+/// Cell text for a top-level JSONL value; nested values are kept as JSON
+fn json_cell(value: &serde_json::Value) -> String {
+    match value {
+        serde_json::Value::String(s) => s.clone(),
+        serde_json::Value::Number(n) => n.to_string(),
+        serde_json::Value::Bool(b) => b.to_string(),
+        serde_json::Value::Null => String::new(),
+        _ => value.to_string(),
+    }
+}
+
+/// Load a JSONL file, or only its first lines when all of them wouldn't fit in `budget` bytes
+fn load_jsonl(file_path: &str, budget: Option<u64>, lenient: bool) -> Result<Dataset, AppError> {
+    let (content, truncated) = memory::read_within(file_path, budget)?;
     
-    let lines: Vec<&str> = content.lines().filter(|l| !l.trim().is_empty()).collect();
+    let mut lines: Vec<(RecordSpan, &str)> = Vec::new();
+    let mut offset = 0;
+    for (i, line) in content.split_inclusive('\n').enumerate() {
+        let text = line.trim_end_matches(['\n', '\r']);
+        if !text.trim().is_empty() {
+            let span = RecordSpan {
+                start: offset as u64,
+                end: (offset + text.len()) as u64,
+                line: i as u64 + 1,
+                end_line: i as u64 + 1,
+            };
+            lines.push((span, text));
+        }
+        offset += line.len();
+    }
+    
+    let mut parse_failures = Vec::new();
+    if lenient {
+        // Set aside lines that aren't JSON objects, so the first good line gives the headers
+        lines.retain(|(span, line)| match serde_json::from_str::<serde_json::Value>(line) {
+            Ok(value) if value.is_object() => true,
+            result => {
+                parse_failures.push(ParseFailure {
+                    line: span.line,
+                    text: line.to_string(),
+                    error: result.map_or_else(|e| e.to_string(), |_| "Not a JSON object".to_string()),
+                });
+                false
+            }
+        });
+    }
     
     if lines.is_empty() {
-        return Err("JSONL file is empty".to_string());
+        return Err("JSONL file is empty".into());
     }
     
     // Parse first line to get headers
-    let first_line: serde_json::Value = serde_json::from_str(lines[0])
+    let first_line: serde_json::Value = serde_json::from_str(lines[0].1)
         .map_err(|e| format!("Failed to parse first line: {}", e))?;
     
     let headers: Vec<String> = if let Some(obj) = first_line.as_object() {
         obj.keys().map(|k| k.to_string()).collect()
     } else {
-        return Err("JSONL lines must be objects".to_string());
+        return Err(AppError::parse("JSONL lines must be objects".to_string()));
     };
     
     // Parse all lines
     let mut rows = Vec::new();
-    for (i, line) in lines.iter().enumerate() {
+    let mut spans = Vec::new();
+    let mut kinds = HashMap::new();
+    for (span, line) in &lines {
         let obj: serde_json::Value = serde_json::from_str(line)
-            .map_err(|e| format!("Failed to parse line {}: {}", i + 1, e))?;
+            .map_err(|e| format!("Failed to parse line {}: {}", span.line, e))?;
         
         if let Some(obj_map) = obj.as_object() {
+            for (key, value) in obj_map {
+                record_kind(&mut kinds, key, value);
+            }
             let row: Vec<String> = headers.iter()
-                .map(|h| {
-                    obj_map.get(h)
-                        .and_then(|v| match v {
-                            serde_json::Value::String(s) => Some(s.clone()),
-                            serde_json::Value::Number(n) => Some(n.to_string()),
-                            serde_json::Value::Bool(b) => Some(b.to_string()),
-                            serde_json::Value::Null => Some("".to_string()),
-                            _ => Some(v.to_string()),
-                        })
-                        .unwrap_or_else(|| "".to_string())
-                })
+                .map(|h| obj_map.get(h).map(json_cell).unwrap_or_default())
                 .collect();
             rows.push(row);
+            spans.push(*span);
         }
     }
     
-    let row_count = rows.len();
-    let file_name = std::path::Path::new(&file_path)
+    let file_name = std::path::Path::new(file_path)
         .file_name()
         .and_then(|n| n.to_str())
         .unwrap_or("unknown")
         .to_string();
     
-    Ok(FileData {
+    let mut dataset = Dataset {
         headers,
         rows,
-        row_count,
         file_name,
         file_type: "JSONL".to_string(),
-    })
+        source_path: Some(file_path.to_string()),
+        source_stamp: FileStamp::read(file_path),
+        source_format: Some(SourceFormat::Jsonl { kinds }),
+        record_spans: spans,
+        parse_failures,
+        ..Default::default()
+    };
+    dataset.preview = memory::trim_to(&mut dataset, budget) || truncated;
+    Ok(dataset)
 }
 
-/// Export data to CSV format
-#[tauri::command]
-fn export_csv(file_path: String, headers: Vec<String>, rows: Vec<Vec<String>>) -> Result<String, String> {
+/// Apply a schema override, or the file's sidecar schema, to a freshly loaded dataset
+fn with_schema(file_path: &str, schema: Option<SchemaOverride>, loaded: Result<Dataset, AppError>) -> Result<Dataset, AppError> {
+    let mut dataset = loaded?;
+    schema_override::apply_for(file_path, schema, &mut dataset)?;
+    Ok(dataset)
+}
+
+/// Load a CSV, JSON, JSONL, Parquet, QIF, or MT940 file, picking the parser by
+/// extension, with its sidecar schema applied
+fn load_file(file_path: &str) -> Result<Dataset, AppError> {
+    load_file_within(file_path, None)
+}
+
+/// `load_file`, loading only the first rows of CSV and JSONL files when all
+/// of them wouldn't fit in `budget` bytes
+fn load_file_within(file_path: &str, budget: Option<u64>) -> Result<Dataset, AppError> {
+    let extension = std::path::Path::new(file_path)
+        .extension()
+        .and_then(|e| e.to_str())
+        .map(|e| e.to_ascii_lowercase());
+    let loaded = match extension.as_deref() {
+        Some("csv") => load_csv(file_path, budget, true, false),
+        Some("json") => load_json(file_path).map_err(AppError::from),
+        Some("jsonl") => load_jsonl(file_path, budget, false),
+        Some("parquet") => scan::scan_parquet(file_path, &Scan::default(), budget).map_err(AppError::from),
+        Some("qif") => statements::load_qif(file_path).map_err(AppError::from),
+        Some("sta") | Some("mt940") => statements::load_mt940(file_path).map_err(AppError::from),
+        _ => Err(AppError::unsupported_format("Unsupported file type".to_string())),
+    };
+    with_schema(file_path, None, loaded)
+}
+
+/// The rows to export: `rows` as given or, with `row_indices`, those rows of
+/// the `dataset` itself, whose cells hold the full text even where the
+/// frontend's copy was cut short
+fn export_rows(
+    store: &DatasetStore,
+    dataset: Option<u64>,
+    rows: Vec<Vec<String>>,
+    row_indices: Option<Vec<usize>>,
+) -> Result<Vec<Vec<String>>, String> {
+    let Some(indices) = row_indices else {
+        return Ok(rows);
+    };
+    let handle = dataset.ok_or("Row indices need the dataset they refer to")?;
+    store.with(handle, |ds| {
+        indices
+            .iter()
+            .map(|&i| ds.rows.get(i).cloned().ok_or_else(|| format!("Row {} is out of range", i)))
+            .collect()
+    })?
+}
+
+/// Export data to CSV format, emitting `export-progress` as rows are written.
+/// `verify` reads the file back afterwards; see `verify::finish_export`.
+/// `formats_from` writes values as that dataset's display formats show them,
+/// and `row_indices` picks the rows to write from it instead of `rows`.
+#[tauri::command(async)]
+#[allow(clippy::too_many_arguments)]
+fn export_csv(
+    app: AppHandle,
+    store: State<'_, DatasetStore>,
+    file_path: String,
+    headers: Vec<String>,
+    rows: Vec<Vec<String>>,
+    verify: Option<bool>,
+    formats_from: Option<u64>,
+    row_indices: Option<Vec<usize>>,
+) -> Result<String, AppError> {
+    let _timed = logging::timed("export_csv");
+    access::check(&app, &file_path)?;
+    let rows = export_rows(&store, formats_from, rows, row_indices)?;
+    let rows = display::format_export(&store, formats_from, &headers, rows)?;
     let mut writer = csv::Writer::from_path(&file_path)
         .map_err(|e| format!("Failed to create CSV file: {}", e))?;
     
     writer.write_record(&headers)
         .map_err(|e| format!("Failed to write headers: {}", e))?;
     
-    for row in rows {
-        writer.write_record(&row)
+    let mut progress = progress::Progress::new(&app, &file_path, rows.len());
+    for row in &rows {
+        writer.write_record(row)
             .map_err(|e| format!("Failed to write row: {}", e))?;
+        progress.row_written();
     }
     
     writer.flush()
         .map_err(|e| format!("Failed to save file: {}", e))?;
+    drop(writer);
     
-    Ok(format!("Successfully exported to {}", file_path))
+    verify::finish_export(&app, verify, &file_path, "csv", &headers, &rows, progress.finish())
 }
 
-/// Export data to JSON format (array of objects)
-#[tauri::command]
-fn export_json(file_path: String, headers: Vec<String>, rows: Vec<Vec<String>>) -> Result<String, String> {
-    let mut json_array = Vec::new();
+/// Export data to JSON format (array of objects), emitting `export-progress`
+/// as rows are written. `verify` and `formats_from` work as for `export_csv`.
+#[tauri::command(async)]
+#[allow(clippy::too_many_arguments)]
+fn export_json(
+    app: AppHandle,
+    store: State<'_, DatasetStore>,
+    file_path: String,
+    headers: Vec<String>,
+    rows: Vec<Vec<String>>,
+    verify: Option<bool>,
+    formats_from: Option<u64>,
+    row_indices: Option<Vec<usize>>,
+) -> Result<String, AppError> {
+    let _timed = logging::timed("export_json");
+    access::check(&app, &file_path)?;
+    let rows = export_rows(&store, formats_from, rows, row_indices)?;
+    let rows = display::format_export(&store, formats_from, &headers, rows)?;
+    let file = fs::File::create(&file_path)
+        .map_err(|e| format!("Failed to write file: {}", e))?;
+    let mut writer = std::io::BufWriter::new(file);
+    let write_error = |e: std::io::Error| format!("Failed to write file: {}", e);
     
-    for row in rows {
+    // Written an object at a time, laid out as serde_json's pretty printer
+    // would lay out the whole array
+    let mut progress = progress::Progress::new(&app, &file_path, rows.len());
+    writer.write_all(if rows.is_empty() { b"[]" } else { b"[\n" }).map_err(write_error)?;
+    for (n, row) in rows.iter().enumerate() {
         let mut obj = serde_json::Map::new();
         for (i, header) in headers.iter().enumerate() {
             if let Some(value) = row.get(i) {
                 obj.insert(header.clone(), serde_json::Value::String(value.clone()));
             }
         }
-        json_array.push(serde_json::Value::Object(obj));
+        let json_string = serde_json::to_string_pretty(&obj)
+            .map_err(|e| format!("Failed to serialize JSON: {}", e))?;
+        let indented = json_string.replace('\n', "\n  ");
+        let separator = if n + 1 < rows.len() { ",\n" } else { "\n]" };
+        write!(writer, "  {}{}", indented, separator).map_err(write_error)?;
+        progress.row_written();
     }
     
-    let json_string = serde_json::to_string_pretty(&json_array)
-        .map_err(|e| format!("Failed to serialize JSON: {}", e))?;
+    writer.flush().map_err(write_error)?;
+    drop(writer);
     
-    fs::write(&file_path, json_string)
-        .map_err(|e| format!("Failed to write file: {}", e))?;
-    
-    Ok(format!("Successfully exported to {}", file_path))
+    verify::finish_export(&app, verify, &file_path, "json", &headers, &rows, progress.finish())
 }
 
 fn main() {
-    tauri::Builder::default()
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let batch = match batch::BatchArgs::parse(&args) {
+        Ok(batch) => batch,
+        Err(e) => {
+            eprintln!("{}", e);
+            std::process::exit(2);
+        }
+    };
+    let headless = batch.is_some();
+    let stdin_format = match stdin::parse_args(&args) {
+        Ok(format) => format,
+        Err(e) => {
+            eprintln!("{}", e);
+            std::process::exit(2);
+        }
+    };
+
+    let mut builder = tauri::Builder::default();
+    // Batch runs must not be handed over to a running window, nor piped
+    // input, which only this process can read
+    if !headless && stdin_format.is_none() {
+        builder = builder.plugin(tauri_plugin_single_instance::init(launch::second_instance));
+    }
+    let app = builder
+        .plugin(tauri_plugin_deep_link::init())
         .plugin(tauri_plugin_dialog::init())
-        .invoke_handler(tauri::generate_handler![
+        .plugin(tauri_plugin_clipboard_manager::init())
+        .manage(DatasetStore::default())
+        .manage(launch::PendingFiles::default())
+        .manage(stdin::StdinDataset::default())
+        .manage(autosave::Autosave::default())
+        .manage(parquet_file::ParquetFiles::default())
+        .manage(operations::Operations::default())
+        .manage(watch::WatchFolder::default())
+        .invoke_handler(logging::commands(tauri::generate_handler![
             parse_csv,
             parse_json,
             parse_jsonl,
             export_csv,
-            export_json
-        ])
-        .run(tauri::generate_context!())
+            export_json,
+            dataset::close_dataset,
+            dataset::show_dataset,
+            dataset::get_dataset,
+            reshape::melt,
+            reshape::transpose,
+            stats::column_stats,
+            stats::value_counts,
+            stats::data_completeness,
+            stats::histogram,
+            stats::percentiles,
+            stats::correlations,
+            stats::distinct_count_column,
+            dedup::find_duplicates,
+            dedup::deduplicate,
+            dedup::find_fuzzy_duplicates,
+            combine::join_datasets,
+            combine::diff_datasets,
+            combine::union_datasets,
+            profile::profile_dataset,
+            filter::filter_rows,
+            views::save_view,
+            views::list_views,
+            views::delete_view,
+            views::apply_view,
+            views::get_view_state,
+            views::open_view,
+            views::update_view,
+            views::list_open_views,
+            views::close_view,
+            views::export_view,
+            history::get_history,
+            history::clear_history,
+            history::rerun,
+            window::window_columns,
+            edit::update_cell,
+            edit::insert_rows,
+            edit::delete_rows,
+            edit::duplicate_rows,
+            columns::add_column,
+            columns::delete_columns,
+            columns::rename_column,
+            columns::reorder_columns,
+            replace::replace,
+            cast::cast_column,
+            journal::undo,
+            journal::redo,
+            journal::take_snapshot,
+            journal::list_snapshots,
+            journal::delete_snapshot,
+            journal::revert_to_snapshot,
+            changes::get_changes,
+            changes::export_changes,
+            split::split_column,
+            merge::merge_columns,
+            normalize::preview_normalize,
+            normalize::normalize_text,
+            date_formats::detect_date_formats,
+            date_formats::normalize_dates,
+            fill::fill_missing,
+            computed::add_computed_column,
+            epoch::detect_epoch_columns,
+            epoch::convert_epoch,
+            decode::decode_column,
+            units::normalize_units,
+            pii::detect_pii,
+            pii::mask_pii,
+            hashing::hash_columns,
+            recode::recode_column,
+            recipe::get_recipe,
+            recipe::save_recipe,
+            recipe::list_recipes,
+            recipe::delete_recipe,
+            recipe::export_recipe,
+            recipe::import_recipe,
+            recipe::apply_recipe,
+            recent::get_recent_files,
+            recent::pin_recent_file,
+            recent::clear_recent_files,
+            session::set_view_state,
+            session::restore_session,
+            settings::get_settings,
+            settings::set_settings,
+            launch::take_pending_files,
+            remote::open_url,
+            cloud::list_objects,
+            cloud::open_object,
+            database::save_connection,
+            database::list_connections,
+            database::delete_connection,
+            database::query_database,
+            database::run_database_sql,
+            api::fetch_api,
+            clipboard::parse_clipboard,
+            stdin::get_stdin_dataset,
+            viewer::open_in_window,
+            autosave::list_autosaves,
+            autosave::recover_autosave,
+            autosave::discard_autosave,
+            fileinfo::get_file_info,
+            checksum::file_checksum,
+            checksum::check_source_changed,
+            validate::validate_schema,
+            validate::validate_rules,
+            validate::list_validators,
+            schema::compare_schemas,
+            integrity::check_references,
+            integrity::find_key_candidates,
+            chart::chart_data,
+            chart::time_series,
+            chart::pareto,
+            kpi::kpi_summary,
+            statements::parse_qif,
+            statements::parse_mt940,
+            bookmarks::annotate_row,
+            bookmarks::get_annotations,
+            bookmarks::tag_rows,
+            bookmarks::list_tags,
+            bookmarks::filter_by_tag,
+            bookmarks::add_tag_column,
+            formatting::set_format_rules,
+            formatting::get_format_rules,
+            paging::get_rows,
+            paging::get_rows_after,
+            paging::go_to_row,
+            paging::get_full_cell,
+            header::detect_header,
+            raw::get_raw_record,
+            quarantine::export_failed_lines,
+            search::search_all,
+            scan::list_file_columns,
+            open::open_files,
+            operations::cancel_operation,
+            operations::list_operations,
+            parquet_file::open_parquet,
+            parquet_file::load_parquet,
+            parquet_file::get_row_groups,
+            parquet_file::close_parquet,
+            cache::clear_result_cache,
+            watch::watch_folder,
+            watch::stop_watching,
+            watch::get_watch_folder,
+            refresh::refresh_dataset,
+            refresh::set_refresh_interval,
+            display::set_display_format,
+            display::get_display_formats,
+            logging::get_recent_logs,
+            save::save_dataset
+        ]))
+        .setup(move |app| {
+            logging::init(app.handle());
+            // The main window is created here rather than from the config so
+            // that batch runs never open one
+            if !headless {
+                let config = app.config().app.windows[0].clone();
+                tauri::WebviewWindowBuilder::from_config(app.handle(), &config)?.build()?;
+
+                // Installed builds register the scheme at install time; this
+                // covers development and portable builds
+                #[cfg(any(windows, target_os = "linux"))]
+                {
+                    use tauri_plugin_deep_link::DeepLinkExt;
+                    app.deep_link().register_all()?;
+                }
+
+                autosave::start(app.handle());
+                watch::resume(app.handle());
+                refresh::start(app.handle());
+            }
+            Ok(())
+        })
+        .build(tauri::generate_context!())
         .expect("error while running tauri application");
+
+    if let Some(batch) = batch {
+        std::process::exit(batch::run(app.handle(), &batch));
+    }
+    if let Some(format) = stdin_format {
+        stdin::start(app.handle(), &format);
+    }
+    let cwd = std::env::current_dir().unwrap_or_default();
+    launch::queue(app.handle(), launch::file_args(&args, &cwd));
+
+    app.run(|handle, event| match event {
+        // macOS delivers file-association opens and deep links as an event rather than argv
+        #[cfg(any(target_os = "macos", target_os = "ios"))]
+        tauri::RunEvent::Opened { urls } => {
+            let urls: Vec<String> = urls.iter().map(|url| url.to_string()).collect();
+            launch::queue(handle, launch::url_args(&urls));
+        }
+        tauri::RunEvent::Exit => {
+            session::save(handle);
+            autosave::clear(handle);
+        }
+        _ => {}
+    });
 }
@@ -0,0 +1,107 @@
+use crate::dataset::{Dataset, DatasetStore};
+use crate::error::AppError;
+use crate::settings;
+use std::fs::File;
+use std::io::Read;
+use std::mem::size_of;
+use tauri::AppHandle;
+
+/// Bytes sampled from the start of a file to estimate its cell count
+const SAMPLE_SIZE: usize = 64 * 1024;
+
+/// Rough bytes a row holds: cell text plus the `String` and `Vec` headers around it
+pub fn row_bytes(row: &[String]) -> u64 {
+    (size_of::<Vec<String>>() + row.iter().map(|c| c.len() + size_of::<String>()).sum::<usize>()) as u64
+}
+
+pub fn dataset_bytes(ds: &Dataset) -> u64 {
+    ds.rows.iter().map(|row| row_bytes(row)).sum()
+}
+
+/// Memory left for a new dataset under the configured limit, after what
+/// the loaded datasets already use; `None` when there is no limit
+pub fn available(app: &AppHandle, store: &DatasetStore) -> Option<u64> {
+    let limit = settings::load(app).memory_limit_mb.checked_mul(1024 * 1024).filter(|&l| l > 0)?;
+    let used: u64 = store
+        .handles()
+        .into_iter()
+        .filter_map(|handle| store.with(handle, dataset_bytes).ok())
+        .sum();
+    Some(limit.saturating_sub(used))
+}
+
+/// In-memory bytes per byte of file, guessed from how many cells a sample
+/// holds. Each cell costs a `String` header on top of its text, and the text
+/// is held once while reading and once as cells.
+fn expansion(sample: &[u8]) -> f64 {
+    if sample.is_empty() {
+        return 2.0;
+    }
+    let separators = sample.iter().filter(|&&b| matches!(b, b',' | b'\t' | b';' | b'\n')).count();
+    2.0 + (separators * size_of::<String>()) as f64 / sample.len() as f64
+}
+
+/// Read a file whole, or when parsing all of it would take more than
+/// `budget` bytes, only as many complete lines as should fit. The flag is
+/// set when the content was cut short.
+pub fn read_within(path: &str, budget: Option<u64>) -> Result<(String, bool), AppError> {
+    let mut file = File::open(path).map_err(|e| AppError::io(format!("Failed to read file: {}", e)))?;
+    let size = file.metadata().map_err(|e| AppError::io(format!("Failed to read file: {}", e)))?.len();
+    let mut content = Vec::new();
+    file.by_ref()
+        .take(SAMPLE_SIZE as u64)
+        .read_to_end(&mut content)
+        .map_err(|e| AppError::io(format!("Failed to read file: {}", e)))?;
+
+    let ratio = expansion(&content);
+    let limit = match budget {
+        Some(budget) if size as f64 * ratio > budget as f64 => (budget as f64 / ratio) as u64,
+        _ => u64::MAX,
+    };
+    file.take(limit.saturating_sub(content.len() as u64))
+        .read_to_end(&mut content)
+        .map_err(|e| AppError::io(format!("Failed to read file: {}", e)))?;
+
+    let truncated = (content.len() as u64) < size;
+    if truncated {
+        // Drop the partial last line
+        let end = content.iter().rposition(|&b| b == b'\n').map_or(0, |i| i + 1);
+        content.truncate(end);
+    }
+    let content = String::from_utf8(content).map_err(|e| AppError::parse(format!("Failed to parse file as UTF-8: {}", e)))?;
+    Ok((content, truncated))
+}
+
+/// Whether a whole JSON document would fit; it can't be read in part. The
+/// parsed `serde_json::Value` tree costs several times the text.
+pub fn check_json(path: &str, budget: Option<u64>) -> Result<(), String> {
+    let Some(budget) = budget else {
+        return Ok(());
+    };
+    let size = std::fs::metadata(path).map_err(|e| format!("Failed to read file: {}", e))?.len();
+    if size.saturating_mul(4) > budget {
+        return Err(format!(
+            "This JSON file needs about {} MB, more than the {} MB left under the memory limit. Convert it to JSONL to open a preview, or raise the limit in settings.",
+            size * 4 / (1024 * 1024),
+            budget / (1024 * 1024)
+        ));
+    }
+    Ok(())
+}
+
+/// Drop rows from the end until the dataset fits the budget, for when the
+/// estimate made before parsing was too low. Returns whether any were dropped.
+pub fn trim_to(ds: &mut Dataset, budget: Option<u64>) -> bool {
+    let Some(budget) = budget else {
+        return false;
+    };
+    let mut total = dataset_bytes(ds);
+    let before = ds.rows.len();
+    while total > budget {
+        let Some(row) = ds.rows.pop() else { break };
+        total -= row_bytes(&row);
+    }
+    // Spans line up with rows, so they go with the rows dropped
+    ds.record_spans.truncate(ds.rows.len());
+    ds.rows.len() < before
+}
@@ -0,0 +1,117 @@
+use crate::dataset::{Dataset, DatasetStore, FileData};
+use crate::history;
+use crate::error::AppError;
+use tauri::{AppHandle, State};
+
+enum Piece {
+    Text(String),
+    Column(usize),
+}
+
+/// Parse a template like `{street}, {city} {zip}`; `{{` and `}}` are literal braces
+fn parse_template(ds: &Dataset, template: &str) -> Result<Vec<Piece>, String> {
+    let mut pieces = Vec::new();
+    let mut text = String::new();
+    let mut chars = template.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '{' if chars.next_if_eq(&'{').is_some() => text.push('{'),
+            '}' if chars.next_if_eq(&'}').is_some() => text.push('}'),
+            '{' => {
+                let mut name = String::new();
+                loop {
+                    match chars.next() {
+                        Some('}') => break,
+                        Some(ch) => name.push(ch),
+                        None => return Err("Unclosed '{' in template".to_string()),
+                    }
+                }
+                if !text.is_empty() {
+                    pieces.push(Piece::Text(std::mem::take(&mut text)));
+                }
+                pieces.push(Piece::Column(ds.column_index(name.trim())?));
+            }
+            '}' => return Err("Unmatched '}' in template".to_string()),
+            _ => text.push(c),
+        }
+    }
+    if !text.is_empty() {
+        pieces.push(Piece::Text(text));
+    }
+    Ok(pieces)
+}
+
+/// Combine columns into a new column `name` using a `{column}` template.
+/// With `drop_sources`, the referenced columns are removed and the new column
+/// takes the place of the first of them; otherwise it's appended.
+#[tauri::command]
+pub fn merge_columns(
+    app: AppHandle,
+    store: State<'_, DatasetStore>,
+    handle: u64,
+    template: String,
+    name: String,
+    drop_sources: Option<bool>,
+) -> Result<FileData, AppError> {
+    let drop_sources = drop_sources.unwrap_or(false);
+    let mut result = store.with(handle, |ds| {
+        let pieces = parse_template(ds, &template)?;
+        let mut sources: Vec<usize> = pieces
+            .iter()
+            .filter_map(|p| match p {
+                Piece::Column(c) => Some(*c),
+                Piece::Text(_) => None,
+            })
+            .collect();
+        if sources.is_empty() {
+            return Err("The template doesn't reference any columns".to_string());
+        }
+        sources.sort_unstable();
+        sources.dedup();
+
+        let keep: Vec<usize> = (0..ds.headers.len())
+            .filter(|c| !drop_sources || sources.binary_search(c).is_err())
+            .collect();
+        let at = if drop_sources { sources[0] } else { keep.len() };
+        let mut headers: Vec<String> = keep.iter().map(|&c| ds.headers[c].clone()).collect();
+        if headers.contains(&name) {
+            return Err(format!("Column already exists: {}", name));
+        }
+        headers.insert(at, name.clone());
+
+        let rows = ds
+            .rows
+            .iter()
+            .map(|row| {
+                let cell = |c: usize| row.get(c).map(|v| v.as_str()).unwrap_or("");
+                let merged: String = pieces
+                    .iter()
+                    .map(|p| match p {
+                        Piece::Text(t) => t.as_str(),
+                        Piece::Column(c) => cell(*c),
+                    })
+                    .collect();
+                let mut out: Vec<String> = keep.iter().map(|&c| cell(c).to_string()).collect();
+                out.insert(at, merged);
+                out
+            })
+            .collect();
+
+        Ok(Dataset {
+            headers,
+            rows,
+            file_name: format!("{} (merged)", ds.file_name),
+            file_type: ds.file_type.clone(),
+            ..Default::default()
+        })
+    })??;
+    history::record_transform(
+        &app,
+        &store,
+        handle,
+        &mut result,
+        "merge_columns",
+        serde_json::json!({ "template": template, "name": name, "drop_sources": drop_sources }),
+    );
+    Ok(store.register(result))
+}
@@ -0,0 +1,142 @@
+use crate::dataset::{Dataset, DatasetStore, FileData};
+use crate::history;
+use crate::error::AppError;
+use serde::Serialize;
+use tauri::{AppHandle, State};
+use unicode_normalization::UnicodeNormalization;
+
+const OPERATIONS: &[&str] = &[
+    "trim",
+    "collapse_whitespace",
+    "remove_invisible",
+    "upper",
+    "lower",
+    "title",
+    "nfc",
+    "nfkc",
+];
+
+#[derive(Debug, Serialize)]
+pub struct NormalizePreview {
+    pub column: String,
+    /// Cells whose value would change
+    pub changed: usize,
+}
+
+/// Zero-width characters that are easy to paste in and impossible to see
+fn is_invisible(c: char) -> bool {
+    matches!(c, '\u{200B}'..='\u{200D}' | '\u{2060}' | '\u{FEFF}' | '\u{00AD}')
+}
+
+fn title_case(value: &str) -> String {
+    let mut out = String::with_capacity(value.len());
+    let mut start_of_word = true;
+    for c in value.chars() {
+        if start_of_word {
+            out.extend(c.to_uppercase());
+        } else {
+            out.extend(c.to_lowercase());
+        }
+        start_of_word = !(c.is_alphanumeric() || c == '\'');
+    }
+    out
+}
+
+fn apply(value: &str, operation: &str) -> String {
+    match operation {
+        "trim" => value.trim().to_string(),
+        "collapse_whitespace" => value.split_whitespace().collect::<Vec<_>>().join(" "),
+        "remove_invisible" => value.chars().filter(|&c| !is_invisible(c)).collect(),
+        "upper" => value.to_uppercase(),
+        "lower" => value.to_lowercase(),
+        "title" => title_case(value),
+        "nfc" => value.nfc().collect(),
+        "nfkc" => value.nfkc().collect(),
+        _ => value.to_string(),
+    }
+}
+
+fn normalize(value: &str, operations: &[String]) -> String {
+    operations
+        .iter()
+        .fold(value.to_string(), |v, op| apply(&v, op))
+}
+
+/// Resolve the target columns (all when empty) and check the operation names
+fn prepare(ds: &Dataset, columns: &[String], operations: &[String]) -> Result<Vec<usize>, String> {
+    if operations.is_empty() {
+        return Err("Choose at least one normalization".to_string());
+    }
+    if let Some(op) = operations.iter().find(|op| !OPERATIONS.contains(&op.as_str())) {
+        return Err(format!("Unknown normalization: {}", op));
+    }
+    if columns.is_empty() {
+        Ok((0..ds.headers.len()).collect())
+    } else {
+        columns.iter().map(|c| ds.column_index(c)).collect()
+    }
+}
+
+/// Count the cells per column that `normalize_text` would change
+#[tauri::command]
+pub fn preview_normalize(
+    store: State<'_, DatasetStore>,
+    handle: u64,
+    columns: Vec<String>,
+    operations: Vec<String>,
+) -> Result<Vec<NormalizePreview>, AppError> {
+    store.with(handle, |ds| {
+        let cols = prepare(ds, &columns, &operations)?;
+        Ok(cols
+            .into_iter()
+            .map(|c| NormalizePreview {
+                column: ds.headers[c].clone(),
+                changed: ds
+                    .rows
+                    .iter()
+                    .filter_map(|row| row.get(c))
+                    .filter(|v| normalize(v, &operations) != **v)
+                    .count(),
+            })
+            .collect())
+    })?
+}
+
+/// Apply whitespace, case, and Unicode normalizations to the chosen columns
+/// (every column when none are given), in the order listed
+#[tauri::command]
+pub fn normalize_text(
+    app: AppHandle,
+    store: State<'_, DatasetStore>,
+    handle: u64,
+    columns: Vec<String>,
+    operations: Vec<String>,
+) -> Result<FileData, AppError> {
+    let mut result = store.with(handle, |ds| {
+        let cols = prepare(ds, &columns, &operations)?;
+        let mut rows = ds.rows.clone();
+        for row in &mut rows {
+            for &c in &cols {
+                if let Some(cell) = row.get_mut(c) {
+                    *cell = normalize(cell, &operations);
+                }
+            }
+        }
+        Ok::<_, String>(Dataset {
+            headers: ds.headers.clone(),
+            rows,
+            file_name: format!("{} (normalized)", ds.file_name),
+            file_type: ds.file_type.clone(),
+            ..Default::default()
+        })
+    })??;
+    history::record_transform(
+        &app,
+        &store,
+        handle,
+        &mut result,
+        "normalize_text",
+        serde_json::json!({ "columns": columns, "operations": operations }),
+    );
+    Ok(store.register(result))
+}
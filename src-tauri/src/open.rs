@@ -0,0 +1,109 @@
+use crate::dataset::DatasetStore;
+use crate::error::{AppError, ErrorPayload};
+use crate::{access, logging, memory, recent};
+use serde::Serialize;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use tauri::{AppHandle, Emitter, State};
+
+/// Event emitted as each file of `open_files` finishes loading
+pub const FILE_OPENED_EVENT: &str = "file-opened";
+/// Files parsed at the same time, at most
+const MAX_PARALLEL: usize = 4;
+
+#[derive(Debug, Clone, Serialize)]
+pub struct OpenedFile {
+    pub path: String,
+    /// Handle of the loaded dataset; `None` if the file couldn't be opened
+    pub handle: Option<u64>,
+    pub file_name: String,
+    pub row_count: usize,
+    pub error: Option<ErrorPayload>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct FileOpened {
+    #[serde(flatten)]
+    pub file: OpenedFile,
+    /// Files finished so far, including this one
+    pub completed: usize,
+    pub total: usize,
+}
+
+/// Load one file into the store, recording it as recently opened. With
+/// `parallel` files loading at once, each may use that share of the memory
+/// left under the limit.
+pub fn open_one(app: &AppHandle, store: &DatasetStore, path: &str, parallel: usize) -> OpenedFile {
+    let budget = memory::available(app, store).map(|bytes| bytes / parallel.max(1) as u64);
+    let loaded = access::check(app, path).and_then(|_| crate::load_file_within(path, budget));
+    match loaded {
+        Ok(dataset) => {
+            let data = store.register(dataset);
+            recent::record(app, path, &data);
+            OpenedFile {
+                path: path.to_string(),
+                handle: Some(data.handle),
+                file_name: data.file_name,
+                row_count: data.row_count,
+                error: None,
+            }
+        }
+        Err(e) => {
+            // Returned as data rather than as the command's error, so logged here
+            tracing::warn!(code = e.code(), path, "{}", e);
+            OpenedFile {
+                path: path.to_string(),
+                handle: None,
+                file_name: String::new(),
+                row_count: 0,
+                error: Some(e.with_path(path).into()),
+            }
+        }
+    }
+}
+
+/// Load several files at once, a few in parallel, emitting `file-opened` as
+/// each one finishes so the UI can show them as they arrive. Files that fail
+/// don't stop the others; their errors are returned alongside the handles.
+#[tauri::command(async)]
+pub fn open_files(app: AppHandle, store: State<'_, DatasetStore>, paths: Vec<String>) -> Result<Vec<OpenedFile>, AppError> {
+    let _timed = logging::timed("open_files");
+    let store = store.inner();
+    let workers = std::thread::available_parallelism()
+        .map_or(1, |n| n.get())
+        .min(MAX_PARALLEL)
+        .min(paths.len());
+    let next = AtomicUsize::new(0);
+    let completed = AtomicUsize::new(0);
+    let mut opened: Vec<(usize, OpenedFile)> = std::thread::scope(|scope| {
+        let workers: Vec<_> = (0..workers)
+            .map(|_| {
+                scope.spawn(|| {
+                    let mut opened = Vec::new();
+                    loop {
+                        let i = next.fetch_add(1, Ordering::Relaxed);
+                        let Some(path) = paths.get(i) else {
+                            return opened;
+                        };
+                        let file = open_one(&app, store, path, workers);
+                        let _ = app.emit(
+                            FILE_OPENED_EVENT,
+                            FileOpened {
+                                file: file.clone(),
+                                completed: completed.fetch_add(1, Ordering::Relaxed) + 1,
+                                total: paths.len(),
+                            },
+                        );
+                        opened.push((i, file));
+                    }
+                })
+            })
+            .collect();
+        workers
+            .into_iter()
+            .flat_map(|worker| worker.join().unwrap_or_default())
+            .collect()
+    });
+    // Back in the order the paths were given
+    opened.sort_by_key(|(i, _)| *i);
+    Ok(opened.into_iter().map(|(_, file)| file).collect())
+}
@@ -0,0 +1,170 @@
+use crate::error::AppError;
+use crate::settings;
+use serde::Serialize;
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, State};
+
+/// Rows or steps between checks for cancellation
+const CHECK_INTERVAL: usize = 4096;
+/// How often a watcher looks for a stop it has to pass on
+const WATCH_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Why an operation stopped before finishing
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Interrupted {
+    Cancelled,
+    TimedOut,
+}
+
+impl Interrupted {
+    pub fn message(self) -> String {
+        match self {
+            Interrupted::Cancelled => "Operation cancelled".to_string(),
+            Interrupted::TimedOut => "Operation timed out".to_string(),
+        }
+    }
+}
+
+#[derive(Default)]
+struct Flags {
+    cancelled: AtomicBool,
+    finished: AtomicBool,
+}
+
+/// A filter, sort, query, or aggregation in progress. Long loops check it
+/// as they go and stop early once it's cancelled or past its deadline.
+#[derive(Clone)]
+pub struct Operation {
+    pub id: String,
+    flags: Arc<Flags>,
+    deadline: Option<Instant>,
+}
+
+impl Operation {
+    /// An operation nobody can cancel and that never times out, for callers
+    /// without an ID
+    pub fn unbounded() -> Operation {
+        Operation {
+            id: String::new(),
+            flags: Arc::default(),
+            deadline: None,
+        }
+    }
+
+    pub fn interrupted(&self) -> Option<Interrupted> {
+        if self.flags.cancelled.load(Ordering::Relaxed) {
+            Some(Interrupted::Cancelled)
+        } else if self.deadline.is_some_and(|deadline| Instant::now() >= deadline) {
+            Some(Interrupted::TimedOut)
+        } else {
+            None
+        }
+    }
+
+    /// Fail once the operation is interrupted. Cheap enough to call for
+    /// every row `i`: it only looks every few thousand.
+    pub fn check(&self, i: usize) -> Result<(), String> {
+        if !i.is_multiple_of(CHECK_INTERVAL) {
+            return Ok(());
+        }
+        self.check_now()
+    }
+
+    /// Fail once the operation is interrupted, looking right away; for
+    /// checks between steps that each take a while, such as a sort
+    pub fn check_now(&self) -> Result<(), String> {
+        self.interrupted().map_or(Ok(()), |reason| Err(reason.message()))
+    }
+
+    /// Run `interrupt` on a watcher thread if the operation is interrupted
+    /// before it finishes, for work that can't check by itself, such as a
+    /// database waiting on a query
+    pub fn on_interrupt(&self, interrupt: impl FnOnce() + Send + 'static) {
+        if self.id.is_empty() {
+            return;
+        }
+        let op = self.clone();
+        std::thread::spawn(move || {
+            while !op.flags.finished.load(Ordering::Relaxed) {
+                if op.interrupted().is_some() {
+                    interrupt();
+                    return;
+                }
+                std::thread::sleep(WATCH_INTERVAL);
+            }
+        });
+    }
+}
+
+/// Operations that can be cancelled, by ID
+#[derive(Default)]
+pub struct Operations {
+    running: Mutex<HashMap<String, Operation>>,
+    next_id: AtomicU64,
+}
+
+/// A registered operation; dropping it marks it finished
+pub struct Running<'a> {
+    ops: &'a Operations,
+    op: Operation,
+}
+
+impl std::ops::Deref for Running<'_> {
+    type Target = Operation;
+
+    fn deref(&self) -> &Operation {
+        &self.op
+    }
+}
+
+impl Drop for Running<'_> {
+    fn drop(&mut self) {
+        self.op.flags.finished.store(true, Ordering::Relaxed);
+        self.ops.running.lock().unwrap().remove(&self.op.id);
+    }
+}
+
+impl Operations {
+    /// Register an operation under `id` (or a new one) that times out after
+    /// `timeout_ms`, or the timeout from settings when not given
+    pub fn start(&self, app: &AppHandle, id: Option<String>, timeout_ms: Option<u64>) -> Result<Running<'_>, String> {
+        let id = id.unwrap_or_else(|| format!("op-{}", self.next_id.fetch_add(1, Ordering::Relaxed) + 1));
+        let timeout = match timeout_ms {
+            Some(ms) => Duration::from_millis(ms),
+            None => Duration::from_secs(settings::load(app).operation_timeout_secs),
+        };
+        let op = Operation {
+            id: id.clone(),
+            flags: Arc::default(),
+            deadline: (!timeout.is_zero()).then(|| Instant::now().checked_add(timeout)).flatten(),
+        };
+        let mut running = self.running.lock().unwrap();
+        if running.contains_key(&id) {
+            return Err(format!("An operation with ID {} is already running", id));
+        }
+        running.insert(id, op.clone());
+        Ok(Running { ops: self, op })
+    }
+}
+
+/// Ask a running operation to stop. It stops at its next check, returning
+/// what it has so far where a partial result makes sense.
+#[tauri::command]
+pub fn cancel_operation(ops: State<'_, Operations>, id: String) -> Result<(), AppError> {
+    let running = ops.running.lock().unwrap();
+    let op = running.get(&id).ok_or_else(|| format!("No running operation with ID {}", id))?;
+    op.flags.cancelled.store(true, Ordering::Relaxed);
+    Ok(())
+}
+
+/// IDs of the operations still running
+#[tauri::command]
+pub fn list_operations(ops: State<'_, Operations>) -> Vec<String> {
+    let mut ids: Vec<String> = ops.running.lock().unwrap().keys().cloned().collect();
+    ids.sort();
+    ids
+}
@@ -0,0 +1,435 @@
+use crate::dataset::{Dataset, DatasetStore};
+use crate::display::DisplayFormat;
+use crate::formatting::{self, CellStyle};
+use crate::parquet_file::ParquetFiles;
+use crate::settings;
+use crate::views::{self, ViewColumn};
+use crate::error::AppError;
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use base64::Engine;
+use serde::{Deserialize, Serialize};
+use std::borrow::Cow;
+use std::collections::HashMap;
+use tauri::{AppHandle, State};
+
+/// A cell on a page whose text was cut short; see `get_full_cell`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TruncatedCell {
+    pub row: usize,
+    pub column: usize,
+    /// Length of the full value in characters
+    pub length: usize,
+}
+
+#[derive(Debug, Serialize)]
+pub struct RowPage {
+    pub offset: usize,
+    pub rows: Vec<Vec<String>>,
+    pub total_rows: usize,
+    /// Conditional formatting for the rows on this page; `row` is the index
+    /// in the full dataset
+    pub styles: Vec<CellStyle>,
+    pub truncated: Vec<TruncatedCell>,
+}
+
+/// The cell's text, cut to `max_cell_length` characters (0 for no limit)
+/// with the cut noted in `truncated`
+pub fn truncate(cell: &str, row: usize, column: usize, max_cell_length: usize, truncated: &mut Vec<TruncatedCell>) -> String {
+    match cell.char_indices().nth(max_cell_length) {
+        Some((cut, _)) if max_cell_length > 0 => {
+            truncated.push(TruncatedCell {
+                row,
+                column,
+                length: cell.chars().count(),
+            });
+            cell[..cut].to_string()
+        }
+        _ => cell.to_string(),
+    }
+}
+
+/// Rows `offset..offset + limit` of `order` (indices into the dataset, or
+/// every row in turn when `None`), showing `columns` with their display
+/// formats unless `raw`. Cells longer than `max_cell_length` characters (0
+/// for no limit) are cut short so huge values don't clog the IPC. Rows and
+/// columns in styles and truncated cells are positions on the page's
+/// handle, not in the dataset.
+fn page(
+    ds: &Dataset,
+    order: Option<&[usize]>,
+    columns: &[ViewColumn],
+    offset: usize,
+    limit: usize,
+    max_cell_length: usize,
+    raw: bool,
+) -> RowPage {
+    let total = order.map_or(ds.rows.len(), <[usize]>::len);
+    let start = offset.min(total);
+    let end = start.saturating_add(limit).min(total);
+    let sources: Vec<usize> = (start..end).map(|i| order.map_or(i, |order| order[i])).collect();
+    let formats: Vec<Option<&DisplayFormat>> = columns
+        .iter()
+        .map(|column| match column {
+            ViewColumn::Data(c) if !raw => ds.display_formats.get(&ds.headers[*c]),
+            _ => None,
+        })
+        .collect();
+    let mut truncated = Vec::new();
+    let rows = sources
+        .iter()
+        .zip(start..)
+        .map(|(&source, row)| {
+            columns
+                .iter()
+                .enumerate()
+                .map(|(column, &c)| {
+                    let text = match c {
+                        ViewColumn::Data(c) => {
+                            let cell = ds.rows[source].get(c).map_or("", String::as_str);
+                            formats[column].map_or(Cow::Borrowed(cell), |format| Cow::Owned(format.apply(cell)))
+                        }
+                        ViewColumn::Virtual(v) => Cow::Owned(v.cell(ds, source)),
+                    };
+                    truncate(&text, row, column, max_cell_length, &mut truncated)
+                })
+                .collect()
+        })
+        .collect();
+    let positions: HashMap<usize, usize> = sources.iter().zip(start..).map(|(&source, row)| (source, row)).collect();
+    let styles = formatting::evaluate(ds, sources.iter().copied())
+        .into_iter()
+        .filter_map(|style| {
+            let column = match style.column {
+                // Hidden columns have nothing to style
+                Some(c) => Some(columns.iter().position(|&shown| shown == ViewColumn::Data(c))?),
+                None => None,
+            };
+            Some(CellStyle {
+                row: positions[&style.row],
+                column,
+                ..style
+            })
+        })
+        .collect();
+    RowPage {
+        offset: start,
+        rows,
+        total_rows: total,
+        styles,
+        truncated,
+    }
+}
+
+fn all_columns(ds: &Dataset) -> Vec<ViewColumn> {
+    (0..ds.headers.len()).map(ViewColumn::Data).collect()
+}
+
+/// One page of rows starting at `offset`, `limit` rows long (the page size
+/// from settings by default). `handle` can be a dataset or a view opened
+/// over one with `open_view`, or a Parquet file opened with `open_parquet`,
+/// in which case only the row groups the page falls in are read. Cells show
+/// their column's display format; `raw: true` pages the stored values, as
+/// `get_full_cell` always returns.
+#[tauri::command]
+#[allow(clippy::too_many_arguments)]
+pub fn get_rows(
+    app: AppHandle,
+    store: State<'_, DatasetStore>,
+    files: State<'_, ParquetFiles>,
+    handle: u64,
+    offset: usize,
+    limit: Option<usize>,
+    raw: Option<bool>,
+) -> Result<RowPage, AppError> {
+    let raw = raw.unwrap_or(false);
+    let settings = settings::load(&app);
+    let limit = limit.unwrap_or(settings.page_size);
+    if files.contains(handle) {
+        let (rows, total_rows) = files.read_rows(handle, offset, limit)?;
+        let mut truncated = Vec::new();
+        let rows = rows
+            .iter()
+            .zip(offset..)
+            .map(|(cells, row)| {
+                cells
+                    .iter()
+                    .enumerate()
+                    .map(|(column, cell)| truncate(cell, row, column, settings.max_cell_length, &mut truncated))
+                    .collect()
+            })
+            .collect();
+        return Ok(RowPage {
+            offset: offset.min(total_rows),
+            rows,
+            total_rows,
+            styles: Vec::new(),
+            truncated,
+        });
+    }
+    if store.is_view(handle) {
+        return Ok(store.with_view(handle, |ds, view| {
+            let columns = views::view_columns(ds, &view.config);
+            let order = view.rows(ds)?;
+            Ok::<_, String>(page(ds, Some(order), &columns, offset, limit, settings.max_cell_length, raw))
+        })??);
+    }
+    Ok(store.with(handle, |ds| page(ds, None, &all_columns(ds), offset, limit, settings.max_cell_length, raw))?)
+}
+
+/// Where a `get_rows_after` page ended. Sent to the frontend as an opaque
+/// string so its layout can change.
+#[derive(Debug, Serialize, Deserialize)]
+struct Cursor {
+    handle: u64,
+    /// Journal version when the cursor was made; edits since then make
+    /// `after` unreliable
+    version: u64,
+    /// Position on the handle to continue from
+    position: usize,
+    /// Dataset row shown last, so paging resumes after it even if a view's
+    /// rows shifted as the dataset grew
+    after: Option<usize>,
+}
+
+impl Cursor {
+    fn encode(&self) -> String {
+        URL_SAFE_NO_PAD.encode(serde_json::to_vec(self).unwrap_or_default())
+    }
+
+    fn decode(text: &str, handle: u64) -> Result<Cursor, String> {
+        let cursor: Cursor = URL_SAFE_NO_PAD
+            .decode(text)
+            .ok()
+            .and_then(|bytes| serde_json::from_slice(&bytes).ok())
+            .ok_or("Invalid cursor")?;
+        if cursor.handle != handle {
+            return Err("Invalid cursor: it belongs to another handle".to_string());
+        }
+        Ok(cursor)
+    }
+
+    /// Position to continue from in `order` (every row when `None`)
+    fn resume(&self, version: u64, order: Option<&[usize]>, total: usize) -> usize {
+        let found = match (self.after, order) {
+            (Some(after), Some(order)) if self.version == version => order.iter().position(|&row| row == after),
+            (Some(after), None) if self.version == version && after < total => Some(after),
+            _ => None,
+        };
+        found.map_or(self.position.min(total), |position| position + 1)
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct CursorPage {
+    pub offset: usize,
+    pub rows: Vec<Vec<String>>,
+    pub styles: Vec<CellStyle>,
+    pub truncated: Vec<TruncatedCell>,
+    /// Pass back to `get_rows_after` for the next page
+    pub next_cursor: String,
+    /// More rows follow, or may still arrive while `complete` is false
+    pub has_more: bool,
+    /// Rows in the dataset
+    pub total_rows: usize,
+    /// Rows on the handle after a view's filter; the same as `total_rows`
+    /// for a dataset
+    pub total_filtered: usize,
+    /// Every row has been read; counts won't grow further
+    pub complete: bool,
+}
+
+#[allow(clippy::too_many_arguments)]
+fn cursor_page(
+    handle: u64,
+    ds: &Dataset,
+    order: Option<&[usize]>,
+    columns: &[ViewColumn],
+    cursor: Option<&Cursor>,
+    limit: usize,
+    max_cell_length: usize,
+    raw: bool,
+) -> CursorPage {
+    let version = ds.journal.version();
+    let total = order.map_or(ds.rows.len(), <[usize]>::len);
+    let start = cursor.map_or(0, |cursor| cursor.resume(version, order, total));
+    let page = page(ds, order, columns, start, limit, max_cell_length, raw);
+    let position = page.offset + page.rows.len();
+    let after = position.checked_sub(1).map(|last| order.map_or(last, |order| order[last]));
+    CursorPage {
+        offset: page.offset,
+        rows: page.rows,
+        styles: page.styles,
+        truncated: page.truncated,
+        next_cursor: Cursor {
+            handle,
+            version,
+            position,
+            after,
+        }
+        .encode(),
+        has_more: position < total || ds.loading,
+        total_rows: ds.rows.len(),
+        total_filtered: total,
+        complete: !ds.loading,
+    }
+}
+
+/// The page after `cursor` (or the first page without one), `limit` rows
+/// long. Unlike offsets, cursors stay put while a view's rows shift because
+/// the dataset is still loading, so infinite scroll neither skips nor
+/// repeats rows. Works on the same handles, and takes `raw` the same way,
+/// as `get_rows`.
+#[tauri::command]
+#[allow(clippy::too_many_arguments)]
+pub fn get_rows_after(
+    app: AppHandle,
+    store: State<'_, DatasetStore>,
+    files: State<'_, ParquetFiles>,
+    handle: u64,
+    cursor: Option<String>,
+    limit: Option<usize>,
+    raw: Option<bool>,
+) -> Result<CursorPage, AppError> {
+    let raw = raw.unwrap_or(false);
+    let settings = settings::load(&app);
+    let limit = limit.unwrap_or(settings.page_size);
+    let cursor = cursor.as_deref().map(|text| Cursor::decode(text, handle)).transpose()?;
+    if files.contains(handle) {
+        let start = cursor.as_ref().map_or(0, |cursor| cursor.position);
+        let page = get_rows(app, store, files, handle, start, Some(limit), Some(raw))?;
+        let position = page.offset + page.rows.len();
+        return Ok(CursorPage {
+            offset: page.offset,
+            next_cursor: Cursor {
+                handle,
+                version: 0,
+                position,
+                after: None,
+            }
+            .encode(),
+            has_more: position < page.total_rows,
+            total_rows: page.total_rows,
+            total_filtered: page.total_rows,
+            complete: true,
+            rows: page.rows,
+            styles: page.styles,
+            truncated: page.truncated,
+        });
+    }
+    if store.is_view(handle) {
+        return Ok(store.with_view(handle, |ds, view| {
+            let columns = views::view_columns(ds, &view.config);
+            let order = view.rows(ds)?;
+            Ok::<_, String>(cursor_page(
+                handle,
+                ds,
+                Some(order),
+                &columns,
+                cursor.as_ref(),
+                limit,
+                settings.max_cell_length,
+                raw,
+            ))
+        })??);
+    }
+    Ok(store.with(handle, |ds| {
+        cursor_page(handle, ds, None, &all_columns(ds), cursor.as_ref(), limit, settings.max_cell_length, raw)
+    })?)
+}
+
+fn full_cell(ds: &Dataset, row: usize, column: usize) -> Result<String, String> {
+    let cells = ds.rows.get(row).ok_or_else(|| format!("Row {} is out of range", row))?;
+    if column >= ds.headers.len() {
+        return Err(format!("Column {} is out of range", column));
+    }
+    Ok(cells.get(column).cloned().unwrap_or_default())
+}
+
+/// The whole stored value of a cell, for cells a page cut short or showed
+/// with a display format. For a view, `row` and `column` are positions in
+/// the view.
+#[tauri::command]
+pub fn get_full_cell(
+    store: State<'_, DatasetStore>,
+    files: State<'_, ParquetFiles>,
+    handle: u64,
+    row: usize,
+    column: usize,
+) -> Result<String, AppError> {
+    if files.contains(handle) {
+        let (rows, _) = files.read_rows(handle, row, 1)?;
+        let cells = rows.first().ok_or_else(|| format!("Row {} is out of range", row))?;
+        return Ok(cells.get(column).cloned().ok_or_else(|| format!("Column {} is out of range", column))?);
+    }
+    if store.is_view(handle) {
+        return Ok(store.with_view(handle, |ds, view| {
+            let columns = views::view_columns(ds, &view.config);
+            let column = *columns.get(column).ok_or_else(|| format!("Column {} is out of range", column))?;
+            let row = *view.rows(ds)?.get(row).ok_or_else(|| format!("Row {} is out of range", row))?;
+            match column {
+                ViewColumn::Data(column) => full_cell(ds, row, column),
+                ViewColumn::Virtual(v) => Ok(v.cell(ds, row)),
+            }
+        })??);
+    }
+    Ok(store.with(handle, |ds| full_cell(ds, row, column))??)
+}
+
+#[derive(Debug, Serialize)]
+pub struct RowLocation {
+    pub row: usize,
+    /// 0-based page the row is on
+    pub page_index: usize,
+    /// False when the line asked for holds no record (a blank line or the
+    /// header) and the next row after it was picked instead
+    pub exact: bool,
+    pub page: RowPage,
+}
+
+/// The row holding a 1-based line of the source file. Records spanning
+/// several lines match any of them; other lines go to the next row.
+fn row_for_line(ds: &Dataset, line: u64) -> Result<(usize, bool), String> {
+    if ds.record_spans.len() != ds.rows.len() {
+        return Err("Line numbers aren't available: rows were added or removed, or the dataset didn't come from a file".to_string());
+    }
+    let spans = ds.record_spans.iter().enumerate();
+    if let Some((row, _)) = spans.clone().find(|(_, span)| span.line <= line && line <= span.end_line) {
+        return Ok((row, true));
+    }
+    spans
+        .filter(|(_, span)| span.line > line)
+        .min_by_key(|(_, span)| span.line)
+        .map(|(row, _)| (row, false))
+        .ok_or_else(|| format!("Line {} is past the last record", line))
+}
+
+/// Find a row by its 0-based index or by a line number of the source file
+/// (as quoted in error messages), and return the page it's on so the table
+/// can jump straight there
+#[tauri::command]
+pub fn go_to_row(
+    app: AppHandle,
+    store: State<'_, DatasetStore>,
+    handle: u64,
+    row: Option<usize>,
+    line: Option<u64>,
+    page_size: Option<usize>,
+) -> Result<RowLocation, AppError> {
+    let settings = settings::load(&app);
+    let page_size = page_size.unwrap_or(settings.page_size).max(1);
+    Ok(store.with(handle, |ds| {
+        let (row, exact) = match (row, line) {
+            (Some(row), None) if row < ds.rows.len() => (row, true),
+            (Some(row), None) => return Err(format!("Row {} is out of range", row)),
+            (None, Some(line)) => row_for_line(ds, line)?,
+            _ => return Err("Give either a row or a line number".to_string()),
+        };
+        let page_index = row / page_size;
+        Ok(RowLocation {
+            row,
+            page_index,
+            exact,
+            page: page(ds, None, &all_columns(ds), page_index * page_size, page_size, settings.max_cell_length, false),
+        })
+    })??)
+}
@@ -0,0 +1,226 @@
+use crate::dataset::{DatasetStore, FileData};
+use crate::scan::{self, Scan};
+use crate::{access, logging, memory, recent};
+use crate::error::AppError;
+use parquet::file::reader::{FileReader, SerializedFileReader};
+use parquet::file::statistics::Statistics;
+use parquet::record::Field;
+use serde::Serialize;
+use std::collections::HashMap;
+use std::fs::File;
+use std::sync::Mutex;
+use tauri::{AppHandle, State};
+
+/// Min, max, and null count of one column within a row group, as stored in
+/// the file. Values are the physical ones: dates are day numbers, timestamps
+/// are integers.
+#[derive(Debug, Clone, Serialize)]
+pub struct ColumnStats {
+    pub column: String,
+    pub min: Option<String>,
+    pub max: Option<String>,
+    pub null_count: Option<u64>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct RowGroupInfo {
+    pub index: usize,
+    /// Index of the group's first row in the whole file
+    pub first_row: usize,
+    pub num_rows: usize,
+    pub columns: Vec<ColumnStats>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ParquetInfo {
+    /// Page through the file with `get_rows`
+    pub handle: u64,
+    pub file_name: String,
+    pub headers: Vec<String>,
+    pub total_rows: usize,
+    pub row_groups: Vec<RowGroupInfo>,
+}
+
+/// A Parquet file read from disk a row group at a time rather than loaded
+struct ParquetSource {
+    reader: SerializedFileReader<File>,
+    info: ParquetInfo,
+}
+
+/// Open Parquet files, keyed by handles taken from the dataset store so they
+/// never collide with dataset handles
+#[derive(Default)]
+pub struct ParquetFiles(Mutex<HashMap<u64, ParquetSource>>);
+
+fn stats_text(stats: &Statistics) -> (Option<String>, Option<String>) {
+    match stats {
+        Statistics::Boolean(s) => (s.min_opt().map(bool::to_string), s.max_opt().map(bool::to_string)),
+        Statistics::Int32(s) => (s.min_opt().map(i32::to_string), s.max_opt().map(i32::to_string)),
+        Statistics::Int64(s) => (s.min_opt().map(i64::to_string), s.max_opt().map(i64::to_string)),
+        Statistics::Float(s) => (s.min_opt().map(f32::to_string), s.max_opt().map(f32::to_string)),
+        Statistics::Double(s) => (s.min_opt().map(f64::to_string), s.max_opt().map(f64::to_string)),
+        Statistics::ByteArray(s) => {
+            let text = |v: &parquet::data_type::ByteArray| String::from_utf8_lossy(v.data()).into_owned();
+            (s.min_opt().map(text), s.max_opt().map(text))
+        }
+        Statistics::FixedLenByteArray(s) => {
+            let text = |v: &parquet::data_type::FixedLenByteArray| hex::encode(v.data());
+            (s.min_opt().map(text), s.max_opt().map(text))
+        }
+        // Legacy timestamps, which don't order as text
+        Statistics::Int96(_) => (None, None),
+    }
+}
+
+pub fn cell(field: &Field) -> String {
+    match field {
+        Field::Null => String::new(),
+        Field::Str(s) => s.clone(),
+        Field::Bytes(b) => String::from_utf8_lossy(b.data()).into_owned(),
+        // Lists, maps, and groups are shown as JSON-like text
+        other => other.to_string(),
+    }
+}
+
+fn read_info(reader: &SerializedFileReader<File>, handle: u64, file_path: &str) -> ParquetInfo {
+    let metadata = reader.metadata();
+    let headers = metadata
+        .file_metadata()
+        .schema_descr()
+        .root_schema()
+        .get_fields()
+        .iter()
+        .map(|field| field.name().to_string())
+        .collect();
+    let mut first_row = 0;
+    let row_groups = metadata
+        .row_groups()
+        .iter()
+        .enumerate()
+        .map(|(index, group)| {
+            let columns = group
+                .columns()
+                .iter()
+                .map(|column| {
+                    let (min, max) = column.statistics().map(stats_text).unwrap_or_default();
+                    ColumnStats {
+                        column: column.column_path().string(),
+                        min,
+                        max,
+                        null_count: column.statistics().and_then(|s| s.null_count_opt()),
+                    }
+                })
+                .collect();
+            let info = RowGroupInfo {
+                index,
+                first_row,
+                num_rows: group.num_rows() as usize,
+                columns,
+            };
+            first_row += info.num_rows;
+            info
+        })
+        .collect();
+    ParquetInfo {
+        handle,
+        file_name: std::path::Path::new(file_path)
+            .file_name()
+            .and_then(|n| n.to_str())
+            .unwrap_or("unknown")
+            .to_string(),
+        headers,
+        total_rows: first_row,
+        row_groups,
+    }
+}
+
+/// Open a Parquet file for paging. Only the footer is read here; rows are
+/// decoded a row group at a time as pages ask for them.
+#[tauri::command]
+pub fn open_parquet(
+    app: AppHandle,
+    store: State<'_, DatasetStore>,
+    files: State<'_, ParquetFiles>,
+    file_path: String,
+) -> Result<ParquetInfo, AppError> {
+    access::check(&app, &file_path)?;
+    let file = File::open(&file_path).map_err(|e| AppError::from(format!("Failed to read file: {}", e)).with_path(&file_path))?;
+    let reader = SerializedFileReader::new(file)
+        .map_err(|e| AppError::from(format!("Failed to parse Parquet file: {}", e)).with_path(&file_path))?;
+    let handle = store.reserve_handle();
+    let info = read_info(&reader, handle, &file_path);
+    files.0.lock().unwrap().insert(handle, ParquetSource { reader, info: info.clone() });
+    Ok(info)
+}
+
+/// Load a Parquet file into memory as a dataset that can be filtered, edited,
+/// and exported like any other, keeping only what `scan` asks for. Files too
+/// big for the memory limit load their first rows; `open_parquet` pages
+/// through the whole file instead.
+#[tauri::command(async)]
+pub fn load_parquet(
+    app: AppHandle,
+    store: State<'_, DatasetStore>,
+    file_path: String,
+    scan: Option<Scan>,
+) -> Result<FileData, AppError> {
+    let _timed = logging::timed("load_parquet");
+    access::check(&app, &file_path)?;
+    let budget = memory::available(&app, &store);
+    let dataset = scan::scan_parquet(&file_path, &scan.unwrap_or_default(), budget)
+        .map_err(|e| AppError::from(e).with_path(&file_path))?;
+    let data = store.register(dataset);
+    recent::record(&app, &file_path, &data);
+    Ok(data)
+}
+
+/// Row groups of an open Parquet file with their column statistics, so
+/// filters can skip groups whose min/max rule out a match
+#[tauri::command]
+pub fn get_row_groups(files: State<'_, ParquetFiles>, handle: u64) -> Result<Vec<RowGroupInfo>, AppError> {
+    let files = files.0.lock().unwrap();
+    let source = files.get(&handle).ok_or_else(|| format!("Unknown Parquet handle: {}", handle))?;
+    Ok(source.info.row_groups.clone())
+}
+
+#[tauri::command]
+pub fn close_parquet(files: State<'_, ParquetFiles>, handle: u64) -> Result<(), AppError> {
+    Ok(files
+        .0
+        .lock()
+        .unwrap()
+        .remove(&handle)
+        .map(|_| ())
+        .ok_or_else(|| format!("Unknown Parquet handle: {}", handle))?)
+}
+
+impl ParquetFiles {
+    pub fn contains(&self, handle: u64) -> bool {
+        self.0.lock().unwrap().contains_key(&handle)
+    }
+
+    /// Rows `offset..offset + limit` of an open file and its total row count,
+    /// decoding only the row groups they fall in
+    pub fn read_rows(&self, handle: u64, offset: usize, limit: usize) -> Result<(Vec<Vec<String>>, usize), String> {
+        let files = self.0.lock().unwrap();
+        let source = files.get(&handle).ok_or_else(|| format!("Unknown Parquet handle: {}", handle))?;
+        let total = source.info.total_rows;
+        let end = offset.saturating_add(limit).min(total);
+        let mut rows = Vec::new();
+        for group in &source.info.row_groups {
+            let group_end = group.first_row + group.num_rows;
+            if group_end <= offset || group.first_row >= end {
+                continue;
+            }
+            let skip = offset.saturating_sub(group.first_row);
+            let take = end.min(group_end) - group.first_row - skip;
+            let read_error = |e| format!("Failed to read row group {}: {}", group.index, e);
+            let group_reader = source.reader.get_row_group(group.index).map_err(read_error)?;
+            for row in group_reader.get_row_iter(None).map_err(read_error)?.skip(skip).take(take) {
+                let row = row.map_err(read_error)?;
+                rows.push(row.get_column_iter().map(|(_, field)| cell(field)).collect());
+            }
+        }
+        Ok((rows, total))
+    }
+}
@@ -0,0 +1,199 @@
+use crate::dataset::{Dataset, DatasetStore, FileData};
+use crate::dates;
+use crate::history;
+use crate::error::AppError;
+use regex::{Captures, Regex};
+use serde::Serialize;
+use tauri::{AppHandle, State};
+
+/// Kinds of personal data we can find, most specific first so that a card
+/// number isn't also picked up as a phone number
+const PII_PATTERNS: &[(&str, &str)] = &[
+    ("email", r"[A-Za-z0-9._%+-]+@[A-Za-z0-9.-]+\.[A-Za-z]{2,}"),
+    ("credit_card", r"\b\d(?:[ -]?\d){12,18}\b"),
+    // US SSNs and UK National Insurance numbers
+    ("national_id", r"\b\d{3}-\d{2}-\d{4}\b|\b[A-CEGHJ-PR-TW-Z]{2}\s?\d{2}\s?\d{2}\s?\d{2}\s?[A-D]\b"),
+    ("phone", r"\+?\(?\d[\d\s().-]{6,}\d"),
+];
+
+/// Rows scanned per column when detecting PII
+const DETECT_SAMPLE: usize = 10_000;
+
+fn luhn_valid(digits: &[u32]) -> bool {
+    let sum: u32 = digits
+        .iter()
+        .rev()
+        .enumerate()
+        .map(|(i, &d)| if i % 2 == 1 { if d * 2 > 9 { d * 2 - 9 } else { d * 2 } } else { d })
+        .sum();
+    sum.is_multiple_of(10)
+}
+
+fn digits_of(text: &str) -> Vec<u32> {
+    text.chars().filter_map(|c| c.to_digit(10)).collect()
+}
+
+/// Whether a regex match really is the kind of data it looks like
+fn is_genuine(kind: &str, text: &str) -> bool {
+    match kind {
+        "credit_card" => luhn_valid(&digits_of(text)),
+        // ISO dates have enough digits to pass for a phone number
+        "phone" => (8..=15).contains(&digits_of(text).len()) && dates::parse_datetime(text).is_none(),
+        _ => true,
+    }
+}
+
+/// Replace all but the last `keep` alphanumerics with '*', keeping separators
+fn mask_keep_last(text: &str, keep: usize) -> String {
+    let total = text.chars().filter(|c| c.is_alphanumeric()).count();
+    let mut seen = 0;
+    text.chars()
+        .map(|c| {
+            if !c.is_alphanumeric() {
+                return c;
+            }
+            seen += 1;
+            if seen > total.saturating_sub(keep) { c } else { '*' }
+        })
+        .collect()
+}
+
+fn mask(kind: &str, text: &str, partial: bool) -> String {
+    if !partial {
+        return "[REDACTED]".to_string();
+    }
+    match kind {
+        // j***@example.com
+        "email" => match text.split_once('@') {
+            Some((local, domain)) => {
+                let first: String = local.chars().take(1).collect();
+                format!("{}***@{}", first, domain)
+            }
+            None => mask_keep_last(text, 0),
+        },
+        _ => mask_keep_last(text, 4),
+    }
+}
+
+struct Detector {
+    patterns: Vec<(&'static str, Regex)>,
+}
+
+impl Detector {
+    fn new(kinds: &[String]) -> Result<Self, String> {
+        if let Some(unknown) = kinds.iter().find(|k| !PII_PATTERNS.iter().any(|(p, _)| p == k)) {
+            return Err(format!("Unknown PII type: {}", unknown));
+        }
+        let patterns = PII_PATTERNS
+            .iter()
+            .filter(|(kind, _)| kinds.is_empty() || kinds.iter().any(|k| k == kind))
+            .map(|(kind, pattern)| Regex::new(pattern).map(|re| (*kind, re)))
+            .collect::<Result<_, _>>()
+            .map_err(|e| format!("Invalid regex: {}", e))?;
+        Ok(Detector { patterns })
+    }
+
+    fn mask_cell(&self, cell: &str, partial: bool) -> String {
+        let mut out = cell.to_string();
+        for (kind, regex) in &self.patterns {
+            out = regex
+                .replace_all(&out, |caps: &Captures| {
+                    let text = &caps[0];
+                    if is_genuine(kind, text) { mask(kind, text, partial) } else { text.to_string() }
+                })
+                .into_owned();
+        }
+        out
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct PiiHint {
+    pub column: String,
+    pub kind: String,
+    /// Cells containing this kind of data, among the rows scanned
+    pub count: usize,
+}
+
+/// Scan each column for emails, phone numbers, card numbers, and national IDs
+#[tauri::command]
+pub fn detect_pii(store: State<'_, DatasetStore>, handle: u64) -> Result<Vec<PiiHint>, AppError> {
+    let detector = Detector::new(&[])?;
+    Ok(store.with(handle, |ds| {
+        let mut hints = Vec::new();
+        for (col, column) in ds.headers.iter().enumerate() {
+            for (kind, regex) in &detector.patterns {
+                let count = ds
+                    .rows
+                    .iter()
+                    .take(DETECT_SAMPLE)
+                    .filter_map(|row| row.get(col))
+                    .filter(|cell| regex.find_iter(cell).any(|m| is_genuine(kind, m.as_str())))
+                    .count();
+                if count > 0 {
+                    hints.push(PiiHint {
+                        column: column.clone(),
+                        kind: kind.to_string(),
+                        count,
+                    });
+                }
+            }
+        }
+        hints
+    })?)
+}
+
+/// Mask personal data in the chosen columns. `kinds` limits which of email,
+/// phone, credit_card, and national_id are masked (all when empty); `mode`
+/// is "redact" (default) or "partial", which keeps e.g. the last 4 digits.
+#[tauri::command]
+pub fn mask_pii(
+    app: AppHandle,
+    store: State<'_, DatasetStore>,
+    handle: u64,
+    columns: Vec<String>,
+    kinds: Vec<String>,
+    mode: Option<String>,
+) -> Result<FileData, AppError> {
+    let partial = match mode.as_deref().unwrap_or("redact") {
+        "redact" => false,
+        "partial" => true,
+        other => return Err(format!("Unknown masking mode: {}", other).into()),
+    };
+    let detector = Detector::new(&kinds)?;
+
+    let mut result = store.with(handle, |ds| {
+        let cols = if columns.is_empty() {
+            (0..ds.headers.len()).collect()
+        } else {
+            columns
+                .iter()
+                .map(|c| ds.column_index(c))
+                .collect::<Result<Vec<_>, _>>()?
+        };
+        let mut rows = ds.rows.clone();
+        for row in &mut rows {
+            for &c in &cols {
+                if let Some(cell) = row.get_mut(c) {
+                    *cell = detector.mask_cell(cell, partial);
+                }
+            }
+        }
+        Ok::<_, String>(Dataset {
+            headers: ds.headers.clone(),
+            rows,
+            file_name: format!("{} (masked)", ds.file_name),
+            file_type: ds.file_type.clone(),
+            ..Default::default()
+        })
+    })??;
+    history::record_transform(
+        &app,
+        &store,
+        handle,
+        &mut result,
+        "mask_pii",
+        serde_json::json!({ "columns": columns, "kinds": kinds, "mode": mode }),
+    );
+    Ok(store.register(result))
+}
@@ -0,0 +1,153 @@
+use crate::dataset::DatasetStore;
+use crate::sketch::HyperLogLog;
+use crate::stats::{is_null, parse_number, ValueCount, APPROX_DISTINCT_THRESHOLD, TOP_VALUES_SAMPLE};
+use crate::types::{value_type, ColumnType};
+use crate::error::AppError;
+use serde::Serialize;
+use std::collections::HashMap;
+use tauri::State;
+
+const PROFILE_TOP_VALUES: usize = 5;
+const PROFILE_EXAMPLE_ROWS: usize = 5;
+
+#[derive(Debug, Serialize)]
+pub struct ColumnProfile {
+    pub column: String,
+    /// Inferred from the values, unless a schema override declared the type
+    pub inferred_type: ColumnType,
+    pub null_count: usize,
+    pub null_rate: f64,
+    pub distinct: usize,
+    /// False when `distinct` is estimated and top values come from a sample
+    pub distinct_exact: bool,
+    pub min: Option<String>,
+    pub max: Option<String>,
+    pub top_values: Vec<ValueCount>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct DatasetProfile {
+    pub file_name: String,
+    pub row_count: usize,
+    pub column_count: usize,
+    pub columns: Vec<ColumnProfile>,
+    pub example_rows: Vec<Vec<String>>,
+}
+
+/// Running per-column state for the single profiling pass. On large
+/// datasets distinct values go into a sketch and only every `sample_step`-th
+/// row is counted exactly, keeping memory bounded.
+#[derive(Default)]
+struct ColumnAccumulator<'a> {
+    inferred_type: ColumnType,
+    nulls: usize,
+    counts: HashMap<&'a str, usize>,
+    sketch: Option<HyperLogLog>,
+    sample_step: usize,
+    min_number: Option<f64>,
+    max_number: Option<f64>,
+    min_text: Option<&'a str>,
+    max_text: Option<&'a str>,
+}
+
+impl<'a> ColumnAccumulator<'a> {
+    fn new(row_count: usize) -> Self {
+        let approximate = row_count > APPROX_DISTINCT_THRESHOLD;
+        ColumnAccumulator {
+            sketch: approximate.then(HyperLogLog::default),
+            sample_step: if approximate { row_count / TOP_VALUES_SAMPLE } else { 1 },
+            ..Default::default()
+        }
+    }
+
+    fn add(&mut self, row: usize, value: &'a str) {
+        if is_null(value) {
+            self.nulls += 1;
+            return;
+        }
+        self.inferred_type = self.inferred_type.merge(value_type(value));
+        if let Some(sketch) = &mut self.sketch {
+            sketch.add(value);
+        }
+        if row.is_multiple_of(self.sample_step) {
+            *self.counts.entry(value).or_insert(0) += 1;
+        }
+
+        if let Some(n) = parse_number(value) {
+            self.min_number = Some(self.min_number.map_or(n, |m| m.min(n)));
+            self.max_number = Some(self.max_number.map_or(n, |m| m.max(n)));
+        }
+        if self.min_text.is_none_or(|m| value < m) {
+            self.min_text = Some(value);
+        }
+        if self.max_text.is_none_or(|m| value > m) {
+            self.max_text = Some(value);
+        }
+    }
+
+    fn finish(self, column: String, row_count: usize, declared: Option<ColumnType>) -> ColumnProfile {
+        let inferred_type = declared.unwrap_or(self.inferred_type);
+        let (min, max) = match inferred_type {
+            ColumnType::Integer | ColumnType::Float => (
+                self.min_number.map(|n| n.to_string()),
+                self.max_number.map(|n| n.to_string()),
+            ),
+            _ => (
+                self.min_text.map(|s| s.to_string()),
+                self.max_text.map(|s| s.to_string()),
+            ),
+        };
+
+        let mut top_values: Vec<ValueCount> = self
+            .counts
+            .iter()
+            .map(|(value, &count)| ValueCount {
+                value: value.to_string(),
+                count: count * self.sample_step,
+            })
+            .collect();
+        top_values.sort_by(|a, b| b.count.cmp(&a.count).then_with(|| a.value.cmp(&b.value)));
+        top_values.truncate(PROFILE_TOP_VALUES);
+
+        ColumnProfile {
+            column,
+            inferred_type,
+            null_count: self.nulls,
+            null_rate: if row_count == 0 { 0.0 } else { self.nulls as f64 / row_count as f64 },
+            distinct: self.sketch.as_ref().map_or(self.counts.len(), |s| s.estimate()),
+            distinct_exact: self.sketch.is_none(),
+            min,
+            max,
+            top_values,
+        }
+    }
+}
+
+/// Profile every column of a dataset in a single pass over the rows
+#[tauri::command]
+pub fn profile_dataset(store: State<'_, DatasetStore>, handle: u64) -> Result<DatasetProfile, AppError> {
+    Ok(store.with(handle, |ds| {
+        let mut columns: Vec<ColumnAccumulator> = ds
+            .headers
+            .iter()
+            .map(|_| ColumnAccumulator::new(ds.rows.len()))
+            .collect();
+        for (i, row) in ds.rows.iter().enumerate() {
+            for (col, acc) in columns.iter_mut().enumerate() {
+                acc.add(i, row.get(col).map(|v| v.as_str()).unwrap_or(""));
+            }
+        }
+
+        DatasetProfile {
+            file_name: ds.file_name.clone(),
+            row_count: ds.rows.len(),
+            column_count: ds.headers.len(),
+            columns: columns
+                .into_iter()
+                .zip(&ds.headers)
+                .map(|(acc, header)| acc.finish(header.clone(), ds.rows.len(), ds.column_types.get(header).copied()))
+                .collect(),
+            example_rows: ds.rows.iter().take(PROFILE_EXAMPLE_ROWS).cloned().collect(),
+        }
+    })?)
+}
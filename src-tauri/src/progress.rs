@@ -0,0 +1,100 @@
+use serde::Serialize;
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Emitter};
+
+/// Event emitted as an export writes rows
+pub const EXPORT_PROGRESS_EVENT: &str = "export-progress";
+/// Emit at most this often while rows are written
+const EMIT_INTERVAL: Duration = Duration::from_millis(250);
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ExportProgress {
+    pub file_path: String,
+    pub rows_written: usize,
+    pub total_rows: usize,
+    pub percent: f64,
+    /// Estimated time left, from the rate so far; `None` until a row is written
+    pub eta_ms: Option<u64>,
+    pub done: bool,
+    /// Rows are still being gathered from the dataset; the count starts over
+    /// once they're written
+    pub preparing: bool,
+}
+
+/// Counts the rows an export has written and reports them to the frontend
+pub struct Progress<'a> {
+    app: &'a AppHandle,
+    file_path: &'a str,
+    total_rows: usize,
+    rows_written: usize,
+    started: Instant,
+    last_emit: Instant,
+    preparing: bool,
+}
+
+impl<'a> Progress<'a> {
+    pub fn new(app: &'a AppHandle, file_path: &'a str, total_rows: usize) -> Progress<'a> {
+        let now = Instant::now();
+        Progress {
+            app,
+            file_path,
+            total_rows,
+            rows_written: 0,
+            started: now,
+            last_emit: now,
+            preparing: false,
+        }
+    }
+
+    /// Progress of gathering the rows to export, before any are written
+    pub fn preparing(app: &'a AppHandle, file_path: &'a str, total_rows: usize) -> Progress<'a> {
+        Progress {
+            preparing: true,
+            ..Progress::new(app, file_path, total_rows)
+        }
+    }
+
+    pub fn row_written(&mut self) {
+        self.rows_written += 1;
+        if self.last_emit.elapsed() >= EMIT_INTERVAL {
+            self.last_emit = Instant::now();
+            self.emit(false);
+        }
+    }
+
+    fn emit(&self, done: bool) {
+        let elapsed = self.started.elapsed();
+        let remaining = self.total_rows.saturating_sub(self.rows_written);
+        let eta_ms = (self.rows_written > 0)
+            .then(|| (elapsed.as_secs_f64() * 1000.0 / self.rows_written as f64 * remaining as f64) as u64);
+        let percent = if self.total_rows == 0 {
+            100.0
+        } else {
+            self.rows_written as f64 * 100.0 / self.total_rows as f64
+        };
+        let _ = self.app.emit(
+            EXPORT_PROGRESS_EVENT,
+            ExportProgress {
+                file_path: self.file_path.to_string(),
+                rows_written: self.rows_written,
+                total_rows: self.total_rows,
+                percent,
+                eta_ms,
+                done,
+                preparing: self.preparing,
+            },
+        );
+    }
+
+    /// Emit the final progress event and describe the export, with its
+    /// throughput, for the completion message
+    pub fn finish(self) -> String {
+        self.emit(true);
+        let seconds = self.started.elapsed().as_secs_f64();
+        let rate = if seconds > 0.0 { self.rows_written as f64 / seconds } else { 0.0 };
+        format!(
+            "Exported {} rows to {} in {:.1}s ({:.0} rows/s)",
+            self.rows_written, self.file_path, seconds, rate
+        )
+    }
+}
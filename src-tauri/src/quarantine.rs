@@ -0,0 +1,35 @@
+use crate::access;
+use crate::dataset::DatasetStore;
+use crate::error::AppError;
+use tauri::{AppHandle, State};
+
+/// Write the records a lenient load skipped to a CSV file of line number,
+/// parse error, and the record as it appeared in the source, ready to send
+/// back to whoever produced the file
+#[tauri::command]
+pub fn export_failed_lines(
+    app: AppHandle,
+    store: State<'_, DatasetStore>,
+    handle: u64,
+    file_path: String,
+) -> Result<String, AppError> {
+    access::check(&app, &file_path)?;
+    let failures = store.with(handle, |ds| ds.parse_failures.clone())?;
+    if failures.is_empty() {
+        return Err("No failed lines to export: every record parsed".into());
+    }
+
+    let mut writer = csv::Writer::from_path(&file_path)
+        .map_err(|e| AppError::from(format!("Failed to create CSV file: {}", e)).with_path(&file_path))?;
+    writer
+        .write_record(["line", "error", "record"])
+        .map_err(|e| format!("Failed to write headers: {}", e))?;
+    for failure in &failures {
+        writer
+            .write_record([failure.line.to_string().as_str(), &failure.error, &failure.text])
+            .map_err(|e| format!("Failed to write row: {}", e))?;
+    }
+    writer.flush().map_err(|e| format!("Failed to save file: {}", e))?;
+
+    Ok(format!("Exported {} failed lines to {}", failures.len(), file_path))
+}
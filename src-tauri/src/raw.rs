@@ -0,0 +1,102 @@
+use crate::dataset::{Dataset, DatasetStore, FileStamp, JsonLayout, RecordSpan, SourceFormat};
+use crate::error::AppError;
+use serde::Serialize;
+use serde_json::value::RawValue;
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{Read, Seek, SeekFrom};
+use tauri::State;
+
+#[derive(Debug, Serialize)]
+pub struct RawRecord {
+    pub row: usize,
+    /// 1-based line the record starts on
+    pub line: u64,
+    pub start: u64,
+    pub end: u64,
+    /// The record exactly as it appears in the file
+    pub text: String,
+}
+
+/// 1-based line number of a byte offset, given the offsets of every newline
+fn line_at(newlines: &[usize], offset: usize) -> u64 {
+    newlines.partition_point(|&n| n < offset) as u64 + 1
+}
+
+/// Where each row of a JSON document came from, following the layout
+/// `extract_data_array` found. Empty if the document can't be re-read that way.
+pub fn json_spans(content: &str, ds: &Dataset) -> Vec<RecordSpan> {
+    let Some(SourceFormat::Json { layout, .. }) = &ds.source_format else {
+        return Vec::new();
+    };
+    let records: Option<Vec<&RawValue>> = match layout {
+        JsonLayout::Array => serde_json::from_str(content).ok(),
+        JsonLayout::Wrapped(key) => serde_json::from_str::<HashMap<String, &RawValue>>(content)
+            .ok()
+            .and_then(|map| serde_json::from_str(map.get(key)?.get()).ok()),
+        // Rows are keyed by their "Name" column, which always comes first
+        JsonLayout::Keyed => serde_json::from_str::<HashMap<String, &RawValue>>(content).ok().and_then(|map| {
+            ds.rows
+                .iter()
+                .map(|row| row.first().and_then(|name| map.get(name).copied()))
+                .collect()
+        }),
+        JsonLayout::Single => serde_json::from_str(content).ok().map(|raw| vec![raw]),
+    };
+    let Some(records) = records.filter(|r| r.len() == ds.rows.len()) else {
+        return Vec::new();
+    };
+
+    let newlines: Vec<usize> = content.bytes().enumerate().filter(|&(_, b)| b == b'\n').map(|(i, _)| i).collect();
+    records
+        .into_iter()
+        .map(|raw| {
+            let start = raw.get().as_ptr() as usize - content.as_ptr() as usize;
+            let end = start + raw.get().len();
+            RecordSpan {
+                start: start as u64,
+                end: end as u64,
+                line: line_at(&newlines, start),
+                end_line: line_at(&newlines, end),
+            }
+        })
+        .collect()
+}
+
+/// The text a row was parsed from, straight from the file: the CSV line(s),
+/// JSONL line, or JSON object before it was flattened into columns
+#[tauri::command]
+pub fn get_raw_record(store: State<'_, DatasetStore>, handle: u64, row: usize) -> Result<RawRecord, AppError> {
+    let (path, span, stamp) = store.with(handle, |ds| {
+        if row >= ds.rows.len() {
+            return Err(format!("Row {} is out of range", row));
+        }
+        let path = ds
+            .source_path
+            .clone()
+            .ok_or_else(|| "Raw records are only available for datasets loaded from a file".to_string())?;
+        let span = ds
+            .record_spans
+            .get(row)
+            .copied()
+            .ok_or_else(|| "The raw record isn't available: rows were added or removed, or the file was saved".to_string())?;
+        Ok((path, span, ds.source_stamp.clone()))
+    })??;
+    if FileStamp::read(&path) != stamp {
+        return Err("The file has changed since it was loaded; reload it to see its raw records".into());
+    }
+
+    let mut file = File::open(&path).map_err(|e| format!("Failed to read file: {}", e))?;
+    file.seek(SeekFrom::Start(span.start)).map_err(|e| format!("Failed to read file: {}", e))?;
+    let mut bytes = Vec::new();
+    file.take(span.end - span.start)
+        .read_to_end(&mut bytes)
+        .map_err(|e| format!("Failed to read file: {}", e))?;
+    Ok(RawRecord {
+        row,
+        line: span.line,
+        start: span.start,
+        end: span.end,
+        text: String::from_utf8_lossy(&bytes).into_owned(),
+    })
+}
@@ -0,0 +1,88 @@
+use crate::dataset::FileData;
+use crate::storage;
+use crate::error::AppError;
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+use tauri::AppHandle;
+
+const RECENT_FILES: &str = "recent_files.json";
+/// Unpinned entries kept; pinned ones are never dropped
+const MAX_RECENT: usize = 20;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RecentFile {
+    pub path: String,
+    pub format: String,
+    pub last_opened: String,
+    pub row_count: usize,
+    #[serde(default)]
+    pub pinned: bool,
+    /// Whether the file is still there; filled in when the list is fetched
+    #[serde(default, skip_deserializing)]
+    pub exists: bool,
+}
+
+/// Move a just-opened file to the top of the recent list
+pub fn record(app: &AppHandle, file_path: &str, data: &FileData) {
+    let mut recent: Vec<RecentFile> = storage::load(app, RECENT_FILES);
+    let pinned = recent.iter().any(|r| r.path == file_path && r.pinned);
+    recent.retain(|r| r.path != file_path);
+    recent.insert(
+        0,
+        RecentFile {
+            path: file_path.to_string(),
+            format: data.file_type.clone(),
+            last_opened: chrono::Local::now().to_rfc3339(),
+            row_count: data.row_count,
+            pinned,
+            exists: true,
+        },
+    );
+
+    let mut unpinned = 0;
+    recent.retain(|r| {
+        if !r.pinned {
+            unpinned += 1;
+        }
+        r.pinned || unpinned <= MAX_RECENT
+    });
+    let _ = storage::save(app, RECENT_FILES, &recent);
+}
+
+/// Recent files, pinned first, then most recently opened
+#[tauri::command]
+pub fn get_recent_files(app: AppHandle) -> Vec<RecentFile> {
+    let mut recent: Vec<RecentFile> = storage::load(&app, RECENT_FILES);
+    for r in &mut recent {
+        r.exists = Path::new(&r.path).is_file();
+    }
+    // Stable sort keeps most-recent-first order within each group
+    recent.sort_by_key(|r| !r.pinned);
+    recent
+}
+
+#[tauri::command]
+pub fn pin_recent_file(app: AppHandle, path: String, pinned: bool) -> Result<(), AppError> {
+    let mut recent: Vec<RecentFile> = storage::load(&app, RECENT_FILES);
+    let entry = recent
+        .iter_mut()
+        .find(|r| r.path == path)
+        .ok_or_else(|| format!("Not in recent files: {}", path))?;
+    entry.pinned = pinned;
+    Ok(storage::save(&app, RECENT_FILES, &recent)?)
+}
+
+/// Forget recent files. Pinned entries are kept unless `include_pinned` is set;
+/// with `missing_only`, only files that no longer exist are removed.
+#[tauri::command]
+pub fn clear_recent_files(app: AppHandle, include_pinned: Option<bool>, missing_only: Option<bool>) -> Result<(), AppError> {
+    let include_pinned = include_pinned.unwrap_or(false);
+    let missing_only = missing_only.unwrap_or(false);
+    let mut recent: Vec<RecentFile> = storage::load(&app, RECENT_FILES);
+    recent.retain(|r| {
+        let removable = include_pinned || !r.pinned;
+        let targeted = !missing_only || !Path::new(&r.path).is_file();
+        !(removable && targeted)
+    });
+    Ok(storage::save(&app, RECENT_FILES, &recent)?)
+}
@@ -0,0 +1,129 @@
+use crate::access;
+use crate::dataset::{DatasetStore, FileData};
+use crate::history::{self, RerunResult};
+use crate::storage;
+use crate::error::AppError;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::fs;
+use tauri::{AppHandle, State};
+
+const RECIPES_FILE: &str = "recipes.json";
+
+/// One transform in a recipe: the command name and its arguments, as
+/// recorded in the history. Only transforms that derive a new dataset are
+/// steps; in-place edits, casts, and replaces change the dataset itself and
+/// aren't replayed, and neither is hashing, whose secret isn't kept.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RecipeStep {
+    pub command: String,
+    pub args: Value,
+}
+
+/// A named sequence of transforms that can be replayed on another file
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Recipe {
+    pub name: String,
+    pub steps: Vec<RecipeStep>,
+}
+
+/// Read a recipe previously written with `export_recipe`
+pub fn read_recipe_file(file_path: &str) -> Result<Recipe, String> {
+    let content = fs::read_to_string(file_path).map_err(|e| format!("Failed to read recipe: {}", e))?;
+    serde_json::from_str(&content).map_err(|e| format!("Invalid recipe file: {}", e))
+}
+
+/// Run each step on the output of the one before, returning the final dataset.
+/// Intermediate datasets are closed along the way.
+pub fn apply_steps(
+    app: &AppHandle,
+    store: &State<'_, DatasetStore>,
+    handle: u64,
+    steps: &[RecipeStep],
+) -> Result<FileData, String> {
+    if steps.is_empty() {
+        return Err("The recipe has no steps".to_string());
+    }
+    let mut current: Option<FileData> = None;
+    for (i, step) in steps.iter().enumerate() {
+        let input = current.as_ref().map_or(handle, |data| data.handle);
+        let result = history::run_command(app.clone(), store.clone(), input, &step.command, &step.args, None, None);
+        if input != handle {
+            store.remove(input);
+        }
+        match result {
+            Ok(RerunResult::Dataset(data)) => current = Some(data),
+            Ok(_) => return Err(format!("Step {} ({}) is not a transform", i + 1, step.command)),
+            Err(e) => return Err(format!("Step {} ({}) failed: {}", i + 1, step.command, e)),
+        }
+    }
+    current.ok_or_else(|| "The recipe has no steps".to_string())
+}
+
+/// The transforms that produced a dataset from its source file
+#[tauri::command]
+pub fn get_recipe(store: State<'_, DatasetStore>, handle: u64) -> Result<Vec<RecipeStep>, AppError> {
+    Ok(store.with(handle, |ds| ds.steps.clone())?)
+}
+
+#[tauri::command]
+pub fn save_recipe(app: AppHandle, recipe: Recipe) -> Result<(), AppError> {
+    if recipe.name.trim().is_empty() {
+        return Err("Recipe name cannot be empty".into());
+    }
+    let mut recipes: Vec<Recipe> = storage::load(&app, RECIPES_FILE);
+    recipes.retain(|r| r.name != recipe.name);
+    recipes.push(recipe);
+    Ok(storage::save(&app, RECIPES_FILE, &recipes)?)
+}
+
+#[tauri::command]
+pub fn list_recipes(app: AppHandle) -> Vec<Recipe> {
+    storage::load(&app, RECIPES_FILE)
+}
+
+#[tauri::command]
+pub fn delete_recipe(app: AppHandle, name: String) -> Result<(), AppError> {
+    let mut recipes: Vec<Recipe> = storage::load(&app, RECIPES_FILE);
+    recipes.retain(|r| r.name != name);
+    Ok(storage::save(&app, RECIPES_FILE, &recipes)?)
+}
+
+/// Write a saved recipe to a file so it can be shared or used in batch mode
+#[tauri::command]
+pub fn export_recipe(app: AppHandle, name: String, file_path: String) -> Result<String, AppError> {
+    access::check(&app, &file_path)?;
+    let recipes: Vec<Recipe> = storage::load(&app, RECIPES_FILE);
+    let recipe = recipes
+        .iter()
+        .find(|r| r.name == name)
+        .ok_or_else(|| format!("No recipe named {}", name))?;
+    let json = serde_json::to_string_pretty(recipe).map_err(|e| format!("Failed to serialize recipe: {}", e))?;
+    fs::write(&file_path, json).map_err(|e| format!("Failed to write file: {}", e))?;
+    Ok(format!("Recipe saved to {}", file_path))
+}
+
+/// Load a recipe file and add it to the saved recipes
+#[tauri::command]
+pub fn import_recipe(app: AppHandle, file_path: String) -> Result<Recipe, AppError> {
+    access::check(&app, &file_path)?;
+    let recipe = read_recipe_file(&file_path)?;
+    save_recipe(app, recipe.clone())?;
+    Ok(recipe)
+}
+
+/// Replay a saved recipe on another dataset
+#[tauri::command]
+pub fn apply_recipe(
+    app: AppHandle,
+    store: State<'_, DatasetStore>,
+    handle: u64,
+    name: String,
+) -> Result<FileData, AppError> {
+    let recipes: Vec<Recipe> = storage::load(&app, RECIPES_FILE);
+    let recipe = recipes
+        .into_iter()
+        .find(|r| r.name == name)
+        .ok_or_else(|| format!("No recipe named {}", name))?;
+    Ok(apply_steps(&app, &store, handle, &recipe.steps)?)
+}
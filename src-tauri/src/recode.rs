@@ -0,0 +1,125 @@
+use crate::dataset::{Dataset, DatasetStore, FileData};
+use crate::error::AppError;
+use crate::history;
+use crate::stats::{count_values, is_null, ValueCount};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use tauri::{AppHandle, State};
+
+/// A loaded dataset used as a lookup table
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LookupTable {
+    pub handle: u64,
+    pub key_column: String,
+    pub value_column: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct RecodeResult {
+    pub data: FileData,
+    /// Values with no mapping, most frequent first
+    pub unmapped: Vec<ValueCount>,
+}
+
+fn lookup_mapping(ds: &Dataset, lookup: &LookupTable) -> Result<HashMap<String, String>, String> {
+    let key = ds.column_index(&lookup.key_column)?;
+    let value = ds.column_index(&lookup.value_column)?;
+    let mut mapping = HashMap::new();
+    for row in &ds.rows {
+        let cell = |c: usize| row.get(c).cloned().unwrap_or_default();
+        // The first occurrence of a key wins, as in a spreadsheet VLOOKUP
+        mapping.entry(cell(key)).or_insert_with(|| cell(value));
+    }
+    Ok(mapping)
+}
+
+/// Translate the values of `column` through a mapping, given either as
+/// `pairs` of (from, to) or as a `lookup` dataset. Results go to
+/// `target_column` when given (a new column), otherwise replace the original.
+/// Unmapped values are kept unless `blank_unmapped` is set.
+#[tauri::command]
+#[allow(clippy::too_many_arguments)]
+pub fn recode_column(
+    app: AppHandle,
+    store: State<'_, DatasetStore>,
+    handle: u64,
+    column: String,
+    pairs: Option<Vec<(String, String)>>,
+    lookup: Option<LookupTable>,
+    target_column: Option<String>,
+    blank_unmapped: Option<bool>,
+) -> Result<RecodeResult, AppError> {
+    let mapping: HashMap<String, String> = match (&pairs, &lookup) {
+        (Some(pairs), None) => pairs.iter().cloned().collect(),
+        (None, Some(lookup)) => store.with(lookup.handle, |ds| lookup_mapping(ds, lookup))??,
+        _ => return Err("Provide either mapping pairs or a lookup dataset".into()),
+    };
+    let blank_unmapped = blank_unmapped.unwrap_or(false);
+
+    let (mut result, unmapped) = store.with(handle, |ds| {
+        let col = ds.column_index(&column)?;
+        let mut headers = ds.headers.clone();
+        if let Some(target) = &target_column {
+            if headers.contains(target) {
+                return Err(format!("Column already exists: {}", target));
+            }
+            headers.insert(col + 1, target.clone());
+        }
+
+        let mut unmapped: Vec<&str> = Vec::new();
+        let rows = ds
+            .rows
+            .iter()
+            .map(|row| {
+                let value = row.get(col).map(|v| v.as_str()).unwrap_or("");
+                let recoded = match mapping.get(value) {
+                    Some(to) => to.clone(),
+                    None => {
+                        if !is_null(value) {
+                            unmapped.push(value);
+                        }
+                        if blank_unmapped { String::new() } else { value.to_string() }
+                    }
+                };
+                let mut out = row.clone();
+                out.resize(ds.headers.len(), String::new());
+                if target_column.is_some() {
+                    out.insert(col + 1, recoded);
+                } else {
+                    out[col] = recoded;
+                }
+                out
+            })
+            .collect();
+
+        Ok((
+            Dataset {
+                headers,
+                rows,
+                file_name: format!("{} (recoded)", ds.file_name),
+                file_type: ds.file_type.clone(),
+                ..Default::default()
+            },
+            count_values(&unmapped),
+        ))
+    })??;
+    history::record_transform(
+        &app,
+        &store,
+        handle,
+        &mut result,
+        "recode_column",
+        serde_json::json!({
+            "column": column,
+            "pairs": pairs,
+            "lookup": lookup,
+            "target_column": target_column,
+            "blank_unmapped": blank_unmapped,
+        }),
+    );
+
+    Ok(RecodeResult {
+        data: store.register(result),
+        unmapped,
+    })
+}
@@ -0,0 +1,168 @@
+use crate::api::{self, ApiRequest};
+use crate::dataset::{Dataset, DatasetStore, FileData};
+use crate::error::AppError;
+use crate::operations::Operation;
+use crate::{cloud, database, logging, remote};
+use serde::Serialize;
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Emitter, Manager, State};
+
+/// Event emitted after each refresh, scheduled or not
+pub const DATASET_REFRESHED_EVENT: &str = "dataset-refreshed";
+/// How often the scheduler looks for datasets due a refresh
+const TICK: Duration = Duration::from_secs(1);
+/// Shortest refresh interval accepted, so a source isn't hammered
+const MIN_INTERVAL_SECS: u64 = 10;
+
+/// Where a dataset was fetched from, with enough to fetch it again
+#[derive(Debug, Clone)]
+pub enum RemoteSource {
+    Url { url: String, auth_header: Option<String> },
+    Api { request: ApiRequest },
+    Database { connection: String, sql: String, row_limit: usize },
+    Sqlite { path: String, query: String, row_limit: usize },
+    Cloud { url: String },
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct DatasetRefreshed {
+    pub handle: u64,
+    pub row_count: usize,
+    /// Why the refresh failed; the dataset keeps its old rows
+    pub error: Option<String>,
+}
+
+async fn fetch(app: &AppHandle, source: &RemoteSource) -> Result<Dataset, AppError> {
+    if let RemoteSource::Cloud { url } = source {
+        return cloud::fetch_object(app, url).await;
+    }
+    let (app, source) = (app.clone(), source.clone());
+    tauri::async_runtime::spawn_blocking(move || match &source {
+        RemoteSource::Url { url, auth_header } => remote::fetch_url(&app, url, auth_header.as_deref()),
+        RemoteSource::Api { request } => api::fetch_records(request),
+        RemoteSource::Database { connection, sql, row_limit } => {
+            database::run_query(&app, connection, sql, *row_limit, &Operation::unbounded())
+        }
+        RemoteSource::Sqlite { path, query, row_limit } => {
+            database::run_sqlite(path, query, *row_limit, &Operation::unbounded())
+        }
+        RemoteSource::Cloud { .. } => unreachable!(),
+    })
+    .await
+    .map_err(|e| format!("Refresh failed: {}", e))?
+}
+
+/// Per-column settings after a refresh: the ones the fetch reported, plus
+/// earlier ones for columns that are still there
+fn reconcile<T>(settings: &mut HashMap<String, T>, fresh: HashMap<String, T>, headers: &[String]) {
+    settings.retain(|name, _| headers.contains(name));
+    settings.extend(fresh);
+}
+
+/// Fetch a dataset's source again and swap in the new rows. Datasets with
+/// unsaved edits are left alone rather than have the edits thrown away.
+async fn refresh(app: &AppHandle, handle: u64) -> Result<FileData, AppError> {
+    let store = app.state::<DatasetStore>();
+    let source = store.with(handle, |ds| {
+        if ds.dirty {
+            return Err("The dataset has unsaved edits; save or undo them before refreshing".to_string());
+        }
+        ds.remote.clone().ok_or_else(|| "The dataset wasn't fetched from a URL, API, or database".to_string())
+    })??;
+    let fetched = fetch(app, &source).await;
+    store.with_mut(handle, |ds| {
+        ds.last_refresh = Some(Instant::now());
+        let fresh = fetched?;
+        if ds.dirty {
+            return Err(AppError::from("The dataset was edited while it refreshed; the new rows were dropped"));
+        }
+        reconcile(&mut ds.column_types, fresh.column_types, &fresh.headers);
+        reconcile(&mut ds.units, fresh.units, &fresh.headers);
+        reconcile(&mut ds.display_formats, fresh.display_formats, &fresh.headers);
+        ds.headers = fresh.headers;
+        ds.rows = fresh.rows;
+        ds.file_type = fresh.file_type;
+        ds.source_format = fresh.source_format;
+        ds.record_spans = fresh.record_spans;
+        ds.parse_failures = fresh.parse_failures;
+        ds.preview = fresh.preview;
+        ds.journal.reset();
+        ds.baseline = None;
+        Ok(ds.to_file_data(handle))
+    })?
+}
+
+/// Refresh a dataset and tell the frontend how it went
+async fn refresh_and_notify(app: &AppHandle, handle: u64) -> Result<FileData, AppError> {
+    let result = refresh(app, handle).await;
+    let event = match &result {
+        Ok(data) => DatasetRefreshed {
+            handle,
+            row_count: data.row_count,
+            error: None,
+        },
+        Err(e) => DatasetRefreshed {
+            handle,
+            row_count: app.state::<DatasetStore>().with(handle, |ds| ds.rows.len()).unwrap_or(0),
+            error: Some(e.info().message.clone()),
+        },
+    };
+    let _ = app.emit(DATASET_REFRESHED_EVENT, event);
+    result
+}
+
+/// Handles of datasets whose refresh interval has passed
+fn due(store: &DatasetStore) -> Vec<u64> {
+    store
+        .handles()
+        .into_iter()
+        .filter(|&handle| {
+            store
+                .with(handle, |ds| match (ds.refresh_interval, ds.last_refresh) {
+                    (Some(interval), Some(last)) => ds.remote.is_some() && last.elapsed() >= interval,
+                    _ => false,
+                })
+                .unwrap_or(false)
+        })
+        .collect()
+}
+
+/// Refresh datasets in the background as their intervals come round
+pub fn start(app: &AppHandle) {
+    let app = app.clone();
+    std::thread::spawn(move || loop {
+        std::thread::sleep(TICK);
+        for handle in due(&app.state::<DatasetStore>()) {
+            if let Err(e) = tauri::async_runtime::block_on(refresh_and_notify(&app, handle)) {
+                tracing::warn!("Scheduled refresh of dataset {} failed: {}", handle, e.info().message);
+            }
+        }
+    });
+}
+
+/// Fetch a dataset opened from a URL, API, cloud object, or database query
+/// again, replacing its rows. Undo history is cleared; `dataset-refreshed`
+/// is emitted whether or not it succeeds.
+#[tauri::command]
+pub async fn refresh_dataset(app: AppHandle, handle: u64) -> Result<FileData, AppError> {
+    let _timed = logging::timed("refresh_dataset");
+    refresh_and_notify(&app, handle).await
+}
+
+/// Refresh a dataset every `seconds` from now on, or stop with `None` or 0
+#[tauri::command]
+pub fn set_refresh_interval(store: State<'_, DatasetStore>, handle: u64, seconds: Option<u64>) -> Result<(), AppError> {
+    let seconds = seconds.filter(|&s| s > 0);
+    if seconds.is_some_and(|s| s < MIN_INTERVAL_SECS) {
+        return Err(format!("The refresh interval must be at least {} seconds", MIN_INTERVAL_SECS).into());
+    }
+    Ok(store.with_mut(handle, |ds| {
+        if seconds.is_some() && ds.remote.is_none() {
+            return Err("The dataset wasn't fetched from a URL, API, or database".to_string());
+        }
+        ds.refresh_interval = seconds.map(Duration::from_secs);
+        ds.last_refresh = Some(Instant::now());
+        Ok(())
+    })??)
+}
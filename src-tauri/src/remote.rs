@@ -0,0 +1,128 @@
+use crate::dataset::{Dataset, DatasetStore, FileData};
+use crate::error::AppError;
+use crate::logging;
+use crate::refresh::RemoteSource;
+use serde::Serialize;
+use std::fs::File;
+use std::io::{Read, Write};
+use std::path::{Path, PathBuf};
+use tauri::{AppHandle, Emitter, Manager, State};
+
+/// Event emitted while a remote file downloads
+pub const DOWNLOAD_PROGRESS_EVENT: &str = "download-progress";
+/// Emit progress at most once per this many bytes
+const PROGRESS_STEP: u64 = 256 * 1024;
+
+#[derive(Debug, Clone, Serialize)]
+pub struct DownloadProgress {
+    pub url: String,
+    pub downloaded: u64,
+    /// From Content-Length, when the server sent one
+    pub total: Option<u64>,
+}
+
+/// File name at the end of a URL's path, ignoring any query string
+/// (presigned links carry their signature there)
+pub fn url_file_name(url: &str) -> Option<String> {
+    let path = url.split(['?', '#']).next()?;
+    let name = path.rsplit('/').next()?;
+    let decoded = percent_encoding::percent_decode_str(name).decode_utf8_lossy().into_owned();
+    (!decoded.is_empty()).then_some(decoded)
+}
+
+/// Pick the parser from the URL's extension, falling back to the Content-Type
+pub fn file_extension(name: Option<&str>, content_type: &str) -> Result<&'static str, AppError> {
+    let ext = name
+        .and_then(|n| n.rsplit_once('.'))
+        .map(|(_, ext)| ext.to_ascii_lowercase())
+        .unwrap_or_default();
+    let content_type = content_type.split(';').next().unwrap_or("").trim();
+    match (ext.as_str(), content_type) {
+        ("csv", _) | (_, "text/csv") => Ok("csv"),
+        ("jsonl" | "ndjson", _) | (_, "application/jsonl" | "application/x-ndjson") => Ok("jsonl"),
+        ("json", _) | (_, "application/json") => Ok("json"),
+        ("parquet", _) | (_, "application/vnd.apache.parquet") => Ok("parquet"),
+        _ => Err(AppError::unsupported_format(format!("Can't tell the file type of {}", name.unwrap_or("the download")))),
+    }
+}
+
+/// A fresh temp file to download into
+pub fn temp_file(app: &AppHandle, ext: &str) -> Result<PathBuf, String> {
+    let dir = app
+        .path()
+        .temp_dir()
+        .map_err(|e| format!("Failed to locate temp directory: {}", e))?;
+    Ok(dir.join(format!("qdv-download-{}.{}", chrono::Local::now().timestamp_millis(), ext)))
+}
+
+/// Parse a downloaded file, then delete it, marking the dataset as coming
+/// from `origin`. Edits can't be saved back to it; transforms still trace back.
+pub fn load_download(temp: &Path, origin: &str, name: Option<String>) -> Result<Dataset, AppError> {
+    let loaded = crate::load_file(&temp.to_string_lossy());
+    let _ = std::fs::remove_file(temp);
+    let mut dataset = loaded?;
+    dataset.source_path = None;
+    dataset.origin = Some(origin.to_string());
+    dataset.file_name = name.unwrap_or_else(|| origin.to_string());
+    Ok(dataset)
+}
+
+/// Stream a response body to `path`, reporting progress as it goes
+fn download_to(app: &AppHandle, url: &str, response: ureq::Response, path: &Path) -> Result<(), String> {
+    let total = response.header("Content-Length").and_then(|v| v.parse().ok());
+    let mut reader = response.into_reader();
+    let mut file = File::create(path).map_err(|e| format!("Failed to create temp file: {}", e))?;
+    let mut buffer = vec![0u8; 64 * 1024];
+    let mut downloaded = 0u64;
+    let mut reported = 0u64;
+    loop {
+        let n = reader
+            .read(&mut buffer)
+            .map_err(|e| format!("Download failed: {}", e))?;
+        if n == 0 {
+            break;
+        }
+        file.write_all(&buffer[..n])
+            .map_err(|e| format!("Failed to write temp file: {}", e))?;
+        downloaded += n as u64;
+        if downloaded - reported >= PROGRESS_STEP {
+            reported = downloaded;
+            let _ = app.emit(DOWNLOAD_PROGRESS_EVENT, DownloadProgress { url: url.to_string(), downloaded, total });
+        }
+    }
+    let _ = app.emit(DOWNLOAD_PROGRESS_EVENT, DownloadProgress { url: url.to_string(), downloaded, total });
+    Ok(())
+}
+
+/// Download a file over HTTP(S) and parse it
+pub fn fetch_url(app: &AppHandle, url: &str, auth_header: Option<&str>) -> Result<Dataset, AppError> {
+    if !url.starts_with("http://") && !url.starts_with("https://") {
+        return Err("Only http:// and https:// URLs can be opened".into());
+    }
+    let mut request = ureq::get(url);
+    if let Some(auth) = auth_header.filter(|a| !a.trim().is_empty()) {
+        request = request.set("Authorization", auth);
+    }
+    let response = request.call().map_err(|e| format!("Failed to fetch {}: {}", url, e))?;
+
+    let name = url_file_name(url);
+    let ext = file_extension(name.as_deref(), response.content_type())?;
+    let temp = temp_file(app, ext)?;
+    download_to(app, url, response, &temp)?;
+    load_download(&temp, url, name)
+}
+
+/// Download a CSV/JSON/JSONL/Parquet file over HTTP(S) to a temp file and open it.
+/// Redirects are followed; `auth_header` is sent as the Authorization header.
+#[tauri::command(async)]
+pub fn open_url(
+    app: AppHandle,
+    store: State<'_, DatasetStore>,
+    url: String,
+    auth_header: Option<String>,
+) -> Result<FileData, AppError> {
+    let _timed = logging::timed("open_url");
+    let mut dataset = fetch_url(&app, &url, auth_header.as_deref())?;
+    dataset.remote = Some(RemoteSource::Url { url, auth_header });
+    Ok(store.register(dataset))
+}
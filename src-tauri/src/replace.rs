@@ -0,0 +1,84 @@
+use crate::dataset::DatasetStore;
+use crate::journal::Edit;
+use crate::error::AppError;
+use regex::{NoExpand, Regex, RegexBuilder};
+use serde::Deserialize;
+use tauri::State;
+
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct ReplaceOptions {
+    /// Treat the pattern as a regex; the replacement may then use `$1` or `${name}`
+    #[serde(default)]
+    pub regex: bool,
+    #[serde(default)]
+    pub case_insensitive: bool,
+    /// Only replace cells whose entire value matches
+    #[serde(default)]
+    pub whole_cell: bool,
+    /// Columns to search; every column when omitted or empty
+    #[serde(default)]
+    pub columns: Vec<String>,
+}
+
+fn build_pattern(pattern: &str, options: &ReplaceOptions) -> Result<Regex, String> {
+    let source = if options.regex { pattern.to_string() } else { regex::escape(pattern) };
+    let source = if options.whole_cell { format!("^(?:{})$", source) } else { source };
+    RegexBuilder::new(&source)
+        .case_insensitive(options.case_insensitive)
+        .build()
+        .map_err(|e| format!("Invalid regex: {}", e))
+}
+
+/// Replace every occurrence of `pattern` in the selected columns and return
+/// how many occurrences were replaced
+#[tauri::command]
+pub fn replace(
+    store: State<'_, DatasetStore>,
+    handle: u64,
+    pattern: String,
+    replacement: String,
+    options: Option<ReplaceOptions>,
+) -> Result<usize, AppError> {
+    let options = options.unwrap_or_default();
+    if pattern.is_empty() {
+        return Err("Search pattern cannot be empty".into());
+    }
+    let regex = build_pattern(&pattern, &options)?;
+
+    store.with_mut(handle, |ds| {
+        let cols = if options.columns.is_empty() {
+            (0..ds.headers.len()).collect()
+        } else {
+            options
+                .columns
+                .iter()
+                .map(|c| ds.column_index(c))
+                .collect::<Result<Vec<_>, _>>()?
+        };
+
+        let mut count = 0;
+        let mut previous = Vec::new();
+        for (i, row) in ds.rows.iter_mut().enumerate() {
+            for &c in &cols {
+                let Some(cell) = row.get_mut(c) else { continue };
+                let matches = regex.find_iter(cell).count();
+                if matches == 0 {
+                    continue;
+                }
+                let replaced = if options.regex {
+                    regex.replace_all(cell, replacement.as_str())
+                } else {
+                    regex.replace_all(cell, NoExpand(&replacement))
+                };
+                let new = replaced.into_owned();
+                previous.push((i, c, std::mem::replace(cell, new)));
+                count += matches;
+            }
+        }
+        if count > 0 {
+            ds.record_edit(Edit::Cells(previous));
+            ds.dirty = true;
+        }
+        Ok(count)
+    })?
+}
@@ -0,0 +1,143 @@
+use crate::dataset::{Dataset, DatasetStore, FileData};
+use crate::history;
+use crate::error::AppError;
+use serde_json::json;
+use tauri::{AppHandle, State};
+
+/// Unpivot wide data into long form: every non-id column becomes a
+/// (variable, value) pair on its own row, with the id columns repeated.
+/// e.g. name,jan,feb -> name,variable,value with one row per month
+#[tauri::command]
+pub fn melt(
+    app: AppHandle,
+    store: State<'_, DatasetStore>,
+    handle: u64,
+    id_columns: Vec<String>,
+    value_columns: Option<Vec<String>>,
+    var_name: Option<String>,
+    value_name: Option<String>,
+) -> Result<FileData, AppError> {
+    let mut melted = store.with(handle, |ds| {
+        melt_dataset(ds, &id_columns, value_columns.as_deref(), var_name.clone(), value_name.clone())
+    })??;
+    history::record_transform(
+        &app,
+        &store,
+        handle,
+        &mut melted,
+        "melt",
+        json!({
+            "id_columns": id_columns,
+            "value_columns": value_columns,
+            "var_name": var_name,
+            "value_name": value_name,
+        }),
+    );
+    Ok(store.register(melted))
+}
+
+fn melt_dataset(
+    ds: &Dataset,
+    id_columns: &[String],
+    value_columns: Option<&[String]>,
+    var_name: Option<String>,
+    value_name: Option<String>,
+) -> Result<Dataset, String> {
+    let id_idx = id_columns
+        .iter()
+        .map(|c| ds.column_index(c))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    // Default to every column that isn't an id column
+    let value_idx = match value_columns {
+        Some(cols) => cols
+            .iter()
+            .map(|c| ds.column_index(c))
+            .collect::<Result<Vec<_>, _>>()?,
+        None => (0..ds.headers.len()).filter(|i| !id_idx.contains(i)).collect(),
+    };
+    if value_idx.is_empty() {
+        return Err("No value columns to melt".to_string());
+    }
+
+    let mut headers: Vec<String> = id_idx.iter().map(|&i| ds.headers[i].clone()).collect();
+    headers.push(var_name.unwrap_or_else(|| "variable".to_string()));
+    headers.push(value_name.unwrap_or_else(|| "value".to_string()));
+
+    let mut rows = Vec::with_capacity(ds.rows.len() * value_idx.len());
+    for row in &ds.rows {
+        let ids: Vec<String> = id_idx
+            .iter()
+            .map(|&i| row.get(i).cloned().unwrap_or_default())
+            .collect();
+        for &v in &value_idx {
+            let mut out = ids.clone();
+            out.push(ds.headers[v].clone());
+            out.push(row.get(v).cloned().unwrap_or_default());
+            rows.push(out);
+        }
+    }
+
+    Ok(Dataset {
+        headers,
+        rows,
+        file_name: format!("{} (melted)", ds.file_name),
+        file_type: ds.file_type.clone(),
+        ..Default::default()
+    })
+}
+
+/// Swap rows and columns. The first column's values become the new headers
+/// when `use_first_column` is set, otherwise rows are labelled by index.
+#[tauri::command]
+pub fn transpose(
+    app: AppHandle,
+    store: State<'_, DatasetStore>,
+    handle: u64,
+    use_first_column: Option<bool>,
+) -> Result<FileData, AppError> {
+    let mut transposed = store.with(handle, |ds| {
+        transpose_dataset(ds, use_first_column.unwrap_or(true))
+    })?;
+    history::record_transform(
+        &app,
+        &store,
+        handle,
+        &mut transposed,
+        "transpose",
+        json!({ "use_first_column": use_first_column }),
+    );
+    Ok(store.register(transposed))
+}
+
+fn transpose_dataset(ds: &Dataset, use_first_column: bool) -> Dataset {
+    let cell = |row: &Vec<String>, i: usize| row.get(i).cloned().unwrap_or_default();
+    let use_first_column = use_first_column && !ds.headers.is_empty();
+    let first_value_col = if use_first_column { 1 } else { 0 };
+
+    let mut headers = Vec::with_capacity(ds.rows.len() + 1);
+    if use_first_column {
+        headers.push(ds.headers[0].clone());
+        headers.extend(ds.rows.iter().map(|row| cell(row, 0)));
+    } else {
+        headers.push("column".to_string());
+        headers.extend((1..=ds.rows.len()).map(|i| format!("row {}", i)));
+    }
+
+    let rows = (first_value_col..ds.headers.len())
+        .map(|c| {
+            let mut out = Vec::with_capacity(ds.rows.len() + 1);
+            out.push(ds.headers[c].clone());
+            out.extend(ds.rows.iter().map(|row| cell(row, c)));
+            out
+        })
+        .collect();
+
+    Dataset {
+        headers,
+        rows,
+        file_name: format!("{} (transposed)", ds.file_name),
+        file_type: ds.file_type.clone(),
+        ..Default::default()
+    }
+}
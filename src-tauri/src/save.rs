@@ -0,0 +1,214 @@
+use crate::access;
+use crate::dataset::{Dataset, DatasetStore, FileStamp, JsonKind, JsonLayout, SourceFormat};
+use crate::error::AppError;
+use serde_json::{Map, Value};
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use tauri::{AppHandle, State};
+
+/// Turn a cell back into JSON using the type the column had in the source file
+fn cell_to_json(cell: &str, kind: Option<JsonKind>) -> Value {
+    let kind = kind.unwrap_or(JsonKind::String);
+    if cell.is_empty() && kind != JsonKind::String {
+        return Value::Null;
+    }
+    match kind {
+        JsonKind::Number => serde_json::from_str::<serde_json::Number>(cell)
+            .map(Value::Number)
+            .unwrap_or_else(|_| Value::String(cell.to_string())),
+        JsonKind::Bool => match cell {
+            "true" => Value::Bool(true),
+            "false" => Value::Bool(false),
+            _ => Value::String(cell.to_string()),
+        },
+        // Nested arrays/objects were kept as JSON text; short primitive
+        // arrays were joined with ", " by `flatten_object`
+        JsonKind::Array => match serde_json::from_str::<Value>(cell) {
+            Ok(v @ Value::Array(_)) => v,
+            _ => Value::Array(
+                cell.split(", ")
+                    .map(|item| serde_json::from_str::<Value>(item)
+                        .ok()
+                        .filter(|v| v.is_number() || v.is_boolean())
+                        .unwrap_or_else(|| Value::String(item.to_string())))
+                    .collect(),
+            ),
+        },
+        JsonKind::Object => match serde_json::from_str::<Value>(cell) {
+            Ok(v @ Value::Object(_)) => v,
+            _ => Value::String(cell.to_string()),
+        },
+        JsonKind::String | JsonKind::Mixed => Value::String(cell.to_string()),
+    }
+}
+
+/// Insert a value at a dot-separated path, creating nested objects as needed.
+/// Falls back to the literal key if the path collides with a non-object value.
+fn insert_path(map: &mut Map<String, Value>, key: &str, value: Value) {
+    if let Some((head, rest)) = key.split_once('.') {
+        let entry = map
+            .entry(head.to_string())
+            .or_insert_with(|| Value::Object(Map::new()));
+        if let Value::Object(inner) = entry {
+            insert_path(inner, rest, value);
+            return;
+        }
+    }
+    map.insert(key.to_string(), value);
+}
+
+/// Rebuild a (re-nested) JSON object from a row
+fn row_to_object(headers: &[String], row: &[String], kinds: &HashMap<String, JsonKind>, nest: bool) -> Map<String, Value> {
+    let mut obj = Map::new();
+    for (header, cell) in headers.iter().zip(row) {
+        let value = cell_to_json(cell, kinds.get(header).copied());
+        if nest {
+            insert_path(&mut obj, header, value);
+        } else {
+            obj.insert(header.clone(), value);
+        }
+    }
+    obj
+}
+
+fn render_csv(ds: &Dataset, format: &SourceFormat) -> Result<Vec<u8>, String> {
+    let SourceFormat::Csv { crlf, header, delimiter, quote_all, final_newline } = *format else {
+        return Err("Not a CSV source format".to_string());
+    };
+    let terminator = if crlf { csv::Terminator::CRLF } else { csv::Terminator::Any(b'\n') };
+    let quote_style = if quote_all { csv::QuoteStyle::Always } else { csv::QuoteStyle::Necessary };
+    let mut writer = csv::WriterBuilder::new()
+        .terminator(terminator)
+        .delimiter(delimiter)
+        .quote_style(quote_style)
+        .from_writer(Vec::new());
+    if header {
+        writer
+            .write_record(&ds.headers)
+            .map_err(|e| format!("Failed to write headers: {}", e))?;
+    }
+    for row in &ds.rows {
+        writer
+            .write_record(row)
+            .map_err(|e| format!("Failed to write row: {}", e))?;
+    }
+    let mut out = writer.into_inner().map_err(|e| format!("Failed to save file: {}", e))?;
+    if !final_newline {
+        let terminator: &[u8] = if crlf { b"\r\n" } else { b"\n" };
+        if out.ends_with(terminator) {
+            out.truncate(out.len() - terminator.len());
+        }
+    }
+    Ok(out)
+}
+
+fn render_json(ds: &Dataset, layout: &JsonLayout, kinds: &HashMap<String, JsonKind>, original: &str) -> Result<String, String> {
+    let objects = ds.rows.iter().map(|row| Value::Object(row_to_object(&ds.headers, row, kinds, true)));
+
+    let document = match layout {
+        JsonLayout::Array => Value::Array(objects.collect()),
+        JsonLayout::Single if ds.rows.len() == 1 => objects.into_iter().next().unwrap_or(Value::Null),
+        JsonLayout::Single => Value::Array(objects.collect()),
+        JsonLayout::Wrapped(key) => {
+            // Keep the rest of the wrapping object as it was
+            let mut root = match serde_json::from_str::<Value>(original) {
+                Ok(Value::Object(map)) => map,
+                _ => Map::new(),
+            };
+            root.insert(key.clone(), Value::Array(objects.collect()));
+            Value::Object(root)
+        }
+        JsonLayout::Keyed => {
+            let mut root = match serde_json::from_str::<Value>(original) {
+                Ok(Value::Object(map)) => map,
+                _ => Map::new(),
+            };
+            root.retain(|_, v| !v.is_object());
+            for value in objects {
+                if let Value::Object(mut obj) = value {
+                    let name = match obj.remove("Name") {
+                        Some(Value::String(s)) => s,
+                        Some(other) => other.to_string(),
+                        None => String::new(),
+                    };
+                    root.insert(name, Value::Object(obj));
+                }
+            }
+            Value::Object(root)
+        }
+    };
+
+    serde_json::to_string_pretty(&document).map_err(|e| format!("Failed to serialize JSON: {}", e))
+}
+
+fn render_jsonl(ds: &Dataset, kinds: &HashMap<String, JsonKind>) -> Result<String, String> {
+    let mut out = String::new();
+    for row in &ds.rows {
+        let obj = row_to_object(&ds.headers, row, kinds, false);
+        let line = serde_json::to_string(&obj).map_err(|e| format!("Failed to serialize JSON: {}", e))?;
+        out.push_str(&line);
+        out.push('\n');
+    }
+    Ok(out)
+}
+
+/// The file's new contents, in its original format. `original` is the file
+/// as it is now, whose other keys a wrapped or keyed JSON document keeps.
+fn render(ds: &Dataset, original: &str) -> Result<Vec<u8>, String> {
+    match &ds.source_format {
+        Some(format @ SourceFormat::Csv { .. }) => render_csv(ds, format),
+        Some(SourceFormat::Json { layout, kinds }) => render_json(ds, layout, kinds, original).map(String::into_bytes),
+        Some(SourceFormat::Jsonl { kinds }) => render_jsonl(ds, kinds).map(String::into_bytes),
+        None => Err("Unknown source format; use export instead".to_string()),
+    }
+}
+
+/// Write a dataset back to the file it was loaded from, in its original
+/// format. The previous version of the file is kept alongside as `<name>.bak`.
+/// The new contents are rendered under the store's lock and written after
+/// it's released, so other commands aren't held up by the disk.
+#[tauri::command]
+pub fn save_dataset(app: AppHandle, store: State<'_, DatasetStore>, handle: u64) -> Result<String, AppError> {
+    let path_str = store
+        .with(handle, |ds| ds.source_path.clone())?
+        .ok_or_else(|| "This dataset has no source file; use export instead".to_string())?;
+    access::check(&app, &path_str)?;
+    let path = Path::new(&path_str);
+    let original = fs::read_to_string(path).unwrap_or_default();
+
+    let (content, version) = store.with(handle, |ds| {
+        if ds.preview {
+            return Err("Only part of this file is loaded; saving would drop the rest. Use export instead".to_string());
+        }
+        if ds.scan.is_some() {
+            return Err("Only the rows and columns picked when loading are here; saving would drop the rest. Use export instead".to_string());
+        }
+        if !ds.parse_failures.is_empty() {
+            return Err("Records that didn't parse were skipped when loading; saving would drop them. Use export instead".to_string());
+        }
+        if ds.schema.is_some() {
+            return Err("A schema rewrote values or headers when loading; saving would write them back over the file. Use export instead".to_string());
+        }
+        Ok((render(ds, &original)?, ds.journal.version()))
+    })??;
+
+    let backup = format!("{}.bak", path_str);
+    fs::copy(path, &backup).map_err(|e| format!("Failed to create backup: {}", e))?;
+
+    // Write to a temporary file first so a failed save can't truncate the
+    // original. Named after the whole file name, so "a.csv" and "a.json" don't share one.
+    let temp = PathBuf::from(format!("{}.qdv-tmp", path_str));
+    fs::write(&temp, content).map_err(|e| format!("Failed to write file: {}", e))?;
+    fs::rename(&temp, path).map_err(|e| format!("Failed to replace file: {}", e))?;
+
+    store.with_mut(handle, |ds| {
+        // Edits made while the file was being written are still unsaved
+        if ds.journal.version() == version {
+            ds.dirty = false;
+        }
+        ds.source_stamp = FileStamp::read(&path_str);
+        ds.record_spans.clear();
+    })?;
+    Ok(format!("Saved {} (backup at {})", path_str, backup))
+}
@@ -0,0 +1,422 @@
+use crate::dataset::{Dataset, FileStamp, ParseFailure, RecordSpan, SourceFormat};
+use crate::error::AppError;
+use crate::expr::{self, CompiledExpr};
+use crate::{access, memory, parquet_file};
+use parquet::file::reader::{FileReader, SerializedFileReader};
+use parquet::schema::types::Type as SchemaType;
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{BufRead, BufReader, Read, Seek, SeekFrom};
+use tauri::AppHandle;
+
+/// Bytes checked for CRLF line endings
+const CRLF_SAMPLE: u64 = 64 * 1024;
+
+/// Rows and columns to keep while a file is read, so the rest is never
+/// turned into cells
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct Scan {
+    /// Expression rows must match, as in `filter_rows`. It can use any
+    /// column of the file, not only the ones kept.
+    #[serde(default)]
+    pub filter: Option<String>,
+    /// Columns to keep, in this order
+    #[serde(default)]
+    pub columns: Option<Vec<String>>,
+}
+
+impl Scan {
+    /// Nothing to filter or drop, so the file can be loaded as usual
+    pub fn is_empty(&self) -> bool {
+        self.filter.as_deref().is_none_or(|f| f.trim().is_empty()) && self.columns.is_none()
+    }
+}
+
+/// A `Scan` resolved against the file's columns
+struct Plan {
+    filter: Option<CompiledExpr>,
+    columns: Option<Vec<usize>>,
+    /// Every column of the file
+    headers: Vec<String>,
+    /// The columns loaded
+    kept: Vec<String>,
+}
+
+impl Plan {
+    fn new(scan: &Scan, headers: Vec<String>) -> Result<Plan, String> {
+        let filter = match scan.filter.as_deref() {
+            Some(filter) if !filter.trim().is_empty() => {
+                Some(expr::compile(filter, &headers).map_err(|e| format!("Invalid filter: {}", e))?)
+            }
+            _ => None,
+        };
+        let columns = match &scan.columns {
+            Some(names) if names.is_empty() => return Err("Choose at least one column to load".to_string()),
+            Some(names) => Some(
+                names
+                    .iter()
+                    .map(|name| {
+                        headers
+                            .iter()
+                            .position(|h| h == name)
+                            .ok_or_else(|| format!("Column not found: {}", name))
+                    })
+                    .collect::<Result<Vec<_>, _>>()?,
+            ),
+            None => None,
+        };
+        let kept = match &columns {
+            Some(columns) => columns.iter().map(|&c| headers[c].clone()).collect(),
+            None => headers.clone(),
+        };
+        Ok(Plan { filter, columns, headers, kept })
+    }
+
+    /// The row as loaded, or `None` when the filter drops it
+    fn apply(&self, row: Vec<String>) -> Option<Vec<String>> {
+        if self.filter.as_ref().is_some_and(|f| !f.matches(&row)) {
+            return None;
+        }
+        Some(match &self.columns {
+            Some(columns) => columns.iter().map(|&c| row.get(c).cloned().unwrap_or_default()).collect(),
+            None => row,
+        })
+    }
+}
+
+/// Collects kept rows until they'd take more than the memory budget
+struct Rows {
+    rows: Vec<Vec<String>>,
+    spans: Vec<RecordSpan>,
+    bytes: u64,
+    budget: Option<u64>,
+    full: bool,
+}
+
+impl Rows {
+    fn new(budget: Option<u64>) -> Rows {
+        Rows {
+            rows: Vec::new(),
+            spans: Vec::new(),
+            bytes: 0,
+            budget,
+            full: false,
+        }
+    }
+
+    /// Keep a row; false once the budget is used up and reading should stop
+    fn push(&mut self, row: Vec<String>, span: Option<RecordSpan>) -> bool {
+        self.bytes += memory::row_bytes(&row);
+        if self.budget.is_some_and(|budget| self.bytes > budget) {
+            self.full = true;
+            return false;
+        }
+        self.rows.push(row);
+        self.spans.extend(span);
+        true
+    }
+}
+
+fn file_name(file_path: &str) -> String {
+    std::path::Path::new(file_path)
+        .file_name()
+        .and_then(|n| n.to_str())
+        .unwrap_or("unknown")
+        .to_string()
+}
+
+fn open(file_path: &str) -> Result<File, String> {
+    File::open(file_path).map_err(|e| format!("Failed to read file: {}", e))
+}
+
+/// The text of records that failed to parse, read back from where they start
+fn failed_records(file_path: &str, failed: Vec<(u64, ParseFailure)>) -> Result<Vec<ParseFailure>, String> {
+    if failed.is_empty() {
+        return Ok(Vec::new());
+    }
+    let mut reader = BufReader::new(open(file_path)?);
+    failed
+        .into_iter()
+        .map(|(start, mut failure)| {
+            reader.seek(SeekFrom::Start(start)).map_err(|e| format!("Failed to read file: {}", e))?;
+            let mut text = String::new();
+            loop {
+                text.clear();
+                if reader.read_line(&mut text).map_err(|e| format!("Failed to read file: {}", e))? == 0 {
+                    break;
+                }
+                if !text.trim_end_matches(['\n', '\r']).is_empty() {
+                    break;
+                }
+                // The position can be just before the previous line break
+                failure.line += 1;
+            }
+            failure.text = text.trim_end_matches(['\n', '\r']).to_string();
+            Ok(failure)
+        })
+        .collect()
+}
+
+/// Read a CSV file record by record, keeping only the rows and columns the
+/// scan asks for. The file is never held in memory whole, so a filter that
+/// keeps a few rows of a huge file loads quickly. Raw records and line
+/// lookups aren't available for the result.
+pub fn scan_csv(file_path: &str, has_header: bool, lenient: bool, scan: &Scan, budget: Option<u64>) -> Result<Dataset, String> {
+    let mut sample = Vec::new();
+    open(file_path)?
+        .take(CRLF_SAMPLE)
+        .read_to_end(&mut sample)
+        .map_err(|e| format!("Failed to read file: {}", e))?;
+
+    let mut reader = csv::ReaderBuilder::new()
+        .has_headers(has_header)
+        .from_reader(BufReader::new(open(file_path)?));
+    let headers: Option<Vec<String>> = if has_header {
+        Some(
+            reader
+                .headers()
+                .map_err(|e| format!("Failed to read headers: {}", e))?
+                .iter()
+                .map(|s| s.to_string())
+                .collect(),
+        )
+    } else {
+        None
+    };
+
+    let mut plan: Option<Plan> = headers.map(|h| Plan::new(scan, h)).transpose()?;
+    let mut rows = Rows::new(budget);
+    let mut failed = Vec::new();
+    for result in reader.records() {
+        let record = match result {
+            Ok(record) => record,
+            Err(e) => match e.position() {
+                Some(position) if lenient => {
+                    let failure = ParseFailure {
+                        line: position.line(),
+                        text: String::new(),
+                        error: e.to_string(),
+                    };
+                    failed.push((position.byte(), failure));
+                    continue;
+                }
+                _ => return Err(format!("Failed to read record: {}", e)),
+            },
+        };
+        let row: Vec<String> = record.iter().map(|s| s.to_string()).collect();
+        // Without a header row, the first record says how many columns there are
+        let plan = match &mut plan {
+            Some(plan) => plan,
+            slot => slot.insert(Plan::new(scan, (1..=row.len()).map(|i| format!("column_{}", i)).collect())?),
+        };
+        if let Some(row) = plan.apply(row) {
+            if !rows.push(row, None) {
+                break;
+            }
+        }
+    }
+
+    Ok(Dataset {
+        headers: plan.map(|plan| plan.kept).unwrap_or_default(),
+        rows: rows.rows,
+        file_name: file_name(file_path),
+        file_type: "CSV".to_string(),
+        source_path: Some(file_path.to_string()),
+        source_stamp: FileStamp::read(file_path),
+        // Scanned datasets can't be saved back, so a sample is enough
+        source_format: Some(SourceFormat::csv(&sample, b',', has_header)),
+        preview: rows.full,
+        parse_failures: failed_records(file_path, failed)?,
+        scan: Some(scan.clone()),
+        ..Default::default()
+    })
+}
+
+/// Read a JSONL file line by line, keeping only the rows and columns the
+/// scan asks for. Columns come from the first object, as in `parse_jsonl`.
+pub fn scan_jsonl(file_path: &str, lenient: bool, scan: &Scan, budget: Option<u64>) -> Result<Dataset, String> {
+    let mut reader = BufReader::new(open(file_path)?);
+    let mut plan: Option<Plan> = None;
+    let mut rows = Rows::new(budget);
+    let mut kinds = HashMap::new();
+    let mut parse_failures = Vec::new();
+    let mut line = String::new();
+    let (mut offset, mut number) = (0u64, 0u64);
+    loop {
+        line.clear();
+        let read = reader.read_line(&mut line).map_err(|e| format!("Failed to read file: {}", e))?;
+        if read == 0 {
+            break;
+        }
+        number += 1;
+        let start = offset;
+        offset += read as u64;
+        let text = line.trim_end_matches(['\n', '\r']);
+        if text.trim().is_empty() {
+            continue;
+        }
+
+        let object = match serde_json::from_str::<serde_json::Value>(text) {
+            Ok(serde_json::Value::Object(object)) => object,
+            Err(e) if !lenient => return Err(format!("Failed to parse line {}: {}", number, e)),
+            // As in `parse_jsonl`, the first line must be an object and later
+            // lines that aren't are skipped
+            Ok(_) if !lenient && plan.is_none() => return Err("JSONL lines must be objects".to_string()),
+            Ok(_) if !lenient => continue,
+            result => {
+                parse_failures.push(ParseFailure {
+                    line: number,
+                    text: text.to_string(),
+                    error: result.map_or_else(|e| e.to_string(), |_| "Not a JSON object".to_string()),
+                });
+                continue;
+            }
+        };
+        let plan = match &mut plan {
+            Some(plan) => plan,
+            slot => slot.insert(Plan::new(scan, object.keys().cloned().collect())?),
+        };
+        let row: Vec<String> = plan
+            .headers
+            .iter()
+            .map(|h| object.get(h).map(crate::json_cell).unwrap_or_default())
+            .collect();
+        if let Some(row) = plan.apply(row) {
+            for key in &plan.kept {
+                if let Some(value) = object.get(key) {
+                    crate::record_kind(&mut kinds, key, value);
+                }
+            }
+            let span = RecordSpan {
+                start,
+                end: start + text.len() as u64,
+                line: number,
+                end_line: number,
+            };
+            if !rows.push(row, Some(span)) {
+                break;
+            }
+        }
+    }
+
+    let plan = plan.ok_or("JSONL file is empty")?;
+    Ok(Dataset {
+        headers: plan.kept,
+        rows: rows.rows,
+        file_name: file_name(file_path),
+        file_type: "JSONL".to_string(),
+        source_path: Some(file_path.to_string()),
+        source_stamp: FileStamp::read(file_path),
+        source_format: Some(SourceFormat::Jsonl { kinds }),
+        record_spans: rows.spans,
+        preview: rows.full,
+        parse_failures,
+        scan: Some(scan.clone()),
+        ..Default::default()
+    })
+}
+
+/// Load a Parquet file into memory, keeping only the rows and columns the
+/// scan asks for. Without a filter, only the kept columns are decoded.
+pub fn scan_parquet(file_path: &str, scan: &Scan, budget: Option<u64>) -> Result<Dataset, String> {
+    let reader = SerializedFileReader::new(open(file_path)?).map_err(|e| format!("Failed to parse Parquet file: {}", e))?;
+    let schema = reader.metadata().file_metadata().schema_descr().root_schema();
+    let fields = schema.get_fields();
+    let headers: Vec<String> = fields.iter().map(|field| field.name().to_string()).collect();
+    let projection = match &scan.columns {
+        Some(names) if scan.filter.as_deref().is_none_or(|f| f.trim().is_empty()) => {
+            let kept: Vec<_> = fields.iter().filter(|field| names.iter().any(|n| n == field.name())).cloned().collect();
+            Some(
+                SchemaType::group_type_builder(schema.name())
+                    .with_fields(kept)
+                    .build()
+                    .map_err(|e| format!("Failed to pick Parquet columns: {}", e))?,
+            )
+        }
+        _ => None,
+    };
+    // Projected rows hold the kept columns, in the file's order
+    let plan = match &projection {
+        Some(projected) => Plan::new(scan, projected.get_fields().iter().map(|f| f.name().to_string()).collect())?,
+        None => Plan::new(scan, headers)?,
+    };
+
+    let mut rows = Rows::new(budget);
+    let read_error = |e| format!("Failed to read Parquet rows: {}", e);
+    for row in reader.get_row_iter(projection).map_err(read_error)? {
+        let row: Vec<String> = row.map_err(read_error)?.get_column_iter().map(|(_, field)| parquet_file::cell(field)).collect();
+        if let Some(row) = plan.apply(row) {
+            if !rows.push(row, None) {
+                break;
+            }
+        }
+    }
+
+    Ok(Dataset {
+        headers: plan.kept,
+        rows: rows.rows,
+        file_name: file_name(file_path),
+        file_type: "Parquet".to_string(),
+        source_path: Some(file_path.to_string()),
+        source_stamp: FileStamp::read(file_path),
+        preview: rows.full,
+        scan: Some(scan.clone()).filter(|scan| !scan.is_empty()),
+        ..Default::default()
+    })
+}
+
+/// Column names of a CSV, JSONL, or Parquet file, reading no further than
+/// its first record (or footer), so the columns to load can be picked before
+/// `parse_csv`, `parse_jsonl`, or `load_parquet` is called with a `scan`
+#[tauri::command]
+pub fn list_file_columns(app: AppHandle, file_path: String, has_header: Option<bool>) -> Result<Vec<String>, AppError> {
+    access::check(&app, &file_path)?;
+    let extension = std::path::Path::new(&file_path)
+        .extension()
+        .and_then(|e| e.to_str())
+        .map(|e| e.to_ascii_lowercase());
+    let columns = match extension.as_deref() {
+        Some("csv") => {
+            let mut reader = csv::ReaderBuilder::new()
+                .has_headers(false)
+                .from_reader(BufReader::new(open(&file_path)?));
+            let first = match reader.records().next() {
+                Some(record) => record.map_err(|e| format!("Failed to read headers: {}", e))?,
+                None => return Ok(Vec::new()),
+            };
+            if has_header.unwrap_or(true) {
+                first.iter().map(|s| s.to_string()).collect()
+            } else {
+                (1..=first.len()).map(|i| format!("column_{}", i)).collect()
+            }
+        }
+        Some("jsonl") => {
+            let reader = BufReader::new(open(&file_path)?);
+            let mut columns = Vec::new();
+            for (i, line) in reader.lines().enumerate() {
+                let line = line.map_err(|e| format!("Failed to read file: {}", e))?;
+                if line.trim().is_empty() {
+                    continue;
+                }
+                let value: serde_json::Value = serde_json::from_str(&line)
+                    .map_err(|e| format!("Failed to parse line {}: {}", i + 1, e))?;
+                let object = value.as_object().ok_or("JSONL lines must be objects")?;
+                columns = object.keys().cloned().collect();
+                break;
+            }
+            columns
+        }
+        Some("parquet") => {
+            let reader = SerializedFileReader::new(open(&file_path)?)
+                .map_err(|e| format!("Failed to parse Parquet file: {}", e))?;
+            let schema = reader.metadata().file_metadata().schema_descr().root_schema();
+            schema.get_fields().iter().map(|field| field.name().to_string()).collect()
+        }
+        _ => {
+            return Err(AppError::from("Unsupported file type: columns can be listed for CSV, JSONL, and Parquet files")
+                .with_path(&file_path))
+        }
+    };
+    Ok(columns)
+}
@@ -0,0 +1,156 @@
+use crate::dataset::{Dataset, DatasetStore};
+use crate::types::ColumnType;
+use crate::error::AppError;
+use serde::Serialize;
+use tauri::State;
+
+/// How alike two column names must be for a removed and an added column to
+/// be reported as a rename
+const RENAME_SIMILARITY: f64 = 0.8;
+
+#[derive(Debug, Serialize)]
+pub struct SchemaColumn {
+    pub name: String,
+    #[serde(rename = "type")]
+    pub kind: ColumnType,
+}
+
+#[derive(Debug, Serialize)]
+pub struct RenamedColumn {
+    pub from: String,
+    pub to: String,
+    #[serde(rename = "type")]
+    pub kind: ColumnType,
+    pub similarity: f64,
+}
+
+#[derive(Debug, Serialize)]
+pub struct TypeChange {
+    pub column: String,
+    pub from: ColumnType,
+    pub to: ColumnType,
+}
+
+/// Differences between one dataset and the next in the comparison
+#[derive(Debug, Serialize)]
+pub struct SchemaChanges {
+    pub from_handle: u64,
+    pub to_handle: u64,
+    pub from_file: String,
+    pub to_file: String,
+    pub added: Vec<SchemaColumn>,
+    pub removed: Vec<SchemaColumn>,
+    /// Removed and added columns that look like the same column under a new name
+    pub renamed: Vec<RenamedColumn>,
+    pub type_changed: Vec<TypeChange>,
+    /// The shared columns are in a different order
+    pub reordered: bool,
+}
+
+fn schema_of(ds: &Dataset) -> Vec<SchemaColumn> {
+    ds.headers
+        .iter()
+        .enumerate()
+        .map(|(col, name)| SchemaColumn {
+            name: name.clone(),
+            kind: ds.column_type(col),
+        })
+        .collect()
+}
+
+/// Lowercase with separators dropped, so "Customer_ID" and "customerId" compare equal
+fn normalize_name(name: &str) -> String {
+    name.chars().filter(|c| c.is_alphanumeric()).flat_map(char::to_lowercase).collect()
+}
+
+/// Types match unless both are known and differ
+fn same_type(a: ColumnType, b: ColumnType) -> bool {
+    a == b || a == ColumnType::Empty || b == ColumnType::Empty
+}
+
+fn compare(from_handle: u64, from: &Dataset, to_handle: u64, to: &Dataset) -> SchemaChanges {
+    let old = schema_of(from);
+    let new = schema_of(to);
+    let type_changed = new
+        .iter()
+        .filter_map(|n| {
+            let o = old.iter().find(|o| o.name == n.name)?;
+            (!same_type(o.kind, n.kind)).then(|| TypeChange {
+                column: n.name.clone(),
+                from: o.kind,
+                to: n.kind,
+            })
+        })
+        .collect();
+    let shared_old: Vec<&String> = from.headers.iter().filter(|h| to.headers.contains(h)).collect();
+    let shared_new: Vec<&String> = to.headers.iter().filter(|h| from.headers.contains(h)).collect();
+    let reordered = shared_old != shared_new;
+
+    let removed: Vec<SchemaColumn> = old.into_iter().filter(|o| !to.headers.contains(&o.name)).collect();
+    let added: Vec<SchemaColumn> = new.into_iter().filter(|n| !from.headers.contains(&n.name)).collect();
+
+    // Pair up the most similar removed/added columns first
+    let mut candidates: Vec<(f64, usize, usize)> = Vec::new();
+    for (r, old) in removed.iter().enumerate() {
+        for (a, new) in added.iter().enumerate() {
+            let similarity = strsim::jaro_winkler(&normalize_name(&old.name), &normalize_name(&new.name));
+            if similarity >= RENAME_SIMILARITY && same_type(old.kind, new.kind) {
+                candidates.push((similarity, r, a));
+            }
+        }
+    }
+    candidates.sort_by(|x, y| y.0.total_cmp(&x.0));
+    let mut pairs: Vec<(f64, usize, usize)> = Vec::new();
+    for (similarity, r, a) in candidates {
+        if !pairs.iter().any(|&(_, pr, pa)| pr == r || pa == a) {
+            pairs.push((similarity, r, a));
+        }
+    }
+    let renamed = pairs
+        .iter()
+        .map(|&(similarity, r, a)| RenamedColumn {
+            from: removed[r].name.clone(),
+            to: added[a].name.clone(),
+            kind: if added[a].kind == ColumnType::Empty { removed[r].kind } else { added[a].kind },
+            similarity: (similarity * 100.0).round() / 100.0,
+        })
+        .collect();
+
+    SchemaChanges {
+        from_handle,
+        to_handle,
+        from_file: from.file_name.clone(),
+        to_file: to.file_name.clone(),
+        added: added
+            .into_iter()
+            .enumerate()
+            .filter(|(a, _)| !pairs.iter().any(|p| p.2 == *a))
+            .map(|(_, c)| c)
+            .collect(),
+        removed: removed
+            .into_iter()
+            .enumerate()
+            .filter(|(r, _)| !pairs.iter().any(|p| p.1 == *r))
+            .map(|(_, c)| c)
+            .collect(),
+        renamed,
+        type_changed,
+        reordered,
+    }
+}
+
+/// Compare the columns and inferred types of each dataset with the next one,
+/// e.g. successive monthly deliveries of the same feed
+#[tauri::command]
+pub fn compare_schemas(store: State<'_, DatasetStore>, handles: Vec<u64>) -> Result<Vec<SchemaChanges>, AppError> {
+    if handles.len() < 2 {
+        return Err("Select at least two datasets to compare".into());
+    }
+    Ok(store.with_many(&handles, |datasets| {
+        handles
+            .windows(2)
+            .zip(datasets.windows(2))
+            .map(|(h, ds)| compare(h[0], ds[0], h[1], ds[1]))
+            .collect()
+    })?)
+}
@@ -0,0 +1,161 @@
+use crate::cast;
+use crate::dataset::{Dataset, JsonKind};
+use crate::types::ColumnType;
+use chrono::format::{Item, StrftimeItems};
+use chrono::Timelike;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// How one column of a file should be read, whatever its values look like
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ColumnSpec {
+    /// The column's header in the file (column_1, column_2, ... without one)
+    pub name: String,
+    /// Name to give the column instead
+    pub rename: Option<String>,
+    #[serde(rename = "type")]
+    pub kind: Option<ColumnType>,
+    /// chrono format the column's dates are written in, e.g. "%d/%m/%Y".
+    /// Dates are rewritten as ISO dates, and the column is a date column.
+    pub date_format: Option<String>,
+    /// Values that mean "no value" in this column, on top of the schema-wide ones
+    pub null_tokens: Vec<String>,
+}
+
+/// An explicit schema for parsing a file, which wins over type inference
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SchemaOverride {
+    pub columns: Vec<ColumnSpec>,
+    /// Values read as empty in every column, e.g. "NA" or "-"
+    pub null_tokens: Vec<String>,
+}
+
+/// `data.schema.json` for `data.csv`
+fn sidecar_path(file_path: &str) -> PathBuf {
+    Path::new(file_path).with_extension("schema.json")
+}
+
+/// The schema in the file's sidecar `.schema.json`, if it has one
+pub fn load_sidecar(file_path: &str) -> Result<Option<SchemaOverride>, String> {
+    let path = sidecar_path(file_path);
+    if !path.is_file() {
+        return Ok(None);
+    }
+    let content = fs::read_to_string(&path).map_err(|e| format!("Failed to read schema {}: {}", path.display(), e))?;
+    serde_json::from_str(&content)
+        .map(Some)
+        .map_err(|e| format!("Failed to parse schema {}: {}", path.display(), e))
+}
+
+/// A date read with the column's format, as an ISO date (with the time, if any)
+fn normalize_date(value: &str, format: &str) -> Option<String> {
+    let dt = cast::parse_date(value.trim(), Some(format))?;
+    let output = if dt.time().num_seconds_from_midnight() == 0 { "%Y-%m-%d" } else { "%Y-%m-%d %H:%M:%S" };
+    Some(dt.format(output).to_string())
+}
+
+/// Clear null tokens, rewrite dates, record declared types, and rename
+/// columns as the schema says. Every column it names must be in the file,
+/// unless a scan left some out. A schema that changed what saving would
+/// write is kept on the dataset, so it isn't saved back over the file.
+pub fn apply(ds: &mut Dataset, schema: &SchemaOverride) -> Result<(), String> {
+    let mut specs = Vec::new();
+    for spec in &schema.columns {
+        match ds.headers.iter().position(|h| *h == spec.name) {
+            Some(col) => specs.push((col, spec)),
+            None if ds.scan.is_some() => {}
+            None => return Err(format!("The schema names a column the file doesn't have: {}", spec.name)),
+        }
+        if let Some(format) = &spec.date_format {
+            if format.is_empty() || StrftimeItems::new(format).any(|item| item == Item::Error) {
+                return Err(format!("Invalid date format for column {}: {}", spec.name, format));
+            }
+        }
+        if spec.date_format.is_some() && spec.kind.is_some_and(|kind| kind != ColumnType::Date) {
+            return Err(format!("Column {} has a date format but isn't a date column", spec.name));
+        }
+    }
+    let mut names: Vec<&str> = ds.headers.iter().map(String::as_str).collect();
+    for (col, spec) in &specs {
+        if let Some(rename) = &spec.rename {
+            names[*col] = rename;
+        }
+    }
+    if let Some(name) = names.iter().enumerate().find_map(|(i, n)| names[..i].contains(n).then_some(n)) {
+        return Err(format!("The schema leaves two columns named {}", name));
+    }
+
+    // Whether cells, headers, or saved JSON types differ from the file's
+    let mut rewritten = false;
+    let global: Vec<&str> = schema.null_tokens.iter().map(|t| t.trim()).collect();
+    if !global.is_empty() {
+        for cell in ds.rows.iter_mut().flatten() {
+            if !cell.is_empty() && global.contains(&cell.trim()) {
+                cell.clear();
+                rewritten = true;
+            }
+        }
+    }
+    for &(col, spec) in &specs {
+        let tokens: Vec<&str> = spec.null_tokens.iter().map(|t| t.trim()).collect();
+        let mut unparsed = 0;
+        for cell in ds.rows.iter_mut().filter_map(|row| row.get_mut(col)) {
+            if !cell.is_empty() && tokens.contains(&cell.trim()) {
+                cell.clear();
+                rewritten = true;
+            }
+            if let Some(format) = spec.date_format.as_deref().filter(|_| !cell.trim().is_empty()) {
+                match normalize_date(cell, format) {
+                    Some(date) if date != *cell => {
+                        *cell = date;
+                        rewritten = true;
+                    }
+                    Some(_) => {}
+                    None => unparsed += 1,
+                }
+            }
+        }
+        if unparsed > 0 {
+            tracing::warn!("{} values in column {} don't match its date format", unparsed, spec.name);
+        }
+
+        let kind = spec.kind.or(spec.date_format.as_ref().map(|_| ColumnType::Date));
+        let name = match &spec.rename {
+            Some(rename) => {
+                rewritten |= *rename != spec.name;
+                ds.rename_column_at(col, rename.clone());
+                rename.clone()
+            }
+            None => spec.name.clone(),
+        };
+        if let Some(kind) = kind {
+            ds.column_types.insert(name.clone(), kind);
+            if let Some(format) = &mut ds.source_format {
+                rewritten = true;
+                format.set_kind(
+                    &name,
+                    match kind {
+                        ColumnType::Integer | ColumnType::Float => JsonKind::Number,
+                        ColumnType::Boolean => JsonKind::Bool,
+                        _ => JsonKind::String,
+                    },
+                );
+            }
+        }
+    }
+    if rewritten {
+        ds.schema = Some(schema.clone());
+    }
+    Ok(())
+}
+
+/// Apply `schema`, or else the file's sidecar schema if there is one
+pub fn apply_for(file_path: &str, schema: Option<SchemaOverride>, ds: &mut Dataset) -> Result<(), String> {
+    match schema.map_or_else(|| load_sidecar(file_path), |s| Ok(Some(s)))? {
+        Some(schema) => apply(ds, &schema),
+        None => Ok(()),
+    }
+}
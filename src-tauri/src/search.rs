@@ -0,0 +1,100 @@
+use crate::dataset::DatasetStore;
+use crate::error::AppError;
+use crate::logging;
+use regex::{Regex, RegexBuilder};
+use serde::Serialize;
+use tauri::State;
+
+/// Hits kept per dataset unless the caller asks for more
+const DEFAULT_HIT_LIMIT: usize = 100;
+
+/// Rows searched per hold of the store's lock
+const ROWS_PER_LOCK: usize = 20_000;
+
+#[derive(Debug, Serialize)]
+pub struct SearchHit {
+    pub row: usize,
+    pub column: String,
+    pub value: String,
+}
+
+/// The hits in one dataset
+#[derive(Debug, Serialize)]
+pub struct DatasetHits {
+    pub handle: u64,
+    pub file_name: String,
+    pub hits: Vec<SearchHit>,
+    /// Matching cells, including any past the hit limit
+    pub total_hits: usize,
+}
+
+/// Search one dataset a chunk of rows at a time, letting go of the store
+/// between chunks so other commands aren't held up by a long search. `None`
+/// if the dataset was closed meanwhile.
+fn search(store: &DatasetStore, handle: u64, matcher: &Regex, limit: usize) -> Option<DatasetHits> {
+    let mut result = DatasetHits {
+        handle,
+        file_name: store.with(handle, |ds| ds.file_name.clone()).ok()?,
+        hits: Vec::new(),
+        total_hits: 0,
+    };
+    let mut start = 0;
+    loop {
+        let more = store
+            .with(handle, |ds| {
+                let end = ds.rows.len().min(start + ROWS_PER_LOCK);
+                for (row, cells) in ds.rows.iter().enumerate().take(end).skip(start) {
+                    for (col, cell) in cells.iter().enumerate() {
+                        if !matcher.is_match(cell) {
+                            continue;
+                        }
+                        result.total_hits += 1;
+                        if result.hits.len() < limit {
+                            result.hits.push(SearchHit {
+                                row,
+                                column: ds.headers.get(col).cloned().unwrap_or_default(),
+                                value: cell.clone(),
+                            });
+                        }
+                    }
+                }
+                start = end;
+                end < ds.rows.len()
+            })
+            .ok()?;
+        if !more {
+            return Some(result);
+        }
+    }
+}
+
+/// Find cells containing `query` (ignoring case) in every loaded dataset,
+/// searching them in parallel. Datasets without a hit are left out; each
+/// returns at most `limit` hits (100 by default).
+#[tauri::command(async)]
+pub fn search_all(store: State<'_, DatasetStore>, query: String, limit: Option<usize>) -> Result<Vec<DatasetHits>, AppError> {
+    let _timed = logging::timed("search_all");
+    let needle = query.trim();
+    if needle.is_empty() {
+        return Err("Search query cannot be empty".into());
+    }
+    let matcher = RegexBuilder::new(&regex::escape(needle))
+        .case_insensitive(true)
+        .build()
+        .map_err(|e| format!("Invalid search query: {}", e))?;
+    let limit = limit.unwrap_or(DEFAULT_HIT_LIMIT);
+    let store: &DatasetStore = &store;
+    let matcher = &matcher;
+    Ok(std::thread::scope(|scope| {
+        let searches: Vec<_> = store
+            .handles()
+            .into_iter()
+            .map(|handle| scope.spawn(move || search(store, handle, matcher, limit)))
+            .collect();
+        searches
+            .into_iter()
+            .filter_map(|search| search.join().ok().flatten())
+            .filter(|hits| hits.total_hits > 0)
+            .collect()
+    }))
+}
@@ -0,0 +1,110 @@
+use crate::access;
+use crate::dataset::{DatasetStore, FileData};
+use crate::recipe::{self, RecipeStep};
+use crate::storage;
+use crate::views::{self, ViewConfig};
+use crate::error::AppError;
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Manager, State};
+
+const SESSION_FILE: &str = "session.json";
+
+/// A dataset that was open at shutdown: the file it came from, the
+/// transforms that produced it, and what the frontend was showing
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SessionEntry {
+    pub path: String,
+    #[serde(default)]
+    pub steps: Vec<RecipeStep>,
+    #[serde(default)]
+    pub view: Option<ViewConfig>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct RestoredDataset {
+    pub data: FileData,
+    pub view: Option<ViewConfig>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct SessionRestore {
+    pub datasets: Vec<RestoredDataset>,
+    /// Entries that could not be reopened, e.g. because the file was moved
+    pub errors: Vec<String>,
+}
+
+/// Remember the open datasets. Called on exit; datasets that weren't loaded
+/// from a local file are skipped, and unsaved edits are not part of the session.
+pub fn save(app: &AppHandle) {
+    let store = app.state::<DatasetStore>();
+    let entries: Vec<SessionEntry> = store
+        .handles()
+        .into_iter()
+        .filter_map(|handle| {
+            store
+                .with(handle, |ds| {
+                    ds.origin_path()
+                        .filter(|path| !path.contains("://"))
+                        .map(|path| SessionEntry {
+                            path,
+                            steps: ds.steps.clone(),
+                            view: ds.view.clone(),
+                        })
+                })
+                .ok()
+                .flatten()
+        })
+        .collect();
+    let _ = storage::save(app, SESSION_FILE, &entries);
+}
+
+/// Note the filter, sort, and column layout the frontend is showing for a
+/// dataset, so the session can bring them back. For datasets loaded from a
+/// file it's also kept for the next time that file is opened.
+#[tauri::command]
+pub fn set_view_state(
+    app: AppHandle,
+    store: State<'_, DatasetStore>,
+    handle: u64,
+    view: Option<ViewConfig>,
+) -> Result<(), AppError> {
+    let path = store.with_mut(handle, |ds| {
+        ds.view = view.clone();
+        ds.source_path.clone()
+    })?;
+    match path {
+        Some(path) => Ok(views::remember_view_state(&app, &path, view.as_ref())?),
+        None => Ok(()),
+    }
+}
+
+fn reopen(app: &AppHandle, store: &State<'_, DatasetStore>, entry: &SessionEntry) -> Result<FileData, String> {
+    access::check(app, &entry.path).map_err(|e| e.to_string())?;
+    let handle = store.insert(crate::load_file(&entry.path)?);
+    if entry.steps.is_empty() {
+        return store.with(handle, |ds| ds.to_file_data(handle));
+    }
+    let result = recipe::apply_steps(app, store, handle, &entry.steps);
+    store.remove(handle);
+    result
+}
+
+/// Reopen the datasets that were open when the app was last closed
+#[tauri::command]
+pub fn restore_session(app: AppHandle, store: State<'_, DatasetStore>) -> SessionRestore {
+    let entries: Vec<SessionEntry> = storage::load(&app, SESSION_FILE);
+    let mut restore = SessionRestore {
+        datasets: Vec::new(),
+        errors: Vec::new(),
+    };
+    for entry in entries {
+        match reopen(&app, &store, &entry) {
+            Ok(data) => {
+                let _ = store.with_mut(data.handle, |ds| ds.view = entry.view.clone());
+                restore.datasets.push(RestoredDataset { data, view: entry.view });
+            }
+            Err(e) => restore.errors.push(format!("{}: {}", entry.path, e)),
+        }
+    }
+    restore
+}
@@ -0,0 +1,154 @@
+use crate::error::AppError;
+use chrono::format::{Item, StrftimeItems};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+use tauri::{AppHandle, Manager};
+
+const SETTINGS_FILE: &str = "settings.json";
+
+/// Defaults for exports started from the UI
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ExportSettings {
+    /// "csv" or "json"
+    pub format: String,
+    pub delimiter: String,
+    pub include_headers: bool,
+    pub pretty_json: bool,
+    /// "lf" or "crlf"
+    pub line_ending: String,
+    /// Read each export back and compare it with the rows that were written
+    pub verify: bool,
+}
+
+impl Default for ExportSettings {
+    fn default() -> Self {
+        ExportSettings {
+            format: "csv".to_string(),
+            delimiter: ",".to_string(),
+            include_headers: true,
+            pretty_json: true,
+            line_ending: "lf".to_string(),
+            verify: false,
+        }
+    }
+}
+
+/// Directories backend commands may read from or write to. Denied
+/// directories win over allowed ones; an empty allow list allows everything
+/// that isn't denied. Only editable in the settings file itself, so the
+/// webview can't widen its own access.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AccessSettings {
+    pub allow: Vec<String>,
+    pub deny: Vec<String>,
+}
+
+/// Longest cell text sent to the frontend unless the settings say otherwise
+pub const DEFAULT_MAX_CELL_LENGTH: usize = 10_000;
+
+/// User preferences. Missing fields fall back to their defaults, so older
+/// settings files keep loading as new options are added.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Settings {
+    /// Rows shown per page in the table
+    pub page_size: usize,
+    /// Longest cell text, in characters, sent with a page of rows; longer
+    /// values are cut short and fetched whole on demand. 0 for no limit.
+    pub max_cell_length: usize,
+    /// chrono format string used to display dates
+    pub date_format: String,
+    /// Encoding assumed for files that don't declare one
+    pub default_encoding: String,
+    /// Largest amount of memory, in MB, loaded datasets may use; 0 for no limit
+    pub memory_limit_mb: u64,
+    /// Longest a filter, sort, query, or aggregation may run, in seconds;
+    /// 0 for no limit
+    pub operation_timeout_secs: u64,
+    pub export: ExportSettings,
+    pub access: AccessSettings,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Settings {
+            page_size: 100,
+            max_cell_length: DEFAULT_MAX_CELL_LENGTH,
+            date_format: "%Y-%m-%d".to_string(),
+            default_encoding: "utf-8".to_string(),
+            memory_limit_mb: 0,
+            operation_timeout_secs: 0,
+            export: ExportSettings::default(),
+            access: AccessSettings::default(),
+        }
+    }
+}
+
+impl Settings {
+    fn validate(&self) -> Result<(), String> {
+        if self.page_size == 0 {
+            return Err("Page size must be at least 1".to_string());
+        }
+        if self.date_format.is_empty() || StrftimeItems::new(&self.date_format).any(|item| item == Item::Error) {
+            return Err(format!("Invalid date format: {}", self.date_format));
+        }
+        if !matches!(self.default_encoding.to_ascii_lowercase().as_str(), "utf-8" | "utf-16" | "latin-1" | "windows-1252") {
+            return Err(format!("Unsupported encoding: {}", self.default_encoding));
+        }
+        let export = &self.export;
+        if export.format != "csv" && export.format != "json" {
+            return Err(format!("Unsupported export format: {}", export.format));
+        }
+        if export.delimiter.len() != 1 {
+            return Err("Export delimiter must be a single ASCII character".to_string());
+        }
+        if export.line_ending != "lf" && export.line_ending != "crlf" {
+            return Err(format!("Unknown line ending: {}", export.line_ending));
+        }
+        for dir in self.access.allow.iter().chain(&self.access.deny) {
+            if !Path::new(dir).is_absolute() {
+                return Err(format!("Access directories must be absolute paths: {}", dir));
+            }
+        }
+        Ok(())
+    }
+}
+
+fn settings_path(app: &AppHandle) -> Result<PathBuf, String> {
+    let dir = app
+        .path()
+        .app_config_dir()
+        .map_err(|e| format!("Failed to locate app config directory: {}", e))?;
+    fs::create_dir_all(&dir).map_err(|e| format!("Failed to create app config directory: {}", e))?;
+    Ok(dir.join(SETTINGS_FILE))
+}
+
+/// Current settings, or the defaults when none were saved or the file is unreadable
+pub fn load(app: &AppHandle) -> Settings {
+    settings_path(app)
+        .ok()
+        .and_then(|path| fs::read_to_string(path).ok())
+        .and_then(|content| serde_json::from_str(&content).ok())
+        .unwrap_or_default()
+}
+
+#[tauri::command]
+pub fn get_settings(app: AppHandle) -> Settings {
+    load(&app)
+}
+
+/// Validate and store new settings, returning them as saved
+#[tauri::command]
+pub fn set_settings(app: AppHandle, settings: Settings) -> Result<Settings, AppError> {
+    settings.validate()?;
+    if settings.access != load(&app).access {
+        return Err("File access rules can only be changed by editing the settings file".into());
+    }
+    let json = serde_json::to_string_pretty(&settings)
+        .map_err(|e| format!("Failed to serialize settings: {}", e))?;
+    fs::write(settings_path(&app)?, json).map_err(|e| format!("Failed to write settings: {}", e))?;
+    Ok(settings)
+}
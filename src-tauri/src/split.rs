@@ -0,0 +1,167 @@
+use crate::dataset::{Dataset, DatasetStore, FileData};
+use crate::history;
+use crate::error::AppError;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, State};
+
+/// How to split a column. `method` is "delimiter", "widths", or "regex".
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SplitSpec {
+    pub method: String,
+    /// Separator for the delimiter method
+    #[serde(default)]
+    pub delimiter: Option<String>,
+    /// Maximum number of parts for the delimiter method; the last part keeps the rest
+    #[serde(default)]
+    pub max_parts: Option<usize>,
+    /// Character widths of each part for the widths method
+    #[serde(default)]
+    pub widths: Vec<usize>,
+    /// Regex whose capture groups become the new columns
+    #[serde(default)]
+    pub pattern: Option<String>,
+    /// Names for the new columns (defaults to the regex group names, or e.g. "name_1")
+    #[serde(default)]
+    pub names: Vec<String>,
+    /// Don't trim whitespace around each part
+    #[serde(default)]
+    pub keep_whitespace: bool,
+    /// Keep the source column alongside the new ones
+    #[serde(default)]
+    pub keep_original: bool,
+}
+
+/// Split a single value by character widths, with any leftover text as a final part
+fn split_widths(value: &str, widths: &[usize]) -> Vec<String> {
+    let mut chars = value.chars();
+    let mut parts: Vec<String> = widths.iter().map(|&w| chars.by_ref().take(w).collect()).collect();
+    parts.push(chars.collect());
+    parts
+}
+
+/// The parts of each row's value, plus default names for the new columns
+type SplitParts = (Vec<Vec<String>>, Vec<Option<String>>);
+
+/// Split every row's value in the source column
+fn split_values(ds: &Dataset, col: usize, spec: &SplitSpec) -> Result<SplitParts, String> {
+    let values = ds.rows.iter().map(|row| row.get(col).map(|v| v.as_str()).unwrap_or(""));
+
+    let (mut parts, mut names): SplitParts = match spec.method.as_str() {
+        "delimiter" => {
+            let delimiter = spec.delimiter.as_deref().filter(|d| !d.is_empty()).ok_or("A delimiter is required")?;
+            let max_parts = spec.max_parts.unwrap_or(usize::MAX).max(1);
+            (
+                values
+                    .map(|v| v.splitn(max_parts, delimiter).map(str::to_string).collect())
+                    .collect(),
+                Vec::new(),
+            )
+        }
+        "widths" => {
+            if spec.widths.is_empty() {
+                return Err("At least one width is required".to_string());
+            }
+            let mut parts: Vec<Vec<String>> = values.map(|v| split_widths(v, &spec.widths)).collect();
+            // Only keep the overflow column if some value actually spills into it
+            if parts.iter().all(|p| p.last().is_some_and(|s| s.is_empty())) {
+                for p in &mut parts {
+                    p.pop();
+                }
+            }
+            (parts, Vec::new())
+        }
+        "regex" => {
+            let pattern = spec.pattern.as_deref().ok_or("A regex pattern is required")?;
+            let regex = Regex::new(pattern).map_err(|e| format!("Invalid regex: {}", e))?;
+            if regex.captures_len() < 2 {
+                return Err("The regex needs at least one capture group".to_string());
+            }
+            let groups = regex.captures_len() - 1;
+            (
+                values
+                    .map(|v| match regex.captures(v) {
+                        Some(caps) => (1..=groups)
+                            .map(|g| caps.get(g).map(|m| m.as_str().to_string()).unwrap_or_default())
+                            .collect(),
+                        None => vec![String::new(); groups],
+                    })
+                    .collect(),
+                regex.capture_names().skip(1).map(|n| n.map(str::to_string)).collect(),
+            )
+        }
+        other => return Err(format!("Unknown split method: {}", other)),
+    };
+
+    if !spec.keep_whitespace {
+        for part in parts.iter_mut().flatten() {
+            *part = part.trim().to_string();
+        }
+    }
+    let width = parts.iter().map(|p| p.len()).max().unwrap_or(0).max(names.len());
+    names.resize(width, None);
+    Ok((parts, names))
+}
+
+/// Split one column into several new columns, placed where the original was
+#[tauri::command]
+pub fn split_column(
+    app: AppHandle,
+    store: State<'_, DatasetStore>,
+    handle: u64,
+    column: String,
+    spec: SplitSpec,
+) -> Result<FileData, AppError> {
+    let mut result = store.with(handle, |ds| {
+        let col = ds.column_index(&column)?;
+        let (parts, default_names) = split_values(ds, col, &spec)?;
+
+        let new_headers: Vec<String> = default_names
+            .into_iter()
+            .enumerate()
+            .map(|(i, name)| {
+                spec.names
+                    .get(i)
+                    .cloned()
+                    .or(name)
+                    .unwrap_or_else(|| format!("{}_{}", column, i + 1))
+            })
+            .collect();
+        let width = new_headers.len();
+        // The new columns replace the source column, or follow it when it's kept
+        let replaced = if spec.keep_original { col + 1..col + 1 } else { col..col + 1 };
+
+        let mut headers = ds.headers.clone();
+        headers.splice(replaced.clone(), new_headers);
+
+        let rows = ds
+            .rows
+            .iter()
+            .zip(parts)
+            .map(|(row, mut values)| {
+                values.resize(width, String::new());
+                let mut row = row.clone();
+                row.resize(ds.headers.len(), String::new());
+                row.splice(replaced.clone(), values);
+                row
+            })
+            .collect();
+
+        Ok::<_, String>(Dataset {
+            headers,
+            rows,
+            file_name: format!("{} (split)", ds.file_name),
+            file_type: ds.file_type.clone(),
+            ..Default::default()
+        })
+    })??;
+    history::record_transform(
+        &app,
+        &store,
+        handle,
+        &mut result,
+        "split_column",
+        serde_json::json!({ "column": column, "spec": spec }),
+    );
+    Ok(store.register(result))
+}
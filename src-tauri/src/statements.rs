@@ -0,0 +1,320 @@
+use crate::access;
+use crate::dataset::{Dataset, DatasetStore, FileData};
+use crate::recent;
+use crate::error::AppError;
+use chrono::NaiveDate;
+use std::fs;
+use tauri::{AppHandle, State};
+
+/// Columns of the normalized transaction table both formats produce
+const TRANSACTION_HEADERS: [&str; 7] = ["date", "amount", "currency", "counterparty", "reference", "description", "category"];
+
+#[derive(Default)]
+struct Transaction {
+    date: String,
+    amount: String,
+    currency: String,
+    counterparty: String,
+    reference: String,
+    description: String,
+    category: String,
+}
+
+impl Transaction {
+    fn into_row(self) -> Vec<String> {
+        vec![
+            self.date,
+            self.amount,
+            self.currency,
+            self.counterparty,
+            self.reference,
+            self.description,
+            self.category,
+        ]
+    }
+}
+
+/// Bank exports are often Latin-1 rather than UTF-8
+fn read_text(file_path: &str) -> Result<String, String> {
+    let bytes = fs::read(file_path).map_err(|e| format!("Failed to read file: {}", e))?;
+    Ok(match String::from_utf8(bytes) {
+        Ok(text) => text,
+        Err(e) => e.into_bytes().iter().map(|&b| b as char).collect(),
+    })
+}
+
+fn transactions_dataset(file_path: &str, file_type: &str, transactions: Vec<Transaction>) -> Dataset {
+    let file_name = std::path::Path::new(file_path)
+        .file_name()
+        .and_then(|n| n.to_str())
+        .unwrap_or("unknown")
+        .to_string();
+    Dataset {
+        headers: TRANSACTION_HEADERS.map(String::from).to_vec(),
+        rows: transactions.into_iter().map(Transaction::into_row).collect(),
+        file_name,
+        file_type: file_type.to_string(),
+        source_path: Some(file_path.to_string()),
+        ..Default::default()
+    }
+}
+
+/// "1,234.56" -> "1234.56"
+fn plain_amount(amount: &str) -> String {
+    amount.trim().replace(',', "")
+}
+
+/// Split a QIF date ("3/25/2024", "3/25'24", "25.03.2024") into numbers
+fn qif_date_parts(date: &str) -> Option<[u32; 3]> {
+    let parts: Vec<u32> = date
+        .split(['/', '\'', '-', '.'])
+        .map(|p| p.trim().parse().ok())
+        .collect::<Option<_>>()?;
+    let parts: [u32; 3] = parts.try_into().ok()?;
+    // A two-digit year after an apostrophe is in the 2000s; Quicken's own convention
+    let year = match parts[2] {
+        y if y < 100 && date.contains('\'') => 2000 + y,
+        y if y < 100 => if y < 70 { 2000 + y } else { 1900 + y },
+        y => y,
+    };
+    Some([parts[0], parts[1], year])
+}
+
+/// ISO dates for QIF dates, which are month-first unless the file shows
+/// otherwise. Years first ("2024-03-25") are taken as they are.
+fn normalize_qif_dates(transactions: &mut [Transaction]) {
+    let parsed: Vec<Option<[u32; 3]>> = transactions
+        .iter()
+        .map(|t| if t.date.len() >= 8 && t.date.as_bytes()[4] == b'-' { None } else { qif_date_parts(&t.date) })
+        .collect();
+    let day_first = parsed.iter().flatten().any(|[first, _, _]| *first > 12);
+    for (t, parts) in transactions.iter_mut().zip(parsed) {
+        let Some([a, b, year]) = parts else { continue };
+        let (month, day) = if day_first { (b, a) } else { (a, b) };
+        if let Some(date) = NaiveDate::from_ymd_opt(year as i32, month, day) {
+            t.date = date.format("%Y-%m-%d").to_string();
+        }
+    }
+}
+
+/// QIF sections that hold transactions, as opposed to accounts, categories, etc.
+fn is_transaction_section(header: &str) -> bool {
+    let header = header.trim().to_ascii_lowercase();
+    ["!type:bank", "!type:cash", "!type:ccard", "!type:oth a", "!type:oth l", "!type:invst"]
+        .iter()
+        .any(|t| header.starts_with(t))
+}
+
+fn parse_qif_text(text: &str) -> Vec<Transaction> {
+    let mut transactions = Vec::new();
+    let mut in_transactions = false;
+    let mut current = Transaction::default();
+    let mut has_fields = false;
+    for line in text.lines() {
+        let line = line.trim_end();
+        if line.starts_with('!') {
+            in_transactions = is_transaction_section(line);
+            continue;
+        }
+        if !in_transactions || line.is_empty() {
+            continue;
+        }
+        let (code, value) = line.split_at(line.chars().next().map_or(0, char::len_utf8));
+        let value = value.trim().to_string();
+        match code {
+            "^" => {
+                if has_fields {
+                    transactions.push(std::mem::take(&mut current));
+                }
+                has_fields = false;
+                continue;
+            }
+            "D" => current.date = value,
+            // "U" repeats the amount in newer exports
+            "T" | "U" if current.amount.is_empty() => current.amount = plain_amount(&value),
+            "P" => current.counterparty = value,
+            "N" => current.reference = value,
+            "M" => current.description = value,
+            "L" => current.category = value,
+            // Splits, addresses, and cleared status aren't part of the table
+            _ => {}
+        }
+        has_fields = true;
+    }
+    if has_fields {
+        transactions.push(current);
+    }
+    normalize_qif_dates(&mut transactions);
+    transactions
+}
+
+/// Load a Quicken Interchange Format file as a transaction table
+pub fn load_qif(file_path: &str) -> Result<Dataset, String> {
+    let transactions = parse_qif_text(&read_text(file_path)?);
+    if transactions.is_empty() {
+        return Err("No transactions found in QIF file".to_string());
+    }
+    Ok(transactions_dataset(file_path, "QIF", transactions))
+}
+
+/// MT940 tags and their values, with continuation lines joined by newlines
+fn mt940_fields(text: &str) -> Vec<(String, String)> {
+    let mut fields: Vec<(String, String)> = Vec::new();
+    for line in text.lines() {
+        let line = line.trim_end();
+        let tag = line
+            .strip_prefix(':')
+            .and_then(|rest| rest.split_once(':'))
+            .filter(|(tag, _)| !tag.is_empty() && tag.len() <= 3 && tag.chars().all(|c| c.is_ascii_alphanumeric()));
+        match tag {
+            Some((tag, value)) => fields.push((tag.to_string(), value.to_string())),
+            None if line == "-" || line.starts_with('{') => {}
+            None => {
+                if let Some((_, value)) = fields.last_mut() {
+                    value.push('\n');
+                    value.push_str(line);
+                }
+            }
+        }
+    }
+    fields
+}
+
+/// YYMMDD -> ISO date
+fn mt940_date(value: &str) -> Option<String> {
+    NaiveDate::parse_from_str(value.get(..6)?, "%y%m%d")
+        .ok()
+        .map(|d| d.format("%Y-%m-%d").to_string())
+}
+
+/// Parse a :61: statement line into date, signed amount, and reference
+fn parse_statement_line(value: &str) -> Option<Transaction> {
+    let first_line = value.lines().next()?;
+    let date = mt940_date(first_line)?;
+    let mut rest = &first_line[6..];
+    // Optional MMDD entry date
+    if rest.get(..4).is_some_and(|entry| entry.bytes().all(|b| b.is_ascii_digit())) {
+        rest = &rest[4..];
+    }
+    let (negative, rest) = if let Some(r) = rest.strip_prefix("RC") {
+        (true, r)
+    } else if let Some(r) = rest.strip_prefix("RD") {
+        (false, r)
+    } else if let Some(r) = rest.strip_prefix('C') {
+        (false, r)
+    } else {
+        (true, rest.strip_prefix('D')?)
+    };
+    // Optional funds code: the third letter of the currency
+    let rest = rest.strip_prefix(|c: char| c.is_ascii_alphabetic()).unwrap_or(rest);
+    let amount_len = rest.find(|c: char| !c.is_ascii_digit() && c != ',')?;
+    let amount = rest[..amount_len].replace(',', ".");
+    let amount = amount.strip_suffix('.').unwrap_or(&amount).to_string();
+    // Transaction type code (e.g. NTRF), then customer reference // bank reference
+    let references = rest[amount_len..].get(4..).unwrap_or("");
+    let (customer, bank) = references.split_once("//").unwrap_or((references, ""));
+    let reference = if customer.is_empty() || customer == "NONREF" { bank } else { customer };
+
+    Some(Transaction {
+        date,
+        amount: if negative { format!("-{}", amount) } else { amount },
+        reference: reference.trim().to_string(),
+        ..Default::default()
+    })
+}
+
+/// Description and counterparty from a :86: field. German banks use ?NN
+/// subfields (?20-?29 purpose, ?32/?33 name); SWIFT's structured form uses
+/// /CODE/value pairs with /NAME/ for the counterparty.
+fn apply_information(transaction: &mut Transaction, info: &str) {
+    let info = info.replace('\n', "");
+    if info.contains('?') {
+        let mut purpose = String::new();
+        let mut name = String::new();
+        for part in info.split('?').skip(1) {
+            let (code, text) = part.split_at(part.char_indices().nth(2).map_or(part.len(), |(i, _)| i));
+            match code.parse::<u32>() {
+                Ok(20..=29) | Ok(60..=63) => purpose.push_str(text),
+                Ok(32..=33) => name.push_str(text),
+                _ => {}
+            }
+        }
+        transaction.description = purpose.trim().to_string();
+        transaction.counterparty = name.trim().to_string();
+    } else if info.starts_with('/') {
+        let parts: Vec<&str> = info.split('/').collect();
+        let value_of = |code: &str| {
+            parts
+                .iter()
+                .position(|p| *p == code)
+                .and_then(|i| parts.get(i + 1))
+                .map(|v| v.trim().to_string())
+                .unwrap_or_default()
+        };
+        transaction.counterparty = value_of("NAME");
+        transaction.description = match value_of("REMI") {
+            remi if remi.is_empty() => info.trim().to_string(),
+            remi => remi,
+        };
+    } else {
+        transaction.description = info.trim().to_string();
+    }
+}
+
+fn parse_mt940_text(text: &str) -> Vec<Transaction> {
+    let mut transactions: Vec<Transaction> = Vec::new();
+    let mut currency = String::new();
+    let mut last_was_transaction = false;
+    for (tag, value) in mt940_fields(text) {
+        match tag.as_str() {
+            // Opening balance: D/C mark, YYMMDD, currency, amount
+            "60F" | "60M" => {
+                currency = value.get(7..10).unwrap_or("").to_string();
+                last_was_transaction = false;
+            }
+            "61" => {
+                last_was_transaction = false;
+                if let Some(mut transaction) = parse_statement_line(&value) {
+                    transaction.currency = currency.clone();
+                    transactions.push(transaction);
+                    last_was_transaction = true;
+                }
+            }
+            "86" if last_was_transaction => {
+                if let Some(transaction) = transactions.last_mut() {
+                    apply_information(transaction, &value);
+                }
+                last_was_transaction = false;
+            }
+            _ => last_was_transaction = false,
+        }
+    }
+    transactions
+}
+
+/// Load a SWIFT MT940 statement as a transaction table
+pub fn load_mt940(file_path: &str) -> Result<Dataset, String> {
+    let transactions = parse_mt940_text(&read_text(file_path)?);
+    if transactions.is_empty() {
+        return Err("No transactions found in MT940 file".to_string());
+    }
+    Ok(transactions_dataset(file_path, "MT940", transactions))
+}
+
+/// Parse a QIF bank export into date, amount, counterparty, reference, and memo
+#[tauri::command]
+pub fn parse_qif(app: AppHandle, store: State<'_, DatasetStore>, file_path: String) -> Result<FileData, AppError> {
+    access::check(&app, &file_path)?;
+    let data = store.register(load_qif(&file_path).map_err(|e| AppError::from(e).with_path(&file_path))?);
+    recent::record(&app, &file_path, &data);
+    Ok(data)
+}
+
+/// Parse an MT940 bank statement into date, amount, currency, counterparty, reference, and purpose
+#[tauri::command]
+pub fn parse_mt940(app: AppHandle, store: State<'_, DatasetStore>, file_path: String) -> Result<FileData, AppError> {
+    access::check(&app, &file_path)?;
+    let data = store.register(load_mt940(&file_path).map_err(|e| AppError::from(e).with_path(&file_path))?);
+    recent::record(&app, &file_path, &data);
+    Ok(data)
+}
@@ -0,0 +1,722 @@
+use crate::cache;
+use crate::dataset::{Dataset, DatasetStore};
+use crate::dates::{self, TimeUnit};
+use crate::logging;
+use crate::quantile::{exact_quantile, P2Estimator};
+use crate::sketch::HyperLogLog;
+use crate::error::AppError;
+use crate::operations::{Operation, Operations};
+use serde::Serialize;
+use std::collections::{BTreeMap, HashMap};
+use tauri::{AppHandle, State};
+
+const TOP_VALUES: usize = 5;
+
+/// Columns with more values than this get approximate distinct counts
+pub const APPROX_DISTINCT_THRESHOLD: usize = 1_000_000;
+
+/// Approximate top values are counted over roughly this many sampled rows
+pub const TOP_VALUES_SAMPLE: usize = 100_000;
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ValueCount {
+    pub value: String,
+    pub count: usize,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct NumericStats {
+    pub mean: f64,
+    pub median: f64,
+    pub std_dev: f64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct TextStats {
+    pub min_length: usize,
+    pub max_length: usize,
+    pub top_values: Vec<ValueCount>,
+}
+
+/// Summary statistics for one column. `numeric` is filled in when every
+/// non-empty value parses as a number, `text` otherwise.
+#[derive(Debug, Clone, Serialize)]
+pub struct ColumnStats {
+    pub column: String,
+    pub count: usize,
+    pub nulls: usize,
+    pub distinct: usize,
+    /// False when `distinct` is a HyperLogLog estimate and top values come from a sample
+    pub distinct_exact: bool,
+    pub min: Option<String>,
+    pub max: Option<String>,
+    pub numeric: Option<NumericStats>,
+    pub text: Option<TextStats>,
+}
+
+/// Empty cells (including JSON nulls, which flatten to "") count as missing
+pub fn is_null(value: &str) -> bool {
+    value.trim().is_empty()
+}
+
+pub fn parse_number(value: &str) -> Option<f64> {
+    value.trim().parse::<f64>().ok().filter(|n| n.is_finite())
+}
+
+/// Non-null values of a column, in row order
+pub fn column_values(ds: &Dataset, col: usize) -> Vec<&str> {
+    ds.rows
+        .iter()
+        .filter_map(|row| row.get(col))
+        .map(|v| v.as_str())
+        .filter(|v| !is_null(v))
+        .collect()
+}
+
+/// A copy of a column's cells, blank ones included, so it can be
+/// aggregated with the store unlocked
+pub fn copy_column(ds: &Dataset, column: &str) -> Result<Vec<String>, String> {
+    let col = ds.column_index(column)?;
+    Ok(ds.rows.iter().map(|row| row.get(col).cloned().unwrap_or_default()).collect())
+}
+
+/// Non-null values among copied cells, in row order
+fn present(cells: &[String]) -> Vec<&str> {
+    cells.iter().map(String::as_str).filter(|v| !is_null(v)).collect()
+}
+
+/// Count occurrences of each value, most frequent first (ties broken alphabetically)
+pub fn count_values(values: &[&str]) -> Vec<ValueCount> {
+    let mut counts: HashMap<&str, usize> = HashMap::new();
+    for v in values {
+        *counts.entry(v).or_insert(0) += 1;
+    }
+    let mut counts: Vec<ValueCount> = counts
+        .into_iter()
+        .map(|(value, count)| ValueCount { value: value.to_string(), count })
+        .collect();
+    counts.sort_by(|a, b| b.count.cmp(&a.count).then_with(|| a.value.cmp(&b.value)));
+    counts
+}
+
+/// Count values over every `step`-th entry only, scaling the counts back up
+pub fn count_values_sampled(values: &[&str], step: usize) -> Vec<ValueCount> {
+    let sample: Vec<&str> = values.iter().step_by(step).copied().collect();
+    let mut counts = count_values(&sample);
+    counts.iter_mut().for_each(|c| c.count *= step);
+    counts
+}
+
+/// Number of distinct values, exact below the threshold unless forced either way
+pub fn distinct_count(values: &[&str], exact: Option<bool>) -> (usize, bool) {
+    if exact.unwrap_or(values.len() <= APPROX_DISTINCT_THRESHOLD) {
+        let set: std::collections::HashSet<&str> = values.iter().copied().collect();
+        (set.len(), true)
+    } else {
+        let mut hll = HyperLogLog::default();
+        values.iter().for_each(|v| hll.add(v));
+        (hll.estimate(), false)
+    }
+}
+
+/// Median of an already sorted slice
+pub fn median(sorted: &[f64]) -> f64 {
+    let mid = sorted.len() / 2;
+    if sorted.len().is_multiple_of(2) {
+        (sorted[mid - 1] + sorted[mid]) / 2.0
+    } else {
+        sorted[mid]
+    }
+}
+
+/// Compute summary statistics for a single column. Aggregations run as
+/// cancellable operations, as `filter_rows` does, failing once interrupted.
+#[tauri::command(async)]
+pub fn column_stats(
+    app: AppHandle,
+    store: State<'_, DatasetStore>,
+    ops: State<'_, Operations>,
+    handle: u64,
+    column: String,
+    operation_id: Option<String>,
+    timeout_ms: Option<u64>,
+) -> Result<ColumnStats, AppError> {
+    let _timed = logging::timed("column_stats");
+    let op = ops.start(&app, operation_id, timeout_ms)?;
+    Ok(store.with_cached_unlocked(
+        handle,
+        cache::key("column_stats", &column),
+        |ds| copy_column(ds, &column),
+        |cells| compute_column_stats(&column, &cells?, &op),
+    )??)
+}
+
+pub fn compute_column_stats(column: &str, cells: &[String], op: &Operation) -> Result<ColumnStats, String> {
+    let values = present(cells);
+    let nulls = cells.len() - values.len();
+    op.check_now()?;
+    let (distinct, distinct_exact) = distinct_count(&values, None);
+    op.check_now()?;
+    let counts = if distinct_exact {
+        count_values(&values)
+    } else {
+        count_values_sampled(&values, values.len() / TOP_VALUES_SAMPLE)
+    };
+    op.check_now()?;
+
+    let numbers: Option<Vec<f64>> = values.iter().map(|v| parse_number(v)).collect();
+    let numbers = numbers.filter(|n| !n.is_empty());
+
+    let (min, max, numeric, text) = match numbers {
+        Some(mut nums) => {
+            nums.sort_by(|a, b| a.total_cmp(b));
+            op.check_now()?;
+            let n = nums.len() as f64;
+            let mean = nums.iter().sum::<f64>() / n;
+            let variance = if nums.len() > 1 {
+                nums.iter().map(|x| (x - mean).powi(2)).sum::<f64>() / (n - 1.0)
+            } else {
+                0.0
+            };
+            (
+                Some(nums[0].to_string()),
+                Some(nums[nums.len() - 1].to_string()),
+                Some(NumericStats {
+                    mean,
+                    median: median(&nums),
+                    std_dev: variance.sqrt(),
+                }),
+                None,
+            )
+        }
+        None => {
+            let lengths = values.iter().map(|v| v.chars().count());
+            let text = (!values.is_empty()).then(|| TextStats {
+                min_length: lengths.clone().min().unwrap_or(0),
+                max_length: lengths.max().unwrap_or(0),
+                top_values: counts.iter().take(TOP_VALUES).cloned().collect(),
+            });
+            (
+                values.iter().min().map(|v| v.to_string()),
+                values.iter().max().map(|v| v.to_string()),
+                None,
+                text,
+            )
+        }
+    };
+
+    Ok(ColumnStats {
+        column: column.to_string(),
+        count: values.len(),
+        nulls,
+        distinct,
+        distinct_exact,
+        min,
+        max,
+        numeric,
+        text,
+    })
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ValueFrequency {
+    pub value: String,
+    pub count: usize,
+    pub percent: f64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ValueCounts {
+    pub column: String,
+    pub total: usize,
+    pub distinct: usize,
+    pub values: Vec<ValueFrequency>,
+}
+
+/// Distinct values of a column with their counts, most frequent first.
+/// Blank cells are included (as "") so the UI can offer them as a facet.
+#[tauri::command(async)]
+#[allow(clippy::too_many_arguments)]
+pub fn value_counts(
+    app: AppHandle,
+    store: State<'_, DatasetStore>,
+    ops: State<'_, Operations>,
+    handle: u64,
+    column: String,
+    limit: Option<usize>,
+    operation_id: Option<String>,
+    timeout_ms: Option<u64>,
+) -> Result<ValueCounts, AppError> {
+    let _timed = logging::timed("value_counts");
+    let op = ops.start(&app, operation_id, timeout_ms)?;
+    Ok(store.with_cached_unlocked(
+        handle,
+        cache::key("value_counts", &(&column, limit)),
+        |ds| copy_column(ds, &column),
+        |cells| {
+            let cells = cells?;
+            let values: Vec<&str> = cells.iter().map(String::as_str).collect();
+            op.check_now()?;
+            let counts = count_values(&values);
+            op.check_now()?;
+            let total = values.len();
+
+            Ok::<_, String>(ValueCounts {
+                column: column.clone(),
+                total,
+                distinct: counts.len(),
+                values: counts
+                    .into_iter()
+                    .take(limit.unwrap_or(usize::MAX))
+                    .map(|c| ValueFrequency {
+                        percent: c.count as f64 * 100.0 / total as f64,
+                        value: c.value,
+                        count: c.count,
+                    })
+                    .collect(),
+            })
+        },
+    )??)
+}
+
+/// Placeholder values that exports commonly use to mean "no value"
+pub const NULL_TOKENS: &[&str] = &["NULL", "N/A", "-"];
+
+pub fn is_null_token(value: &str) -> bool {
+    let trimmed = value.trim();
+    NULL_TOKENS.iter().any(|t| t.eq_ignore_ascii_case(trimmed))
+}
+
+#[derive(Debug, Serialize)]
+pub struct ColumnCompleteness {
+    pub column: String,
+    pub empty: usize,
+    pub whitespace_only: usize,
+    pub null_tokens: usize,
+    pub filled: usize,
+    pub filled_percent: f64,
+}
+
+/// Per-column counts of empty, whitespace-only and null-token cells
+#[tauri::command(async)]
+pub fn data_completeness(
+    app: AppHandle,
+    store: State<'_, DatasetStore>,
+    ops: State<'_, Operations>,
+    handle: u64,
+    operation_id: Option<String>,
+    timeout_ms: Option<u64>,
+) -> Result<Vec<ColumnCompleteness>, AppError> {
+    let _timed = logging::timed("data_completeness");
+    let op = ops.start(&app, operation_id, timeout_ms)?;
+    let (headers, rows) = store.with(handle, |ds| (ds.headers.clone(), ds.rows.clone()))?;
+    let mut reports: Vec<ColumnCompleteness> = headers
+        .into_iter()
+        .map(|column| ColumnCompleteness {
+            column,
+            empty: 0,
+            whitespace_only: 0,
+            null_tokens: 0,
+            filled: 0,
+            filled_percent: 0.0,
+        })
+        .collect();
+    for (i, row) in rows.iter().enumerate() {
+        op.check(i)?;
+        for (col, report) in reports.iter_mut().enumerate() {
+            let value = row.get(col).map(|v| v.as_str()).unwrap_or("");
+            if value.is_empty() {
+                report.empty += 1;
+            } else if value.trim().is_empty() {
+                report.whitespace_only += 1;
+            } else if is_null_token(value) {
+                report.null_tokens += 1;
+            } else {
+                report.filled += 1;
+            }
+        }
+    }
+    if !rows.is_empty() {
+        for report in &mut reports {
+            report.filled_percent = report.filled as f64 * 100.0 / rows.len() as f64;
+        }
+    }
+    Ok(reports)
+}
+
+const MAX_DATE_BUCKETS: usize = 60;
+
+/// One histogram bar. For date columns `start`/`end` are Unix milliseconds.
+#[derive(Debug, Clone, Serialize)]
+pub struct HistogramBin {
+    pub label: String,
+    pub start: f64,
+    pub end: f64,
+    pub count: usize,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Histogram {
+    pub column: String,
+    pub kind: String,
+    pub bins: Vec<HistogramBin>,
+    pub nulls: usize,
+}
+
+/// Bin a numeric or date column. Numeric columns default to Sturges' rule
+/// for the bin count; date columns are bucketed by the finest calendar unit
+/// that fits within `bins` (or 60) buckets.
+#[tauri::command(async)]
+#[allow(clippy::too_many_arguments)]
+pub fn histogram(
+    app: AppHandle,
+    store: State<'_, DatasetStore>,
+    ops: State<'_, Operations>,
+    handle: u64,
+    column: String,
+    bins: Option<usize>,
+    operation_id: Option<String>,
+    timeout_ms: Option<u64>,
+) -> Result<Histogram, AppError> {
+    let _timed = logging::timed("histogram");
+    let op = ops.start(&app, operation_id, timeout_ms)?;
+    Ok(store.with_cached_unlocked(
+        handle,
+        cache::key("histogram", &(&column, bins)),
+        |ds| copy_column(ds, &column),
+        |cells| {
+            let cells = cells?;
+            let values = present(&cells);
+            let nulls = cells.len() - values.len();
+            if values.is_empty() {
+                return Err(format!("Column has no values: {}", column));
+            }
+            op.check_now()?;
+
+            let numbers: Option<Vec<f64>> = values.iter().map(|v| parse_number(v)).collect();
+            op.check_now()?;
+            if let Some(numbers) = numbers {
+                let bins = bins.unwrap_or_else(|| (numbers.len() as f64).log2().ceil() as usize + 1);
+                return Ok(Histogram {
+                    column: column.clone(),
+                    kind: "numeric".to_string(),
+                    bins: numeric_bins(&numbers, bins.clamp(1, 1000)),
+                    nulls,
+                });
+            }
+
+            let dates: Option<Vec<_>> = values.iter().map(|v| dates::parse_datetime(v)).collect();
+            op.check_now()?;
+            if let Some(dates) = dates {
+                return Ok(Histogram {
+                    column: column.clone(),
+                    kind: "date".to_string(),
+                    bins: date_bins(&dates, bins.unwrap_or(MAX_DATE_BUCKETS).max(1)),
+                    nulls,
+                });
+            }
+
+            Err(format!("Column is neither numeric nor a date: {}", column))
+        },
+    )??)
+}
+
+fn numeric_bins(numbers: &[f64], bins: usize) -> Vec<HistogramBin> {
+    let min = numbers.iter().copied().fold(f64::INFINITY, f64::min);
+    let max = numbers.iter().copied().fold(f64::NEG_INFINITY, f64::max);
+    // A constant column gets one bin rather than zero-width ones
+    let bins = if min == max { 1 } else { bins };
+    let width = if min == max { 1.0 } else { (max - min) / bins as f64 };
+
+    let mut counts = vec![0usize; bins];
+    for &n in numbers {
+        let i = (((n - min) / width) as usize).min(bins - 1);
+        counts[i] += 1;
+    }
+
+    counts
+        .into_iter()
+        .enumerate()
+        .map(|(i, count)| {
+            let start = min + width * i as f64;
+            let end = if i + 1 == bins { max.max(start) } else { start + width };
+            HistogramBin {
+                label: format!("{} – {}", start, end),
+                start,
+                end,
+                count,
+            }
+        })
+        .collect()
+}
+
+fn date_bins(values: &[chrono::NaiveDateTime], max_buckets: usize) -> Vec<HistogramBin> {
+    let min = *values.iter().min().unwrap();
+    let max = *values.iter().max().unwrap();
+    let unit = TimeUnit::choose(min, max, max_buckets);
+
+    let mut counts: BTreeMap<chrono::NaiveDateTime, usize> = BTreeMap::new();
+    for &dt in values {
+        *counts.entry(unit.truncate(dt)).or_insert(0) += 1;
+    }
+
+    // Walk every bucket in range so gaps show up as zero-height bars
+    let mut bins = Vec::new();
+    let mut start = unit.truncate(min);
+    while start <= max {
+        let end = unit.next(start);
+        bins.push(HistogramBin {
+            label: unit.label(start),
+            start: start.and_utc().timestamp_millis() as f64,
+            end: end.and_utc().timestamp_millis() as f64,
+            count: counts.get(&start).copied().unwrap_or(0),
+        });
+        start = end;
+    }
+    bins
+}
+
+/// Columns larger than this use the streaming estimator unless exact mode is requested
+const EXACT_PERCENTILE_LIMIT: usize = 1_000_000;
+
+#[derive(Debug, Clone, Serialize)]
+pub struct PercentileValue {
+    pub percentile: f64,
+    pub value: Option<f64>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Percentiles {
+    pub column: String,
+    pub count: usize,
+    pub exact: bool,
+    pub values: Vec<PercentileValue>,
+}
+
+/// Arbitrary percentiles (0-100) of a numeric column. Small columns are
+/// sorted for exact answers; large ones are streamed through P² estimators
+/// so memory stays constant regardless of column size.
+#[tauri::command(async)]
+#[allow(clippy::too_many_arguments)]
+pub fn percentiles(
+    app: AppHandle,
+    store: State<'_, DatasetStore>,
+    ops: State<'_, Operations>,
+    handle: u64,
+    column: String,
+    percentiles: Vec<f64>,
+    exact: Option<bool>,
+    operation_id: Option<String>,
+    timeout_ms: Option<u64>,
+) -> Result<Percentiles, AppError> {
+    let _timed = logging::timed("percentiles");
+    if let Some(p) = percentiles.iter().find(|p| !(0.0..=100.0).contains(*p)) {
+        return Err(format!("Percentile out of range (0-100): {}", p).into());
+    }
+
+    let op = ops.start(&app, operation_id, timeout_ms)?;
+    Ok(store.with_cached_unlocked(
+        handle,
+        cache::key("percentiles", &(&column, &percentiles, exact)),
+        |ds| copy_column(ds, &column),
+        |cells| {
+            let cells = cells?;
+            let numbers = present(&cells).into_iter().filter_map(parse_number);
+            let exact = exact.unwrap_or(cells.len() <= EXACT_PERCENTILE_LIMIT);
+
+            let (count, estimates) = if exact {
+                let mut sorted: Vec<f64> = numbers.collect();
+                op.check_now()?;
+                sorted.sort_by(|a, b| a.total_cmp(b));
+                let estimates = percentiles
+                    .iter()
+                    .map(|p| (!sorted.is_empty()).then(|| exact_quantile(&sorted, p / 100.0)))
+                    .collect();
+                (sorted.len(), estimates)
+            } else {
+                let mut estimators: Vec<P2Estimator> =
+                    percentiles.iter().map(|p| P2Estimator::new(p / 100.0)).collect();
+                let mut count = 0;
+                for n in numbers {
+                    op.check(count)?;
+                    count += 1;
+                    estimators.iter_mut().for_each(|e| e.add(n));
+                }
+                (count, estimators.iter().map(|e| e.estimate()).collect::<Vec<_>>())
+            };
+
+            Ok::<_, String>(Percentiles {
+                column: column.clone(),
+                count,
+                exact,
+                values: percentiles
+                    .iter()
+                    .zip(estimates)
+                    .map(|(&percentile, value)| PercentileValue { percentile, value })
+                    .collect(),
+            })
+        },
+    )??)
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct CorrelationMatrix {
+    pub method: String,
+    pub columns: Vec<String>,
+    /// `matrix[i][j]` is the correlation between `columns[i]` and `columns[j]`,
+    /// or null when fewer than two rows have both values or one side is constant
+    pub matrix: Vec<Vec<Option<f64>>>,
+}
+
+/// Pairwise correlations between all numeric columns, using only the rows
+/// where both values are present. `method` is "pearson" (default) or "spearman".
+#[tauri::command(async)]
+pub fn correlations(
+    app: AppHandle,
+    store: State<'_, DatasetStore>,
+    ops: State<'_, Operations>,
+    handle: u64,
+    method: Option<String>,
+    operation_id: Option<String>,
+    timeout_ms: Option<u64>,
+) -> Result<CorrelationMatrix, AppError> {
+    let _timed = logging::timed("correlations");
+    let method = method.unwrap_or_else(|| "pearson".to_string());
+    let spearman = match method.as_str() {
+        "pearson" => false,
+        "spearman" => true,
+        other => return Err(format!("Unknown correlation method: {}", other).into()),
+    };
+
+    let op = ops.start(&app, operation_id, timeout_ms)?;
+    Ok(store.with_cached_unlocked(
+        handle,
+        cache::key("correlations", &method),
+        |ds| (ds.headers.clone(), ds.rows.clone()),
+        |(headers, rows)| {
+            // A column counts as numeric when it has values and every one parses
+            let mut columns = Vec::new();
+            let mut parsed: Vec<Vec<Option<f64>>> = Vec::new();
+            for (c, name) in headers.into_iter().enumerate() {
+                op.check_now()?;
+                let values: Option<Vec<Option<f64>>> = rows
+                    .iter()
+                    .map(|row| match row.get(c).map_or("", String::as_str) {
+                        v if is_null(v) => Some(None),
+                        v => parse_number(v).map(Some),
+                    })
+                    .collect();
+                if let Some(values) = values.filter(|v| v.iter().any(Option::is_some)) {
+                    columns.push(name);
+                    parsed.push(values);
+                }
+            }
+
+            let n = columns.len();
+            let mut matrix = vec![vec![None; n]; n];
+            for i in 0..n {
+                for j in i..n {
+                    op.check_now()?;
+                    let (mut xs, mut ys): (Vec<f64>, Vec<f64>) = parsed[i]
+                        .iter()
+                        .zip(&parsed[j])
+                        .filter_map(|(x, y)| Some(((*x)?, (*y)?)))
+                        .unzip();
+                    if spearman {
+                        xs = ranks(&xs);
+                        ys = ranks(&ys);
+                    }
+                    let r = pearson(&xs, &ys);
+                    matrix[i][j] = r;
+                    matrix[j][i] = r;
+                }
+            }
+
+            Ok::<_, String>(CorrelationMatrix {
+                method: method.clone(),
+                columns,
+                matrix,
+            })
+        },
+    )??)
+}
+
+fn pearson(xs: &[f64], ys: &[f64]) -> Option<f64> {
+    if xs.len() < 2 {
+        return None;
+    }
+    let n = xs.len() as f64;
+    let mean_x = xs.iter().sum::<f64>() / n;
+    let mean_y = ys.iter().sum::<f64>() / n;
+    let (mut cov, mut var_x, mut var_y) = (0.0, 0.0, 0.0);
+    for (x, y) in xs.iter().zip(ys) {
+        cov += (x - mean_x) * (y - mean_y);
+        var_x += (x - mean_x).powi(2);
+        var_y += (y - mean_y).powi(2);
+    }
+    if var_x == 0.0 || var_y == 0.0 {
+        return None;
+    }
+    Some(cov / (var_x.sqrt() * var_y.sqrt()))
+}
+
+/// Convert values to 1-based ranks, giving tied values their average rank
+fn ranks(values: &[f64]) -> Vec<f64> {
+    let mut order: Vec<usize> = (0..values.len()).collect();
+    order.sort_by(|&a, &b| values[a].total_cmp(&values[b]));
+
+    let mut ranks = vec![0.0; values.len()];
+    let mut start = 0;
+    while start < order.len() {
+        let mut end = start + 1;
+        while end < order.len() && values[order[end]] == values[order[start]] {
+            end += 1;
+        }
+        let rank = (start + end + 1) as f64 / 2.0;
+        for &i in &order[start..end] {
+            ranks[i] = rank;
+        }
+        start = end;
+    }
+    ranks
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct DistinctCount {
+    pub column: String,
+    pub distinct: usize,
+    pub exact: bool,
+}
+
+/// Distinct non-null values in a column. Large columns use a HyperLogLog
+/// estimate unless `exact` is set.
+#[tauri::command(async)]
+#[allow(clippy::too_many_arguments)]
+pub fn distinct_count_column(
+    app: AppHandle,
+    store: State<'_, DatasetStore>,
+    ops: State<'_, Operations>,
+    handle: u64,
+    column: String,
+    exact: Option<bool>,
+    operation_id: Option<String>,
+    timeout_ms: Option<u64>,
+) -> Result<DistinctCount, AppError> {
+    let _timed = logging::timed("distinct_count_column");
+    let op = ops.start(&app, operation_id, timeout_ms)?;
+    Ok(store.with_cached_unlocked(
+        handle,
+        cache::key("distinct_count", &(&column, exact)),
+        |ds| copy_column(ds, &column),
+        |cells| {
+            let cells = cells?;
+            op.check_now()?;
+            let (distinct, exact) = distinct_count(&present(&cells), exact);
+            Ok::<_, String>(DistinctCount {
+                column: column.clone(),
+                distinct,
+                exact,
+            })
+        },
+    )??)
+}
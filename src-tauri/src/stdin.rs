@@ -0,0 +1,186 @@
+use crate::dataset::{Dataset, DatasetStore, FileData, ParseFailure};
+use crate::error::AppError;
+use serde::Serialize;
+use std::io::{self, BufRead, Read};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Emitter, Manager, State};
+
+/// Event emitted as rows arrive on stdin
+pub const STDIN_ROWS_EVENT: &str = "stdin-rows";
+/// Emit at most this often while rows stream in
+const EMIT_INTERVAL: Duration = Duration::from_millis(250);
+
+/// Handle of the dataset being filled from stdin, if the app was started with `--stdin`
+#[derive(Default)]
+pub struct StdinDataset(Mutex<Option<u64>>);
+
+#[derive(Debug, Clone, Serialize)]
+pub struct StdinRows {
+    pub handle: u64,
+    pub row_count: usize,
+    /// The stream has ended; no more rows will arrive
+    pub done: bool,
+    /// Why a record was just skipped, or why reading stopped
+    pub error: Option<String>,
+}
+
+/// Read `--stdin [--format jsonl|csv|json]`; `Ok(None)` when `--stdin` wasn't given
+pub fn parse_args(args: &[String]) -> Result<Option<String>, String> {
+    if !args.iter().any(|a| a == "--stdin") {
+        return Ok(None);
+    }
+    let format = match args.iter().position(|a| a == "--format") {
+        Some(i) => args.get(i + 1).cloned().ok_or("--format needs a value")?,
+        None => "jsonl".to_string(),
+    };
+    if !matches!(format.as_str(), "jsonl" | "csv" | "json") {
+        return Err(format!("Unsupported stdin format: {}", format));
+    }
+    Ok(Some(format))
+}
+
+/// Append a JSONL record, adding columns for keys not seen before
+fn append_object(ds: &mut Dataset, object: &serde_json::Map<String, serde_json::Value>) {
+    let columns = ds.headers.len();
+    for key in object.keys() {
+        if !ds.headers.contains(key) {
+            ds.headers.push(key.clone());
+        }
+    }
+    if ds.headers.len() > columns {
+        for row in &mut ds.rows {
+            row.resize(ds.headers.len(), String::new());
+        }
+    }
+    let row = ds
+        .headers
+        .iter()
+        .map(|h| object.get(h).map(crate::json_cell).unwrap_or_default())
+        .collect();
+    ds.rows.push(row);
+}
+
+/// Read stdin to the end, appending rows to the dataset as they arrive
+fn pump(app: &AppHandle, handle: u64, format: &str) -> Result<(), String> {
+    let store = app.state::<DatasetStore>();
+    let mut last_emit = Instant::now();
+    let notify = |done: bool, error: Option<String>, last_emit: &mut Instant| {
+        if done || error.is_some() || last_emit.elapsed() >= EMIT_INTERVAL {
+            *last_emit = Instant::now();
+            let row_count = store.with(handle, |ds| ds.rows.len()).unwrap_or(0);
+            let _ = app.emit(STDIN_ROWS_EVENT, StdinRows { handle, row_count, done, error });
+        }
+    };
+
+    match format {
+        "jsonl" => {
+            for (i, line) in io::stdin().lock().lines().enumerate() {
+                let line = line.map_err(|e| format!("Failed to read stdin: {}", e))?;
+                if line.trim().is_empty() {
+                    continue;
+                }
+                // A bad line is skipped and reported; the rest keeps streaming
+                let parsed = match serde_json::from_str::<serde_json::Value>(&line) {
+                    Ok(serde_json::Value::Object(object)) => Ok(object),
+                    Ok(_) => Err(format!("Line {} is not a JSON object", i + 1)),
+                    Err(e) => Err(format!("Failed to parse line {}: {}", i + 1, e)),
+                };
+                match parsed {
+                    Ok(object) => {
+                        store.with_mut(handle, |ds| append_object(ds, &object))?;
+                        notify(false, None, &mut last_emit);
+                    }
+                    Err(error) => {
+                        store.with_mut(handle, |ds| {
+                            ds.parse_failures.push(ParseFailure {
+                                line: i as u64 + 1,
+                                text: line,
+                                error: error.clone(),
+                            })
+                        })?;
+                        notify(false, Some(error), &mut last_emit);
+                    }
+                }
+            }
+        }
+        "csv" => {
+            let mut reader = csv::ReaderBuilder::new().flexible(true).from_reader(io::stdin().lock());
+            let headers: Vec<String> = reader
+                .headers()
+                .map_err(|e| format!("Failed to read headers: {}", e))?
+                .iter()
+                .map(|s| s.to_string())
+                .collect();
+            store.with_mut(handle, |ds| ds.headers = headers)?;
+            for record in reader.records() {
+                let record = record.map_err(|e| format!("Failed to read record: {}", e))?;
+                store.with_mut(handle, |ds| ds.rows.push(record.iter().map(|s| s.to_string()).collect()))?;
+                notify(false, None, &mut last_emit);
+            }
+        }
+        _ => {
+            // A JSON document can only be parsed once it is complete
+            let mut content = String::new();
+            io::stdin()
+                .read_to_string(&mut content)
+                .map_err(|e| format!("Failed to read stdin: {}", e))?;
+            let parsed = serde_json::from_str(&content).map_err(|e| format!("Failed to parse JSON: {}", e))?;
+            let parsed = crate::json_to_dataset(parsed)?;
+            store.with_mut(handle, |ds| {
+                ds.headers = parsed.headers;
+                ds.rows = parsed.rows;
+            })?;
+        }
+    }
+    store.with_mut(handle, |ds| ds.loading = false)?;
+    notify(true, None, &mut last_emit);
+    Ok(())
+}
+
+/// Create the stdin dataset and start filling it on a background thread
+pub fn start(app: &AppHandle, format: &str) {
+    let handle = app.state::<DatasetStore>().insert(Dataset {
+        file_name: "stdin".to_string(),
+        file_type: format.to_ascii_uppercase(),
+        loading: true,
+        ..Default::default()
+    });
+    *app.state::<StdinDataset>().0.lock().unwrap() = Some(handle);
+
+    let app = app.clone();
+    let format = format.to_string();
+    std::thread::spawn(move || {
+        if let Err(e) = pump(&app, handle, &format) {
+            tracing::error!("{}", e);
+            let row_count = app
+                .state::<DatasetStore>()
+                .with_mut(handle, |ds| {
+                    ds.loading = false;
+                    ds.rows.len()
+                })
+                .unwrap_or(0);
+            let _ = app.emit(
+                STDIN_ROWS_EVENT,
+                StdinRows {
+                    handle,
+                    row_count,
+                    done: true,
+                    error: Some(e),
+                },
+            );
+        }
+    });
+}
+
+/// The dataset read from stdin so far, if the app was started with `--stdin`
+#[tauri::command]
+pub fn get_stdin_dataset(
+    store: State<'_, DatasetStore>,
+    stdin: State<'_, StdinDataset>,
+) -> Result<Option<FileData>, AppError> {
+    match *stdin.0.lock().unwrap() {
+        Some(handle) => Ok(store.with(handle, |ds| Some(ds.to_file_data(handle)))?),
+        None => Ok(None),
+    }
+}
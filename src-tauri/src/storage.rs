@@ -0,0 +1,39 @@
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use std::fs;
+use std::path::PathBuf;
+use tauri::{AppHandle, Manager};
+
+fn store_path(app: &AppHandle, file_name: &str) -> Result<PathBuf, String> {
+    let dir = app
+        .path()
+        .app_data_dir()
+        .map_err(|e| format!("Failed to locate app data directory: {}", e))?;
+    fs::create_dir_all(&dir).map_err(|e| format!("Failed to create app data directory: {}", e))?;
+    Ok(dir.join(file_name))
+}
+
+/// A subdirectory of the app data directory, created if needed
+pub fn dir(app: &AppHandle, name: &str) -> Result<PathBuf, String> {
+    let dir = store_path(app, name)?;
+    fs::create_dir_all(&dir).map_err(|e| format!("Failed to create {} directory: {}", name, e))?;
+    Ok(dir)
+}
+
+/// Load a JSON document from the app data directory, falling back to the
+/// default value when it doesn't exist yet or can't be read
+pub fn load<T: DeserializeOwned + Default>(app: &AppHandle, file_name: &str) -> T {
+    store_path(app, file_name)
+        .ok()
+        .and_then(|path| fs::read_to_string(path).ok())
+        .and_then(|content| serde_json::from_str(&content).ok())
+        .unwrap_or_default()
+}
+
+/// Write a JSON document to the app data directory
+pub fn save<T: Serialize>(app: &AppHandle, file_name: &str, value: &T) -> Result<(), String> {
+    let path = store_path(app, file_name)?;
+    let json = serde_json::to_string_pretty(value)
+        .map_err(|e| format!("Failed to serialize {}: {}", file_name, e))?;
+    fs::write(&path, json).map_err(|e| format!("Failed to write {}: {}", file_name, e))
+}
@@ -0,0 +1,80 @@
+use crate::dates;
+use crate::stats::{is_null, parse_number};
+use serde::{Deserialize, Serialize};
+
+/// Inferred type of a column's values
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ColumnType {
+    /// No non-null values seen yet
+    #[default]
+    Empty,
+    Integer,
+    #[serde(alias = "number")]
+    Float,
+    Boolean,
+    Date,
+    String,
+}
+
+pub fn parse_bool(value: &str) -> Option<bool> {
+    match value.trim().to_ascii_lowercase().as_str() {
+        "true" | "yes" => Some(true),
+        "false" | "no" => Some(false),
+        _ => None,
+    }
+}
+
+/// Type of a single cell
+pub fn value_type(value: &str) -> ColumnType {
+    let trimmed = value.trim();
+    if is_null(trimmed) {
+        ColumnType::Empty
+    } else if trimmed.parse::<i64>().is_ok() {
+        ColumnType::Integer
+    } else if parse_number(trimmed).is_some() {
+        ColumnType::Float
+    } else if parse_bool(trimmed).is_some() {
+        ColumnType::Boolean
+    } else if dates::parse_datetime(trimmed).is_some() {
+        ColumnType::Date
+    } else {
+        ColumnType::String
+    }
+}
+
+impl ColumnType {
+    /// Widen a running column type to also cover `other`
+    pub fn merge(self, other: ColumnType) -> ColumnType {
+        use ColumnType::*;
+        match (self, other) {
+            (a, b) if a == b => a,
+            (Empty, t) | (t, Empty) => t,
+            (Integer, Float) | (Float, Integer) => Float,
+            _ => String,
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            ColumnType::Empty => "empty",
+            ColumnType::Integer => "integer",
+            ColumnType::Float => "number",
+            ColumnType::Boolean => "boolean",
+            ColumnType::Date => "date",
+            ColumnType::String => "text",
+        }
+    }
+
+    /// Whether a cell fits in a column of this type (nulls always do)
+    pub fn accepts(self, value: &str) -> bool {
+        self.merge(value_type(value)) == self
+    }
+}
+
+/// Infer a column type from its cell values
+pub fn infer_type<'a>(values: impl IntoIterator<Item = &'a str>) -> ColumnType {
+    values
+        .into_iter()
+        .fold(ColumnType::Empty, |t, v| t.merge(value_type(v)))
+}
@@ -0,0 +1,151 @@
+use crate::dataset::{Dataset, DatasetStore, FileData};
+use crate::history;
+use crate::stats::is_null;
+use crate::error::AppError;
+use std::collections::HashMap;
+use tauri::{AppHandle, State};
+
+/// Data size suffixes and their size in bytes (binary multiples, as most tools report them)
+const BYTE_UNITS: &[(&str, f64)] = &[
+    ("B", 1.0),
+    ("KB", 1024.0),
+    ("MB", 1_048_576.0),
+    ("GB", 1_073_741_824.0),
+    ("TB", 1_099_511_627_776.0),
+];
+
+fn byte_factor(unit: &str) -> Option<f64> {
+    let unit = unit.to_ascii_uppercase().replace("IB", "B");
+    BYTE_UNITS.iter().find(|(u, _)| *u == unit).map(|(_, f)| *f)
+}
+
+/// Split a value like "$1,234.50", "(45.00 EUR)", "12.3 MB", or "45%" into
+/// its number and unit. `decimal_comma` reads "1.234,50" European-style.
+fn parse_quantity(value: &str, decimal_comma: bool) -> Option<(f64, String)> {
+    let mut s = value.trim();
+    let mut negative = false;
+    if let Some(inner) = s.strip_prefix('(').and_then(|v| v.strip_suffix(')')) {
+        negative = true;
+        s = inner.trim();
+    }
+    if let Some(rest) = s.strip_prefix('-') {
+        negative = !negative;
+        s = rest.trim_start();
+    }
+
+    let is_numeric = |c: char| c.is_ascii_digit() || matches!(c, '.' | ',' | '\'' | ' ' | '\u{a0}' | '\u{202f}');
+    let start = s.find(|c: char| c.is_ascii_digit())?;
+    let end = s[start..]
+        .find(|c: char| !is_numeric(c))
+        .map_or(s.len(), |i| start + i);
+    let (prefix, core, suffix) = (&s[..start], s[start..end].trim(), s[end..].trim());
+
+    // A minus sign may also sit between a currency symbol and the number ("$-5")
+    let prefix = match prefix.trim().strip_suffix('-') {
+        Some(p) => {
+            negative = !negative;
+            p
+        }
+        None => prefix,
+    };
+    let (group, decimal) = if decimal_comma { ('.', ',') } else { (',', '.') };
+    let number: String = core
+        .chars()
+        .filter(|&c| c != group && !matches!(c, '\'' | ' ' | '\u{a0}' | '\u{202f}'))
+        .map(|c| if c == decimal { '.' } else { c })
+        .collect();
+    let n = number.parse::<f64>().ok()?;
+
+    let unit = [prefix.trim(), suffix]
+        .into_iter()
+        .filter(|u| !u.is_empty())
+        .collect::<Vec<_>>()
+        .join(" ");
+    if unit.chars().any(|c| c.is_ascii_digit()) {
+        return None;
+    }
+    Some((if negative { -n } else { n }, unit))
+}
+
+/// Turn formatted amounts and measurements into plain numbers. The unit found
+/// is recorded in the dataset's `units`; data sizes in mixed units (KB, MB, …)
+/// are scaled to the most common one. Values with other units, or that don't
+/// parse, are left as they were.
+#[tauri::command]
+pub fn normalize_units(
+    app: AppHandle,
+    store: State<'_, DatasetStore>,
+    handle: u64,
+    columns: Vec<String>,
+    decimal_comma: Option<bool>,
+) -> Result<FileData, AppError> {
+    let decimal_comma = decimal_comma.unwrap_or(false);
+    if columns.is_empty() {
+        return Err("Select at least one column to normalize".into());
+    }
+
+    let mut result = store.with(handle, |ds| {
+        let mut rows = ds.rows.clone();
+        let mut units = ds.units.clone();
+        for column in &columns {
+            let col = ds.column_index(column)?;
+            let parsed: Vec<Option<(f64, String)>> = ds
+                .rows
+                .iter()
+                .map(|row| {
+                    let value = row.get(col).map(|v| v.as_str()).unwrap_or("");
+                    if is_null(value) { None } else { parse_quantity(value, decimal_comma) }
+                })
+                .collect();
+
+            // The column's unit is the one used most often
+            let mut counts: HashMap<&str, usize> = HashMap::new();
+            for (_, unit) in parsed.iter().flatten() {
+                *counts.entry(unit.as_str()).or_insert(0) += 1;
+            }
+            let Some(unit) = counts
+                .into_iter()
+                .max_by(|a, b| a.1.cmp(&b.1).then_with(|| b.0.cmp(a.0)))
+                .map(|(u, _)| u.to_string())
+            else {
+                continue;
+            };
+            let target_bytes = byte_factor(&unit);
+
+            for (row, quantity) in rows.iter_mut().zip(&parsed) {
+                let Some((n, value_unit)) = quantity else { continue };
+                let converted = if *value_unit == unit || value_unit.is_empty() {
+                    Some(*n)
+                } else {
+                    target_bytes
+                        .zip(byte_factor(value_unit))
+                        .map(|(target, factor)| n * factor / target)
+                };
+                if let Some(n) = converted {
+                    row[col] = n.to_string();
+                }
+            }
+            if !unit.is_empty() {
+                units.insert(column.clone(), unit);
+            }
+        }
+
+        Ok::<_, String>(Dataset {
+            headers: ds.headers.clone(),
+            rows,
+            file_name: format!("{} (units normalized)", ds.file_name),
+            file_type: ds.file_type.clone(),
+            units,
+            ..Default::default()
+        })
+    })??;
+    history::record_transform(
+        &app,
+        &store,
+        handle,
+        &mut result,
+        "normalize_units",
+        serde_json::json!({ "columns": columns, "decimal_comma": decimal_comma }),
+    );
+    Ok(store.register(result))
+}
@@ -0,0 +1,474 @@
+use crate::access;
+use crate::dataset::{Dataset, DatasetStore, FileData};
+use crate::stats::{is_null, parse_number};
+use crate::types::ColumnType;
+use crate::error::AppError;
+use chrono::{DateTime, NaiveDate, NaiveDateTime};
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::hash_map::{Entry, HashMap};
+use std::fs;
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
+use tauri::{AppHandle, State};
+
+/// Stop collecting violations past this many; the counts keep going
+const MAX_VIOLATIONS: usize = 10_000;
+
+/// Checks for one column, from a JSON Schema property or a column-spec entry
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct ColumnRules {
+    pub name: String,
+    /// "string", "integer", "number", "boolean", or "date"
+    #[serde(rename = "type")]
+    pub kind: Option<String>,
+    /// Every row must have a value
+    pub required: bool,
+    pub pattern: Option<String>,
+    pub minimum: Option<f64>,
+    pub maximum: Option<f64>,
+    pub min_length: Option<usize>,
+    pub max_length: Option<usize>,
+    #[serde(rename = "enum")]
+    pub allowed: Option<Vec<String>>,
+    /// No two rows may share a value
+    pub unique: bool,
+    /// One of the built-in validators; see `list_validators`
+    pub format: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Violation {
+    /// Index of the offending row; `None` for problems with the columns themselves
+    pub row: Option<usize>,
+    pub column: String,
+    pub rule: String,
+    pub message: String,
+    pub value: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct RuleCount {
+    pub column: String,
+    pub rule: String,
+    pub count: usize,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ValidationReport {
+    pub violations: Vec<Violation>,
+    /// Violations found, including any past the ones returned
+    pub violation_count: usize,
+    /// Violations per column and rule, over all of them
+    pub counts: Vec<RuleCount>,
+    pub invalid_rows: usize,
+    pub total_rows: usize,
+}
+
+impl ValidationReport {
+    fn push(&mut self, violation: Violation) {
+        self.violation_count += 1;
+        match self
+            .counts
+            .iter_mut()
+            .find(|c| c.column == violation.column && c.rule == violation.rule)
+        {
+            Some(count) => count.count += 1,
+            None => self.counts.push(RuleCount {
+                column: violation.column.clone(),
+                rule: violation.rule.clone(),
+                count: 1,
+            }),
+        }
+        if self.violations.len() < MAX_VIOLATIONS {
+            self.violations.push(violation);
+        }
+    }
+}
+
+/// Built-in validators: name, description, and the JSON Schema formats they cover
+const VALIDATORS: &[(&str, &str, &[&str])] = &[
+    ("email", "Email address", &["email"]),
+    ("url", "http(s) or other absolute URL", &["uri", "url"]),
+    ("uuid", "UUID, e.g. 123e4567-e89b-12d3-a456-426614174000", &["uuid"]),
+    ("ip", "IPv4 or IPv6 address", &[]),
+    ("ipv4", "IPv4 address", &["ipv4"]),
+    ("ipv6", "IPv6 address", &["ipv6"]),
+    ("iso_date", "ISO 8601 date, e.g. 2024-03-31", &["date"]),
+    ("iso_datetime", "ISO 8601 date and time, e.g. 2024-03-31T14:05:00Z", &["date-time"]),
+];
+
+/// A built-in validator, with any regex it needs compiled
+enum Format {
+    Email(Regex),
+    Url,
+    Uuid(Regex),
+    Ip,
+    Ipv4,
+    Ipv6,
+    IsoDate,
+    IsoDateTime,
+}
+
+impl Format {
+    fn new(name: &str) -> Option<Format> {
+        let regex = |pattern: &str| Regex::new(pattern).expect("built-in validator regex");
+        Some(match name {
+            "email" => Format::Email(regex(r"^[A-Za-z0-9._%+-]+@[A-Za-z0-9-]+(\.[A-Za-z0-9-]+)*\.[A-Za-z]{2,}$")),
+            "url" => Format::Url,
+            "uuid" => Format::Uuid(regex(r"^(?i)[0-9a-f]{8}-[0-9a-f]{4}-[0-9a-f]{4}-[0-9a-f]{4}-[0-9a-f]{12}$")),
+            "ip" => Format::Ip,
+            "ipv4" => Format::Ipv4,
+            "ipv6" => Format::Ipv6,
+            "iso_date" => Format::IsoDate,
+            "iso_datetime" => Format::IsoDateTime,
+            _ => return None,
+        })
+    }
+
+    fn accepts(&self, value: &str) -> bool {
+        let value = value.trim();
+        match self {
+            Format::Email(regex) | Format::Uuid(regex) => regex.is_match(value),
+            Format::Url => url::Url::parse(value).is_ok_and(|url| url.has_host() || url.scheme() == "file"),
+            Format::Ip => value.parse::<IpAddr>().is_ok(),
+            Format::Ipv4 => value.parse::<Ipv4Addr>().is_ok(),
+            Format::Ipv6 => value.parse::<Ipv6Addr>().is_ok(),
+            Format::IsoDate => NaiveDate::parse_from_str(value, "%Y-%m-%d").is_ok(),
+            Format::IsoDateTime => {
+                DateTime::parse_from_rfc3339(value).is_ok()
+                    || NaiveDateTime::parse_from_str(value, "%Y-%m-%dT%H:%M:%S%.f").is_ok()
+            }
+        }
+    }
+}
+
+/// Our validator for a JSON Schema "format"; unknown formats are ignored, as the spec allows
+fn schema_format(format: &str) -> Option<String> {
+    VALIDATORS
+        .iter()
+        .find(|(_, _, formats)| formats.contains(&format))
+        .map(|(name, _, _)| name.to_string())
+}
+
+/// A schema ready to run against a dataset
+struct Compiled {
+    rules: ColumnRules,
+    col: Option<usize>,
+    pattern: Option<Regex>,
+    format: Option<Format>,
+}
+
+/// JSON Schema type names mapped to ours; "null" only matters for required-ness
+fn schema_type(value: &Value) -> (Option<String>, bool) {
+    let types: Vec<&str> = match value {
+        Value::String(t) => vec![t.as_str()],
+        Value::Array(ts) => ts.iter().filter_map(|t| t.as_str()).collect(),
+        _ => Vec::new(),
+    };
+    let nullable = types.contains(&"null");
+    let kind = types.into_iter().find(|t| *t != "null").map(|t| t.to_string());
+    (kind, nullable)
+}
+
+/// Column rules from a JSON Schema describing one row as an object
+fn from_json_schema(schema: &Value) -> Result<(Vec<ColumnRules>, bool), String> {
+    let properties = schema
+        .get("properties")
+        .and_then(|p| p.as_object())
+        .ok_or("JSON Schema must have an object of \"properties\"")?;
+    let required: Vec<&str> = schema
+        .get("required")
+        .and_then(|r| r.as_array())
+        .map(|r| r.iter().filter_map(|v| v.as_str()).collect())
+        .unwrap_or_default();
+
+    let rules = properties
+        .iter()
+        .map(|(name, property)| {
+            let (kind, nullable) = property.get("type").map(schema_type).unwrap_or_default();
+            let number = |key: &str| property.get(key).and_then(|v| v.as_f64());
+            let length = |key: &str| property.get(key).and_then(|v| v.as_u64()).map(|n| n as usize);
+            ColumnRules {
+                name: name.clone(),
+                kind,
+                required: required.contains(&name.as_str()) && !nullable,
+                pattern: property.get("pattern").and_then(|p| p.as_str()).map(str::to_string),
+                minimum: number("minimum"),
+                maximum: number("maximum"),
+                min_length: length("minLength"),
+                max_length: length("maxLength"),
+                unique: false,
+                format: property.get("format").and_then(|f| f.as_str()).and_then(schema_format),
+                // Cells are text, so compare enum members as text too
+                allowed: property.get("enum").and_then(|e| e.as_array()).map(|values| {
+                    values.iter().map(|v| v.as_str().map(str::to_string).unwrap_or_else(|| crate::json_cell(v))).collect()
+                }),
+            }
+        })
+        .collect();
+    let closed = schema.get("additionalProperties") == Some(&Value::Bool(false));
+    Ok((rules, closed))
+}
+
+/// Read a JSON Schema, or a column-spec file: an array of `ColumnRules`, or
+/// an object with such an array under "columns". Also returns whether
+/// columns outside the schema are an error.
+fn read_schema(path: &str) -> Result<(Vec<ColumnRules>, bool), String> {
+    let content = fs::read_to_string(path).map_err(|e| format!("Failed to read schema: {}", e))?;
+    let schema: Value = serde_json::from_str(&content).map_err(|e| format!("Failed to parse schema: {}", e))?;
+    let columns = match &schema {
+        Value::Object(map) if map.contains_key("properties") => return from_json_schema(&schema),
+        Value::Object(map) => map.get("columns").cloned().ok_or("Schema must have \"properties\" or \"columns\"")?,
+        _ => schema,
+    };
+    let rules = serde_json::from_value(columns).map_err(|e| format!("Invalid column spec: {}", e))?;
+    Ok((rules, false))
+}
+
+fn compile(ds: &Dataset, rules: Vec<ColumnRules>) -> Result<Vec<Compiled>, String> {
+    rules
+        .into_iter()
+        .map(|rules| {
+            let pattern = rules
+                .pattern
+                .as_deref()
+                .map(|p| Regex::new(p).map_err(|e| format!("Invalid pattern for {}: {}", rules.name, e)))
+                .transpose()?;
+            if let Some(kind) = &rules.kind {
+                column_type(kind).ok_or_else(|| format!("Unknown type for {}: {}", rules.name, kind))?;
+            }
+            let format = rules
+                .format
+                .as_deref()
+                .map(|f| Format::new(f).ok_or_else(|| format!("Unknown validator for {}: {}", rules.name, f)))
+                .transpose()?;
+            let col = ds.headers.iter().position(|h| *h == rules.name);
+            Ok(Compiled { rules, col, pattern, format })
+        })
+        .collect()
+}
+
+fn column_type(kind: &str) -> Option<ColumnType> {
+    match kind {
+        "string" => Some(ColumnType::String),
+        "integer" => Some(ColumnType::Integer),
+        "number" => Some(ColumnType::Float),
+        "boolean" => Some(ColumnType::Boolean),
+        "date" => Some(ColumnType::Date),
+        _ => None,
+    }
+}
+
+/// Rules a cell breaks, as (rule, message) pairs
+fn check_cell(compiled: &Compiled, value: &str) -> Vec<(&'static str, String)> {
+    let rules = &compiled.rules;
+    let mut failed = Vec::new();
+    if is_null(value) {
+        if rules.required {
+            failed.push(("required", "Value is required".to_string()));
+        }
+        return failed;
+    }
+    if let Some(kind) = &rules.kind {
+        if !column_type(kind).is_some_and(|t| t.accepts(value)) {
+            failed.push(("type", format!("Expected {}, got \"{}\"", kind, value)));
+        }
+    }
+    if let Some(format) = &compiled.format {
+        if !format.accepts(value) {
+            let name = rules.format.as_deref().unwrap_or_default();
+            failed.push(("format", format!("\"{}\" is not a valid {}", value, name)));
+        }
+    }
+    if let Some(pattern) = &compiled.pattern {
+        if !pattern.is_match(value) {
+            failed.push(("pattern", format!("\"{}\" does not match {}", value, pattern.as_str())));
+        }
+    }
+    if rules.minimum.is_some() || rules.maximum.is_some() {
+        match parse_number(value) {
+            Some(n) if rules.minimum.is_some_and(|min| n < min) => {
+                failed.push(("minimum", format!("{} is less than {}", value, rules.minimum.unwrap_or_default())));
+            }
+            Some(n) if rules.maximum.is_some_and(|max| n > max) => {
+                failed.push(("maximum", format!("{} is greater than {}", value, rules.maximum.unwrap_or_default())));
+            }
+            Some(_) => {}
+            // Already reported when the column has a type
+            None if rules.kind.is_some() => {}
+            None => failed.push(("range", format!("\"{}\" is not a number", value))),
+        }
+    }
+    let length = value.chars().count();
+    if rules.min_length.is_some_and(|min| length < min) {
+        failed.push(("min_length", format!("Shorter than {} characters", rules.min_length.unwrap_or_default())));
+    }
+    if rules.max_length.is_some_and(|max| length > max) {
+        failed.push(("max_length", format!("Longer than {} characters", rules.max_length.unwrap_or_default())));
+    }
+    if let Some(allowed) = &rules.allowed {
+        if !allowed.iter().any(|a| a == value) {
+            failed.push(("enum", format!("\"{}\" is not one of the allowed values", value)));
+        }
+    }
+    failed
+}
+
+fn validate(ds: &Dataset, schema: &[Compiled], closed: bool) -> ValidationReport {
+    let mut report = ValidationReport {
+        violations: Vec::new(),
+        violation_count: 0,
+        counts: Vec::new(),
+        invalid_rows: 0,
+        total_rows: ds.rows.len(),
+    };
+
+    for compiled in schema.iter().filter(|c| c.col.is_none() && c.rules.required) {
+        report.push(Violation {
+            row: None,
+            column: compiled.rules.name.clone(),
+            rule: "required".to_string(),
+            message: "Required column is missing".to_string(),
+            value: String::new(),
+        });
+    }
+    if closed {
+        for header in ds.headers.iter().filter(|h| !schema.iter().any(|c| c.rules.name == **h)) {
+            report.push(Violation {
+                row: None,
+                column: header.clone(),
+                rule: "additional".to_string(),
+                message: "Column is not in the schema".to_string(),
+                value: String::new(),
+            });
+        }
+    }
+
+    // First row holding each value, per column that must be unique
+    let mut seen: Vec<HashMap<&str, usize>> = vec![HashMap::new(); schema.len()];
+    for (i, row) in ds.rows.iter().enumerate() {
+        let mut valid = true;
+        for (compiled, seen) in schema.iter().zip(&mut seen) {
+            let Some(col) = compiled.col else { continue };
+            let value = row.get(col).map(String::as_str).unwrap_or("");
+            let mut failed = check_cell(compiled, value);
+            if compiled.rules.unique && !is_null(value) {
+                match seen.entry(value) {
+                    Entry::Occupied(first) => failed.push(("unique", format!("Duplicate of row {}", first.get() + 1))),
+                    Entry::Vacant(entry) => {
+                        entry.insert(i);
+                    }
+                }
+            }
+            for (rule, message) in failed {
+                valid = false;
+                report.push(Violation {
+                    row: Some(i),
+                    column: compiled.rules.name.clone(),
+                    rule: rule.to_string(),
+                    message,
+                    value: value.to_string(),
+                });
+            }
+        }
+        if !valid {
+            report.invalid_rows += 1;
+        }
+    }
+    report
+}
+
+/// Check every row against a JSON Schema or a column-spec file
+#[tauri::command]
+pub fn validate_schema(
+    app: AppHandle,
+    store: State<'_, DatasetStore>,
+    handle: u64,
+    schema_path: String,
+) -> Result<ValidationReport, AppError> {
+    access::check(&app, &schema_path)?;
+    let (rules, closed) = read_schema(&schema_path)?;
+    store.with(handle, |ds| {
+        let schema = compile(ds, rules)?;
+        Ok(validate(ds, &schema, closed))
+    })?
+}
+
+#[derive(Debug, Serialize)]
+pub struct ValidatorInfo {
+    pub name: String,
+    pub description: String,
+}
+
+/// The built-in validators that can be set as a column's `format`
+#[tauri::command]
+pub fn list_validators() -> Vec<ValidatorInfo> {
+    VALIDATORS
+        .iter()
+        .map(|(name, description, _)| ValidatorInfo {
+            name: name.to_string(),
+            description: description.to_string(),
+        })
+        .collect()
+}
+
+#[derive(Debug, Serialize)]
+pub struct RulesResult {
+    /// One row per violation: row number, column, rule, message, and value
+    pub data: FileData,
+    pub counts: Vec<RuleCount>,
+    pub violation_count: usize,
+    pub invalid_rows: usize,
+    pub total_rows: usize,
+}
+
+/// Check columns against rules (required, pattern, range, allowed values,
+/// length, uniqueness, built-in format) and open the violations as a new dataset
+#[tauri::command]
+pub fn validate_rules(
+    store: State<'_, DatasetStore>,
+    handle: u64,
+    rules: Vec<ColumnRules>,
+) -> Result<RulesResult, AppError> {
+    if rules.is_empty() {
+        return Err("Add at least one rule".into());
+    }
+    let (dataset, report) = store.with(handle, |ds| {
+        for r in &rules {
+            ds.column_index(&r.name)?;
+        }
+        let schema = compile(ds, rules)?;
+        let report = validate(ds, &schema, false);
+        let rows = report
+            .violations
+            .iter()
+            .map(|v| {
+                vec![
+                    v.row.map(|r| (r + 1).to_string()).unwrap_or_default(),
+                    v.column.clone(),
+                    v.rule.clone(),
+                    v.message.clone(),
+                    v.value.clone(),
+                ]
+            })
+            .collect();
+        let dataset = Dataset {
+            headers: ["row", "column", "rule", "message", "value"].map(String::from).to_vec(),
+            rows,
+            file_name: format!("{} (violations)", ds.file_name),
+            file_type: ds.file_type.clone(),
+            ..Default::default()
+        };
+        Ok::<_, String>((dataset, report))
+    })??;
+
+    Ok(RulesResult {
+        data: store.register(dataset),
+        counts: report.counts,
+        violation_count: report.violation_count,
+        invalid_rows: report.invalid_rows,
+        total_rows: report.total_rows,
+    })
+}
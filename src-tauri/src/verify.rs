@@ -0,0 +1,180 @@
+use crate::error::AppError;
+use crate::settings;
+use serde_json::Value;
+use std::fs;
+use tauri::AppHandle;
+
+/// Rows compared cell by cell, spread evenly from first to last
+const SAMPLE_ROWS: usize = 200;
+/// Discrepancies spelled out before the rest are only counted
+const MAX_LISTED: usize = 10;
+
+/// How an exported file compares with the rows that were written to it
+#[derive(Debug, Clone)]
+pub struct Verification {
+    pub rows_found: usize,
+    pub columns_found: usize,
+    pub cells_checked: usize,
+    pub discrepancies: Vec<String>,
+}
+
+impl Verification {
+    /// One line for the completion message
+    pub fn summary(&self) -> String {
+        if self.discrepancies.is_empty() {
+            return format!(
+                "Verified: {} rows and {} columns read back, {} sampled cells match",
+                self.rows_found, self.columns_found, self.cells_checked
+            );
+        }
+        let mut listed: Vec<String> = self.discrepancies.iter().take(MAX_LISTED).cloned().collect();
+        if self.discrepancies.len() > MAX_LISTED {
+            listed.push(format!("and {} more", self.discrepancies.len() - MAX_LISTED));
+        }
+        format!("The written file doesn't match the exported rows: {}", listed.join("; "))
+    }
+}
+
+/// Read an exported CSV file back, with the header row apart
+fn read_csv(path: &str) -> Result<(Vec<String>, Vec<Vec<String>>), String> {
+    let mut reader = csv::ReaderBuilder::new()
+        .flexible(true)
+        .from_path(path)
+        .map_err(|e| format!("Failed to read back {}: {}", path, e))?;
+    let headers = reader
+        .headers()
+        .map_err(|e| format!("Failed to read back {}: {}", path, e))?
+        .iter()
+        .map(String::from)
+        .collect();
+    let rows = reader
+        .records()
+        .map(|record| record.map(|r| r.iter().map(String::from).collect()))
+        .collect::<Result<_, _>>()
+        .map_err(|e| format!("Failed to read back {}: {}", path, e))?;
+    Ok((headers, rows))
+}
+
+/// Read an exported JSON array back. Keys are looked up by the exported
+/// headers, so key order doesn't matter; the headers returned are every key
+/// found, in the order first seen.
+fn read_json(path: &str, expected: &[String]) -> Result<(Vec<String>, Vec<Vec<String>>), String> {
+    let content = fs::read_to_string(path).map_err(|e| format!("Failed to read back {}: {}", path, e))?;
+    let value: Value = serde_json::from_str(&content).map_err(|e| format!("Failed to parse {} back: {}", path, e))?;
+    let Value::Array(items) = value else {
+        return Err(format!("{} no longer holds a JSON array", path));
+    };
+    // An empty array has no keys to check the columns against
+    if items.is_empty() {
+        return Ok((expected.to_vec(), Vec::new()));
+    }
+    let mut headers: Vec<String> = Vec::new();
+    let mut rows = Vec::with_capacity(items.len());
+    for item in &items {
+        let Value::Object(obj) = item else {
+            return Err(format!("{} holds something other than objects", path));
+        };
+        for key in obj.keys() {
+            if !headers.contains(key) {
+                headers.push(key.clone());
+            }
+        }
+        rows.push(
+            expected
+                .iter()
+                .map(|h| match obj.get(h) {
+                    Some(Value::String(s)) => s.clone(),
+                    Some(other) => other.to_string(),
+                    None => String::new(),
+                })
+                .collect(),
+        );
+    }
+    Ok((headers, rows))
+}
+
+/// Rows to compare, spread evenly over `len` and always taking the first and last
+fn sample(len: usize) -> Vec<usize> {
+    if len <= SAMPLE_ROWS {
+        return (0..len).collect();
+    }
+    let mut rows: Vec<usize> = (0..SAMPLE_ROWS).map(|k| k * (len - 1) / (SAMPLE_ROWS - 1)).collect();
+    rows.dedup();
+    rows
+}
+
+/// Parse the file an export just wrote and compare its row and column counts,
+/// header names, and a sample of cells with what was meant to be written
+pub fn verify_export(path: &str, format: &str, headers: &[String], rows: &[Vec<String>]) -> Result<Verification, String> {
+    let (found_headers, found_rows) = match format {
+        "csv" => read_csv(path)?,
+        "json" => read_json(path, headers)?,
+        other => return Err(format!("Can't verify {} exports", other)),
+    };
+    let mut discrepancies = Vec::new();
+    if found_rows.len() != rows.len() {
+        discrepancies.push(format!("{} rows written but {} read back", rows.len(), found_rows.len()));
+    }
+    if found_headers.len() != headers.len() {
+        discrepancies.push(format!("{} columns written but {} read back", headers.len(), found_headers.len()));
+    }
+    // JSON keys come back in whatever order the file has them
+    if format == "csv" {
+        for (i, (expected, found)) in headers.iter().zip(&found_headers).enumerate() {
+            if expected != found {
+                discrepancies.push(format!("column {} is headed \"{}\" instead of \"{}\"", i + 1, found, expected));
+            }
+        }
+    } else if let Some(missing) = headers.iter().find(|h| !found_headers.contains(h)) {
+        discrepancies.push(format!("column \"{}\" is missing", missing));
+    }
+
+    let mut cells_checked = 0;
+    for row in sample(rows.len().min(found_rows.len())) {
+        for (col, header) in headers.iter().enumerate() {
+            let expected = rows[row].get(col).map_or("", String::as_str);
+            let found = found_rows[row].get(col).map_or("", String::as_str);
+            cells_checked += 1;
+            if expected != found {
+                discrepancies.push(format!(
+                    "row {}, column \"{}\" reads back as {:?} instead of {:?}",
+                    row + 1,
+                    header,
+                    found,
+                    expected
+                ));
+            }
+        }
+    }
+    Ok(Verification {
+        rows_found: found_rows.len(),
+        columns_found: found_headers.len(),
+        cells_checked,
+        discrepancies,
+    })
+}
+
+/// Finish an export's completion message. When verifying (by default, if
+/// the export settings say so), the file is read back first: a match is
+/// noted in the message, and any discrepancy fails the export, although the
+/// file stays where it was written.
+pub fn finish_export(
+    app: &AppHandle,
+    verify: Option<bool>,
+    path: &str,
+    format: &str,
+    headers: &[String],
+    rows: &[Vec<String>],
+    message: String,
+) -> Result<String, AppError> {
+    if !verify.unwrap_or_else(|| settings::load(app).export.verify) {
+        return Ok(message);
+    }
+    let verification = verify_export(path, format, headers, rows)?;
+    if verification.discrepancies.is_empty() {
+        Ok(format!("{}. {}", message, verification.summary()))
+    } else {
+        tracing::warn!("Export to {} failed verification: {:?}", path, verification.discrepancies);
+        Err(AppError::from(format!("{}. {}", message, verification.summary())).with_path(path))
+    }
+}
@@ -0,0 +1,25 @@
+use crate::dataset::DatasetStore;
+use crate::error::AppError;
+use crate::logging;
+use tauri::{AppHandle, Manager, State, WebviewUrl, WebviewWindowBuilder};
+
+/// Open another viewer window showing a loaded dataset, e.g. to compare two
+/// files side by side. Both windows share the dataset; returns the new
+/// window's label.
+#[tauri::command(async)]
+pub fn open_in_window(app: AppHandle, store: State<'_, DatasetStore>, handle: u64) -> Result<String, AppError> {
+    let _timed = logging::timed("open_in_window");
+    let file_name = store.with(handle, |ds| ds.file_name.clone())?;
+    let label = (1..)
+        .map(|n| format!("dataset-{}-{}", handle, n))
+        .find(|label| app.get_webview_window(label).is_none())
+        .unwrap_or_default();
+
+    WebviewWindowBuilder::new(&app, &label, WebviewUrl::App(format!("index.html?handle={}", handle).into()))
+        .title(format!("{} - Quick Data Viewer", file_name))
+        .inner_size(1200.0, 800.0)
+        .min_inner_size(800.0, 600.0)
+        .build()
+        .map_err(|e| format!("Failed to open window: {}", e))?;
+    Ok(label)
+}
@@ -0,0 +1,451 @@
+use crate::dataset::{Dataset, DatasetStore, DatasetView};
+use crate::stats::parse_number;
+use crate::types::ColumnType;
+use crate::operations::{Interrupted, Operation, Operations};
+use crate::progress::Progress;
+use crate::{cache, expr, history, logging, storage};
+use crate::error::AppError;
+use serde::{Deserialize, Serialize};
+use std::cmp::Ordering;
+use std::collections::HashMap;
+use tauri::{AppHandle, State};
+
+const SAVED_VIEWS_FILE: &str = "saved_views.json";
+const VIEW_STATE_FILE: &str = "view_state.json";
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SortSpec {
+    pub column: String,
+    #[serde(default)]
+    pub descending: bool,
+}
+
+/// A column worked out from where a row sits rather than stored in it, so
+/// it keeps pointing at the same record through filters and sorts
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum VirtualColumn {
+    /// 1-based position of the row in the dataset
+    RowNumber,
+    /// Line of the source file the row's record starts on; empty once rows
+    /// have been added or removed
+    SourceLine,
+}
+
+impl VirtualColumn {
+    pub fn header(self) -> &'static str {
+        match self {
+            VirtualColumn::RowNumber => "_row",
+            VirtualColumn::SourceLine => "_line",
+        }
+    }
+
+    pub fn cell(self, ds: &Dataset, row: usize) -> String {
+        match self {
+            VirtualColumn::RowNumber => (row + 1).to_string(),
+            VirtualColumn::SourceLine if ds.record_spans.len() == ds.rows.len() => {
+                ds.record_spans.get(row).map(|span| span.line.to_string()).unwrap_or_default()
+            }
+            VirtualColumn::SourceLine => String::new(),
+        }
+    }
+}
+
+/// A column a view shows: one of the dataset's, by index, or a virtual one
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ViewColumn {
+    Data(usize),
+    Virtual(VirtualColumn),
+}
+
+impl ViewColumn {
+    pub fn header(self, ds: &Dataset) -> String {
+        match self {
+            ViewColumn::Data(c) => ds.headers[c].clone(),
+            ViewColumn::Virtual(v) => v.header().to_string(),
+        }
+    }
+
+    /// The column's value in dataset row `row`
+    pub fn cell(self, ds: &Dataset, row: usize) -> String {
+        match self {
+            ViewColumn::Data(c) => ds.rows[row].get(c).cloned().unwrap_or_default(),
+            ViewColumn::Virtual(v) => v.cell(ds, row),
+        }
+    }
+}
+
+/// A named filter/sort/column-visibility configuration
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ViewConfig {
+    pub name: String,
+    #[serde(default)]
+    pub filter: Option<String>,
+    #[serde(default)]
+    pub sort: Option<SortSpec>,
+    #[serde(default)]
+    pub hidden_columns: Vec<String>,
+    /// Columns to show first, in this order; the rest follow as in the file
+    #[serde(default)]
+    pub column_order: Vec<String>,
+    /// Display widths in pixels, only used by the frontend
+    #[serde(default)]
+    pub column_widths: HashMap<String, u32>,
+    /// Virtual columns to show before the dataset's, in this order
+    #[serde(default)]
+    pub virtual_columns: Vec<VirtualColumn>,
+}
+
+impl ViewConfig {
+    /// Drop references to columns the dataset doesn't have (any more)
+    fn retain_columns(&mut self, headers: &[String]) {
+        if self.sort.as_ref().is_some_and(|s| !headers.contains(&s.column)) {
+            self.sort = None;
+        }
+        self.hidden_columns.retain(|c| headers.contains(c));
+        self.column_order.retain(|c| headers.contains(c));
+        self.column_widths.retain(|c, _| headers.contains(c));
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct ViewResult {
+    pub headers: Vec<String>,
+    /// Indices into the full dataset, in display order
+    pub indices: Vec<usize>,
+    pub rows: Vec<Vec<String>>,
+    pub row_count: usize,
+    pub total_rows: usize,
+}
+
+/// Saved views, keyed by the file path they belong to
+type SavedViews = HashMap<String, Vec<ViewConfig>>;
+
+/// The last view shown for each file, keyed by path
+type ViewStates = HashMap<String, ViewConfig>;
+
+/// Compare cells the way the table sorts them: numerically when both parse
+/// as numbers, otherwise as text
+pub fn compare_cells(a: &str, b: &str) -> Ordering {
+    match (parse_number(a), parse_number(b)) {
+        (Some(x), Some(y)) => x.total_cmp(&y),
+        _ => a.cmp(b),
+    }
+}
+
+/// Indices of the rows a view's filter keeps, in the order of its sort.
+/// Fails if `op` is interrupted, as a partly filtered or sorted view would
+/// be misleading.
+pub fn view_rows(ds: &Dataset, view: &ViewConfig, op: &Operation) -> Result<Vec<usize>, String> {
+    let mut indices = Vec::new();
+    match &view.filter {
+        Some(filter) if !filter.trim().is_empty() => {
+            let compiled = expr::compile(filter, &ds.headers)?;
+            for (i, row) in ds.rows.iter().enumerate() {
+                op.check(i)?;
+                if compiled.matches(row) {
+                    indices.push(i);
+                }
+            }
+        }
+        _ => indices.extend(0..ds.rows.len()),
+    }
+
+    if let Some(sort) = &view.sort {
+        let col = ds.column_index(&sort.column)?;
+        let cell = |i: usize| ds.rows[i].get(col).map(|v| v.as_str()).unwrap_or("");
+        // Codes declared as text, like "007", sort as text
+        let as_text = ds.column_types.get(&sort.column) == Some(&ColumnType::String);
+        // A sort can't be left halfway, so once interrupted every
+        // comparison says equal to let it finish quickly
+        let mut comparisons = 0;
+        let mut stopped = false;
+        indices.sort_by(|&a, &b| {
+            comparisons += 1;
+            stopped = stopped || op.check(comparisons).is_err();
+            if stopped {
+                return Ordering::Equal;
+            }
+            let ord = if as_text { cell(a).cmp(cell(b)) } else { compare_cells(cell(a), cell(b)) };
+            if sort.descending { ord.reverse() } else { ord }
+        });
+        if stopped {
+            return Err(op.interrupted().unwrap_or(Interrupted::Cancelled).message());
+        }
+    }
+    Ok(indices)
+}
+
+/// The columns a view shows, in display order: its virtual columns, then
+/// the dataset's
+pub fn view_columns(ds: &Dataset, view: &ViewConfig) -> Vec<ViewColumn> {
+    let mut order: Vec<usize> = view
+        .column_order
+        .iter()
+        .filter_map(|name| ds.headers.iter().position(|h| h == name))
+        .collect();
+    let rest: Vec<usize> = (0..ds.headers.len()).filter(|c| !order.contains(c)).collect();
+    order.extend(rest);
+    let data = order
+        .into_iter()
+        .filter(|&c| !view.hidden_columns.contains(&ds.headers[c]))
+        .map(ViewColumn::Data);
+    view.virtual_columns.iter().copied().map(ViewColumn::Virtual).chain(data).collect()
+}
+
+/// Apply a view's column visibility to the rows its filter and sort keep
+/// (as found by `view_rows`)
+pub fn evaluate_view(ds: &Dataset, view: &ViewConfig, indices: Vec<usize>) -> ViewResult {
+    let visible = view_columns(ds, view);
+    let rows = indices
+        .iter()
+        .map(|&i| visible.iter().map(|c| c.cell(ds, i)).collect())
+        .collect();
+
+    ViewResult {
+        headers: visible.iter().map(|c| c.header(ds)).collect(),
+        row_count: indices.len(),
+        total_rows: ds.rows.len(),
+        indices,
+        rows,
+    }
+}
+
+/// Save (or overwrite by name) a view for a file
+#[tauri::command]
+pub fn save_view(app: AppHandle, file_path: String, view: ViewConfig) -> Result<(), AppError> {
+    if view.name.trim().is_empty() {
+        return Err("View name cannot be empty".into());
+    }
+    let mut saved: SavedViews = storage::load(&app, SAVED_VIEWS_FILE);
+    let views = saved.entry(file_path).or_default();
+    views.retain(|v| v.name != view.name);
+    views.push(view);
+    Ok(storage::save(&app, SAVED_VIEWS_FILE, &saved)?)
+}
+
+#[tauri::command]
+pub fn list_views(app: AppHandle, file_path: String) -> Vec<ViewConfig> {
+    let mut saved: SavedViews = storage::load(&app, SAVED_VIEWS_FILE);
+    saved.remove(&file_path).unwrap_or_default()
+}
+
+#[tauri::command]
+pub fn delete_view(app: AppHandle, file_path: String, name: String) -> Result<(), AppError> {
+    let mut saved: SavedViews = storage::load(&app, SAVED_VIEWS_FILE);
+    if let Some(views) = saved.get_mut(&file_path) {
+        views.retain(|v| v.name != name);
+        if views.is_empty() {
+            saved.remove(&file_path);
+        }
+    }
+    Ok(storage::save(&app, SAVED_VIEWS_FILE, &saved)?)
+}
+
+/// Apply a view saved for the dataset's source file. Its filter and sort
+/// run as a cancellable operation, as in `filter_rows`.
+#[tauri::command(async)]
+pub fn apply_view(
+    app: AppHandle,
+    store: State<'_, DatasetStore>,
+    ops: State<'_, Operations>,
+    handle: u64,
+    name: String,
+    operation_id: Option<String>,
+    timeout_ms: Option<u64>,
+) -> Result<ViewResult, AppError> {
+    let _timed = logging::timed("apply_view");
+    let op = ops.start(&app, operation_id, timeout_ms)?;
+    let result = store.with_cache(handle, |ds, cache| {
+        let path = ds
+            .source_path
+            .as_ref()
+            .ok_or_else(|| "Saved views are only available for datasets loaded from a file".to_string())?;
+        let saved: SavedViews = storage::load(&app, SAVED_VIEWS_FILE);
+        let view = saved
+            .get(path)
+            .and_then(|views| views.iter().find(|v| v.name == name))
+            .ok_or_else(|| format!("No saved view named {}", name))?;
+        // Filtering and sorting are the slow part; columns are cheap to redo
+        let key = cache::key("view_rows", &(&view.filter, &view.sort));
+        let indices = cache.get_or_insert(handle, key, || view_rows(ds, view, &op))?;
+        Ok::<_, String>(evaluate_view(ds, view, indices))
+    })??;
+    history::record(&app, &store, handle, "view", "apply_view", serde_json::json!({ "name": name }));
+    Ok(result)
+}
+
+/// Remember the view shown for a file so it can be restored when the file is
+/// opened again; `None` forgets it
+pub fn remember_view_state(app: &AppHandle, file_path: &str, view: Option<&ViewConfig>) -> Result<(), String> {
+    let mut states: ViewStates = storage::load(app, VIEW_STATE_FILE);
+    match view {
+        Some(view) => states.insert(file_path.to_string(), view.clone()),
+        None => states.remove(file_path),
+    };
+    storage::save(app, VIEW_STATE_FILE, &states)
+}
+
+/// The sort, filter, and column layout last used for the dataset's file,
+/// without any columns the file no longer has
+#[tauri::command]
+pub fn get_view_state(
+    app: AppHandle,
+    store: State<'_, DatasetStore>,
+    handle: u64,
+) -> Result<Option<ViewConfig>, AppError> {
+    Ok(store.with(handle, |ds| {
+        let path = ds.source_path.as_ref()?;
+        let mut states: ViewStates = storage::load(&app, VIEW_STATE_FILE);
+        let mut view = states.remove(path)?;
+        view.retain_columns(&ds.headers);
+        Some(view)
+    })?)
+}
+
+/// A view open over a loaded dataset; page through it by passing `handle` to
+/// `get_rows`
+#[derive(Debug, Serialize)]
+pub struct OpenView {
+    pub handle: u64,
+    pub dataset: u64,
+    pub view: ViewConfig,
+    /// Columns shown, in order
+    pub headers: Vec<String>,
+    pub row_count: usize,
+    pub total_rows: usize,
+}
+
+fn describe(handle: u64, ds: &Dataset, view: &mut DatasetView, op: &Operation) -> Result<OpenView, String> {
+    let row_count = view.rows_within(ds, op)?.len();
+    Ok(OpenView {
+        handle,
+        dataset: view.dataset,
+        view: view.config.clone(),
+        headers: view_columns(ds, &view.config).iter().map(|c| c.header(ds)).collect(),
+        row_count,
+        total_rows: ds.rows.len(),
+    })
+}
+
+/// Fail if another view open over the dataset already has this name
+fn check_view_name(store: &DatasetStore, dataset: u64, name: &str, except: Option<u64>) -> Result<(), String> {
+    if name.trim().is_empty() {
+        return Err("View name cannot be empty".to_string());
+    }
+    let taken = store
+        .view_handles(dataset)
+        .into_iter()
+        .filter(|&h| Some(h) != except)
+        .any(|h| store.with_view(h, |_, v| v.config.name == name).unwrap_or(false));
+    if taken {
+        return Err(format!("A view named {} is already open for this dataset", name));
+    }
+    Ok(())
+}
+
+/// Open a named filter/sort/column view over a dataset under a handle of its
+/// own, so e.g. "errors only" and "all rows" tabs can page the same rows
+/// without loading the file twice. Views follow edits to the dataset. The
+/// first filter and sort run as a cancellable operation.
+#[tauri::command(async)]
+pub fn open_view(
+    app: AppHandle,
+    store: State<'_, DatasetStore>,
+    ops: State<'_, Operations>,
+    handle: u64,
+    view: ViewConfig,
+    operation_id: Option<String>,
+    timeout_ms: Option<u64>,
+) -> Result<OpenView, AppError> {
+    let _timed = logging::timed("open_view");
+    check_view_name(&store, handle, &view.name, None)?;
+    let op = ops.start(&app, operation_id, timeout_ms)?;
+    let view_handle = store.insert_view(handle, view)?;
+    let opened = store.with_view(view_handle, |ds, v| describe(view_handle, ds, v, &op)).and_then(|r| r);
+    if opened.is_err() {
+        store.remove_view(view_handle);
+    }
+    Ok(opened?)
+}
+
+/// Change what an open view shows; it keeps its handle. If the new filter
+/// or sort is cancelled, the view goes back to what it showed before.
+#[tauri::command(async)]
+pub fn update_view(
+    app: AppHandle,
+    store: State<'_, DatasetStore>,
+    ops: State<'_, Operations>,
+    handle: u64,
+    view: ViewConfig,
+    operation_id: Option<String>,
+    timeout_ms: Option<u64>,
+) -> Result<OpenView, AppError> {
+    let _timed = logging::timed("update_view");
+    let dataset = store.with_view(handle, |_, v| v.dataset)?;
+    check_view_name(&store, dataset, &view.name, Some(handle))?;
+    let op = ops.start(&app, operation_id, timeout_ms)?;
+    Ok(store.with_view(handle, |ds, v| {
+        let previous = v.config.clone();
+        v.set_config(view);
+        describe(handle, ds, v, &op).inspect_err(|_| v.set_config(previous))
+    })??)
+}
+
+/// The views open over a dataset, oldest first. Views whose filter or sort
+/// no longer fits the dataset's columns are left out until updated.
+#[tauri::command]
+pub fn list_open_views(store: State<'_, DatasetStore>, handle: u64) -> Result<Vec<OpenView>, AppError> {
+    store.with(handle, |_| ())?;
+    Ok(store
+        .view_handles(handle)
+        .into_iter()
+        .filter_map(|h| store.with_view(h, |ds, v| describe(h, ds, v, &Operation::unbounded())).ok()?.ok())
+        .collect())
+}
+
+#[tauri::command]
+pub fn close_view(store: State<'_, DatasetStore>, handle: u64) -> Result<(), AppError> {
+    Ok(store
+        .remove_view(handle)
+        .map(|_| ())
+        .ok_or_else(|| format!("Unknown view handle: {}", handle))?)
+}
+
+/// Export a view opened with `open_view`, or a whole dataset, the way it
+/// pages: the view's rows in order and its columns, virtual ones included,
+/// with display formats applied. `format` is "csv" or "json"; `verify`
+/// works as for `export_csv`.
+#[tauri::command(async)]
+pub fn export_view(
+    app: AppHandle,
+    store: State<'_, DatasetStore>,
+    handle: u64,
+    file_path: String,
+    format: String,
+    verify: Option<bool>,
+) -> Result<String, AppError> {
+    let _timed = logging::timed("export_view");
+    let (dataset, headers, rows) = if store.is_view(handle) {
+        store.with_view(handle, |ds, view| {
+            let columns = view_columns(ds, &view.config);
+            let view_rows = view.rows(ds)?;
+            let mut progress = Progress::preparing(&app, &file_path, view_rows.len());
+            let rows = view_rows
+                .iter()
+                .map(|&row| {
+                    progress.row_written();
+                    columns.iter().map(|c| c.cell(ds, row)).collect()
+                })
+                .collect();
+            Ok::<_, String>((view.dataset, columns.iter().map(|c| c.header(ds)).collect(), rows))
+        })??
+    } else {
+        store.with(handle, |ds| (handle, ds.headers.clone(), ds.rows.clone()))?
+    };
+    match format.as_str() {
+        "csv" => crate::export_csv(app, store, file_path, headers, rows, verify, Some(dataset), None),
+        "json" => crate::export_json(app, store, file_path, headers, rows, verify, Some(dataset), None),
+        other => Err(format!("Unsupported export format: {}", other).into()),
+    }
+}
@@ -0,0 +1,242 @@
+use crate::dataset::{DatasetStore, FileStamp};
+use crate::error::AppError;
+use crate::launch::{self, OpenRequest};
+use crate::open::{self, OpenedFile};
+use crate::{access, storage};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Emitter, Manager, State};
+
+const WATCH_FILE: &str = "watch_folder.json";
+/// Event emitted as each new file is loaded or queued
+pub const WATCHED_FILE_EVENT: &str = "watched-file";
+/// How often the folder is listed
+const POLL_INTERVAL: Duration = Duration::from_secs(2);
+/// How long a file that failed to load waits before it's tried again,
+/// unless it changes first
+const RETRY_INTERVAL: Duration = Duration::from_secs(60);
+
+/// What to do with a file that arrives in the folder
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum WatchMode {
+    /// Load it in the backend straight away
+    #[default]
+    Load,
+    /// Add it to the files waiting for the frontend to open, as if it had
+    /// been opened from the OS
+    Queue,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WatchConfig {
+    pub directory: String,
+    /// File names to pick up, e.g. "*.csv" or "orders_*.csv; *.jsonl".
+    /// Matching ignores case.
+    #[serde(default = "default_pattern")]
+    pub pattern: String,
+    #[serde(default)]
+    pub mode: WatchMode,
+}
+
+fn default_pattern() -> String {
+    "*".to_string()
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct WatchedFile {
+    #[serde(flatten)]
+    pub file: OpenedFile,
+    /// Queued for the frontend rather than loaded
+    pub queued: bool,
+}
+
+/// The running watcher's stop flag, if a folder is being watched
+#[derive(Default)]
+pub struct WatchFolder(Mutex<Option<Arc<AtomicBool>>>);
+
+/// Whether a file name matches a `*`/`?` wildcard pattern, or any of several
+/// separated by `;` or `,`
+fn matches(pattern: &str, name: &str) -> bool {
+    fn wildcard(pattern: &[char], name: &[char]) -> bool {
+        let (mut p, mut n) = (0, 0);
+        // The last `*` seen and where in the name its match currently ends;
+        // on a mismatch it takes one more character and matching resumes
+        let mut star: Option<(usize, usize)> = None;
+        while n < name.len() {
+            match pattern.get(p) {
+                Some('*') => {
+                    star = Some((p, n));
+                    p += 1;
+                }
+                Some(&c) if c == '?' || c == name[n] => {
+                    p += 1;
+                    n += 1;
+                }
+                _ => match star {
+                    Some((star_p, star_n)) => {
+                        star = Some((star_p, star_n + 1));
+                        p = star_p + 1;
+                        n = star_n + 1;
+                    }
+                    None => return false,
+                },
+            }
+        }
+        pattern[p..].iter().all(|&c| c == '*')
+    }
+    let name: Vec<char> = name.to_lowercase().chars().collect();
+    pattern
+        .split([';', ','])
+        .map(str::trim)
+        .filter(|p| !p.is_empty())
+        .any(|p| wildcard(&p.to_lowercase().chars().collect::<Vec<_>>(), &name))
+}
+
+/// Files in the folder matching the pattern, with their sizes and
+/// modification times
+fn list(config: &WatchConfig) -> HashMap<PathBuf, FileStamp> {
+    let Ok(entries) = fs::read_dir(&config.directory) else {
+        return HashMap::new();
+    };
+    entries
+        .flatten()
+        .filter(|entry| matches(&config.pattern, &entry.file_name().to_string_lossy()))
+        .filter_map(|entry| {
+            let metadata = entry.metadata().ok()?;
+            metadata.is_file().then(|| {
+                let stamp = FileStamp {
+                    size: metadata.len(),
+                    modified: metadata.modified().ok(),
+                };
+                (entry.path(), stamp)
+            })
+        })
+        .collect()
+}
+
+/// Load or queue a new file, returning whether that worked
+fn ingest(app: &AppHandle, config: &WatchConfig, path: &Path) -> bool {
+    let path = path.to_string_lossy();
+    let watched = match config.mode {
+        WatchMode::Load => WatchedFile {
+            file: open::open_one(app, &app.state::<DatasetStore>(), &path, 1),
+            queued: false,
+        },
+        WatchMode::Queue => {
+            launch::queue(app, vec![OpenRequest::file(Path::new(path.as_ref()))]);
+            WatchedFile {
+                file: OpenedFile {
+                    path: path.to_string(),
+                    handle: None,
+                    file_name: Path::new(path.as_ref())
+                        .file_name()
+                        .map(|n| n.to_string_lossy().into_owned())
+                        .unwrap_or_default(),
+                    row_count: 0,
+                    error: None,
+                },
+                queued: true,
+            }
+        }
+    };
+    if let Some(error) = &watched.file.error {
+        tracing::warn!("Failed to load watched file {}: {}", path, error.info.message);
+    }
+    let loaded = watched.file.error.is_none();
+    let _ = app.emit(WATCHED_FILE_EVENT, watched);
+    loaded
+}
+
+/// Poll the folder until `stop` is set. Files already there when watching
+/// starts are left alone; new ones are picked up once their size holds
+/// steady between two listings, so half-copied files aren't read. A file
+/// that fails to load is tried again once it changes, or after a while.
+fn run(app: AppHandle, config: WatchConfig, stop: Arc<AtomicBool>) {
+    let mut seen: HashSet<PathBuf> = list(&config).into_keys().collect();
+    let mut growing: HashMap<PathBuf, FileStamp> = HashMap::new();
+    let mut failed: HashMap<PathBuf, (FileStamp, Instant)> = HashMap::new();
+    while !stop.load(Ordering::Relaxed) {
+        std::thread::sleep(POLL_INTERVAL);
+        if stop.load(Ordering::Relaxed) {
+            break;
+        }
+        let files = list(&config);
+        // Files that went away can be picked up again if they come back
+        seen.retain(|path| files.contains_key(path));
+        growing.retain(|path, _| files.contains_key(path));
+        failed.retain(|path, (stamp, at)| files.get(path) == Some(stamp) && at.elapsed() < RETRY_INTERVAL);
+        let mut arrived: Vec<&PathBuf> = Vec::new();
+        for (path, stamp) in &files {
+            if seen.contains(path) || failed.contains_key(path) {
+                continue;
+            }
+            if growing.insert(path.clone(), stamp.clone()).as_ref() == Some(stamp) {
+                arrived.push(path);
+            }
+        }
+        arrived.sort();
+        for path in arrived {
+            let stamp = growing.remove(path);
+            if ingest(&app, &config, path) {
+                seen.insert(path.clone());
+            } else if let Some(stamp) = stamp {
+                failed.insert(path.clone(), (stamp, Instant::now()));
+            }
+        }
+    }
+}
+
+fn start(app: &AppHandle, config: WatchConfig) {
+    let stop = Arc::new(AtomicBool::new(false));
+    if let Some(previous) = app.state::<WatchFolder>().0.lock().unwrap().replace(stop.clone()) {
+        previous.store(true, Ordering::Relaxed);
+    }
+    let app = app.clone();
+    std::thread::spawn(move || run(app, config, stop));
+}
+
+/// Go back to watching the folder from the last session, if there was one
+pub fn resume(app: &AppHandle) {
+    let config: Option<WatchConfig> = storage::load(app, WATCH_FILE);
+    if let Some(config) = config.filter(|c| access::check(app, &c.directory).is_ok() && Path::new(&c.directory).is_dir()) {
+        start(app, config);
+    }
+}
+
+/// Watch a folder and load (or queue) new files matching the pattern as they
+/// arrive, emitting `watched-file` for each. Replaces any folder watched
+/// before, and carries on in later sessions until `stop_watching`.
+#[tauri::command]
+pub fn watch_folder(app: AppHandle, config: WatchConfig) -> Result<(), AppError> {
+    access::check(&app, &config.directory)?;
+    if !Path::new(&config.directory).is_dir() {
+        return Err(AppError::from(format!("Not a folder: {}", config.directory)).with_path(&config.directory));
+    }
+    if config.pattern.trim().is_empty() {
+        return Err("File pattern cannot be empty".into());
+    }
+    storage::save(&app, WATCH_FILE, &Some(&config))?;
+    start(&app, config);
+    Ok(())
+}
+
+#[tauri::command]
+pub fn stop_watching(app: AppHandle, watch: State<'_, WatchFolder>) -> Result<(), AppError> {
+    if let Some(stop) = watch.0.lock().unwrap().take() {
+        stop.store(true, Ordering::Relaxed);
+    }
+    Ok(storage::save(&app, WATCH_FILE, &None::<WatchConfig>)?)
+}
+
+/// The folder being watched, if any
+#[tauri::command]
+pub fn get_watch_folder(app: AppHandle, watch: State<'_, WatchFolder>) -> Option<WatchConfig> {
+    watch.0.lock().unwrap().as_ref()?;
+    storage::load(&app, WATCH_FILE)
+}
@@ -0,0 +1,263 @@
+use crate::dataset::{Dataset, DatasetStore, FileData};
+use crate::history;
+use crate::stats::parse_number;
+use crate::views::{compare_cells, SortSpec};
+use crate::error::AppError;
+use serde::{Deserialize, Serialize};
+use std::cmp::Ordering;
+use std::collections::{HashMap, VecDeque};
+use tauri::{AppHandle, State};
+
+/// One windowed column to compute. `function` is one of cumulative_sum,
+/// row_number, rank, dense_rank, lag, lead, rolling_mean, rolling_sum,
+/// rolling_min, or rolling_max.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WindowSpec {
+    pub function: String,
+    /// Input column (required for cumulative_sum, lag, and lead)
+    #[serde(default)]
+    pub column: Option<String>,
+    #[serde(default)]
+    pub partition_by: Vec<String>,
+    /// Ordering within each partition; file order when omitted
+    #[serde(default)]
+    pub order_by: Option<SortSpec>,
+    /// Row distance for lag/lead (default 1)
+    #[serde(default)]
+    pub offset: Option<usize>,
+    /// Rows in each rolling window, ending at the current row (default 7)
+    #[serde(default)]
+    pub size: Option<usize>,
+    /// Name of the new column (defaults to e.g. "rank_amount")
+    #[serde(default)]
+    pub name: Option<String>,
+}
+
+const WINDOW_FUNCTIONS: &[&str] = &[
+    "cumulative_sum",
+    "row_number",
+    "rank",
+    "dense_rank",
+    "lag",
+    "lead",
+    "rolling_mean",
+    "rolling_sum",
+    "rolling_min",
+    "rolling_max",
+];
+
+/// Reduce the numbers in a rolling window; `None` when it has none
+/// The last `size` values of a partition, with the sum, count, and min/max
+/// candidates kept up to date as it slides, so each row costs O(1) on average
+struct Rolling {
+    size: usize,
+    values: VecDeque<Option<f64>>,
+    pushed: usize,
+    sum: f64,
+    count: usize,
+    /// (position, value) pairs that can still become the minimum, increasing
+    min: VecDeque<(usize, f64)>,
+    /// Likewise for the maximum, decreasing
+    max: VecDeque<(usize, f64)>,
+}
+
+impl Rolling {
+    fn new(size: usize) -> Rolling {
+        Rolling {
+            size,
+            values: VecDeque::with_capacity(size),
+            pushed: 0,
+            sum: 0.0,
+            count: 0,
+            min: VecDeque::new(),
+            max: VecDeque::new(),
+        }
+    }
+
+    fn push(&mut self, value: Option<f64>) {
+        if self.values.len() == self.size {
+            if let Some(Some(old)) = self.values.pop_front() {
+                self.sum -= old;
+                self.count -= 1;
+            }
+        }
+        let position = self.pushed;
+        self.pushed += 1;
+        let start = self.pushed.saturating_sub(self.size);
+        while self.min.front().is_some_and(|&(p, _)| p < start) {
+            self.min.pop_front();
+        }
+        while self.max.front().is_some_and(|&(p, _)| p < start) {
+            self.max.pop_front();
+        }
+
+        let value = value.filter(|v| !v.is_nan());
+        self.values.push_back(value);
+        if let Some(v) = value {
+            self.sum += v;
+            self.count += 1;
+            while self.min.back().is_some_and(|&(_, m)| m >= v) {
+                self.min.pop_back();
+            }
+            self.min.push_back((position, v));
+            while self.max.back().is_some_and(|&(_, m)| m <= v) {
+                self.max.pop_back();
+            }
+            self.max.push_back((position, v));
+        }
+        if self.count == 0 {
+            // Don't carry rounding left over from values that slid out
+            self.sum = 0.0;
+        }
+    }
+
+    fn value(&self, function: &str) -> Option<f64> {
+        match function {
+            "rolling_mean" => (self.count > 0).then(|| self.sum / self.count as f64),
+            "rolling_sum" => Some(self.sum),
+            "rolling_min" => self.min.front().map(|&(_, v)| v),
+            _ => self.max.front().map(|&(_, v)| v),
+        }
+    }
+}
+
+fn compute_window(ds: &Dataset, spec: &WindowSpec) -> Result<(String, Vec<String>), String> {
+    if !WINDOW_FUNCTIONS.contains(&spec.function.as_str()) {
+        return Err(format!("Unknown window function: {}", spec.function));
+    }
+    let input = spec.column.as_deref().map(|c| ds.column_index(c)).transpose()?;
+    let partition_cols = spec
+        .partition_by
+        .iter()
+        .map(|c| ds.column_index(c))
+        .collect::<Result<Vec<_>, _>>()?;
+    let order = match &spec.order_by {
+        Some(sort) => Some((ds.column_index(&sort.column)?, sort.descending)),
+        None => None,
+    };
+    let cell = |row: usize, col: usize| ds.rows[row].get(col).map(|v| v.as_str()).unwrap_or("");
+
+    // Group row indices by partition key, then order each partition
+    let mut partitions: HashMap<Vec<&str>, Vec<usize>> = HashMap::new();
+    for i in 0..ds.rows.len() {
+        let key = partition_cols.iter().map(|&c| cell(i, c)).collect();
+        partitions.entry(key).or_default().push(i);
+    }
+    let order_cmp = |a: usize, b: usize| -> Ordering {
+        match order {
+            Some((col, descending)) => {
+                let ord = compare_cells(cell(a, col), cell(b, col));
+                if descending { ord.reverse() } else { ord }
+            }
+            None => Ordering::Equal,
+        }
+    };
+
+    let needs_input = !matches!(spec.function.as_str(), "row_number" | "rank" | "dense_rank");
+    let input = match (needs_input, input) {
+        (true, None) => return Err(format!("{} needs an input column", spec.function)),
+        (_, input) => input,
+    };
+    let offset = spec.offset.unwrap_or(1);
+    let size = spec.size.unwrap_or(7);
+    if size == 0 {
+        return Err("Rolling window size must be at least 1".to_string());
+    }
+
+    let mut out = vec![String::new(); ds.rows.len()];
+    for rows in partitions.values_mut() {
+        rows.sort_by(|&a, &b| order_cmp(a, b));
+        match spec.function.as_str() {
+            "row_number" => {
+                for (n, &r) in rows.iter().enumerate() {
+                    out[r] = (n + 1).to_string();
+                }
+            }
+            "rank" | "dense_rank" => {
+                let dense = spec.function == "dense_rank";
+                let mut rank = 0;
+                for (n, &r) in rows.iter().enumerate() {
+                    if n == 0 || order_cmp(rows[n - 1], r) != Ordering::Equal {
+                        rank = if dense { rank + 1 } else { n + 1 };
+                    }
+                    out[r] = rank.to_string();
+                }
+            }
+            "cumulative_sum" => {
+                let col = input.unwrap_or_default();
+                let mut total = 0.0;
+                for &r in rows.iter() {
+                    total += parse_number(cell(r, col)).unwrap_or(0.0);
+                    out[r] = total.to_string();
+                }
+            }
+            "lag" | "lead" => {
+                let col = input.unwrap_or_default();
+                let lag = spec.function == "lag";
+                for (n, &r) in rows.iter().enumerate() {
+                    let source = if lag { n.checked_sub(offset) } else { Some(n + offset) };
+                    if let Some(&s) = source.and_then(|s| rows.get(s)) {
+                        out[r] = cell(s, col).to_string();
+                    }
+                }
+            }
+            // Rows before a full window are computed over the rows so far
+            function if function.starts_with("rolling_") => {
+                let col = input.unwrap_or_default();
+                // A window wider than the partition is the whole partition
+                let mut window = Rolling::new(size.min(rows.len()));
+                for &r in rows.iter() {
+                    window.push(parse_number(cell(r, col)));
+                    out[r] = window.value(function).map(|v| v.to_string()).unwrap_or_default();
+                }
+            }
+            _ => unreachable!(),
+        }
+    }
+
+    let name = spec.name.clone().unwrap_or_else(|| match &spec.column {
+        Some(c) => format!("{}_{}", spec.function, c),
+        None => spec.function.clone(),
+    });
+    Ok((name, out))
+}
+
+/// Compute windowed columns (running totals, ranks, row numbers, lag/lead,
+/// moving averages and other rolling aggregates)
+/// and return a new dataset with them appended
+#[tauri::command]
+pub fn window_columns(
+    app: AppHandle,
+    store: State<'_, DatasetStore>,
+    handle: u64,
+    windows: Vec<WindowSpec>,
+) -> Result<FileData, AppError> {
+    let mut result = store.with(handle, |ds| {
+        let mut result = Dataset {
+            headers: ds.headers.clone(),
+            rows: ds.rows.clone(),
+            file_name: format!("{} (windowed)", ds.file_name),
+            file_type: ds.file_type.clone(),
+            ..Default::default()
+        };
+        for spec in &windows {
+            let (name, values) = compute_window(ds, spec)?;
+            result.headers.push(name);
+            let width = result.headers.len() - 1;
+            for (row, value) in result.rows.iter_mut().zip(values) {
+                row.resize(width, String::new());
+                row.push(value);
+            }
+        }
+        Ok::<_, String>(result)
+    })??;
+    history::record_transform(
+        &app,
+        &store,
+        handle,
+        &mut result,
+        "window_columns",
+        serde_json::json!({ "windows": windows }),
+    );
+    Ok(store.register(result))
+}
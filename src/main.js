@@ -1,10 +1,18 @@
 const { invoke } = window.__TAURI__.core;
 const { open, save } = window.__TAURI__.dialog;
+const { listen } = window.__TAURI__.event;
 
 let currentData = null;
 let filteredData = null;
+let expressionRows = null;
 let sortColumn = null;
 let sortDirection = 'asc';
+// Column layout from the saved view state, passed back unchanged when reporting
+let columnLayout = { hidden_columns: [], column_order: [], column_widths: {} };
+
+// Set when this is an extra window opened for a dataset the main window owns
+const windowHandle = Number(new URLSearchParams(window.location.search).get('handle')) || null;
+const windowLabel = window.__TAURI__.webviewWindow.getCurrentWebviewWindow().label;
 
 // DOM elements
 const dropZone = document.getElementById('dropZone');
@@ -14,8 +22,18 @@ const controls = document.getElementById('controls');
 const tableContainer = document.getElementById('tableContainer');
 const errorMessage = document.getElementById('errorMessage');
 const searchInput = document.getElementById('searchInput');
+const filterInput = document.getElementById('filterInput');
+const saveBtn = document.getElementById('saveBtn');
+const newWindowBtn = document.getElementById('newWindowBtn');
 const exportCsvBtn = document.getElementById('exportCsvBtn');
 const exportJsonBtn = document.getElementById('exportJsonBtn');
+const recentFiles = document.getElementById('recentFiles');
+const columnDialog = document.getElementById('columnDialog');
+const columnDialogInfo = document.getElementById('columnDialogInfo');
+const columnList = document.getElementById('columnList');
+
+// Files with more columns than this offer to load only some of them
+const COLUMN_PICKER_THRESHOLD = 30;
 
 // File selection
 selectFileBtn.addEventListener('click', async () => {
@@ -24,7 +42,7 @@ selectFileBtn.addEventListener('click', async () => {
             multiple: false,
             filters: [{
                 name: 'Data Files',
-                extensions: ['csv', 'json', 'jsonl']
+                extensions: ['csv', 'json', 'jsonl', 'qif', 'sta', 'mt940']
             }]
         });
 
@@ -32,13 +50,144 @@ selectFileBtn.addEventListener('click', async () => {
             await loadFile(selected);
         }
     } catch (error) {
-        showError(`Failed to select file: ${error}`);
+        showError(`Failed to select file: ${errorText(error)}`);
     }
 });
 
-// Drag and drop via Tauri's native drag-drop events
-const { listen } = window.__TAURI__.event;
+// Recent files, shown until a file is loaded
+async function showRecentFiles() {
+    const recent = await invoke('get_recent_files');
+    recentFiles.innerHTML = '';
+    recentFiles.classList.toggle('hidden', recent.length === 0);
+    
+    recent.forEach(file => {
+        const li = document.createElement('li');
+        li.title = file.path;
+        li.classList.toggle('missing', !file.exists);
+        
+        const name = document.createElement('span');
+        const rows = file.row_count.toLocaleString();
+        name.textContent = `${file.path.split(/[\\/]/).pop()} (${file.format}, ${rows} rows)`;
+        li.appendChild(name);
+        
+        const pin = document.createElement('button');
+        pin.className = file.pinned ? 'pin pinned' : 'pin';
+        pin.textContent = '📌';
+        pin.title = file.pinned ? 'Unpin' : 'Pin';
+        pin.addEventListener('click', async (e) => {
+            e.stopPropagation();
+            await invoke('pin_recent_file', { path: file.path, pinned: !file.pinned });
+            await showRecentFiles();
+        });
+        li.appendChild(pin);
+        
+        if (file.exists) {
+            li.addEventListener('click', () => loadFile(file.path));
+        }
+        recentFiles.appendChild(li);
+    });
+}
+
+// Open files passed on the command line or by the OS
+async function openPendingFiles() {
+    const pending = await invoke('take_pending_files');
+    for (const request of pending) {
+        if (!await loadFile(request.path)) {
+            continue;
+        }
+        if (request.filter) {
+            filterInput.value = request.filter;
+            await applyExpressionFilter();
+            reportViewState();
+        }
+        if (request.row) {
+            highlightRow(request.row - 1);
+        }
+    }
+}
+
+// Scroll a row of the full dataset into view, e.g. from a deep link
+function highlightRow(index) {
+    const position = filteredData.rows.indexOf(currentData.rows[index]);
+    const tr = document.getElementById('tableBody').rows[position];
+    if (position < 0 || !tr) {
+        return;
+    }
+    tr.classList.add('highlighted');
+    tr.scrollIntoView({ block: 'center' });
+}
+
+// Only the main window picks up files opened from outside
+if (!windowHandle) {
+    listen('open-files', openPendingFiles);
+}
 
+// Data piped in with --stdin; refreshed as more rows arrive
+async function showStdinDataset() {
+    const data = await invoke('get_stdin_dataset');
+    if (!data) {
+        return;
+    }
+    if (currentData?.handle === data.handle) {
+        currentData = data;
+        searchInput.dispatchEvent(new Event('input'));
+    } else {
+        await showDataset(data);
+    }
+}
+
+listen('stdin-rows', (event) => {
+    if (event.payload.error) {
+        showError(`Reading stdin: ${event.payload.error}`);
+    }
+    if (currentData?.handle === event.payload.handle) {
+        showStdinDataset();
+    }
+});
+
+// Files loaded from the watched folder; queued ones arrive via open-files
+listen('watched-file', async (event) => {
+    const { file_name, handle, error } = event.payload;
+    if (error) {
+        showError(`Failed to load ${file_name || event.payload.path}: ${errorText(error)}`);
+    } else if (handle && !currentData) {
+        await showDataset(await invoke('get_dataset', { handle }));
+    }
+});
+
+listen('dataset-refreshed', async (event) => {
+    const { handle, error } = event.payload;
+    if (currentData?.handle !== handle) {
+        return;
+    }
+    if (error) {
+        showError(`Failed to refresh ${currentData.file_name}: ${error}`);
+        return;
+    }
+    const data = await invoke('get_dataset', { handle });
+    currentData = data;
+    filteredData = { ...data };
+    expressionRows = null;
+    displayData(data);
+    searchInput.dispatchEvent(new Event('input'));
+});
+
+if (windowHandle) {
+    invoke('get_dataset', { handle: windowHandle })
+        .then(showDataset)
+        .catch(error => showError(`Failed to open dataset: ${errorText(error)}`));
+} else {
+    showRecentFiles()
+        .catch(error => showError(`Failed to load recent files: ${errorText(error)}`))
+        .then(restoreSession)
+        .catch(error => showError(`Failed to restore session: ${errorText(error)}`))
+        .then(offerRecovery)
+        .catch(error => showError(`Failed to recover autosaved work: ${errorText(error)}`))
+        .then(openPendingFiles)
+        .then(showStdinDataset);
+}
+
+// Drag and drop via Tauri's native drag-drop events
 listen('tauri://drag-over', () => {
     dropZone.classList.add('dragover');
 });
@@ -50,11 +199,62 @@ listen('tauri://drag-leave', () => {
 listen('tauri://drag-drop', async (event) => {
     dropZone.classList.remove('dragover');
     const paths = event.payload.paths;
-    if (paths && paths.length > 0) {
+    if (paths && paths.length === 1) {
         await loadFile(paths[0]);
+    } else if (paths && paths.length > 1) {
+        await loadFiles(paths);
     }
 });
 
+// Open several dropped files in parallel and show the first one that loads;
+// the rest stay open for search_all and the dataset list
+async function loadFiles(paths) {
+    try {
+        hideError();
+        const opened = await invoke('open_files', { paths });
+        const failed = opened.filter(file => file.error);
+        const first = opened.find(file => file.handle !== null);
+        if (first) {
+            await showDataset(await invoke('get_dataset', { handle: first.handle }));
+        }
+        if (failed.length > 0) {
+            showError(failed.map(file => `${file.path}: ${errorText(file.error)}`).join('\n'));
+        }
+    } catch (error) {
+        showError(error);
+    }
+}
+
+// Load a CSV or JSONL file, and if a malformed record stops it, offer to
+// load it again skipping the records that don't parse
+async function parseLeniently(command, args) {
+    try {
+        return await invoke(command, args);
+    } catch (error) {
+        if (error?.code !== 'parse' || !confirm(`${errorText(error)}\n\nLoad the file anyway, skipping records that don't parse?`)) {
+            throw error;
+        }
+        return await invoke(command, { ...args, lenient: true });
+    }
+}
+
+// Offer to save the records a lenient load skipped, to send back to whoever made the file
+async function offerFailedLinesExport(data) {
+    if (!confirm(`${data.failed_lines} record(s) couldn't be parsed and were skipped. Save them to a file with their line numbers and errors?`)) {
+        return;
+    }
+    const filePath = await save({
+        defaultPath: 'failed_lines.csv',
+        filters: [{
+            name: 'CSV File',
+            extensions: ['csv']
+        }]
+    });
+    if (filePath) {
+        alert(await invoke('export_failed_lines', { handle: data.handle, filePath }));
+    }
+}
+
 // Load and parse file
 async function loadFile(filePath) {
     try {
@@ -65,24 +265,156 @@ async function loadFile(filePath) {
         let data;
         
         if (ext === 'csv') {
-            data = await invoke('parse_csv', { filePath });
+            const hasHeader = await askHasHeader(filePath);
+            const columns = await chooseColumns(filePath, hasHeader);
+            data = await parseLeniently('parse_csv', { filePath, hasHeader, scan: columns && { columns } });
         } else if (ext === 'json') {
             data = await invoke('parse_json', { filePath });
         } else if (ext === 'jsonl') {
-            data = await invoke('parse_jsonl', { filePath });
+            const columns = await chooseColumns(filePath, true);
+            data = await parseLeniently('parse_jsonl', { filePath, scan: columns && { columns } });
+        } else if (ext === 'qif') {
+            data = await invoke('parse_qif', { filePath });
+        } else if (ext === 'sta' || ext === 'mt940') {
+            data = await invoke('parse_mt940', { filePath });
         } else {
-            throw new Error('Unsupported file type. Please use CSV, JSON, or JSONL files.');
+            throw new Error('Unsupported file type. Please use CSV, JSON, JSONL, QIF, or MT940 files.');
         }
         
-        currentData = data;
-        filteredData = { ...data };
-        displayData(data);
+        await showDataset(data);
+        await applyView(data, await invoke('get_view_state', { handle: data.handle }));
+        if (data.failed_lines > 0) {
+            await offerFailedLinesExport(data);
+        }
+        return true;
         
     } catch (error) {
         showError(error);
+        return false;
     }
 }
 
+// Whether a CSV file starts with a header row, asking when the guess is unsure
+async function askHasHeader(filePath) {
+    const guess = await invoke('detect_header', { filePath });
+    if (guess.certain) {
+        return guess.has_header;
+    }
+    const name = filePath.split(/[\\/]/).pop();
+    return confirm(`Does the first row of ${name} hold column names?\n\n${guess.reasons.join('\n')}`);
+}
+
+// For wide files, let the user pick the columns to load; null loads them all
+async function chooseColumns(filePath, hasHeader) {
+    const columns = await invoke('list_file_columns', { filePath, hasHeader });
+    if (columns.length <= COLUMN_PICKER_THRESHOLD) {
+        return null;
+    }
+    columnDialogInfo.textContent = `This file has ${columns.length} columns. Loading only the ones you need is faster and uses less memory.`;
+    columnList.replaceChildren(...columns.map(name => {
+        const label = document.createElement('label');
+        const checkbox = document.createElement('input');
+        checkbox.type = 'checkbox';
+        checkbox.value = name;
+        label.append(checkbox, ` ${name}`);
+        return label;
+    }));
+    const closed = new Promise(resolve => columnDialog.addEventListener('close', resolve, { once: true }));
+    columnDialog.showModal();
+    await closed;
+    const selected = [...columnList.querySelectorAll('input:checked')].map(checkbox => checkbox.value);
+    return columnDialog.returnValue === 'selected' && selected.length > 0 ? selected : null;
+}
+
+// Replace the displayed dataset
+async function showDataset(data) {
+    // The backend releases the previous dataset unless another window still shows it
+    await invoke('show_dataset', { label: windowLabel, handle: data.handle });
+    
+    currentData = data;
+    filteredData = { ...data };
+    expressionRows = null;
+    sortColumn = null;
+    sortDirection = 'asc';
+    columnLayout = { hidden_columns: [], column_order: [], column_widths: {} };
+    filterInput.value = '';
+    displayData(data);
+    if (data.preview) {
+        showError(`Showing the first ${data.row_count.toLocaleString()} rows only: the whole file would exceed the memory limit set in settings.`);
+    }
+}
+
+// Reopen what was open when the app was last closed
+async function restoreSession() {
+    const restored = await invoke('restore_session');
+    if (restored.errors.length > 0) {
+        showError(`Could not restore: ${restored.errors.join('; ')}`);
+    }
+    if (restored.datasets.length === 0) {
+        return;
+    }
+    
+    // Only one dataset is shown at a time; keep the most recently opened
+    const { data, view } = restored.datasets.pop();
+    for (const other of restored.datasets) {
+        await invoke('close_dataset', { handle: other.data.handle });
+    }
+    await showDataset(data);
+    await applyView(data, view);
+}
+
+// Bring back the filter and sort of a saved view state
+async function applyView(data, view) {
+    if (!view) {
+        return;
+    }
+    columnLayout = {
+        hidden_columns: view.hidden_columns ?? [],
+        column_order: view.column_order ?? [],
+        column_widths: view.column_widths ?? {}
+    };
+    if (view.filter) {
+        filterInput.value = view.filter;
+        await applyExpressionFilter();
+    }
+    const sortIndex = view.sort ? data.headers.indexOf(view.sort.column) : -1;
+    if (sortIndex >= 0) {
+        sortTable(sortIndex);
+        if (view.sort.descending) {
+            sortTable(sortIndex);
+        }
+    }
+}
+
+// Offer to bring back unsaved work from a session that crashed
+async function offerRecovery() {
+    const autosaves = await invoke('list_autosaves');
+    for (const autosave of autosaves) {
+        const when = new Date(autosave.saved_at).toLocaleString();
+        const what = autosave.edited ? 'unsaved edits to' : 'transformed data from';
+        if (confirm(`Recover ${what} ${autosave.file_name} (autosaved ${when})?`)) {
+            await showDataset(await invoke('recover_autosave', { id: autosave.id }));
+        } else {
+            await invoke('discard_autosave', { id: autosave.id });
+        }
+    }
+}
+
+// Tell the backend what is shown so it can be restored next time
+function reportViewState() {
+    const expression = filterInput.value.trim();
+    const view = {
+        name: '',
+        filter: expression || null,
+        sort: sortColumn === null ? null : {
+            column: currentData.headers[sortColumn],
+            descending: sortDirection === 'desc'
+        },
+        ...columnLayout
+    };
+    invoke('set_view_state', { handle: currentData.handle, view }).catch(() => {});
+}
+
 // Display data in table
 function displayData(data) {
     // Update file info
@@ -93,6 +425,7 @@ function displayData(data) {
     
     // Show elements
     dropZone.classList.add('hidden');
+    recentFiles.classList.add('hidden');
     fileInfo.classList.remove('hidden');
     controls.classList.remove('hidden');
     tableContainer.classList.remove('hidden');
@@ -132,15 +465,57 @@ function renderTable(data) {
     // Create data rows
     data.rows.forEach(row => {
         const tr = document.createElement('tr');
-        row.forEach(cell => {
+        row.forEach((cell, colIndex) => {
             const td = document.createElement('td');
             td.textContent = cell;
+            td.addEventListener('dblclick', () => editCell(td, row, colIndex));
             tr.appendChild(td);
         });
         tableBody.appendChild(tr);
     });
 }
 
+// Inline cell editing, validated by the backend
+function editCell(td, row, colIndex) {
+    const original = td.textContent;
+    td.contentEditable = 'true';
+    td.focus();
+    
+    td.onkeydown = (e) => {
+        if (e.key === 'Enter') {
+            e.preventDefault();
+            td.blur();
+        } else if (e.key === 'Escape') {
+            td.textContent = original;
+            td.blur();
+        }
+    };
+    
+    td.onblur = async () => {
+        td.contentEditable = 'false';
+        td.onblur = null;
+        td.onkeydown = null;
+        const value = td.textContent;
+        if (value === original) {
+            return;
+        }
+        
+        try {
+            await invoke('update_cell', {
+                handle: currentData.handle,
+                row: currentData.rows.indexOf(row),
+                column: currentData.headers[colIndex],
+                value
+            });
+            row[colIndex] = value;
+            hideError();
+        } catch (error) {
+            td.textContent = original;
+            showError(`Edit failed: ${errorText(error)}`);
+        }
+    };
+}
+
 // Sort table
 function sortTable(columnIndex) {
     if (sortColumn === columnIndex) {
@@ -150,6 +525,8 @@ function sortTable(columnIndex) {
         sortDirection = 'asc';
     }
     
+    // Columns a schema declared as text (e.g. zero-padded codes) never sort as numbers
+    const asText = currentData.column_types?.[currentData.headers[columnIndex]] === 'string';
     const rows = [...filteredData.rows];
     rows.sort((a, b) => {
         const aVal = a[columnIndex] || '';
@@ -159,7 +536,7 @@ function sortTable(columnIndex) {
         const aNum = parseFloat(aVal);
         const bNum = parseFloat(bVal);
         
-        if (!isNaN(aNum) && !isNaN(bNum)) {
+        if (!asText && !isNaN(aNum) && !isNaN(bNum)) {
             return sortDirection === 'asc' ? aNum - bNum : bNum - aNum;
         }
         
@@ -171,19 +548,22 @@ function sortTable(columnIndex) {
     
     filteredData.rows = rows;
     renderTable(filteredData);
+    reportViewState();
 }
 
 // Search functionality
 searchInput.addEventListener('input', (e) => {
     const searchTerm = e.target.value.toLowerCase();
+    const baseRows = expressionRows || currentData.rows;
     
     if (!searchTerm) {
-        filteredData = { ...currentData };
+        filteredData = { ...currentData, rows: baseRows, row_count: baseRows.length };
+        document.getElementById('rowCount').textContent = baseRows.length.toLocaleString();
         renderTable(filteredData);
         return;
     }
     
-    const filtered = currentData.rows.filter(row => {
+    const filtered = baseRows.filter(row => {
         return row.some(cell => 
             cell.toLowerCase().includes(searchTerm)
         );
@@ -199,6 +579,109 @@ searchInput.addEventListener('input', (e) => {
     renderTable(filteredData);
 });
 
+// Expression filter, evaluated in the backend
+filterInput.addEventListener('keydown', async (e) => {
+    if (e.key !== 'Enter' || !currentData) {
+        return;
+    }
+    
+    try {
+        hideError();
+        await applyExpressionFilter();
+        reportViewState();
+    } catch (error) {
+        showError(`Filter failed: ${errorText(error)}`);
+    }
+});
+
+// ID of the filter being run, so Escape can cancel it
+let runningFilterId = null;
+let filterRuns = 0;
+
+filterInput.addEventListener('keydown', (event) => {
+    if (event.key === 'Escape' && runningFilterId) {
+        invoke('cancel_operation', { id: runningFilterId }).catch(() => {});
+    }
+});
+
+async function applyExpressionFilter() {
+    const expression = filterInput.value.trim();
+    if (expression) {
+        const operationId = `filter-${++filterRuns}`;
+        runningFilterId = operationId;
+        let result;
+        try {
+            result = await invoke('filter_rows', { handle: currentData.handle, expression, operationId });
+        } finally {
+            if (runningFilterId === operationId) runningFilterId = null;
+        }
+        // Reuse the loaded row objects so edits stay in sync
+        expressionRows = result.indices.map(i => currentData.rows[i]);
+        if (result.interrupted) {
+            const reason = result.interrupted === 'timed_out' ? 'timed out' : 'was cancelled';
+            showError(`Filter ${reason}: showing matches in the first ${result.scanned_rows} of ${result.total_rows} rows`);
+        }
+    } else {
+        expressionRows = null;
+    }
+    searchInput.dispatchEvent(new Event('input'));
+}
+
+// Show the current dataset in another window, e.g. on a second monitor
+newWindowBtn.addEventListener('click', async () => {
+    try {
+        await invoke('open_in_window', { handle: currentData.handle });
+    } catch (error) {
+        showError(`Failed to open window: ${errorText(error)}`);
+    }
+});
+
+// Save edits back to the original file
+saveBtn.addEventListener('click', async () => {
+    try {
+        const result = await invoke('save_dataset', { handle: currentData.handle });
+        alert(result);
+    } catch (error) {
+        showError(`Save failed: ${errorText(error)}`);
+    }
+});
+
+// Show export progress on the button that started it
+let exportButton = null;
+
+listen('export-progress', (event) => {
+    if (!exportButton) {
+        return;
+    }
+    const { percent, eta_ms, done, preparing } = event.payload;
+    if (done) {
+        exportButton.textContent = exportButton.dataset.label;
+        return;
+    }
+    const eta = eta_ms == null ? '' : `, ${Math.ceil(eta_ms / 1000)}s left`;
+    exportButton.textContent = `${preparing ? 'Preparing' : 'Exporting'} ${Math.floor(percent)}%${eta}`;
+});
+
+async function runExport(button, command, args) {
+    exportButton = button;
+    button.dataset.label ??= button.textContent;
+    button.disabled = true;
+    try {
+        return await invoke(command, args);
+    } finally {
+        button.textContent = button.dataset.label;
+        button.disabled = false;
+        exportButton = null;
+    }
+}
+
+// Positions in the dataset of the rows shown, so exports are written from
+// the backend's copy, which has the full text of cells cut short here
+function exportRowIndices() {
+    const positions = new Map(currentData.rows.map((row, i) => [row, i]));
+    return filteredData.rows.map(row => positions.get(row));
+}
+
 // Export to CSV
 exportCsvBtn.addEventListener('click', async () => {
     try {
@@ -211,15 +694,17 @@ exportCsvBtn.addEventListener('click', async () => {
         });
         
         if (filePath) {
-            const result = await invoke('export_csv', {
+            const result = await runExport(exportCsvBtn, 'export_csv', {
                 filePath,
                 headers: currentData.headers,
-                rows: filteredData.rows
+                rows: [],
+                formatsFrom: currentData.handle,
+                rowIndices: exportRowIndices()
             });
             alert(result);
         }
     } catch (error) {
-        showError(`Export failed: ${error}`);
+        showError(`Export failed: ${errorText(error)}`);
     }
 });
 
@@ -235,21 +720,95 @@ exportJsonBtn.addEventListener('click', async () => {
         });
         
         if (filePath) {
-            const result = await invoke('export_json', {
+            const result = await runExport(exportJsonBtn, 'export_json', {
                 filePath,
                 headers: currentData.headers,
-                rows: filteredData.rows
+                rows: [],
+                formatsFrom: currentData.handle,
+                rowIndices: exportRowIndices()
             });
             alert(result);
         }
     } catch (error) {
-        showError(`Export failed: ${error}`);
+        showError(`Export failed: ${errorText(error)}`);
+    }
+});
+
+// Paste a table copied from a spreadsheet, CSV, or JSON as a new dataset
+document.addEventListener('paste', async (e) => {
+    if (e.target.isContentEditable || e.target.tagName === 'INPUT') {
+        return;
+    }
+    
+    e.preventDefault();
+    try {
+        hideError();
+        await showDataset(await invoke('parse_clipboard'));
+    } catch (error) {
+        showError(`Paste failed: ${errorText(error)}`);
+    }
+});
+
+// Undo/redo of backend edits
+document.addEventListener('keydown', async (e) => {
+    if (!currentData || !(e.ctrlKey || e.metaKey)) {
+        return;
+    }
+    if (e.target.isContentEditable || e.target.tagName === 'INPUT') {
+        return;
+    }
+    
+    const key = e.key.toLowerCase();
+    let command = null;
+    if (key === 'z') {
+        command = e.shiftKey ? 'redo' : 'undo';
+    } else if (key === 'y') {
+        command = 'redo';
+    }
+    if (!command) {
+        return;
+    }
+    
+    e.preventDefault();
+    try {
+        hideError();
+        const data = await invoke(command, { handle: currentData.handle });
+        currentData = data;
+        filteredData = { ...data };
+        expressionRows = null;
+        filterInput.value = '';
+        displayData(data);
+        searchInput.dispatchEvent(new Event('input'));
+    } catch (error) {
+        showError(error);
+    }
+});
+
+// Warn when the open file was changed by another program while we were away
+window.addEventListener('focus', async () => {
+    if (!currentData) {
+        return;
+    }
+    try {
+        const status = await invoke('check_source_changed', { handle: currentData.handle });
+        if (status.missing) {
+            showError(`File no longer exists: ${status.path}`);
+        } else if (status.changed) {
+            showError(`File changed externally: ${status.path}. Reopen it to see the changes; saving will overwrite them.`);
+        }
+    } catch (error) {
+        // The dataset may have been closed in another window
     }
 });
 
 // Error handling
+// Commands reject with {code, message, detail, path, line}
+function errorText(error) {
+    return error?.message ?? String(error);
+}
+
 function showError(error) {
-    errorMessage.textContent = error;
+    errorMessage.textContent = errorText(error);
     errorMessage.classList.remove('hidden');
 }
 